@@ -0,0 +1,319 @@
+//! Subcommands of the `agixt` binary and their output.
+
+use agixt_sdk::{AGiXTSDK, Result};
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "agixt", version, about = "Command-line client for AGiXT")]
+struct Cli {
+    /// AGiXT server address
+    #[arg(long, env = "AGIXT_URI", default_value = "http://localhost:7437")]
+    uri: String,
+    /// API key or JWT
+    #[arg(long, env = "AGIXT_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+    /// Print raw responses as they arrive
+    #[arg(long)]
+    verbose: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage agents
+    #[command(subcommand)]
+    Agents(AgentCommand),
+    /// Manage conversations
+    #[command(subcommand)]
+    Conversations(ConversationCommand),
+    /// Send a message to an agent in a conversation
+    Chat {
+        agent_id: String,
+        conversation_id: String,
+        message: String,
+    },
+    /// List and run prompts
+    #[command(subcommand)]
+    Prompts(PromptCommand),
+    /// List and run chains
+    #[command(subcommand)]
+    Chains(ChainCommand),
+    /// Add text, URLs or files to an agent's memory
+    #[command(subcommand)]
+    Learn(LearnCommand),
+}
+
+#[derive(Subcommand)]
+enum AgentCommand {
+    /// List agents
+    List,
+    /// Show an agent's configuration
+    Show { agent_id: String },
+    /// Create an agent
+    Create {
+        name: String,
+        /// Agent settings
+        #[command(flatten)]
+        settings: KeyValues,
+    },
+    /// Delete an agent
+    Delete { agent_id: String },
+}
+
+#[derive(Subcommand)]
+enum ConversationCommand {
+    /// List conversations
+    List {
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Show a conversation's messages
+    History {
+        conversation_id: String,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Create a conversation with an agent
+    New { agent_id: String, name: String },
+    /// Delete a conversation
+    Delete { conversation_id: String },
+}
+
+#[derive(Subcommand)]
+enum PromptCommand {
+    /// List prompts
+    List {
+        /// Only prompts of this category
+        #[arg(long)]
+        category: Option<String>,
+    },
+    /// Run a prompt with an agent
+    Run {
+        agent_id: String,
+        prompt_name: String,
+        #[command(flatten)]
+        args: KeyValues,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChainCommand {
+    /// List chains
+    List,
+    /// Run a chain
+    Run {
+        chain_id: String,
+        user_input: String,
+        /// Agent that overrides the agents of the chain's steps
+        #[arg(long)]
+        agent: Option<String>,
+        #[command(flatten)]
+        args: KeyValues,
+    },
+}
+
+#[derive(Subcommand)]
+enum LearnCommand {
+    /// Memorize text
+    Text { agent_id: String, text: String },
+    /// Memorize the content of web pages
+    Url {
+        agent_id: String,
+        #[arg(required = true)]
+        urls: Vec<String>,
+    },
+    /// Memorize local files
+    File {
+        agent_id: String,
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct PageArgs {
+    /// Number of items per page
+    #[arg(long)]
+    limit: Option<i32>,
+    /// 1-based page number
+    #[arg(long)]
+    page: Option<i32>,
+}
+
+#[derive(Args)]
+struct KeyValues {
+    /// KEY=VALUE pair; values that parse as JSON are sent as such
+    #[arg(long = "arg", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pairs: Vec<(String, Value)>,
+}
+
+impl KeyValues {
+    fn into_map(self) -> HashMap<String, Value> {
+        self.pairs.into_iter().collect()
+    }
+}
+
+fn parse_key_value(pair: &str) -> std::result::Result<(String, Value), String> {
+    let (key, value) = pair
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", pair))?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.into()));
+    Ok((key.to_string(), value))
+}
+
+pub fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let client = AGiXTSDK::new(Some(cli.uri), cli.api_key, cli.verbose);
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("error: cannot start the async runtime: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match runtime.block_on(run(&client, cli.command)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(client: &AGiXTSDK, command: Command) -> Result<()> {
+    match command {
+        Command::Agents(command) => match command {
+            AgentCommand::List => print(&client.get_agents().await?),
+            AgentCommand::Show { agent_id } => print(&client.get_agentconfig(&agent_id).await?),
+            AgentCommand::Create { name, settings } => print(
+                &client
+                    .add_agent(&name, Some(settings.into_map()), None, None)
+                    .await?,
+            ),
+            AgentCommand::Delete { agent_id } => print(&client.delete_agent(&agent_id).await?),
+        },
+        Command::Conversations(command) => match command {
+            ConversationCommand::List { page } => {
+                print(&client.get_conversations(page.limit, page.page).await?)
+            }
+            ConversationCommand::History {
+                conversation_id,
+                page,
+            } => print(
+                &client
+                    .get_conversation(&conversation_id, page.limit, page.page)
+                    .await?,
+            ),
+            ConversationCommand::New { agent_id, name } => {
+                print(&client.new_conversation(&agent_id, &name, None).await?)
+            }
+            ConversationCommand::Delete { conversation_id } => {
+                print(&client.delete_conversation(&conversation_id).await?)
+            }
+        },
+        Command::Chat {
+            agent_id,
+            conversation_id,
+            message,
+        } => print(
+            &client
+                .chat(&agent_id, &message, &conversation_id, None)
+                .await?,
+        ),
+        Command::Prompts(command) => match command {
+            PromptCommand::List { category } => {
+                print(&client.get_prompts(category.as_deref()).await?)
+            }
+            PromptCommand::Run {
+                agent_id,
+                prompt_name,
+                args,
+            } => print(
+                &client
+                    .prompt_agent(&agent_id, &prompt_name, args.into_map())
+                    .await?,
+            ),
+        },
+        Command::Chains(command) => match command {
+            ChainCommand::List => print(&client.get_chains().await?),
+            ChainCommand::Run {
+                chain_id,
+                user_input,
+                agent,
+                args,
+            } => print(
+                &client
+                    .run_chain(
+                        &chain_id,
+                        &user_input,
+                        agent.as_deref(),
+                        None,
+                        None,
+                        Some(args.into_map()),
+                    )
+                    .await?,
+            ),
+        },
+        Command::Learn(command) => match command {
+            LearnCommand::Text { agent_id, text } => {
+                print(&client.learn_text(&agent_id, &text, &text, None).await?)
+            }
+            LearnCommand::Url { agent_id, urls } => learned(
+                client
+                    .learn_urls_concurrent(&agent_id, &urls, None, 4)
+                    .await,
+            ),
+            LearnCommand::File { agent_id, paths } => {
+                let mut files = Vec::with_capacity(paths.len());
+                for path in paths {
+                    let content = std::fs::read_to_string(&path).map_err(|e| {
+                        agixt_sdk::Error::Other(format!("cannot read {}: {}", path.display(), e))
+                    })?;
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    files.push((name, content));
+                }
+                learned(
+                    client
+                        .learn_files_concurrent(&agent_id, &files, None, 4)
+                        .await,
+                )
+            }
+        },
+    }
+}
+
+/// Print the outcome of each batch item, failing if any item failed.
+fn learned(results: Vec<(String, Result<String>)>) -> Result<()> {
+    let mut failures = 0;
+    for (item, result) in results {
+        match result {
+            Ok(message) => println!("{}: {}", item, message),
+            Err(e) => {
+                eprintln!("{}: {}", item, e);
+                failures += 1;
+            }
+        }
+    }
+    match failures {
+        0 => Ok(()),
+        n => Err(agixt_sdk::Error::Other(format!("{} item(s) failed", n))),
+    }
+}
+
+/// Print text results such as agent replies as they are, anything else as JSON.
+fn print<T: Serialize>(value: &T) -> Result<()> {
+    match serde_json::to_value(value)? {
+        Value::String(text) => println!("{}", text),
+        value => println!("{}", serde_json::to_string_pretty(&value)?),
+    }
+    Ok(())
+}
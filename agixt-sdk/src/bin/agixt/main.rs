@@ -0,0 +1,23 @@
+//! `agixt`: script an AGiXT server from the shell.
+//!
+//! Built with the `cli` feature. Agent replies are printed as text and other
+//! results as JSON, so they can be piped into tools such as `jq`.
+//!
+//! ```text
+//! agixt agents list
+//! agixt conversations history <conversation-id> --limit 20
+//! agixt prompts run <agent-id> "Think About It" --arg user_input="Hello"
+//! agixt learn url <agent-id> https://docs.agixt.com
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> std::process::ExitCode {
+    cli::main()
+}
+
+/// The CLI needs a native target; there is nothing to run in a browser.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
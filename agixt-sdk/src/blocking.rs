@@ -0,0 +1,122 @@
+//! Blocking client for scripts and CLI tools.
+//!
+//! `blocking::AGiXTSDK` mirrors the async client method for method. Each call
+//! runs the corresponding async method to completion on a current-thread tokio
+//! runtime owned by the client, so callers never need to set up a runtime
+//! themselves. tokio is still a dependency of the crate: the client is a thin
+//! wrapper over the async one, not a separate `reqwest::blocking` client.
+//!
+//! The blocking client must not be used from within an async runtime; use the
+//! async [`crate::AGiXTSDK`] there instead.
+//!
+//! # Example
+//! ```rust,no_run
+//! use agixt_sdk::blocking::AGiXTSDK;
+//!
+//! fn main() -> agixt_sdk::Result<()> {
+//!     let client = AGiXTSDK::new(None, Some("your-api-key".to_string()), false)?;
+//!     let agents = client.get_agents()?;
+//!     println!("{} agents", agents.len());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::api::prelude::*;
+use std::sync::Arc;
+
+/// Generate blocking wrappers that run the async method of the same name.
+macro_rules! blocking_methods {
+    ($($(#[$meta:meta])* fn $name:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;)*) => {
+        $(
+            $(#[$meta])*
+            #[doc = concat!("Blocking version of [`crate::AGiXTSDK::", stringify!($name), "`].")]
+            pub fn $name(&self $(, $arg: $ty)*) -> $ret {
+                crate::error::WithOperation::with_operation(
+                    self.runtime.block_on(self.inner.$name($($arg),*)),
+                    stringify!($name),
+                )
+            }
+        )*
+    };
+}
+
+/// Synchronous AGiXT client.
+#[derive(Clone)]
+pub struct AGiXTSDK {
+    inner: crate::AGiXTSDK,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl AGiXTSDK {
+    /// Create a new blocking client. See [`crate::AGiXTSDK::new`].
+    ///
+    /// Fails if the client's runtime cannot be started.
+    pub fn new(base_uri: Option<String>, api_key: Option<String>, verbose: bool) -> Result<Self> {
+        Self::from_async(crate::AGiXTSDK::new(base_uri, api_key, verbose))
+    }
+
+    /// Wrap a configured async client, e.g. one created with
+    /// [`crate::AGiXTSDK::builder`].
+    ///
+    /// Fails if the client's runtime cannot be started.
+    pub fn from_async(inner: crate::AGiXTSDK) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| {
+                crate::Error::Other(format!(
+                    "failed to start the blocking client runtime: {}",
+                    e
+                ))
+            })?;
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Access the underlying async client.
+    pub fn as_async(&self) -> &crate::AGiXTSDK {
+        &self.inner
+    }
+
+    /// Return a client that applies `options` to every request it makes.
+    /// See [`crate::AGiXTSDK::with_options`].
+    pub fn with_options(&self, options: crate::RequestOptions) -> Self {
+        Self {
+            inner: self.inner.with_options(options),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Drop all cached responses. See [`crate::AGiXTSDK::invalidate_cache`].
+    pub fn invalidate_cache(&self) {
+        self.inner.invalidate_cache()
+    }
+
+    /// Remove the API key so subsequent requests are unauthenticated.
+    pub fn clear_api_key(&self) {
+        self.runtime.block_on(self.inner.clear_api_key())
+    }
+
+    crate::client::api::for_each_api_method!(blocking_methods);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocking_request() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("GET", "/v1/user/exists")
+            .match_query(mockito::Matcher::Any)
+            .with_body("true")
+            .create();
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false).unwrap();
+        assert!(client.user_exists("user@example.com").unwrap());
+        mock.assert();
+    }
+}
@@ -1,19 +1,25 @@
 //! Agent operations using /v1 endpoints with ID-based parameters.
 
 use crate::error::Result;
+use crate::models::{
+    Agent, AgentConfig, AgentCreated, ExecuteCommand, Extension, Memory, PromptOptions,
+    ProviderFallback, UploadSource,
+};
+use futures_util::stream::{self, StreamExt};
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    super::stream::{text_event, StreamEvent, StreamEvents},
+    crate::error::Error,
+};
 
 impl super::AGiXTSDK {
     // ==================== Agents ====================
 
     /// Get list of all agents. Returns list of agents with their IDs.
-    pub async fn get_agents(&self) -> Result<Vec<HashMap<String, serde_json::Value>>> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/agent", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+    pub async fn get_agents(&self) -> Result<Vec<Agent>> {
+        let request = self.client.get(format!("{}/agent", self.api_root));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -24,46 +30,46 @@ impl super::AGiXTSDK {
 
         #[derive(serde::Deserialize)]
         struct AgentsResponse {
-            agents: Vec<HashMap<String, serde_json::Value>>,
+            agents: Vec<Agent>,
         }
 
-        let result: AgentsResponse = serde_json::from_str(&text)?;
+        let result: AgentsResponse = text.json()?;
         Ok(result.agents)
     }
 
     /// Get agent ID by name. Returns None if not found.
     pub async fn get_agent_id_by_name(&self, agent_name: &str) -> Result<Option<String>> {
         let agents = self.get_agents().await?;
-        for agent in agents {
-            if let Some(name) = agent.get("name").and_then(|v| v.as_str()) {
-                if name == agent_name {
-                    return Ok(agent.get("id").and_then(|v| v.as_str()).map(String::from));
-                }
-            }
-        }
-        Ok(None)
+        Ok(agents
+            .into_iter()
+            .find(|agent| agent.name == agent_name)
+            .map(|agent| agent.id))
     }
 
-    /// Add a new agent. Returns agent info including agent_id.
+    /// Add a new agent. Returns the new agent's ID.
+    ///
+    /// The agent learns the pages of `training_urls`, which must be `http` or
+    /// `https` URLs; an invalid one is rejected before anything is sent.
     pub async fn add_agent(
         &self,
         agent_name: &str,
         settings: Option<HashMap<String, serde_json::Value>>,
         commands: Option<HashMap<String, serde_json::Value>>,
         training_urls: Option<Vec<String>>,
-    ) -> Result<serde_json::Value> {
-        let response = self
-            .client
-            .post(&format!("{}/v1/agent", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({
-                "agent_name": agent_name,
-                "settings": settings.unwrap_or_default(),
-                "commands": commands.unwrap_or_default(),
-                "training_urls": training_urls.unwrap_or_default(),
-            }))
-            .send()
-            .await?;
+    ) -> Result<AgentCreated> {
+        validate_training_urls(training_urls.as_deref().unwrap_or_default())?;
+        let settings = settings.unwrap_or_default();
+        self.check_agent_settings(&settings).await?;
+        let request =
+            self.client
+                .post(format!("{}/agent", self.api_root))
+                .json(&serde_json::json!({
+                    "agent_name": agent_name,
+                    "settings": settings,
+                    "commands": commands.unwrap_or_default(),
+                    "training_urls": training_urls.unwrap_or_default(),
+                }));
+        let response = self.execute_create(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -72,7 +78,15 @@ impl super::AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
+    }
+
+    /// Create `new_name` with the same settings and commands as an existing agent,
+    /// e.g. to stamp out several agents from a template.
+    pub async fn copy_agent(&self, source_agent_id: &str, new_name: &str) -> Result<AgentCreated> {
+        let source = self.get_agentconfig(source_agent_id).await?;
+        self.add_agent(new_name, Some(source.settings), Some(source.commands), None)
+            .await
     }
 
     /// Import an agent configuration.
@@ -81,18 +95,16 @@ impl super::AGiXTSDK {
         agent_name: &str,
         settings: Option<HashMap<String, serde_json::Value>>,
         commands: Option<HashMap<String, serde_json::Value>>,
-    ) -> Result<serde_json::Value> {
-        let response = self
+    ) -> Result<AgentCreated> {
+        let request = self
             .client
-            .post(&format!("{}/v1/agent/import", self.base_uri))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/agent/import", self.api_root))
             .json(&serde_json::json!({
                 "agent_name": agent_name,
                 "settings": settings.unwrap_or_default(),
                 "commands": commands.unwrap_or_default(),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute_create(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -101,18 +113,16 @@ impl super::AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
     }
 
     /// Rename an agent by ID.
     pub async fn rename_agent(&self, agent_id: &str, new_name: &str) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .patch(&format!("{}/v1/agent/{}", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({ "new_name": new_name }))
-            .send()
-            .await?;
+            .patch(format!("{}/agent/{}", self.api_root, agent_id))
+            .json(&serde_json::json!({ "new_name": new_name }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -121,7 +131,7 @@ impl super::AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
     }
 
     /// Update agent settings by ID.
@@ -131,18 +141,22 @@ impl super::AGiXTSDK {
         settings: HashMap<String, serde_json::Value>,
         agent_name: Option<&str>,
     ) -> Result<String> {
-        let response = self
+        if self.validate_settings {
+            // Only changed settings may be given, so check them with the rest
+            let mut merged = self.get_agentconfig(agent_id).await?.settings;
+            merged.extend(settings.clone());
+            self.check_agent_settings(&merged).await?;
+        }
+        let request = self
             .client
-            .put(&format!("{}/v1/agent/{}", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
+            .put(format!("{}/agent/{}", self.api_root, agent_id))
             .json(&serde_json::json!({
                 "agent_name": agent_name.unwrap_or(""),
                 "settings": settings,
                 "commands": {},
                 "training_urls": [],
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -156,23 +170,84 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
-    /// Update agent commands by ID.
+    /// Set an agent's provider and the fallbacks used when it fails, by ID.
+    ///
+    /// Every listed provider must be offered by the server, appear once and
+    /// not contain a comma; otherwise nothing is changed.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, ProviderFallback};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let fallback = ProviderFallback::new("openai").fallback("anthropic");
+    /// client.set_provider_fallback("agent-id", &fallback).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_provider_fallback(
+        &self,
+        agent_id: &str,
+        fallback: &ProviderFallback,
+    ) -> Result<String> {
+        let mut seen = std::collections::HashSet::new();
+        for provider in fallback.providers() {
+            if provider.trim().is_empty() || provider.contains(',') {
+                return Err(crate::Error::InvalidInput(format!(
+                    "invalid provider name '{}'",
+                    provider
+                )));
+            }
+            if !seen.insert(provider) {
+                return Err(crate::Error::InvalidInput(format!(
+                    "provider {} is listed more than once",
+                    provider
+                )));
+            }
+        }
+
+        let available: Vec<String> = self
+            .get_providers()
+            .await?
+            .iter()
+            .filter_map(|provider| {
+                provider
+                    .as_str()
+                    .or_else(|| provider.get("name").and_then(|v| v.as_str()))
+                    .map(str::to_string)
+            })
+            .collect();
+        if let Some(unknown) = fallback
+            .providers()
+            .find(|provider| !available.iter().any(|name| name == provider))
+        {
+            return Err(crate::Error::InvalidInput(format!(
+                "provider {} is not available; the server offers {}",
+                unknown,
+                available.join(", ")
+            )));
+        }
+
+        self.update_agent_settings(agent_id, fallback.to_settings(), None)
+            .await
+    }
+
+    /// Update agent commands by ID, replacing the whole commands map.
+    /// Use `set_command_enabled` to change a single command.
     pub async fn update_agent_commands(
         &self,
         agent_id: &str,
         commands: HashMap<String, serde_json::Value>,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .put(&format!("{}/v1/agent/{}/commands", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({ "commands": commands }))
-            .send()
-            .await?;
+            .put(format!("{}/agent/{}/commands", self.api_root, agent_id))
+            .json(&serde_json::json!({ "commands": commands }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -186,18 +261,16 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
     /// Delete an agent by ID.
     pub async fn delete_agent(&self, agent_id: &str) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .delete(&format!("{}/v1/agent/{}", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .delete(format!("{}/agent/{}", self.api_root, agent_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -211,18 +284,16 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
     /// Get agent configuration by ID.
-    pub async fn get_agentconfig(&self, agent_id: &str) -> Result<HashMap<String, serde_json::Value>> {
-        let response = self
+    pub async fn get_agentconfig(&self, agent_id: &str) -> Result<AgentConfig> {
+        let request = self
             .client
-            .get(&format!("{}/v1/agent/{}", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/agent/{}", self.api_root, agent_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -233,10 +304,10 @@ impl super::AGiXTSDK {
 
         #[derive(serde::Deserialize)]
         struct AgentResponse {
-            agent: HashMap<String, serde_json::Value>,
+            agent: AgentConfig,
         }
 
-        let result: AgentResponse = serde_json::from_str(&text)?;
+        let result: AgentResponse = text.json()?;
         Ok(result.agent)
     }
 
@@ -244,12 +315,10 @@ impl super::AGiXTSDK {
 
     /// Get available commands for an agent by ID.
     pub async fn get_commands(&self, agent_id: &str) -> Result<HashMap<String, serde_json::Value>> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/agent/{}/command", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/agent/{}/command", self.api_root, agent_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -263,22 +332,29 @@ impl super::AGiXTSDK {
             commands: HashMap<String, serde_json::Value>,
         }
 
-        let result: CommandsResponse = serde_json::from_str(&text)?;
+        let result: CommandsResponse = text.json()?;
         Ok(result.commands)
     }
 
-    /// Toggle a command for an agent by ID.
-    pub async fn toggle_command(&self, agent_id: &str, command_name: &str, enable: bool) -> Result<String> {
-        let response = self
+    /// Enable or disable a single command of an agent by ID.
+    ///
+    /// Only the named command changes, so concurrent clients toggling
+    /// different commands don't overwrite each other the way whole-map
+    /// updates with `update_agent_commands` can.
+    pub async fn set_command_enabled(
+        &self,
+        agent_id: &str,
+        command_name: &str,
+        enabled: bool,
+    ) -> Result<String> {
+        let request = self
             .client
-            .patch(&format!("{}/v1/agent/{}/command", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
+            .patch(format!("{}/agent/{}/command", self.api_root, agent_id))
             .json(&serde_json::json!({
                 "command_name": command_name,
-                "enable": enable,
-            }))
-            .send()
-            .await?;
+                "enable": enabled,
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -292,29 +368,45 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
+    /// Toggle a command for an agent by ID. Same as `set_command_enabled`.
+    pub async fn toggle_command(
+        &self,
+        agent_id: &str,
+        command_name: &str,
+        enable: bool,
+    ) -> Result<String> {
+        self.set_command_enabled(agent_id, command_name, enable)
+            .await
+    }
+
     /// Execute a command on an agent by ID.
+    ///
+    /// With a conversation (name or ID) the command and its output are logged
+    /// to that conversation's history; without one the server runs it outside
+    /// any conversation.
     pub async fn execute_command(
         &self,
         agent_id: &str,
         command_name: &str,
         command_args: HashMap<String, serde_json::Value>,
-        conversation_id: Option<&str>,
+        conversation: Option<&str>,
     ) -> Result<serde_json::Value> {
-        let response = self
+        let mut body = serde_json::json!({
+            "command_name": command_name,
+            "command_args": command_args,
+        });
+        if let Some(conversation) = conversation {
+            body["conversation_name"] = conversation.into();
+        }
+        let request = self
             .client
-            .post(&format!("{}/v1/agent/{}/command", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({
-                "command_name": command_name,
-                "command_args": command_args,
-                "conversation_name": conversation_id.unwrap_or(""),
-            }))
-            .send()
-            .await?;
+            .post(format!("{}/agent/{}/command", self.api_root, agent_id))
+            .json(&body);
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -328,10 +420,39 @@ impl super::AGiXTSDK {
             response: serde_json::Value,
         }
 
-        let result: ResponseWrapper = serde_json::from_str(&text)?;
+        let result: ResponseWrapper = text.json()?;
         Ok(result.response)
     }
 
+    /// Execute a command built and checked with [`ExecuteCommand::builder`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, ExecuteCommand};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let schema = client.get_command_args("Web Search").await?;
+    /// let command = ExecuteCommand::builder("Web Search", &schema)
+    ///     .arg("query", "AGiXT release notes")
+    ///     .build()?;
+    /// let output = client.run_command("agent-id", &command).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_command(
+        &self,
+        agent_id: &str,
+        command: &ExecuteCommand,
+    ) -> Result<serde_json::Value> {
+        self.execute_command(
+            agent_id,
+            &command.command_name,
+            command.command_args.clone(),
+            command.conversation_id.as_deref(),
+        )
+        .await
+    }
+
     // ==================== Prompting ====================
 
     /// Send a prompt to an agent by ID.
@@ -341,39 +462,274 @@ impl super::AGiXTSDK {
         prompt_name: &str,
         prompt_args: HashMap<String, serde_json::Value>,
     ) -> Result<String> {
-        let response = self
-            .client
-            .post(&format!("{}/v1/agent/{}/prompt", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({
-                "prompt_name": prompt_name,
-                "prompt_args": prompt_args,
-            }))
-            .send()
-            .await?;
+        let body = serde_json::json!({
+            "prompt_name": prompt_name,
+            "prompt_args": prompt_args,
+        });
+        let started = web_time::Instant::now();
+        let result = async {
+            let request = self
+                .client
+                .post(format!("{}/agent/{}/prompt", self.api_root, agent_id))
+                .json(&body);
+            let response = self.execute(request).await?;
+
+            let status = response.status();
+            let text = response.text().await?;
+
+            if self.verbose {
+                self.parse_response(status, &text).await?;
+            }
 
-        let status = response.status();
-        let text = response.text().await?;
+            #[derive(serde::Deserialize)]
+            struct ResponseWrapper {
+                response: String,
+            }
 
-        if self.verbose {
-            self.parse_response(status, &text).await?;
+            let result: ResponseWrapper = text.json()?;
+            Ok(result.response)
         }
+        .await;
+        self.record_transcript("prompt", agent_id, &body, &result, started);
+        result
+    }
 
-        #[derive(serde::Deserialize)]
-        struct ResponseWrapper {
-            response: String,
+    /// Send a prompt to an agent by ID with context options, such as web
+    /// search or the number of memories injected. Options that are set take
+    /// precedence over the same keys in `prompt_args`.
+    pub async fn prompt_agent_with_options(
+        &self,
+        agent_id: &str,
+        prompt_name: &str,
+        mut prompt_args: HashMap<String, serde_json::Value>,
+        options: &PromptOptions,
+    ) -> Result<String> {
+        prompt_args.extend(options.to_args());
+        self.prompt_agent(agent_id, prompt_name, prompt_args).await
+    }
+
+    /// Send a prompt to an agent by ID, with arguments taken from the fields of
+    /// a `Serialize` struct by [`to_args`](crate::prompt::to_args).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// #[derive(serde::Serialize)]
+    /// struct Translate<'a> {
+    ///     user_input: &'a str,
+    ///     language: &'a str,
+    /// }
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let answer = client
+    ///     .prompt_agent_with_args(
+    ///         "agent-id",
+    ///         "Translate",
+    ///         &Translate { user_input: "Good morning", language: "French" },
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn prompt_agent_with_args<T: serde::Serialize + ?Sized>(
+        &self,
+        agent_id: &str,
+        prompt_name: &str,
+        prompt_args: &T,
+    ) -> Result<String> {
+        let prompt_args = crate::prompt::to_args(prompt_args)?;
+        self.prompt_agent(agent_id, prompt_name, prompt_args).await
+    }
+
+    /// Send a prompt to an agent by ID and follow its progress: the agent's
+    /// activities (subtasks, command executions) arrive as
+    /// `StreamEvent::Activity` while it works, then the answer as a single
+    /// `StreamEvent::ContentDelta` followed by `StreamEvent::Done`.
+    ///
+    /// Activities are read from the conversation in `prompt_args`'
+    /// `conversation_name` (a conversation ID), which is polled every
+    /// `poll_interval` until the answer arrives.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, StreamEvent};
+    /// use futures_util::StreamExt;
+    /// use std::collections::HashMap;
+    /// use std::time::Duration;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let args = HashMap::from([
+    ///     ("user_input".to_string(), "Research Rust web frameworks".into()),
+    ///     ("conversation_name".to_string(), "conversation-id".into()),
+    /// ]);
+    /// let mut events = client
+    ///     .prompt_agent_stream("agent-id", "Think About It", args, Duration::from_secs(1))
+    ///     .await?;
+    /// while let Some(event) = events.next().await {
+    ///     match event? {
+    ///         StreamEvent::Activity { text, .. } => eprintln!("... {}", text),
+    ///         StreamEvent::ContentDelta { text, .. } => println!("{}", text),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn prompt_agent_stream(
+        &self,
+        agent_id: &str,
+        prompt_name: &str,
+        prompt_args: HashMap<String, serde_json::Value>,
+        poll_interval: std::time::Duration,
+    ) -> Result<StreamEvents> {
+        let conversation_id = prompt_args
+            .get("conversation_name")
+            .and_then(|v| v.as_str())
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                Error::InvalidInput(
+                    "prompt_args needs a conversation_name to stream activities from".to_string(),
+                )
+            })?;
+        // Messages already in the conversation are not reported; a conversation
+        // the prompt creates starts out empty
+        let mut seen = self
+            .get_full_conversation(&conversation_id)
+            .await
+            .map(|history| history.len())
+            .unwrap_or(0);
+
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let client = self.clone();
+        let agent_id = agent_id.to_string();
+        let prompt_name = prompt_name.to_string();
+        tokio::spawn(async move {
+            let prompt = client.prompt_agent(&agent_id, &prompt_name, prompt_args);
+            tokio::pin!(prompt);
+            let mut ticks = tokio::time::interval(poll_interval);
+            let answer = loop {
+                tokio::select! {
+                    answer = &mut prompt => break answer,
+                    _ = ticks.tick() => {
+                        if sender.is_closed() {
+                            return;
+                        }
+                        client.send_new_activities(&conversation_id, &mut seen, &sender).await;
+                    }
+                }
+            };
+            client
+                .send_new_activities(&conversation_id, &mut seen, &sender)
+                .await;
+            let events = match answer {
+                Ok(answer) => vec![
+                    Ok(StreamEvent::ContentDelta {
+                        index: 0,
+                        text: answer,
+                    }),
+                    Ok(StreamEvent::Done {
+                        finish_reason: Some("stop".to_string()),
+                        usage: None,
+                    }),
+                ],
+                Err(e) => vec![Err(e)],
+            };
+            for event in events {
+                let _ = sender.send(event);
+            }
+        });
+
+        Ok(Box::pin(stream::unfold(
+            receiver,
+            |mut receiver| async move { receiver.recv().await.map(|event| (event, receiver)) },
+        )))
+    }
+
+    /// Send an instruction to an agent and follow its activities; see
+    /// `prompt_agent_stream`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn instruct_stream(
+        &self,
+        agent_id: &str,
+        user_input: &str,
+        conversation_id: &str,
+        poll_interval: std::time::Duration,
+    ) -> Result<StreamEvents> {
+        let mut args = HashMap::new();
+        args.insert("user_input".to_string(), serde_json::json!(user_input));
+        args.insert("disable_memory".to_string(), serde_json::json!(true));
+        args.insert(
+            "conversation_name".to_string(),
+            serde_json::json!(conversation_id),
+        );
+
+        self.prompt_agent_stream(agent_id, "instruct", args, poll_interval)
+            .await
+    }
+
+    /// Send the activity messages written to a conversation after the first
+    /// `seen` messages. Failed polls are skipped; the next one catches up.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn send_new_activities(
+        &self,
+        conversation_id: &str,
+        seen: &mut usize,
+        sender: &tokio::sync::mpsc::UnboundedSender<Result<StreamEvent>>,
+    ) {
+        let Ok(messages) = self
+            .conversation_messages_from(conversation_id, *seen)
+            .await
+        else {
+            return;
+        };
+        *seen += messages.len();
+        for message in messages {
+            let event = text_event(0, &message.text());
+            if matches!(event, StreamEvent::Activity { .. }) {
+                let _ = sender.send(Ok(event));
+            }
         }
+    }
 
-        let result: ResponseWrapper = serde_json::from_str(&text)?;
-        Ok(result.response)
+    /// Send a prompt to an agent by ID with files attached, e.g. to analyze a
+    /// document in one call. The files are base64-encoded into the prompt's
+    /// `file_urls` argument.
+    pub async fn prompt_agent_with_files(
+        &self,
+        agent_id: &str,
+        prompt_name: &str,
+        mut prompt_args: HashMap<String, serde_json::Value>,
+        files: Vec<UploadSource>,
+    ) -> Result<String> {
+        let mut file_urls = match prompt_args.remove("file_urls") {
+            Some(serde_json::Value::Array(urls)) => urls,
+            Some(url) => vec![url],
+            None => Vec::new(),
+        };
+        for file in files {
+            file_urls.push(serde_json::Value::String(file.into_data_uri()?));
+        }
+        prompt_args.insert("file_urls".to_string(), serde_json::Value::Array(file_urls));
+        self.prompt_agent(agent_id, prompt_name, prompt_args).await
     }
 
     /// Send an instruction to an agent.
-    pub async fn instruct(&self, agent_id: &str, user_input: &str, conversation_id: &str) -> Result<String> {
+    pub async fn instruct(
+        &self,
+        agent_id: &str,
+        user_input: &str,
+        conversation_id: &str,
+    ) -> Result<String> {
         let mut args = HashMap::new();
         args.insert("user_input".to_string(), serde_json::json!(user_input));
         args.insert("disable_memory".to_string(), serde_json::json!(true));
-        args.insert("conversation_name".to_string(), serde_json::json!(conversation_id));
+        args.insert(
+            "conversation_name".to_string(),
+            serde_json::json!(conversation_id),
+        );
 
         self.prompt_agent(agent_id, "instruct", args).await
     }
@@ -385,26 +741,57 @@ impl super::AGiXTSDK {
         user_input: &str,
         conversation_id: &str,
         context_results: Option<i32>,
+    ) -> Result<String> {
+        let options =
+            PromptOptions::new().context_results(context_results.unwrap_or(4).max(0) as u32);
+        self.chat_with_options(agent_id, user_input, conversation_id, &options)
+            .await
+    }
+
+    /// Chat with an agent, with web search, link browsing or other context
+    /// options. Unless set in `options`, 4 memories are injected as context and
+    /// the exchange is not stored in memory, as with `chat`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, PromptOptions};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let options = PromptOptions::new().websearch(true).browse_links(true);
+    /// let answer = client
+    ///     .chat_with_options("agent-id", "What's new in Rust 1.80?", "conversation-id", &options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_with_options(
+        &self,
+        agent_id: &str,
+        user_input: &str,
+        conversation_id: &str,
+        options: &PromptOptions,
     ) -> Result<String> {
         let mut args = HashMap::new();
         args.insert("user_input".to_string(), serde_json::json!(user_input));
-        args.insert("context_results".to_string(), serde_json::json!(context_results.unwrap_or(4)));
-        args.insert("conversation_name".to_string(), serde_json::json!(conversation_id));
+        args.insert("context_results".to_string(), serde_json::json!(4));
+        args.insert(
+            "conversation_name".to_string(),
+            serde_json::json!(conversation_id),
+        );
         args.insert("disable_memory".to_string(), serde_json::json!(true));
 
-        self.prompt_agent(agent_id, "Chat", args).await
+        self.prompt_agent_with_options(agent_id, "Chat", args, options)
+            .await
     }
 
     // ==================== Persona ====================
 
     /// Get agent persona by ID.
     pub async fn get_persona(&self, agent_id: &str) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/agent/{}/persona", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/agent/{}/persona", self.api_root, agent_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -418,19 +805,17 @@ impl super::AGiXTSDK {
             message: serde_json::Value,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
     /// Update agent persona by ID.
     pub async fn update_persona(&self, agent_id: &str, persona: &str) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .put(&format!("{}/v1/agent/{}/persona", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({ "persona": persona }))
-            .send()
-            .await?;
+            .put(format!("{}/agent/{}/persona", self.api_root, agent_id))
+            .json(&serde_json::json!({ "persona": persona }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -444,7 +829,7 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -452,12 +837,10 @@ impl super::AGiXTSDK {
 
     /// Get extensions for an agent by ID.
     pub async fn get_agent_extensions(&self, agent_id: &str) -> Result<Vec<serde_json::Value>> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/agent/{}/extensions", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/agent/{}/extensions", self.api_root, agent_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -471,10 +854,33 @@ impl super::AGiXTSDK {
             extensions: Vec<serde_json::Value>,
         }
 
-        let result: ExtensionsResponse = serde_json::from_str(&text)?;
+        let result: ExtensionsResponse = text.json()?;
         Ok(result.extensions)
     }
 
+    /// Get the agent's extensions with their commands, each marked enabled or
+    /// disabled for the agent, e.g. to render command toggles in a settings UI.
+    pub async fn get_extension_commands(&self, agent_id: &str) -> Result<Vec<Extension>> {
+        let (extensions, commands) = futures_util::future::join(
+            self.get_agent_extensions(agent_id),
+            self.get_commands(agent_id),
+        )
+        .await;
+        let commands = commands?;
+
+        let mut catalogue = Vec::new();
+        for extension in extensions? {
+            let mut extension: Extension = serde_json::from_value(extension)?;
+            for command in &mut extension.commands {
+                if let Some(enabled) = commands.get(&command.name).and_then(|v| v.as_bool()) {
+                    command.enabled = enabled;
+                }
+            }
+            catalogue.push(extension);
+        }
+        Ok(catalogue)
+    }
+
     // ==================== Feedback ====================
 
     /// Submit feedback for an agent response.
@@ -487,19 +893,17 @@ impl super::AGiXTSDK {
         positive: bool,
         conversation_id: Option<&str>,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/agent/{}/feedback", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/agent/{}/feedback", self.api_root, agent_id))
             .json(&serde_json::json!({
                 "user_input": user_input,
                 "message": message,
                 "feedback": feedback,
                 "positive": positive,
                 "conversation_name": conversation_id.unwrap_or(""),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -513,7 +917,7 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -526,7 +930,15 @@ impl super::AGiXTSDK {
         feedback: &str,
         conversation_id: Option<&str>,
     ) -> Result<String> {
-        self.submit_feedback(agent_id, message, user_input, feedback, true, conversation_id).await
+        self.submit_feedback(
+            agent_id,
+            message,
+            user_input,
+            feedback,
+            true,
+            conversation_id,
+        )
+        .await
     }
 
     /// Submit negative feedback for an agent response.
@@ -538,7 +950,15 @@ impl super::AGiXTSDK {
         feedback: &str,
         conversation_id: Option<&str>,
     ) -> Result<String> {
-        self.submit_feedback(agent_id, message, user_input, feedback, false, conversation_id).await
+        self.submit_feedback(
+            agent_id,
+            message,
+            user_input,
+            feedback,
+            false,
+            conversation_id,
+        )
+        .await
     }
 
     // ==================== Learning ====================
@@ -551,17 +971,15 @@ impl super::AGiXTSDK {
         text: &str,
         collection_number: Option<&str>,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/agent/{}/learn/text", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/agent/{}/learn/text", self.api_root, agent_id))
             .json(&serde_json::json!({
                 "user_input": user_input,
                 "text": text,
                 "collection_number": collection_number.unwrap_or("0"),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -575,7 +993,7 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -586,16 +1004,14 @@ impl super::AGiXTSDK {
         url: &str,
         collection_number: Option<&str>,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/agent/{}/learn/url", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/agent/{}/learn/url", self.api_root, agent_id))
             .json(&serde_json::json!({
                 "url": url,
                 "collection_number": collection_number.unwrap_or("0"),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -609,10 +1025,24 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
+    /// Add training sources to an existing agent by ID, as `training_urls` do
+    /// when the agent is created. Returns the server's message for each URL.
+    ///
+    /// All URLs are checked before the first is sent, and the agent stops
+    /// learning at the first URL the server rejects.
+    pub async fn add_training_urls(&self, agent_id: &str, urls: &[String]) -> Result<Vec<String>> {
+        validate_training_urls(urls)?;
+        let mut messages = Vec::with_capacity(urls.len());
+        for url in urls {
+            messages.push(self.learn_url(agent_id, url, None).await?);
+        }
+        Ok(messages)
+    }
+
     /// Teach agent content from a file by ID.
     pub async fn learn_file(
         &self,
@@ -621,17 +1051,15 @@ impl super::AGiXTSDK {
         file_content: &str,
         collection_number: Option<&str>,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/agent/{}/learn/file", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/agent/{}/learn/file", self.api_root, agent_id))
             .json(&serde_json::json!({
                 "file_name": file_name,
                 "file_content": file_content,
                 "collection_number": collection_number.unwrap_or("0"),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -645,10 +1073,62 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
+    /// Teach an agent several URLs, running up to `max_concurrency` requests at once.
+    ///
+    /// Returns one `(url, result)` pair per URL in input order; a failed URL does
+    /// not stop the others.
+    pub async fn learn_urls_concurrent(
+        &self,
+        agent_id: &str,
+        urls: &[String],
+        collection_number: Option<&str>,
+        max_concurrency: usize,
+    ) -> Vec<(String, Result<String>)> {
+        // Build the futures up front: a closure returning borrowing futures
+        // inside the stream trips the `Send` check of the async trait.
+        let requests: Vec<_> = urls
+            .iter()
+            .map(|url| async move {
+                let result = self.learn_url(agent_id, url, collection_number).await;
+                (url.clone(), result)
+            })
+            .collect();
+        stream::iter(requests)
+            .buffered(max_concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Teach an agent several files given as `(file_name, file_content)` pairs,
+    /// running up to `max_concurrency` requests at once.
+    ///
+    /// Returns one `(file_name, result)` pair per file in input order.
+    pub async fn learn_files_concurrent(
+        &self,
+        agent_id: &str,
+        files: &[(String, String)],
+        collection_number: Option<&str>,
+        max_concurrency: usize,
+    ) -> Vec<(String, Result<String>)> {
+        let requests: Vec<_> = files
+            .iter()
+            .map(|(file_name, file_content)| async move {
+                let result = self
+                    .learn_file(agent_id, file_name, file_content, collection_number)
+                    .await;
+                (file_name.clone(), result)
+            })
+            .collect();
+        stream::iter(requests)
+            .buffered(max_concurrency.max(1))
+            .collect()
+            .await
+    }
+
     // ==================== Memory ====================
 
     /// Get agent memories.
@@ -659,19 +1139,17 @@ impl super::AGiXTSDK {
         limit: Option<i32>,
         min_relevance: Option<f32>,
         collection_number: Option<&str>,
-    ) -> Result<Vec<serde_json::Value>> {
-        let response = self
+    ) -> Result<Vec<Memory>> {
+        let request = self
             .client
-            .post(&format!("{}/v1/agent/{}/memory/query", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/agent/{}/memory/query", self.api_root, agent_id))
             .json(&serde_json::json!({
                 "user_input": user_input,
                 "limit": limit.unwrap_or(10),
                 "min_relevance_score": min_relevance.unwrap_or(0.0),
                 "collection_number": collection_number.unwrap_or("0"),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -682,10 +1160,10 @@ impl super::AGiXTSDK {
 
         #[derive(serde::Deserialize)]
         struct MemoriesResponse {
-            memories: Vec<serde_json::Value>,
+            memories: Vec<Memory>,
         }
 
-        let result: MemoriesResponse = serde_json::from_str(&text)?;
+        let result: MemoriesResponse = text.json()?;
         Ok(result.memories)
     }
 
@@ -696,15 +1174,16 @@ impl super::AGiXTSDK {
         memory_id: &str,
         collection_number: Option<&str>,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .delete(&format!("{}/v1/agent/{}/memory/{}", self.base_uri, agent_id, memory_id))
-            .headers(self.headers.lock().await.clone())
+            .delete(format!(
+                "{}/agent/{}/memory/{}",
+                self.api_root, agent_id, memory_id
+            ))
             .json(&serde_json::json!({
                 "collection_number": collection_number.unwrap_or("0"),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -718,7 +1197,7 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -728,15 +1207,13 @@ impl super::AGiXTSDK {
         agent_id: &str,
         collection_number: Option<&str>,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .delete(&format!("{}/v1/agent/{}/memory", self.base_uri, agent_id))
-            .headers(self.headers.lock().await.clone())
+            .delete(format!("{}/agent/{}/memory", self.api_root, agent_id))
             .json(&serde_json::json!({
                 "collection_number": collection_number.unwrap_or(""),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -750,7 +1227,29 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 }
+
+/// Check that every training URL is an absolute `http` or `https` URL.
+pub(crate) fn validate_training_urls(urls: &[String]) -> Result<()> {
+    for url in urls {
+        match url::Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            Ok(_) => {
+                return Err(crate::Error::InvalidInput(format!(
+                    "training URL '{}' must use http or https",
+                    url
+                )))
+            }
+            Err(e) => {
+                return Err(crate::Error::InvalidInput(format!(
+                    "invalid training URL '{}': {}",
+                    url, e
+                )))
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,793 @@
+//! The `AgixtApi` trait describing the client's endpoint methods.
+//!
+//! The method list lives in `for_each_api_method!` so the trait, its
+//! implementation for `AGiXTSDK` and the blocking client stay in sync.
+//! New endpoint methods are added to the list below.
+
+use crate::AGiXTSDK;
+use prelude::*;
+
+/// Types used in the signatures of the listed methods.
+pub(crate) mod prelude {
+    pub(crate) use crate::client::{Orchestration, OrchestrationResult, ToolRegistry};
+    pub(crate) use crate::diff::ConversationDiff;
+    pub(crate) use crate::error::Result;
+    pub(crate) use crate::models::{
+        Agent, AgentConfig, AgentCreated, Artifact, Attachment, ChainProgress, ChainRun,
+        ChatCompletions, ChatResponse, Company, CompanyUser, ConversationMetadata,
+        ConversationSummary, DownloadProgress, DpoTriple, Embedder, ExecuteCommand, Extension,
+        FleetPlan, FleetSpec, HistoryQuery, Invitation, LoginOutcome, Memory, Message,
+        MessageCreated, Notification, Page, PromptOptions, ProviderDetails, ProviderFallback,
+        ProviderService, ServerInfo, SettingsValidation, SsoProvider, Task, TaskFrequency,
+        TaskOutcome, Transcription, TranscriptionOptions, UploadSource, User, UserUpdate,
+        UserWallet, VoiceReply, Webhook, WorkspaceFile,
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) use crate::models::{
+        BackupManifest, ExportFormat, FineTuneOptions, GeneratedImage, ImageOptions,
+        PromptSyncReport,
+    };
+    pub(crate) use crate::templates::ConversationTemplate;
+    pub(crate) use serde_json::Value;
+    pub(crate) use std::collections::HashMap;
+    pub(crate) use std::time::Duration;
+}
+
+/// Invoke `$callback!` with the signature of every async endpoint method.
+macro_rules! for_each_api_method {
+    ($callback:ident) => {
+        $callback! {
+            // ==================== Client ====================
+            fn set_api_key(&self, api_key: &str) -> Result<()>;
+            fn login(
+                &self,
+                username: &str,
+                password: &str,
+                mfa_token: Option<&str>,
+            ) -> Result<Option<String>>;
+            fn login_magic_link(&self, email: &str, otp: &str) -> Result<LoginOutcome>;
+            #[cfg(feature = "totp")]
+            fn login_with_secret(&self, email: &str, totp_secret: &str) -> Result<LoginOutcome>;
+            #[allow(clippy::too_many_arguments)]
+            fn register_user(
+                &self,
+                email: &str,
+                password: &str,
+                confirm_password: &str,
+                first_name: Option<&str>,
+                last_name: Option<&str>,
+                username: Option<&str>,
+                organization_name: Option<&str>,
+            ) -> Result<Value>;
+            fn get_mfa_setup(&self) -> Result<Value>;
+            fn enable_mfa(&self, mfa_token: &str) -> Result<Value>;
+            fn disable_mfa(&self, password: Option<&str>, mfa_token: Option<&str>) -> Result<Value>;
+            fn change_password(
+                &self,
+                current_password: &str,
+                new_password: &str,
+                confirm_password: &str,
+            ) -> Result<Value>;
+            fn set_password(&self, new_password: &str, confirm_password: &str) -> Result<Value>;
+            fn user_exists(&self, email: &str) -> Result<bool>;
+            fn update_user(&self, updates: UserUpdate) -> Result<User>;
+            fn get_user(&self) -> Result<User>;
+            fn get_user_wallet(&self) -> Result<UserWallet>;
+            fn delete_user(&self) -> Result<String>;
+            fn logout(&self) -> Result<()>;
+            fn get_chains(&self) -> Result<Vec<Value>>;
+            fn get_chain_id_by_name(&self, chain_name: &str) -> Result<Option<String>>;
+            fn get_chain(&self, chain_id: &str) -> Result<Value>;
+            fn get_chain_responses(&self, chain_id: &str) -> Result<Value>;
+            fn get_chain_runs(&self, chain_id: &str) -> Result<Vec<ChainRun>>;
+            fn get_chain_run(&self, chain_id: &str, run_id: &str) -> Result<ChainRun>;
+            fn get_chain_args(&self, chain_id: &str) -> Result<Vec<String>>;
+            fn run_chain(
+                &self,
+                chain_id: &str,
+                user_input: &str,
+                agent_id: Option<&str>,
+                all_responses: Option<bool>,
+                from_step: Option<i32>,
+                chain_args: Option<HashMap<String, Value>>,
+            ) -> Result<Value>;
+            fn run_chain_with_progress(
+                &self,
+                chain_id: &str,
+                user_input: &str,
+                agent_id: Option<&str>,
+                chain_args: Option<HashMap<String, Value>>,
+                progress: &(dyn Fn(ChainProgress) + Send + Sync),
+            ) -> Result<Value>;
+            fn run_chain_step(
+                &self,
+                chain_id: &str,
+                step_number: i32,
+                user_input: &str,
+                agent_id: Option<&str>,
+                chain_args: Option<HashMap<String, Value>>,
+            ) -> Result<Value>;
+            fn add_chain(&self, chain_name: &str) -> Result<Value>;
+            fn import_chain(&self, chain_name: &str, steps: Value) -> Result<String>;
+            fn chain_from_conversation(
+                &self,
+                conversation_id: &str,
+                chain_name: &str,
+                agent_name: &str,
+            ) -> Result<String>;
+            fn rename_chain(&self, chain_id: &str, new_name: &str) -> Result<String>;
+            fn delete_chain(&self, chain_id: &str) -> Result<String>;
+            fn add_step(
+                &self,
+                chain_id: &str,
+                step_number: i32,
+                agent_id: &str,
+                prompt_type: &str,
+                prompt: Value,
+            ) -> Result<String>;
+            fn update_step(
+                &self,
+                chain_id: &str,
+                step_number: i32,
+                agent_id: &str,
+                prompt_type: &str,
+                prompt: Value,
+            ) -> Result<String>;
+            fn move_step(
+                &self,
+                chain_id: &str,
+                old_step_number: i32,
+                new_step_number: i32,
+            ) -> Result<String>;
+            fn delete_step(&self, chain_id: &str, step_number: i32) -> Result<String>;
+            fn add_prompt(
+                &self,
+                prompt_name: &str,
+                prompt: &str,
+                prompt_category: Option<&str>,
+            ) -> Result<Value>;
+            fn get_prompt(&self, prompt_id: &str) -> Result<Value>;
+            fn get_prompts(&self, prompt_category: Option<&str>) -> Result<Vec<Value>>;
+            fn get_all_prompts(&self) -> Result<Value>;
+            fn get_prompt_id_by_name(
+                &self,
+                prompt_name: &str,
+                category: Option<&str>,
+            ) -> Result<Option<String>>;
+            fn get_prompt_categories(&self) -> Result<Vec<Value>>;
+            fn get_prompts_by_category_id(&self, category_id: &str) -> Result<Vec<Value>>;
+            fn get_prompt_args(&self, prompt_id: &str) -> Result<Value>;
+            fn delete_prompt(&self, prompt_id: &str) -> Result<String>;
+            fn update_prompt(&self, prompt_id: &str, prompt: &str) -> Result<String>;
+            fn rename_prompt(&self, prompt_id: &str, new_name: &str) -> Result<String>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn sync_prompts_from_dir(
+                &self,
+                dir: &std::path::Path,
+                category: &str,
+                dry_run: bool,
+            ) -> Result<PromptSyncReport>;
+            fn get_companies(&self) -> Result<Vec<Company>>;
+            fn get_company(&self, company_id: &str) -> Result<Company>;
+            fn create_company(&self, name: &str, parent_company_id: Option<&str>) -> Result<Company>;
+            fn update_company(&self, company_id: &str, name: &str) -> Result<Company>;
+            fn get_company_users(&self, company_id: &str) -> Result<Vec<CompanyUser>>;
+            fn get_company_agents(&self, company_id: &str) -> Result<Vec<Agent>>;
+            fn get_company_persona(&self, company_id: &str) -> Result<String>;
+            fn update_company_persona(&self, company_id: &str, persona: &str) -> Result<String>;
+            fn set_company(&self, company_id: Option<&str>) -> Result<()>;
+            fn create_invitation(
+                &self,
+                email: &str,
+                role: Option<&str>,
+                company_id: Option<&str>,
+            ) -> Result<Value>;
+            fn get_invitations(&self, company_id: Option<&str>) -> Result<Vec<Invitation>>;
+            fn delete_invitation(&self, invitation_id: &str) -> Result<String>;
+            fn get_oauth_providers(&self) -> Result<Vec<Value>>;
+            fn get_sso_providers(&self) -> Result<Vec<SsoProvider>>;
+            fn oauth2_login(
+                &self,
+                provider: &str,
+                code: &str,
+                referrer: Option<&str>,
+            ) -> Result<Option<String>>;
+            fn text_to_speech(&self, text: &str, voice: Option<&str>) -> Result<Vec<u8>>;
+            fn transcribe_audio(&self, audio: UploadSource, model: &str) -> Result<String>;
+            fn transcribe_audio_with_options(
+                &self,
+                audio: UploadSource,
+                model: &str,
+                options: TranscriptionOptions,
+            ) -> Result<Transcription>;
+            fn transcribe_url(&self, url: &str, model: &str) -> Result<String>;
+            fn transcribe_url_with_options(
+                &self,
+                url: &str,
+                model: &str,
+                options: TranscriptionOptions,
+            ) -> Result<Transcription>;
+            fn voice_chat(
+                &self,
+                agent_id: &str,
+                audio: UploadSource,
+                conversation_id: &str,
+                voice: Option<&str>,
+            ) -> Result<VoiceReply>;
+            fn generate_image(&self, prompt: &str, n: Option<i32>) -> Result<Value>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn generate_image_to_file(
+                &self,
+                prompt: &str,
+                path: &std::path::Path,
+                options: ImageOptions,
+            ) -> Result<GeneratedImage>;
+            fn health(&self) -> Result<bool>;
+            fn server_info(&self) -> Result<ServerInfo>;
+            fn wait_for_ready(&self, timeout: Duration, interval: Duration) -> Result<()>;
+            fn shutdown(&self, deadline: Option<Duration>) -> Result<()>;
+
+            // ==================== Agents ====================
+            fn get_agents(&self) -> Result<Vec<Agent>>;
+            fn get_agent_id_by_name(&self, agent_name: &str) -> Result<Option<String>>;
+            fn add_agent(
+                &self,
+                agent_name: &str,
+                settings: Option<HashMap<String, Value>>,
+                commands: Option<HashMap<String, Value>>,
+                training_urls: Option<Vec<String>>,
+            ) -> Result<AgentCreated>;
+            fn copy_agent(&self, source_agent_id: &str, new_name: &str) -> Result<AgentCreated>;
+            fn import_agent(
+                &self,
+                agent_name: &str,
+                settings: Option<HashMap<String, Value>>,
+                commands: Option<HashMap<String, Value>>,
+            ) -> Result<AgentCreated>;
+            fn rename_agent(&self, agent_id: &str, new_name: &str) -> Result<Value>;
+            fn update_agent_settings(
+                &self,
+                agent_id: &str,
+                settings: HashMap<String, Value>,
+                agent_name: Option<&str>,
+            ) -> Result<String>;
+            fn set_provider_fallback(
+                &self,
+                agent_id: &str,
+                fallback: &ProviderFallback,
+            ) -> Result<String>;
+            fn update_agent_commands(
+                &self,
+                agent_id: &str,
+                commands: HashMap<String, Value>,
+            ) -> Result<String>;
+            fn delete_agent(&self, agent_id: &str) -> Result<String>;
+            fn apply_fleet(&self, spec: &FleetSpec, dry_run: bool) -> Result<FleetPlan>;
+            fn get_agentconfig(&self, agent_id: &str) -> Result<AgentConfig>;
+            fn get_commands(&self, agent_id: &str) -> Result<HashMap<String, Value>>;
+            fn set_command_enabled(
+                &self,
+                agent_id: &str,
+                command_name: &str,
+                enabled: bool,
+            ) -> Result<String>;
+            fn toggle_command(
+                &self,
+                agent_id: &str,
+                command_name: &str,
+                enable: bool,
+            ) -> Result<String>;
+            fn execute_command(
+                &self,
+                agent_id: &str,
+                command_name: &str,
+                command_args: HashMap<String, Value>,
+                conversation: Option<&str>,
+            ) -> Result<Value>;
+            fn run_command(&self, agent_id: &str, command: &ExecuteCommand) -> Result<Value>;
+            fn prompt_agent(
+                &self,
+                agent_id: &str,
+                prompt_name: &str,
+                prompt_args: HashMap<String, Value>,
+            ) -> Result<String>;
+            fn prompt_agent_with_options(
+                &self,
+                agent_id: &str,
+                prompt_name: &str,
+                prompt_args: HashMap<String, Value>,
+                options: &PromptOptions,
+            ) -> Result<String>;
+            fn prompt_agent_with_files(
+                &self,
+                agent_id: &str,
+                prompt_name: &str,
+                prompt_args: HashMap<String, Value>,
+                files: Vec<UploadSource>,
+            ) -> Result<String>;
+            fn instruct(
+                &self,
+                agent_id: &str,
+                user_input: &str,
+                conversation_id: &str,
+            ) -> Result<String>;
+            fn chat(
+                &self,
+                agent_id: &str,
+                user_input: &str,
+                conversation_id: &str,
+                context_results: Option<i32>,
+            ) -> Result<String>;
+            fn chat_with_options(
+                &self,
+                agent_id: &str,
+                user_input: &str,
+                conversation_id: &str,
+                options: &PromptOptions,
+            ) -> Result<String>;
+            fn chat_completions(&self, request: &ChatCompletions) -> Result<ChatResponse>;
+            fn orchestrate(
+                &self,
+                input: &str,
+                orchestration: &Orchestration,
+            ) -> Result<OrchestrationResult>;
+            fn ask(&self, agent_name: &str, question: &str) -> Result<String>;
+            fn chat_completions_batch(
+                &self,
+                requests: &[ChatCompletions],
+                max_concurrency: usize,
+            ) -> Vec<Result<ChatResponse>>;
+            fn run_with_tools(
+                &self,
+                request: &ChatCompletions,
+                tools: &ToolRegistry,
+            ) -> Result<ChatResponse>;
+            fn get_persona(&self, agent_id: &str) -> Result<Value>;
+            fn update_persona(&self, agent_id: &str, persona: &str) -> Result<String>;
+            fn get_agent_extensions(&self, agent_id: &str) -> Result<Vec<Value>>;
+            fn get_extension_commands(&self, agent_id: &str) -> Result<Vec<Extension>>;
+            fn submit_feedback(
+                &self,
+                agent_id: &str,
+                message: &str,
+                user_input: &str,
+                feedback: &str,
+                positive: bool,
+                conversation_id: Option<&str>,
+            ) -> Result<String>;
+            fn positive_feedback(
+                &self,
+                agent_id: &str,
+                message: &str,
+                user_input: &str,
+                feedback: &str,
+                conversation_id: Option<&str>,
+            ) -> Result<String>;
+            fn negative_feedback(
+                &self,
+                agent_id: &str,
+                message: &str,
+                user_input: &str,
+                feedback: &str,
+                conversation_id: Option<&str>,
+            ) -> Result<String>;
+            fn learn_text(
+                &self,
+                agent_id: &str,
+                user_input: &str,
+                text: &str,
+                collection_number: Option<&str>,
+            ) -> Result<String>;
+            fn learn_url(
+                &self,
+                agent_id: &str,
+                url: &str,
+                collection_number: Option<&str>,
+            ) -> Result<String>;
+            fn add_training_urls(&self, agent_id: &str, urls: &[String]) -> Result<Vec<String>>;
+            fn learn_file(
+                &self,
+                agent_id: &str,
+                file_name: &str,
+                file_content: &str,
+                collection_number: Option<&str>,
+            ) -> Result<String>;
+            fn learn_urls_concurrent(
+                &self,
+                agent_id: &str,
+                urls: &[String],
+                collection_number: Option<&str>,
+                max_concurrency: usize,
+            ) -> Vec<(String, Result<String>)>;
+            fn learn_files_concurrent(
+                &self,
+                agent_id: &str,
+                files: &[(String, String)],
+                collection_number: Option<&str>,
+                max_concurrency: usize,
+            ) -> Vec<(String, Result<String>)>;
+            fn get_agent_memories(
+                &self,
+                agent_id: &str,
+                user_input: &str,
+                limit: Option<i32>,
+                min_relevance: Option<f32>,
+                collection_number: Option<&str>,
+            ) -> Result<Vec<Memory>>;
+            fn export_agent_memories(&self, agent_id: &str) -> Result<Vec<Memory>>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn export_memories_to_file(
+                &self,
+                agent_id: &str,
+                path: &std::path::Path,
+                format: ExportFormat,
+            ) -> Result<u64>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn import_conversation_from_openai_json(
+                &self,
+                agent_id: &str,
+                path: &std::path::Path,
+            ) -> Result<Vec<Value>>;
+            fn get_dpo_response(
+                &self,
+                agent_id: &str,
+                user_input: &str,
+                injected_memories: Option<i32>,
+                conversation_id: Option<&str>,
+            ) -> Result<DpoTriple>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn export_fine_tuning_dataset(
+                &self,
+                conversation_ids: &[&str],
+                path: &std::path::Path,
+                options: &FineTuneOptions,
+            ) -> Result<u64>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn export_dpo_dataset(
+                &self,
+                agent_id: &str,
+                prompts: &[&str],
+                path: &std::path::Path,
+                options: &FineTuneOptions,
+            ) -> Result<u64>;
+            fn delete_agent_memory(
+                &self,
+                agent_id: &str,
+                memory_id: &str,
+                collection_number: Option<&str>,
+            ) -> Result<String>;
+            fn wipe_agent_memory(
+                &self,
+                agent_id: &str,
+                collection_number: Option<&str>,
+            ) -> Result<String>;
+
+            // ==================== Conversations ====================
+            fn get_conversations(&self, limit: Option<i32>, page: Option<i32>) -> Result<Page<Value>>;
+            fn get_all_conversations(&self) -> Result<Vec<Value>>;
+            fn get_conversations_with_ids(&self) -> Result<Vec<HashMap<String, String>>>;
+            fn get_conversation_id_by_name(&self, conversation_name: &str) -> Result<Option<String>>;
+            fn get_conversation_metadata(&self, conversation_id: &str) -> Result<ConversationMetadata>;
+            fn get_conversation(
+                &self,
+                conversation_id: &str,
+                limit: Option<i32>,
+                page: Option<i32>,
+            ) -> Result<Vec<Message>>;
+            fn get_conversation_with(
+                &self,
+                conversation_id: &str,
+                query: &HistoryQuery,
+            ) -> Result<Vec<Message>>;
+            fn get_full_conversation(&self, conversation_id: &str) -> Result<Vec<Message>>;
+            fn fork_conversation(&self, conversation_id: &str, message_id: &str) -> Result<Value>;
+            fn compare_conversations(&self, base_id: &str, other_id: &str) -> Result<ConversationDiff>;
+            fn new_conversation(
+                &self,
+                agent_id: &str,
+                conversation_name: &str,
+                conversation_content: Option<Vec<Message>>,
+            ) -> Result<Value>;
+            fn new_conversation_from_template(
+                &self,
+                agent_id: &str,
+                template: &ConversationTemplate,
+            ) -> Result<Value>;
+            fn rename_conversation(&self, conversation_id: &str, new_name: &str) -> Result<Value>;
+            fn summarize_conversation(
+                &self,
+                agent_id: &str,
+                conversation_id: &str,
+            ) -> Result<ConversationSummary>;
+            fn delete_conversation(&self, conversation_id: &str) -> Result<String>;
+            fn delete_conversation_message(
+                &self,
+                conversation_id: &str,
+                message_id: &str,
+            ) -> Result<String>;
+            fn update_conversation_message(
+                &self,
+                conversation_id: &str,
+                message_id: &str,
+                new_message: &str,
+            ) -> Result<String>;
+            fn text_to_speech_for_message(
+                &self,
+                conversation_id: &str,
+                message_id: &str,
+            ) -> Result<String>;
+            fn submit_message_feedback(
+                &self,
+                conversation_id: &str,
+                message_id: &str,
+                positive: bool,
+                comment: Option<&str>,
+            ) -> Result<String>;
+            fn positive_message_feedback(
+                &self,
+                conversation_id: &str,
+                message_id: &str,
+                comment: Option<&str>,
+            ) -> Result<String>;
+            fn negative_message_feedback(
+                &self,
+                conversation_id: &str,
+                message_id: &str,
+                comment: Option<&str>,
+            ) -> Result<String>;
+            fn new_conversation_message(
+                &self,
+                role: &str,
+                message: &str,
+                conversation_id: &str,
+            ) -> Result<MessageCreated>;
+
+            fn get_notifications(&self) -> Result<Vec<Notification>>;
+
+            // ==================== Providers ====================
+            fn get_providers(&self) -> Result<Vec<Value>>;
+            fn get_providers_by_service(&self, service: ProviderService) -> Result<Vec<Value>>;
+            fn get_provider_settings(&self, provider_name: &str) -> Result<ProviderDetails>;
+            fn validate_agent_settings(
+                &self,
+                settings: &HashMap<String, Value>,
+            ) -> Result<SettingsValidation>;
+            fn update_provider_settings(
+                &self,
+                provider_name: &str,
+                settings: HashMap<String, Value>,
+            ) -> Result<String>;
+            fn get_all_providers_with_settings(&self) -> Result<Vec<ProviderDetails>>;
+            fn get_provider_models(&self, provider_name: &str) -> Result<Vec<String>>;
+            fn get_embed_providers(&self) -> Result<Vec<String>>;
+            fn get_embedders(&self) -> Result<Vec<Embedder>>;
+            fn get_embedding_providers_with_details(&self) -> Result<Vec<ProviderDetails>>;
+            fn get_extension_settings(&self) -> Result<Value>;
+            fn get_extensions(&self) -> Result<Vec<Value>>;
+            fn get_command_args(&self, command_name: &str) -> Result<Value>;
+
+            // ==================== Tasks ====================
+            fn create_task(
+                &self,
+                agent_id: &str,
+                title: &str,
+                description: &str,
+                due_date: Option<&str>,
+                conversation_id: Option<&str>,
+            ) -> Result<Task>;
+            fn create_reoccurring_task(
+                &self,
+                agent_id: &str,
+                title: &str,
+                description: &str,
+                frequency: TaskFrequency,
+                start_date: &str,
+                end_date: Option<&str>,
+            ) -> Result<Vec<Task>>;
+            fn get_tasks(&self) -> Result<Vec<Task>>;
+            fn get_task(&self, task_id: &str) -> Result<Task>;
+            fn wait_for_task(
+                &self,
+                task_id: &str,
+                poll_interval: Duration,
+                timeout: Duration,
+            ) -> Result<TaskOutcome>;
+            fn update_task(
+                &self,
+                task_id: &str,
+                title: Option<&str>,
+                description: Option<&str>,
+                due_date: Option<&str>,
+            ) -> Result<Task>;
+            fn complete_task(&self, task_id: &str) -> Result<Task>;
+            fn delete_task(&self, task_id: &str) -> Result<String>;
+
+            // ==================== GitHub ====================
+            fn learn_github_repo(
+                &self,
+                agent_id: &str,
+                github_repo: &str,
+                github_branch: Option<&str>,
+                github_token: Option<&str>,
+                collection_number: Option<&str>,
+            ) -> Result<String>;
+            fn code_review(&self, agent_id: &str, github_repo: &str, pr_diff: &str) -> Result<String>;
+
+            // ==================== Workspace ====================
+            fn upload_file_to_workspace(
+                &self,
+                agent_id: &str,
+                source: UploadSource,
+                destination: Option<&str>,
+            ) -> Result<String>;
+            fn list_workspace_files(&self, agent_id: &str) -> Result<Vec<WorkspaceFile>>;
+            fn download_workspace_file(&self, agent_id: &str, file_path: &str) -> Result<Vec<u8>>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn download_workspace_file_to(
+                &self,
+                agent_id: &str,
+                file_path: &str,
+                local_path: &std::path::Path,
+            ) -> Result<u64>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn download_workspace_file_with_progress(
+                &self,
+                agent_id: &str,
+                file_path: &str,
+                local_path: &std::path::Path,
+                progress: &(dyn Fn(DownloadProgress) + Send + Sync),
+            ) -> Result<u64>;
+
+            // ==================== Artifacts ====================
+            fn get_conversation_attachments(&self, conversation_id: &str) -> Result<Vec<Attachment>>;
+            fn download_artifact(&self, artifact: &Artifact) -> Result<Vec<u8>>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn download_artifact_to(
+                &self,
+                artifact: &Artifact,
+                dir: &std::path::Path,
+            ) -> Result<std::path::PathBuf>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn download_url_to(
+                &self,
+                url: &str,
+                path: &std::path::Path,
+                progress: &(dyn Fn(DownloadProgress) + Send + Sync),
+            ) -> Result<u64>;
+
+            // ==================== Backup ====================
+            #[cfg(not(target_arch = "wasm32"))]
+            fn export_account(&self, dir: &std::path::Path) -> Result<BackupManifest>;
+            #[cfg(not(target_arch = "wasm32"))]
+            fn import_account(&self, dir: &std::path::Path) -> Result<BackupManifest>;
+            // ==================== Webhooks ====================
+            fn create_webhook(
+                &self,
+                name: &str,
+                target_url: &str,
+                event_types: Vec<String>,
+                secret: Option<&str>,
+            ) -> Result<Webhook>;
+            fn get_webhooks(&self) -> Result<Vec<Webhook>>;
+            fn update_webhook(
+                &self,
+                webhook_id: &str,
+                target_url: Option<&str>,
+                event_types: Option<Vec<String>>,
+                secret: Option<&str>,
+                active: Option<bool>,
+            ) -> Result<Webhook>;
+            fn delete_webhook(&self, webhook_id: &str) -> Result<String>;
+        }
+    };
+}
+#[cfg_attr(
+    any(not(feature = "blocking"), target_arch = "wasm32"),
+    allow(unused_imports)
+)]
+pub(crate) use for_each_api_method;
+
+/// Define the `AgixtApi` trait with one method per endpoint.
+///
+/// `async_trait` must see the expanded method list, so the whole trait is
+/// generated here rather than expanding the list inside the trait body.
+macro_rules! define_trait {
+    ($($(#[$meta:meta])* fn $name:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;)*) => {
+        /// The AGiXT API surface as an object-safe trait.
+        ///
+        /// `AGiXTSDK` implements this trait, so application code can depend on
+        /// `dyn AgixtApi` (or a generic `impl AgixtApi`) and swap in a hand-written
+        /// or `mockall`-generated fake in unit tests. Every method has a default
+        /// body returning `Error::Other` naming the method (as the only entry
+        /// of batch results), so a fake only implements the methods it needs
+        /// and a call to any other one fails instead of panicking.
+        ///
+        /// # Example
+        /// ```rust
+        /// use agixt_sdk::{Agent, AgixtApi, Result};
+        /// use std::collections::HashMap;
+        ///
+        /// async fn agent_count(api: &dyn AgixtApi) -> Result<usize> {
+        ///     Ok(api.get_agents().await?.len())
+        /// }
+        ///
+        /// struct FakeApi;
+        ///
+        /// #[async_trait::async_trait]
+        /// impl AgixtApi for FakeApi {
+        ///     async fn get_agents(&self) -> Result<Vec<Agent>> {
+        ///         Ok(vec![Agent {
+        ///             id: "1".to_string(),
+        ///             name: "XT".to_string(),
+        ///             settings: HashMap::new(),
+        ///             commands: HashMap::new(),
+        ///             extra: HashMap::new(),
+        ///         }])
+        ///     }
+        /// }
+        ///
+        /// # tokio_test_block_on(async {
+        /// assert_eq!(agent_count(&FakeApi).await.unwrap(), 1);
+        /// assert!(FakeApi.get_user().await.is_err());
+        /// # });
+        /// # fn tokio_test_block_on(f: impl std::future::Future<Output = ()>) {
+        /// #     tokio::runtime::Runtime::new().unwrap().block_on(f)
+        /// # }
+        /// ```
+        #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+        #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+        pub trait AgixtApi: Send + Sync {
+            $(
+                $(#[$meta])*
+                #[doc = concat!("See [`AGiXTSDK::", stringify!($name), "`].")]
+                #[allow(unused_variables)]
+                async fn $name(&self $(, $arg: $ty)*) -> $ret {
+                    NotImplemented::not_implemented(concat!("AgixtApi::", stringify!($name)))
+                }
+            )*
+        }
+
+        #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+        #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+        impl AgixtApi for AGiXTSDK {
+            $(
+                $(#[$meta])*
+                async fn $name(&self $(, $arg: $ty)*) -> $ret {
+                    crate::error::WithOperation::with_operation(
+                        AGiXTSDK::$name(self $(, $arg)*).await,
+                        stringify!($name),
+                    )
+                }
+            )*
+        }
+    };
+}
+
+/// Return value of an `AgixtApi` method a fake does not implement.
+trait NotImplemented {
+    fn not_implemented(method: &'static str) -> Self;
+}
+
+impl<T> NotImplemented for Result<T> {
+    fn not_implemented(method: &'static str) -> Self {
+        Err(crate::error::Error::Other(format!(
+            "{} not implemented",
+            method
+        )))
+    }
+}
+
+/// Batch methods report the error as their only entry.
+impl<T: NotImplemented> NotImplemented for Vec<T> {
+    fn not_implemented(method: &'static str) -> Self {
+        vec![T::not_implemented(method)]
+    }
+}
+
+impl<T: NotImplemented> NotImplemented for (String, T) {
+    fn not_implemented(method: &'static str) -> Self {
+        (String::new(), T::not_implemented(method))
+    }
+}
+
+for_each_api_method!(define_trait);
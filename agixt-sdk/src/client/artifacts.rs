@@ -0,0 +1,132 @@
+//! Files shared in conversations: listing attachments and downloading what
+//! agents linked.
+
+use crate::error::Result;
+use crate::models::{extract_attachments, Artifact, Attachment};
+use reqwest::header::CONTENT_LENGTH;
+
+impl super::AGiXTSDK {
+    // ==================== Artifacts ====================
+
+    /// Download an artifact found by
+    /// [`extract_artifacts`](crate::models::extract_artifacts).
+    pub async fn download_artifact(&self, artifact: &Artifact) -> Result<Vec<u8>> {
+        self.fetch_url(&artifact.url).await
+    }
+
+    /// Download an artifact into `dir` under its file name, returning the
+    /// path written. The file is streamed to disk, see
+    /// [`download_url_to`](Self::download_url_to).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::models::extract_artifacts;
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK, conversation_id: &str) -> agixt_sdk::Result<()> {
+    /// let history = client.get_conversation(conversation_id, None, None).await?;
+    /// for artifact in extract_artifacts(&history) {
+    ///     let path = client.download_artifact_to(&artifact, "outputs".as_ref()).await?;
+    ///     println!("saved {}", path.display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn download_artifact_to(
+        &self,
+        artifact: &Artifact,
+        dir: &std::path::Path,
+    ) -> Result<std::path::PathBuf> {
+        let name = match artifact.file_name() {
+            "" | "." | ".." => "artifact",
+            name => name,
+        };
+        let path = dir.join(name);
+        self.download_url_to(&artifact.url, &path, &|_| {}).await?;
+        Ok(path)
+    }
+
+    /// List the files and images attached to the messages of a conversation
+    /// by ID, with their workspace URLs and sizes, in message order.
+    ///
+    /// Sizes are read from the `Content-Length` of a `HEAD` request per file
+    /// and are `None` when the server does not report one.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// for attachment in client.get_conversation_attachments("conversation-id").await? {
+    ///     println!("{} ({:?} bytes) from {}", attachment.name, attachment.size, attachment.role);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_conversation_attachments(
+        &self,
+        conversation_id: &str,
+    ) -> Result<Vec<Attachment>> {
+        let history = self.get_full_conversation(conversation_id).await?;
+        let mut attachments = extract_attachments(&history);
+        let sizes = futures_util::future::join_all(
+            attachments
+                .iter()
+                .map(|attachment| self.url_size(&attachment.url)),
+        )
+        .await;
+        for (attachment, size) in attachments.iter_mut().zip(sizes) {
+            attachment.size = size;
+        }
+        Ok(attachments)
+    }
+
+    /// Fetch a path or URL. Paths and URLs on the AGiXT server are requested
+    /// with the client's credentials, other hosts without them.
+    pub(crate) async fn fetch_url(&self, url: &str) -> Result<Vec<u8>> {
+        let (request, on_server) = self.url_request(reqwest::Method::GET, url);
+        if on_server {
+            return self.execute(request).await?.bytes().await;
+        }
+        let response = request.send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Size of the file at a path or URL, from its `Content-Length`.
+    async fn url_size(&self, url: &str) -> Option<u64> {
+        let (request, on_server) = self.url_request(reqwest::Method::HEAD, url);
+        let length = if on_server {
+            self.execute(request)
+                .await
+                .ok()?
+                .headers()
+                .get(CONTENT_LENGTH)
+                .cloned()
+        } else {
+            let response = request.send().await.ok()?.error_for_status().ok()?;
+            response.headers().get(CONTENT_LENGTH).cloned()
+        };
+        length?.to_str().ok()?.parse().ok()
+    }
+
+    /// Build a request for a path or URL, and tell whether it goes to the
+    /// AGiXT server and so needs the client's credentials.
+    pub(super) fn url_request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> (reqwest::RequestBuilder, bool) {
+        if url.starts_with('/') {
+            let request = self
+                .client
+                .request(method, format!("{}{}", self.base_uri, url));
+            return (request, true);
+        }
+        let same_origin = reqwest::Url::parse(url)
+            .ok()
+            .zip(reqwest::Url::parse(&self.base_uri).ok())
+            .is_some_and(|(target, server)| target.origin() == server.origin());
+        (self.client.request(method, url), same_origin)
+    }
+}
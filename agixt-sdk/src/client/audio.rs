@@ -0,0 +1,176 @@
+//! Speech transcription and voice round trips.
+
+use crate::error::Result;
+use crate::models::{Transcription, TranscriptionOptions, UploadSource, VoiceReply};
+
+impl super::AGiXTSDK {
+    // ==================== Transcription ====================
+
+    /// Transcribe audio to text with the transcription provider of the agent
+    /// named `model`.
+    pub async fn transcribe_audio(&self, audio: UploadSource, model: &str) -> Result<String> {
+        let transcription = self
+            .transcribe_audio_with_options(audio, model, TranscriptionOptions::new())
+            .await?;
+        Ok(transcription.text)
+    }
+
+    /// Transcribe audio with a language hint, guiding prompt or response
+    /// format, e.g. to get segment and word timings for subtitles.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, TranscriptionOptions, UploadSource};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let transcription = client
+    ///     .transcribe_audio_with_options(
+    ///         UploadSource::Path("talk.wav".into()),
+    ///         "XT",
+    ///         TranscriptionOptions::new().language("en").segment_timestamps(),
+    ///     )
+    ///     .await?;
+    /// for segment in &transcription.segments {
+    ///     println!("{:.2} --> {:.2} {}", segment.start, segment.end, segment.text);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transcribe_audio_with_options(
+        &self,
+        audio: UploadSource,
+        model: &str,
+        options: TranscriptionOptions,
+    ) -> Result<Transcription> {
+        let (file_name, data) = audio.into_parts()?;
+        let file = reqwest::multipart::Part::bytes(data).file_name(file_name);
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", model.to_string());
+        if let Some(language) = &options.language {
+            form = form.text("language", language.clone());
+        }
+        if let Some(prompt) = &options.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+        if let Some(response_format) = &options.response_format {
+            form = form.text("response_format", response_format.clone());
+        }
+        for granularity in &options.timestamp_granularities {
+            form = form.text("timestamp_granularities[]", granularity.clone());
+        }
+
+        let request = self
+            .client
+            .post(format!("{}/audio/transcriptions", self.api_root))
+            .multipart(form);
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        if options.is_plain_text() {
+            return Ok(Transcription {
+                text: text.to_string(),
+                ..Transcription::default()
+            });
+        }
+        text.json()
+    }
+
+    /// Transcribe audio hosted at a URL, e.g. a podcast episode, with the
+    /// transcription provider of the agent named `model`.
+    ///
+    /// The SDK downloads the file and uploads it like a local one, so any URL
+    /// the client can reach works, including paths and links on the AGiXT
+    /// server. Links to other hosts are fetched without the client's
+    /// credentials.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let text = client
+    ///     .transcribe_url("https://cdn.example.com/episodes/42.mp3", "XT")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn transcribe_url(&self, url: &str, model: &str) -> Result<String> {
+        let transcription = self
+            .transcribe_url_with_options(url, model, TranscriptionOptions::new())
+            .await?;
+        Ok(transcription.text)
+    }
+
+    /// Transcribe audio hosted at a URL with a language hint, guiding prompt
+    /// or response format, like
+    /// [`transcribe_audio_with_options`](Self::transcribe_audio_with_options).
+    pub async fn transcribe_url_with_options(
+        &self,
+        url: &str,
+        model: &str,
+        options: TranscriptionOptions,
+    ) -> Result<Transcription> {
+        let data = self.fetch_url(url).await?;
+        let audio = UploadSource::bytes(url_file_name(url), data);
+        self.transcribe_audio_with_options(audio, model, options)
+            .await
+    }
+
+    // ==================== Voice ====================
+
+    /// Answer a spoken question: transcribe `audio`, chat with the agent by ID in
+    /// the given conversation and synthesize the answer with `voice`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, UploadSource};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let reply = client
+    ///     .voice_chat("agent-id", UploadSource::Path("question.wav".into()), "conversation-id", None)
+    ///     .await?;
+    /// println!("You said: {}", reply.transcript);
+    /// std::fs::write("answer.wav", &reply.answer_audio).unwrap();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn voice_chat(
+        &self,
+        agent_id: &str,
+        audio: UploadSource,
+        conversation_id: &str,
+        voice: Option<&str>,
+    ) -> Result<VoiceReply> {
+        let agent = self.get_agentconfig(agent_id).await?;
+        let transcript = self.transcribe_audio(audio, &agent.name).await?;
+        let answer_text = self
+            .chat(agent_id, &transcript, conversation_id, None)
+            .await?;
+        let answer_audio = self.text_to_speech(&answer_text, voice).await?;
+        Ok(VoiceReply {
+            transcript,
+            answer_text,
+            answer_audio,
+        })
+    }
+}
+
+/// Last path segment of a URL or server path, to name the uploaded file so
+/// the server can tell the audio format from its extension.
+fn url_file_name(url: &str) -> String {
+    let path = match reqwest::Url::parse(url) {
+        Ok(url) => url.path().to_string(),
+        Err(_) => url.split(['?', '#']).next().unwrap_or_default().to_string(),
+    };
+    match path.rsplit('/').next() {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => "audio".to_string(),
+    }
+}
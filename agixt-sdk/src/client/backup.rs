@@ -0,0 +1,277 @@
+//! Whole-account backup to a directory and restore from it.
+
+use super::prompt_sync::prompt_text;
+use crate::error::{Error, Result};
+use crate::models::{AgentConfig, BackupManifest, Message};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Layout version written to `manifest.json`.
+const BACKUP_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct PromptEntry {
+    name: String,
+    category: String,
+    content: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChainEntry {
+    name: String,
+    #[serde(default)]
+    steps: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ConversationEntry {
+    name: String,
+    /// Name of the agent the conversation belongs to, if known
+    #[serde(default)]
+    agent_name: Option<String>,
+    messages: Vec<Message>,
+}
+
+impl super::AGiXTSDK {
+    // ==================== Backup ====================
+
+    /// Back up the account's agents, prompts, chains and conversations into
+    /// the directory `dir`, which is created if needed, for disaster recovery
+    /// with [`import_account`](Self::import_account).
+    ///
+    /// The directory holds `manifest.json`, `agents.json`, `prompts.json`,
+    /// `chains.json` and `conversations.jsonl` (one conversation per line).
+    /// Agent settings are written as is, including provider API keys, so keep
+    /// the backup somewhere private.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let manifest = client.export_account("backups/2024-06-01".as_ref()).await?;
+    /// println!(
+    ///     "backed up {} agents and {} conversations",
+    ///     manifest.agents, manifest.conversations
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_account(&self, dir: &Path) -> Result<BackupManifest> {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| Error::Other(format!("cannot create {}: {}", dir.display(), e)))?;
+
+        let mut agents = Vec::new();
+        let mut agent_names = HashMap::new();
+        for agent in self.get_agents().await? {
+            let config = self.get_agentconfig(&agent.id).await?;
+            agent_names.insert(agent.id, config.name.clone());
+            agents.push(config);
+        }
+
+        let mut prompts = Vec::new();
+        for category in self.get_prompt_categories().await? {
+            let Some(category) = category.get("name").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            for prompt in self.get_prompts(Some(category)).await? {
+                let (Some(name), id) = (
+                    prompt.get("name").and_then(|v| v.as_str()),
+                    prompt.get("id").and_then(|v| v.as_str()),
+                ) else {
+                    continue;
+                };
+                let content = match (prompt_text(&prompt), id) {
+                    (Some(text), _) => text,
+                    (None, Some(id)) => {
+                        prompt_text(&self.get_prompt(id).await?).unwrap_or_default()
+                    }
+                    (None, None) => continue,
+                };
+                prompts.push(PromptEntry {
+                    name: name.to_string(),
+                    category: category.to_string(),
+                    content,
+                });
+            }
+        }
+
+        let mut chains = Vec::new();
+        for chain in self.get_chains().await? {
+            let (Some(id), Some(name)) = (
+                chain.get("id").and_then(|v| v.as_str()),
+                chain.get("name").and_then(|v| v.as_str()),
+            ) else {
+                continue;
+            };
+            let data = self.get_chain(id).await?;
+            chains.push(ChainEntry {
+                name: name.to_string(),
+                steps: data.get("steps").cloned().unwrap_or(Value::Array(vec![])),
+            });
+        }
+
+        let mut conversations = String::new();
+        let mut conversation_count = 0;
+        for conversation in self.get_all_conversations().await? {
+            let (Some(id), Some(name)) = (
+                conversation.get("id").and_then(|v| v.as_str()),
+                conversation.get("name").and_then(|v| v.as_str()),
+            ) else {
+                continue;
+            };
+            let agent_id = match conversation.get("agent_id").and_then(|v| v.as_str()) {
+                Some(agent_id) => Some(agent_id.to_string()),
+                None => self.get_conversation_metadata(id).await?.agent_id,
+            };
+            let entry = ConversationEntry {
+                name: name.to_string(),
+                agent_name: agent_id.and_then(|id| agent_names.get(&id).cloned()),
+                messages: self.get_full_conversation(id).await?,
+            };
+            conversations.push_str(&serde_json::to_string(&entry)?);
+            conversations.push('\n');
+            conversation_count += 1;
+        }
+
+        let manifest = BackupManifest {
+            version: BACKUP_VERSION,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            source: self.base_uri.clone(),
+            agents: agents.len(),
+            prompts: prompts.len(),
+            chains: chains.len(),
+            conversations: conversation_count,
+        };
+        write_file(
+            &dir.join("agents.json"),
+            serde_json::to_vec_pretty(&agents)?,
+        )
+        .await?;
+        write_file(
+            &dir.join("prompts.json"),
+            serde_json::to_vec_pretty(&prompts)?,
+        )
+        .await?;
+        write_file(
+            &dir.join("chains.json"),
+            serde_json::to_vec_pretty(&chains)?,
+        )
+        .await?;
+        write_file(&dir.join("conversations.jsonl"), conversations.into_bytes()).await?;
+        // The manifest goes last, so a directory with one holds a complete backup
+        write_file(
+            &dir.join("manifest.json"),
+            serde_json::to_vec_pretty(&manifest)?,
+        )
+        .await?;
+        Ok(manifest)
+    }
+
+    /// Restore a backup written by [`export_account`](Self::export_account)
+    /// from the directory `dir`, returning how many of each item were created.
+    ///
+    /// Items that already exist are left as they are: agents and chains with
+    /// the same name, prompts with the same name and category, and
+    /// conversations with the same name. Conversations are restored for the
+    /// agent of the same name; those whose agent is unknown are skipped.
+    pub async fn import_account(&self, dir: &Path) -> Result<BackupManifest> {
+        let manifest: BackupManifest = read_json(&dir.join("manifest.json")).await?;
+        if manifest.version > BACKUP_VERSION {
+            return Err(Error::InvalidInput(format!(
+                "backup version {} is newer than this SDK supports ({})",
+                manifest.version, BACKUP_VERSION
+            )));
+        }
+        let mut restored = BackupManifest {
+            version: manifest.version,
+            created_at: manifest.created_at,
+            source: manifest.source,
+            ..BackupManifest::default()
+        };
+
+        let agents: Vec<AgentConfig> = read_json(&dir.join("agents.json")).await?;
+        let mut agent_ids: HashMap<String, String> = self
+            .get_agents()
+            .await?
+            .into_iter()
+            .map(|agent| (agent.name, agent.id))
+            .collect();
+        for agent in agents {
+            if agent_ids.contains_key(&agent.name) {
+                continue;
+            }
+            let created = self
+                .import_agent(&agent.name, Some(agent.settings), Some(agent.commands))
+                .await?;
+            agent_ids.insert(agent.name, created.agent_id);
+            restored.agents += 1;
+        }
+
+        let prompts: Vec<PromptEntry> = read_json(&dir.join("prompts.json")).await?;
+        for prompt in prompts {
+            if self
+                .get_prompt_id_by_name(&prompt.name, Some(&prompt.category))
+                .await?
+                .is_none()
+            {
+                self.add_prompt(&prompt.name, &prompt.content, Some(&prompt.category))
+                    .await?;
+                restored.prompts += 1;
+            }
+        }
+
+        let chains: Vec<ChainEntry> = read_json(&dir.join("chains.json")).await?;
+        for chain in chains {
+            if self.get_chain_id_by_name(&chain.name).await?.is_none() {
+                self.import_chain(&chain.name, chain.steps).await?;
+                restored.chains += 1;
+            }
+        }
+
+        let path = dir.join("conversations.jsonl");
+        let conversations = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| Error::Other(format!("cannot read {}: {}", path.display(), e)))?;
+        let mut existing: HashSet<String> = self
+            .get_conversations_with_ids()
+            .await?
+            .into_iter()
+            .filter_map(|conversation| conversation.get("name").cloned())
+            .collect();
+        for line in conversations.lines().filter(|line| !line.trim().is_empty()) {
+            let conversation: ConversationEntry = serde_json::from_str(line)?;
+            let agent_id = conversation
+                .agent_name
+                .as_ref()
+                .and_then(|name| agent_ids.get(name));
+            let Some(agent_id) = agent_id else {
+                continue;
+            };
+            if !existing.insert(conversation.name.clone()) {
+                continue;
+            }
+            self.new_conversation(agent_id, &conversation.name, Some(conversation.messages))
+                .await?;
+            restored.conversations += 1;
+        }
+        Ok(restored)
+    }
+}
+
+async fn write_file(path: &Path, data: Vec<u8>) -> Result<()> {
+    tokio::fs::write(path, data)
+        .await
+        .map_err(|e| Error::Other(format!("cannot write {}: {}", path.display(), e)))
+}
+
+async fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|e| Error::Other(format!("cannot read {}: {}", path.display(), e)))?;
+    Ok(serde_json::from_slice(&data)?)
+}
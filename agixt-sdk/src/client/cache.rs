@@ -0,0 +1,121 @@
+//! In-memory TTL cache for read-only GET endpoints.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use web_time::Instant;
+
+/// Response bodies keyed by request URL, each valid for a fixed time-to-live.
+///
+/// Bodies that came with an `ETag` are kept after they expire, so the next
+/// request can revalidate them with `If-None-Match` instead of downloading
+/// them again.
+#[derive(Debug)]
+pub(crate) struct ResponseCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    stored: Instant,
+    body: String,
+    etag: Option<String>,
+}
+
+impl ResponseCache {
+    /// Create an empty cache whose entries expire after `ttl`.
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How long entries stay valid.
+    pub(crate) fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Return the cached body for `key` if it has not expired.
+    pub(crate) fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some(entry) if entry.stored.elapsed() < self.ttl => Some(entry.body.clone()),
+            Some(entry) if entry.etag.is_some() => None,
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// The `ETag` of the body cached for `key`, to revalidate it with.
+    pub(crate) fn etag(&self, key: &str) -> Option<String> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(key)?.etag.clone()
+    }
+
+    /// Restart the time-to-live of the body cached for `key` after the server
+    /// confirmed it is unchanged, and return it.
+    pub(crate) fn refresh(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries.get_mut(key)?;
+        entry.stored = Instant::now();
+        Some(entry.body.clone())
+    }
+
+    /// Store a response body for `key`, with its `ETag` if it had one.
+    pub(crate) fn insert(&self, key: String, body: String, etag: Option<String>) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                key,
+                Entry {
+                    stored: Instant::now(),
+                    body,
+                    etag,
+                },
+            );
+    }
+
+    /// Drop all cached responses.
+    pub(crate) fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire() {
+        let cache = ResponseCache::new(Duration::from_millis(20));
+        cache.insert("a".to_string(), "body".to_string(), None);
+        cache.insert(
+            "e".to_string(),
+            "tagged".to_string(),
+            Some("\"v1\"".to_string()),
+        );
+        assert_eq!(cache.get("a").as_deref(), Some("body"));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.etag("a"), None);
+        // Expired bodies with an ETag stay for revalidation
+        assert_eq!(cache.get("e"), None);
+        assert_eq!(cache.etag("e").as_deref(), Some("\"v1\""));
+        assert_eq!(cache.refresh("e").as_deref(), Some("tagged"));
+        assert_eq!(cache.get("e").as_deref(), Some("tagged"));
+
+        cache.insert("b".to_string(), "body".to_string(), None);
+        cache.clear();
+        assert_eq!(cache.get("b"), None);
+    }
+}
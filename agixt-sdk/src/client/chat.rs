@@ -0,0 +1,357 @@
+//! OpenAI-compatible chat completions using the /v1/chat/completions endpoint.
+
+use super::ToolRegistry;
+use crate::error::{Error, Result};
+use crate::models::{ChatCompletions, ChatResponse, Message, ResponseFormat};
+use futures_util::stream::{self, StreamExt};
+use serde::de::DeserializeOwned;
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    super::response::Response,
+    super::stream::{SseDecoder, StreamEvent, StreamEvents},
+    super::usage::UsageTracker,
+    std::collections::VecDeque,
+    std::sync::Arc,
+};
+
+impl super::AGiXTSDK {
+    // ==================== Chat Completions ====================
+
+    /// Send an OpenAI-compatible chat completion. The request's `model` is the
+    /// agent name.
+    pub async fn chat_completions(&self, request: &ChatCompletions) -> Result<ChatResponse> {
+        let started = web_time::Instant::now();
+        let result = async {
+            let request = self
+                .client
+                .post(format!("{}/chat/completions", self.api_root))
+                .json(request);
+            let response = self.execute(request).await?;
+
+            let status = response.status();
+            let text = response.text().await?;
+
+            if self.verbose {
+                self.parse_response(status, &text).await?;
+            }
+
+            let response: ChatResponse = text.json()?;
+            self.record_usage(&response.usage);
+            Ok(response)
+        }
+        .await;
+        self.record_transcript("chat_completion", &request.model, request, &result, started);
+        result
+    }
+
+    /// Ask an agent a single question and return its answer text.
+    ///
+    /// The question is sent in a new throwaway conversation, which is deleted
+    /// once the agent has answered, so no history is shared between calls.
+    /// Failing to delete it does not fail the call.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let answer = client.ask("XT", "What is the capital of Portugal?").await?;
+    /// println!("{}", answer);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ask(&self, agent_name: &str, question: &str) -> Result<String> {
+        let conversation = format!("ask-{}", uuid::Uuid::new_v4());
+        let request = ChatCompletions::builder()
+            .model(agent_name)
+            .message("user", question)
+            .conversation(conversation.as_str())
+            .build()?;
+        let response = self.chat_completions(&request).await;
+
+        if let Ok(Some(id)) = self.get_conversation_id_by_name(&conversation).await {
+            let _ = self.delete_conversation(&id).await;
+        }
+
+        let choice = response?
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Other("the server returned no answer".to_string()))?;
+        Ok(choice.message.text())
+    }
+
+    /// Send a chat completion asking for JSON and deserialize the answer into
+    /// `T`.
+    ///
+    /// Requests without a `response_format` ask for a JSON object. An answer
+    /// wrapped in a Markdown code fence is accepted. When the answer does not
+    /// deserialize, the model is shown the error and asked again, up to
+    /// `max_attempts` requests in total; the last error is returned after that.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, ChatCompletions, ResponseFormat};
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct City {
+    ///     name: String,
+    ///     population: u64,
+    /// }
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let request = ChatCompletions::builder()
+    ///     .model("my_agent")
+    ///     .message("user", "The largest city of Portugal as {\"name\", \"population\"}")
+    ///     .response_format(ResponseFormat::JsonObject)
+    ///     .build()?;
+    /// let city: City = client.chat_completions_typed(&request, 3).await?;
+    /// println!("{} has {} inhabitants", city.name, city.population);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_completions_typed<T: DeserializeOwned>(
+        &self,
+        request: &ChatCompletions,
+        max_attempts: usize,
+    ) -> Result<T> {
+        let mut request = request.clone();
+        request
+            .response_format
+            .get_or_insert(ResponseFormat::JsonObject);
+
+        let mut attempt = 1;
+        loop {
+            let response = self.chat_completions(&request).await?;
+            let message = response
+                .choices
+                .into_iter()
+                .next()
+                .ok_or_else(|| Error::Other("the server returned no answer".to_string()))?
+                .message;
+            let error = match serde_json::from_str(json_answer(&message.text())) {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= max_attempts => return Err(e.into()),
+                Err(e) => e,
+            };
+            let messages = request.messages.get_or_insert_with(Vec::new);
+            messages.push(message);
+            messages.push(Message::user(format!(
+                "That answer could not be parsed: {}. Reply again with only the JSON.",
+                error
+            )));
+            attempt += 1;
+        }
+    }
+
+    /// Send a chat completion with `stream` enabled and return its events as
+    /// they arrive: answer text, tool call pieces, agent activities and the
+    /// finish reason.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, ChatCompletions, StreamEvent};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let request = ChatCompletions::builder()
+    ///     .model("my_agent")
+    ///     .message("user", "Tell me a story")
+    ///     .build()?;
+    /// let mut events = client.chat_completions_stream(&request).await?;
+    /// while let Some(event) = events.next().await {
+    ///     match event? {
+    ///         StreamEvent::ContentDelta { text, .. } => print!("{}", text),
+    ///         StreamEvent::Activity { text, .. } => eprintln!("[{}]", text),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn chat_completions_stream(&self, request: &ChatCompletions) -> Result<StreamEvents> {
+        let mut body = request.clone();
+        body.stream = Some(true);
+        let request = self
+            .client
+            .post(format!("{}/chat/completions", self.api_root))
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .json(&body);
+        let response = self.execute(request).await?;
+        Ok(event_stream(response, self.usage.clone()))
+    }
+
+    /// Send many chat completions, running up to `max_concurrency` requests at
+    /// once.
+    ///
+    /// Returns one result per request in input order; a failed request does not
+    /// stop the others.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, ChatCompletions};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let inputs = ["2 + 2", "The capital of France", "A haiku about Rust"];
+    /// let requests = inputs
+    ///     .iter()
+    ///     .map(|input| ChatCompletions::builder().model("my_agent").message("user", *input).build())
+    ///     .collect::<agixt_sdk::Result<Vec<_>>>()?;
+    /// for (input, result) in inputs.iter().zip(client.chat_completions_batch(&requests, 4).await) {
+    ///     match result {
+    ///         Ok(response) => println!("{}: {}", input, response.choices[0].message.text()),
+    ///         Err(e) => println!("{}: failed: {}", input, e),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_completions_batch(
+        &self,
+        requests: &[ChatCompletions],
+        max_concurrency: usize,
+    ) -> Vec<Result<ChatResponse>> {
+        // Futures are built up front, as in `learn_urls_concurrent`, to keep
+        // the async trait's `Send` check happy
+        let requests: Vec<_> = requests
+            .iter()
+            .map(|request| self.chat_completions(request))
+            .collect();
+        stream::iter(requests)
+            .buffered(max_concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Send a chat completion offering the registry's tools, run every tool the
+    /// model calls and feed the results back until the model gives a final answer.
+    ///
+    /// A tool that fails or is not registered reports `{"error": "..."}` to the
+    /// model instead of aborting the loop. Returns an error if the model is still
+    /// calling tools after the registry's `max_iterations` round trips.
+    pub async fn run_with_tools(
+        &self,
+        request: &ChatCompletions,
+        tools: &ToolRegistry,
+    ) -> Result<ChatResponse> {
+        let mut request = request.clone();
+        request
+            .tools
+            .get_or_insert_with(Vec::new)
+            .extend(tools.tools().iter().cloned());
+
+        for _ in 0..tools.iteration_limit() {
+            let response = self.chat_completions(&request).await?;
+            let message = match response.choices.first() {
+                Some(choice) if !choice.message.tool_calls.is_empty() => choice.message.clone(),
+                _ => return Ok(response),
+            };
+
+            let mut results = Vec::with_capacity(message.tool_calls.len());
+            for call in &message.tool_calls {
+                // Malformed arguments are passed through as a string
+                let arguments = call
+                    .function
+                    .arguments_json()
+                    .unwrap_or_else(|_| serde_json::Value::String(call.function.arguments.clone()));
+                let output = match tools.call(&call.function.name, arguments).await {
+                    Ok(serde_json::Value::String(s)) => s,
+                    Ok(value) => value.to_string(),
+                    Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+                };
+                results.push(Message::tool(call.id.clone(), output));
+            }
+
+            let messages = request.messages.get_or_insert_with(Vec::new);
+            messages.push(message);
+            messages.extend(results);
+        }
+
+        Err(Error::Other(format!(
+            "model was still calling tools after {} iterations",
+            tools.iteration_limit()
+        )))
+    }
+}
+
+/// The JSON of an answer, without a surrounding Markdown code fence.
+fn json_answer(answer: &str) -> &str {
+    let answer = answer.trim();
+    match answer.strip_prefix("```") {
+        Some(fenced) => {
+            let body = fenced.split_once('\n').map_or("", |(_, body)| body);
+            body.trim_end().trim_end_matches("```").trim()
+        }
+        None => answer,
+    }
+}
+
+/// Events of a `text/event-stream` response.
+#[cfg(not(target_arch = "wasm32"))]
+fn event_stream(response: Response, usage: Option<Arc<UsageTracker>>) -> StreamEvents {
+    struct State {
+        response: Response,
+        decoder: SseDecoder,
+        pending: VecDeque<Result<StreamEvent>>,
+        finished: bool,
+        usage: Option<Arc<UsageTracker>>,
+    }
+
+    impl State {
+        fn queue(&mut self, data: &str) {
+            match StreamEvent::parse(data) {
+                Ok(events) => {
+                    for event in events {
+                        if let (
+                            StreamEvent::Done {
+                                usage: Some(usage), ..
+                            },
+                            Some(tracker),
+                        ) = (&event, &self.usage)
+                        {
+                            tracker.record(usage);
+                        }
+                        self.pending.push_back(Ok(event));
+                    }
+                }
+                Err(e) => self.pending.push_back(Err(e)),
+            }
+        }
+    }
+
+    let state = State {
+        response,
+        decoder: SseDecoder::default(),
+        pending: VecDeque::new(),
+        finished: false,
+        usage,
+    };
+    Box::pin(stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((event, state));
+            }
+            if state.finished {
+                return None;
+            }
+            match state.response.chunk().await {
+                Ok(Some(chunk)) => {
+                    for data in state.decoder.push(&chunk) {
+                        state.queue(&data);
+                    }
+                }
+                Ok(None) => {
+                    state.finished = true;
+                    if let Some(data) = state.decoder.finish() {
+                        state.queue(&data);
+                    }
+                }
+                Err(e) => {
+                    state.finished = true;
+                    state.pending.push_back(Err(e));
+                }
+            }
+        }
+    }))
+}
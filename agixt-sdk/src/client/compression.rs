@@ -0,0 +1,32 @@
+//! Gzip compression of large request bodies.
+
+use crate::error::{Error, Result};
+use flate2::write::GzEncoder;
+use reqwest::header::{HeaderValue, CONTENT_ENCODING};
+use std::io::Write;
+
+/// Gzip the body of `request` if it is held in memory and at least `min_size`
+/// bytes long. Streamed bodies and bodies that are already encoded are sent as is.
+pub(crate) fn gzip_body(request: &mut reqwest::Request, min_size: usize) -> Result<()> {
+    if request.headers().contains_key(CONTENT_ENCODING) {
+        return Ok(());
+    }
+    let body = match request.body().and_then(|body| body.as_bytes()) {
+        Some(body) if body.len() >= min_size => body,
+        _ => return Ok(()),
+    };
+
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(body)
+        .map_err(|e| Error::Other(format!("cannot compress request body: {}", e)))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| Error::Other(format!("cannot compress request body: {}", e)))?;
+
+    *request.body_mut() = Some(compressed.into());
+    request
+        .headers_mut()
+        .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    Ok(())
+}
@@ -1,20 +1,41 @@
 //! Conversation operations using /v1 endpoints with ID-based parameters.
 
-use crate::error::Result;
-use crate::models::Message;
+use crate::diff::{diff_conversations, ConversationDiff};
+use crate::error::{Error, Result};
+use crate::models::{
+    history_to_messages, ConversationMetadata, ConversationSummary, HistoryQuery, Message,
+    MessageCreated, Notification, Page,
+};
+use crate::templates::ConversationTemplate;
 use std::collections::HashMap;
 
+/// Page size used by the helpers that fetch every page.
+const FETCH_ALL_PAGE_SIZE: i32 = 100;
+
 impl super::AGiXTSDK {
     // ==================== Conversations ====================
 
-    /// Get all conversations. Returns list with conversation IDs.
-    pub async fn get_conversations(&self) -> Result<Vec<serde_json::Value>> {
-        let response = self
+    /// Get the user's conversations, optionally one page at a time.
+    ///
+    /// Without `limit` the server returns every conversation. `page` is
+    /// 1-based; `Page::total` is set when the server reports a total count.
+    pub async fn get_conversations(
+        &self,
+        limit: Option<i32>,
+        page: Option<i32>,
+    ) -> Result<Page<serde_json::Value>> {
+        let mut query = Vec::new();
+        if let Some(limit) = limit {
+            query.push(("limit", limit.to_string()));
+        }
+        if let Some(page) = page {
+            query.push(("page", page.to_string()));
+        }
+        let request = self
             .client
-            .get(&format!("{}/v1/conversations", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/conversations", self.api_root))
+            .query(&query);
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -23,27 +44,39 @@ impl super::AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        // Handle both list (v1) and dict (legacy) responses
-        let data: serde_json::Value = serde_json::from_str(&text)?;
-        if let Some(arr) = data.as_array() {
-            return Ok(arr.clone());
-        }
-        if let Some(obj) = data.as_object() {
-            if let Some(convs) = obj.get("conversations").and_then(|v| v.as_array()) {
-                return Ok(convs.clone());
+        let data: serde_json::Value = text.json()?;
+        let total = ["total", "total_count", "count"]
+            .iter()
+            .find_map(|key| data.get(key).and_then(|v| v.as_u64()));
+        Ok(Page {
+            items: conversation_list(&data),
+            total,
+            page: page.or(limit.map(|_| 1)),
+            limit,
+        })
+    }
+
+    /// Get every conversation of the user, requesting one page at a time
+    /// until the server has no more.
+    pub async fn get_all_conversations(&self) -> Result<Vec<serde_json::Value>> {
+        let mut conversations = Vec::new();
+        for page in 1.. {
+            let batch = self
+                .get_conversations(Some(FETCH_ALL_PAGE_SIZE), Some(page))
+                .await?;
+            let done = !batch.has_more() || batch.items.is_empty();
+            conversations.extend(batch);
+            if done {
+                break;
             }
         }
-        Ok(vec![])
+        Ok(conversations)
     }
 
     /// Get all conversations with their IDs.
     pub async fn get_conversations_with_ids(&self) -> Result<Vec<HashMap<String, String>>> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/conversations", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+        let request = self.client.get(format!("{}/conversations", self.api_root));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -53,9 +86,9 @@ impl super::AGiXTSDK {
         }
 
         // Parse as list of objects with id and name
-        let data: serde_json::Value = serde_json::from_str(&text)?;
+        let data: serde_json::Value = text.json()?;
         let mut result = Vec::new();
-        
+
         let conversations = if let Some(arr) = data.as_array() {
             arr.clone()
         } else if let Some(obj) = data.as_object() {
@@ -80,12 +113,15 @@ impl super::AGiXTSDK {
                 result.push(map);
             }
         }
-        
+
         Ok(result)
     }
 
     /// Get conversation ID by name. Returns None if not found.
-    pub async fn get_conversation_id_by_name(&self, conversation_name: &str) -> Result<Option<String>> {
+    pub async fn get_conversation_id_by_name(
+        &self,
+        conversation_name: &str,
+    ) -> Result<Option<String>> {
         let conversations = self.get_conversations_with_ids().await?;
         for conv in conversations {
             if let Some(name) = conv.get("name") {
@@ -97,23 +133,84 @@ impl super::AGiXTSDK {
         Ok(None)
     }
 
-    /// Get conversation history by ID.
+    /// Get the name, agent, timestamps and summary of a conversation without
+    /// fetching its message history.
+    pub async fn get_conversation_metadata(
+        &self,
+        conversation_id: &str,
+    ) -> Result<ConversationMetadata> {
+        let request = self.client.get(format!("{}/conversations", self.api_root));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        let data: serde_json::Value = text.json()?;
+        let entry = conversation_list(&data)
+            .into_iter()
+            .find(|item| item.get("id").and_then(|v| v.as_str()) == Some(conversation_id));
+        match entry {
+            Some(entry) => Ok(serde_json::from_value(entry)?),
+            None => Err(Error::NotFound(format!(
+                "Conversation '{}' not found",
+                conversation_id
+            ))),
+        }
+    }
+
+    /// Get conversation history by ID, oldest message first.
+    ///
+    /// Page parameters are sent as a query string; see `get_conversation_with`
+    /// to order or filter the messages.
     pub async fn get_conversation(
         &self,
         conversation_id: &str,
         limit: Option<i32>,
         page: Option<i32>,
     ) -> Result<Vec<Message>> {
-        let response = self
+        let query = HistoryQuery {
+            limit,
+            page,
+            ..HistoryQuery::default()
+        };
+        self.get_conversation_with(conversation_id, &query).await
+    }
+
+    /// Get a page of conversation history by ID in the given order, keeping
+    /// only messages of the requested roles.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, HistoryQuery};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let query = HistoryQuery::new().limit(50).newest_first().role("user");
+    /// for message in client.get_conversation_with("conversation-id", &query).await? {
+    ///     println!("{}", message.text());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_conversation_with(
+        &self,
+        conversation_id: &str,
+        query: &HistoryQuery,
+    ) -> Result<Vec<Message>> {
+        let request = self
             .client
-            .get(&format!("{}/v1/conversation/{}", self.base_uri, conversation_id))
-            .headers(self.headers.lock().await.clone())
+            .get(format!(
+                "{}/conversation/{}",
+                self.api_root, conversation_id
+            ))
             .query(&[
-                ("limit", limit.unwrap_or(100).to_string()),
-                ("page", page.unwrap_or(1).to_string()),
-            ])
-            .send()
-            .await?;
+                ("limit", query.limit.unwrap_or(100).to_string()),
+                ("page", query.page.unwrap_or(1).to_string()),
+            ]);
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -127,8 +224,42 @@ impl super::AGiXTSDK {
             conversation_history: Vec<Message>,
         }
 
-        let result: ConversationResponse = serde_json::from_str(&text)?;
-        Ok(result.conversation_history)
+        let result: ConversationResponse = text.json()?;
+        Ok(query.apply(result.conversation_history))
+    }
+
+    /// Get the complete history of a conversation, requesting one page of
+    /// messages at a time until a page comes back short.
+    pub async fn get_full_conversation(&self, conversation_id: &str) -> Result<Vec<Message>> {
+        self.conversation_messages_from(conversation_id, 0).await
+    }
+
+    /// Messages of a conversation from position `start` (0-based) to the end,
+    /// skipping the pages before it.
+    pub(crate) async fn conversation_messages_from(
+        &self,
+        conversation_id: &str,
+        start: usize,
+    ) -> Result<Vec<Message>> {
+        let page_size = FETCH_ALL_PAGE_SIZE as usize;
+        let first_page = start / page_size + 1;
+        let mut history = Vec::new();
+        for page in first_page.. {
+            let messages = self
+                .get_conversation(
+                    conversation_id,
+                    Some(FETCH_ALL_PAGE_SIZE),
+                    Some(page as i32),
+                )
+                .await?;
+            let last_page = messages.len() < page_size;
+            history.extend(messages);
+            if last_page {
+                break;
+            }
+        }
+        let skip = start - (first_page - 1) * page_size;
+        Ok(history.into_iter().skip(skip).collect())
     }
 
     /// Fork a conversation from a specific message.
@@ -137,12 +268,11 @@ impl super::AGiXTSDK {
         conversation_id: &str,
         message_id: &str,
     ) -> Result<serde_json::Value> {
-        let response = self
-            .client
-            .post(&format!("{}/v1/conversation/fork/{}/{}", self.base_uri, conversation_id, message_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+        let request = self.client.post(format!(
+            "{}/conversation/fork/{}/{}",
+            self.api_root, conversation_id, message_id
+        ));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -151,27 +281,60 @@ impl super::AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
+    }
+
+    /// Compare the full histories of two conversations, e.g. a fork against
+    /// the conversation it was forked from; see [`crate::diff`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let diff = client.compare_conversations("parent-id", "fork-id").await?;
+    /// println!("forked after {} messages", diff.common_prefix);
+    /// for message in diff.added() {
+    ///     println!("fork: {}", message.text());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn compare_conversations(
+        &self,
+        base_id: &str,
+        other_id: &str,
+    ) -> Result<ConversationDiff> {
+        let base = self.get_full_conversation(base_id).await?;
+        let other = self.get_full_conversation(other_id).await?;
+        Ok(diff_conversations(&base, &other))
     }
 
     /// Create a new conversation. Returns conversation with ID.
+    ///
+    /// User messages in `conversation_content` are stored with the `USER`
+    /// role of conversation history.
     pub async fn new_conversation(
         &self,
         agent_id: &str,
         conversation_name: &str,
         conversation_content: Option<Vec<Message>>,
     ) -> Result<serde_json::Value> {
-        let response = self
+        let mut content = serde_json::to_value(conversation_content.unwrap_or_default())?;
+        for message in content.as_array_mut().into_iter().flatten() {
+            if message["role"] == "user" {
+                message["role"] = "USER".into();
+            }
+        }
+        let request = self
             .client
-            .post(&format!("{}/v1/conversation", self.base_uri))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/conversation", self.api_root))
             .json(&serde_json::json!({
                 "conversation_name": conversation_name,
                 "agent_id": agent_id,
-                "conversation_content": conversation_content.unwrap_or_default(),
-            }))
-            .send()
-            .await?;
+                "conversation_content": content,
+            }));
+        let response = self.execute_create(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -180,7 +343,32 @@ impl super::AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
+    }
+
+    /// Create a conversation with an agent by ID, primed with the system
+    /// prompt and opening messages of `template` and named after its title.
+    /// Returns the conversation with its ID, as `new_conversation` does.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::templates;
+    ///
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let template = templates::find_builtin("customer-support").unwrap();
+    /// let conversation = client
+    ///     .new_conversation_from_template("agent-id", &template)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new_conversation_from_template(
+        &self,
+        agent_id: &str,
+        template: &ConversationTemplate,
+    ) -> Result<serde_json::Value> {
+        self.new_conversation(agent_id, &template.title, Some(template.seed_messages()))
+            .await
     }
 
     /// Rename a conversation by ID.
@@ -189,15 +377,16 @@ impl super::AGiXTSDK {
         conversation_id: &str,
         new_name: &str,
     ) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .put(&format!("{}/v1/conversation/{}", self.base_uri, conversation_id))
-            .headers(self.headers.lock().await.clone())
+            .put(format!(
+                "{}/conversation/{}",
+                self.api_root, conversation_id
+            ))
             .json(&serde_json::json!({
                 "new_conversation_name": new_name,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -206,17 +395,52 @@ impl super::AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
+    }
+
+    /// Ask the agent by ID for a short title and a one-paragraph abstract of
+    /// a conversation, e.g. to name chats automatically.
+    ///
+    /// The request is not logged to any conversation. Use `rename_conversation`
+    /// to apply the title.
+    pub async fn summarize_conversation(
+        &self,
+        agent_id: &str,
+        conversation_id: &str,
+    ) -> Result<ConversationSummary> {
+        let history = self.get_full_conversation(conversation_id).await?;
+        let transcript: Vec<String> = history_to_messages(&history)
+            .iter()
+            .map(|message| format!("{}: {}", message.normalized_role(), message.text()))
+            .collect();
+        if transcript.is_empty() {
+            return Err(Error::InvalidInput(format!(
+                "conversation {} has no messages to summarize",
+                conversation_id
+            )));
+        }
+
+        let user_input = format!(
+            "Summarize the conversation below. Answer only with JSON of the form \
+             {{\"title\": \"<at most 6 words>\", \"summary\": \"<2-3 sentences>\"}}.\n\n{}",
+            transcript.join("\n")
+        );
+        let mut args = HashMap::new();
+        args.insert("user_input".to_string(), serde_json::json!(user_input));
+        args.insert("disable_memory".to_string(), serde_json::json!(true));
+        args.insert("log_user_input".to_string(), serde_json::json!(false));
+        args.insert("log_output".to_string(), serde_json::json!(false));
+        let answer = self.prompt_agent(agent_id, "Think About It", args).await?;
+        Ok(parse_summary(&answer))
     }
 
     /// Delete a conversation by ID.
     pub async fn delete_conversation(&self, conversation_id: &str) -> Result<String> {
-        let response = self
-            .client
-            .delete(&format!("{}/v1/conversation/{}", self.base_uri, conversation_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+        let request = self.client.delete(format!(
+            "{}/conversation/{}",
+            self.api_root, conversation_id
+        ));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -230,7 +454,7 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -240,12 +464,11 @@ impl super::AGiXTSDK {
         conversation_id: &str,
         message_id: &str,
     ) -> Result<String> {
-        let response = self
-            .client
-            .delete(&format!("{}/v1/conversation/{}/message/{}", self.base_uri, conversation_id, message_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+        let request = self.client.delete(format!(
+            "{}/conversation/{}/message/{}",
+            self.api_root, conversation_id, message_id
+        ));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -259,7 +482,7 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -270,15 +493,83 @@ impl super::AGiXTSDK {
         message_id: &str,
         new_message: &str,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .put(&format!("{}/v1/conversation/{}/message/{}", self.base_uri, conversation_id, message_id))
-            .headers(self.headers.lock().await.clone())
+            .put(format!(
+                "{}/conversation/{}/message/{}",
+                self.api_root, conversation_id, message_id
+            ))
             .json(&serde_json::json!({
                 "new_message": new_message,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct MessageResponse {
+            message: String,
+        }
+
+        let result: MessageResponse = text.json()?;
+        Ok(result.message)
+    }
+
+    /// Generate speech for a message in a conversation by IDs, e.g. for a "play"
+    /// button on an agent reply. Returns the URL of the generated audio.
+    pub async fn text_to_speech_for_message(
+        &self,
+        conversation_id: &str,
+        message_id: &str,
+    ) -> Result<String> {
+        let request = self.client.get(format!(
+            "{}/conversation/{}/tts/{}",
+            self.api_root, conversation_id, message_id
+        ));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TtsResponse {
+            #[serde(alias = "audio_url", alias = "message")]
+            url: String,
+        }
+
+        let result: TtsResponse = text.json()?;
+        Ok(result.url)
+    }
+
+    /// Rate a message in a conversation by IDs (thumbs up or down), with an
+    /// optional comment.
+    pub async fn submit_message_feedback(
+        &self,
+        conversation_id: &str,
+        message_id: &str,
+        positive: bool,
+        comment: Option<&str>,
+    ) -> Result<String> {
+        let request = self
+            .client
+            .post(format!(
+                "{}/conversation/{}/message/{}/feedback",
+                self.api_root, conversation_id, message_id
+            ))
+            .json(&serde_json::json!({
+                "positive": positive,
+                "feedback": comment.unwrap_or(""),
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -292,27 +583,81 @@ impl super::AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
-    /// Add a new message to a conversation.
+    /// Give a message in a conversation a thumbs up.
+    pub async fn positive_message_feedback(
+        &self,
+        conversation_id: &str,
+        message_id: &str,
+        comment: Option<&str>,
+    ) -> Result<String> {
+        self.submit_message_feedback(conversation_id, message_id, true, comment)
+            .await
+    }
+
+    /// Give a message in a conversation a thumbs down.
+    pub async fn negative_message_feedback(
+        &self,
+        conversation_id: &str,
+        message_id: &str,
+        comment: Option<&str>,
+    ) -> Result<String> {
+        self.submit_message_feedback(conversation_id, message_id, false, comment)
+            .await
+    }
+
+    /// Add a new message to a conversation, returning the ID of the created
+    /// message.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let created = client
+    ///     .new_conversation_message("user", "Hello!", "conversation-id")
+    ///     .await?;
+    /// client
+    ///     .update_conversation_message("conversation-id", &created.id, "Hello there!")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn new_conversation_message(
         &self,
         role: &str,
         message: &str,
         conversation_id: &str,
-    ) -> Result<String> {
-        let response = self
+    ) -> Result<MessageCreated> {
+        let request = self
             .client
-            .post(&format!("{}/v1/conversation/{}/message", self.base_uri, conversation_id))
-            .headers(self.headers.lock().await.clone())
+            .post(format!(
+                "{}/conversation/{}/message",
+                self.api_root, conversation_id
+            ))
             .json(&serde_json::json!({
                 "role": role,
                 "message": message,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute_create(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    // ==================== Notifications ====================
+
+    /// Get the current user's notifications about agent activity in their conversations.
+    pub async fn get_notifications(&self) -> Result<Vec<Notification>> {
+        let request = self.client.get(format!("{}/notifications", self.api_root));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -322,11 +667,55 @@ impl super::AGiXTSDK {
         }
 
         #[derive(serde::Deserialize)]
-        struct MessageResponse {
-            message: String,
+        struct NotificationsResponse {
+            #[serde(default)]
+            notifications: Vec<Notification>,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
-        Ok(result.message)
+        let result: NotificationsResponse = text.json()?;
+        Ok(result.notifications)
+    }
+}
+
+/// Conversations of a `/conversations` response, which come as a list or as a
+/// map keyed by conversation ID, either bare or under `conversations`.
+fn conversation_list(data: &serde_json::Value) -> Vec<serde_json::Value> {
+    match data.get("conversations").unwrap_or(data) {
+        serde_json::Value::Array(items) => items.clone(),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .filter(|(_, item)| item.is_object())
+            .map(|(id, item)| {
+                let mut item = item.clone();
+                if let Some(obj) = item.as_object_mut() {
+                    obj.entry("id").or_insert_with(|| id.as_str().into());
+                }
+                item
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Read the agent's summary answer: the JSON object it was asked for, possibly
+/// in a code fence, or else the first line as title and the rest as summary.
+fn parse_summary(answer: &str) -> ConversationSummary {
+    let json = answer
+        .find('{')
+        .zip(answer.rfind('}'))
+        .and_then(|(start, end)| answer.get(start..=end))
+        .and_then(|json| serde_json::from_str::<ConversationSummary>(json).ok());
+    if let Some(summary) = json {
+        return summary;
+    }
+    let answer = answer.trim();
+    let (title, summary) = answer.split_once('\n').unwrap_or((answer, ""));
+    ConversationSummary {
+        title: title
+            .trim()
+            .trim_matches(|c| c == '#' || c == '"')
+            .trim()
+            .to_string(),
+        summary: summary.trim().to_string(),
     }
 }
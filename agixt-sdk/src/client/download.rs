@@ -0,0 +1,152 @@
+//! Downloads streamed to disk, with progress and resuming of partial files.
+
+use crate::error::{Error, Result};
+use crate::models::DownloadProgress;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+impl super::AGiXTSDK {
+    // ==================== Downloads ====================
+
+    /// Stream the file at a path or URL, such as a workspace link or the
+    /// audio URL from `text_to_speech_for_message`, to `path`. Returns the size
+    /// of the file.
+    ///
+    /// Data is written to `<path>.part` and renamed once complete. If a
+    /// previous download left a `.part` file, it is resumed with a range
+    /// request, or started over when the server does not support ranges.
+    /// `progress` is called after every chunk. Paths and URLs on the AGiXT
+    /// server are requested with the client's credentials, other hosts
+    /// without them.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK, url: &str) -> agixt_sdk::Result<()> {
+    /// client
+    ///     .download_url_to(url, "render.mp4".as_ref(), &|progress| {
+    ///         if let Some(fraction) = progress.fraction() {
+    ///             eprint!("\r{:.0}%", fraction * 100.0);
+    ///         }
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_url_to(
+        &self,
+        url: &str,
+        path: &Path,
+        progress: &(dyn Fn(DownloadProgress) + Send + Sync),
+    ) -> Result<u64> {
+        let (request, on_server) = self.url_request(reqwest::Method::GET, url);
+        self.stream_to_file(request, on_server, path, progress)
+            .await
+    }
+
+    /// Send `request` and stream its body to `path` through a `.part` file,
+    /// resuming one left by an earlier attempt.
+    pub(super) async fn stream_to_file(
+        &self,
+        request: reqwest::RequestBuilder,
+        on_server: bool,
+        path: &Path,
+        progress: &(dyn Fn(DownloadProgress) + Send + Sync),
+    ) -> Result<u64> {
+        let part = part_path(path);
+        let offset = match tokio::fs::metadata(&part).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+        let request = if offset > 0 {
+            request.header(RANGE, format!("bytes={}-", offset))
+        } else {
+            request
+        };
+
+        let mut response = if on_server {
+            match self.execute(request).await {
+                Ok(response) => response,
+                Err(e) if offset > 0 && e.status() == Some(416) => {
+                    return finish(&part, path, offset, progress).await;
+                }
+                Err(e) => return Err(e),
+            }
+        } else {
+            let response = request.send().await?;
+            if offset > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+                return finish(&part, path, offset, progress).await;
+            }
+            super::Response::new(response.error_for_status()?, None, self.deserialize_mode)
+        };
+
+        // A server that ignores the range sends the whole file again
+        let resumed = offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+        let mut received = if resumed { offset } else { 0 };
+        let total = total_size(response.headers(), received);
+        let io_error =
+            |e: std::io::Error| Error::Other(format!("cannot write {}: {}", part.display(), e));
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(&part)
+            .await
+            .map_err(io_error)?;
+        progress(DownloadProgress { received, total });
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await.map_err(io_error)?;
+            received += chunk.len() as u64;
+            progress(DownloadProgress { received, total });
+        }
+        file.flush().await.map_err(io_error)?;
+        drop(file);
+        tokio::fs::rename(&part, path)
+            .await
+            .map_err(|e| Error::Other(format!("cannot write {}: {}", path.display(), e)))?;
+        Ok(received)
+    }
+}
+
+/// Move a partial download that turned out to be complete into place.
+async fn finish(
+    part: &Path,
+    path: &Path,
+    size: u64,
+    progress: &(dyn Fn(DownloadProgress) + Send + Sync),
+) -> Result<u64> {
+    tokio::fs::rename(part, path)
+        .await
+        .map_err(|e| Error::Other(format!("cannot write {}: {}", path.display(), e)))?;
+    progress(DownloadProgress {
+        received: size,
+        total: Some(size),
+    });
+    Ok(size)
+}
+
+/// `<path>.part`, where a download is written until it completes.
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Size of the whole file, from `Content-Range: bytes a-b/total` on partial
+/// responses or the `Content-Length` plus what was already downloaded.
+fn total_size(headers: &reqwest::header::HeaderMap, offset: u64) -> Option<u64> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    if let Some(total) = header(CONTENT_RANGE)
+        .and_then(|range| range.rsplit_once('/'))
+        .and_then(|(_, total)| total.parse().ok())
+    {
+        return Some(total);
+    }
+    header(CONTENT_LENGTH)
+        .and_then(|length| length.parse::<u64>().ok())
+        .map(|length| offset + length)
+}
@@ -0,0 +1,75 @@
+//! Failover between several base URIs of one AGiXT deployment.
+
+use crate::error::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The base URIs a client may send requests to, in order of preference, and
+/// the one currently in use. Shared by all clones of a client, so once an
+/// endpoint fails every request moves on to the next one.
+#[derive(Debug)]
+pub(crate) struct Endpoints {
+    base_uris: Vec<String>,
+    active: AtomicUsize,
+}
+
+impl Endpoints {
+    /// Create the endpoint list; requests are built against the first URI.
+    pub(crate) fn new(base_uris: Vec<String>) -> Self {
+        Self {
+            base_uris,
+            active: AtomicUsize::new(0),
+        }
+    }
+
+    /// The base URI requests are currently sent to.
+    pub(crate) fn active(&self) -> &str {
+        &self.base_uris[self.active.load(Ordering::Relaxed)]
+    }
+
+    /// Send `request`, built against the first base URI, to the active
+    /// endpoint. When the connection fails, the following endpoints are tried
+    /// in turn and the first that answers becomes the active one.
+    ///
+    /// Requests to other hosts, and requests whose body cannot be replayed,
+    /// are sent once without failover.
+    pub(crate) async fn send(
+        &self,
+        client: &reqwest::Client,
+        mut request: reqwest::Request,
+    ) -> Result<reqwest::Response> {
+        let primary = &self.base_uris[0];
+        let Some(path) = request
+            .url()
+            .as_str()
+            .strip_prefix(primary.as_str())
+            .map(str::to_string)
+        else {
+            return Ok(client.execute(request).await?);
+        };
+
+        let mut index = self.active.load(Ordering::Relaxed);
+        let mut remaining = self.base_uris.len();
+        loop {
+            if let Ok(url) = reqwest::Url::parse(&format!("{}{}", self.base_uris[index], path)) {
+                *request.url_mut() = url;
+            }
+            remaining -= 1;
+            let next = if remaining > 0 {
+                request.try_clone()
+            } else {
+                None
+            };
+            match (client.execute(request).await, next) {
+                (Ok(response), _) => {
+                    self.active.store(index, Ordering::Relaxed);
+                    return Ok(response);
+                }
+                (Err(e), Some(next)) if e.is_connect() => {
+                    request = next;
+                    index = (index + 1) % self.base_uris.len();
+                }
+                (Err(e), _) => return Err(e.into()),
+            }
+        }
+    }
+}
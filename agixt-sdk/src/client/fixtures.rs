@@ -0,0 +1,217 @@
+//! Record/replay of HTTP interactions as fixture files.
+
+use crate::error::{Error, Result};
+use base64::Engine;
+use reqwest::ResponseBuilderExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// What the client does with the fixture directory set with
+/// `AGiXTSDKBuilder::fixtures`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Send requests to the server and save every response, replacing earlier
+    /// recordings of the same requests
+    Record,
+    /// Answer requests from saved responses without network access
+    Replay,
+}
+
+/// Headers that describe the encoded body on the wire; recorded bodies are
+/// already decoded.
+const SKIPPED_HEADERS: [&str; 3] = ["content-encoding", "content-length", "transfer-encoding"];
+
+/// Recorded responses to one request, in the order they were received.
+#[derive(Default)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+    /// Index of the interaction served next in replay mode
+    next: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    path: String,
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    body: String,
+    /// Whether `body` is base64 because the response was not UTF-8
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    base64: bool,
+}
+
+/// Fixture directory with the cassettes used so far.
+pub(crate) struct Fixtures {
+    dir: PathBuf,
+    mode: FixtureMode,
+    cassettes: tokio::sync::Mutex<HashMap<String, Cassette>>,
+}
+
+impl Fixtures {
+    pub(crate) fn new(dir: PathBuf, mode: FixtureMode) -> Self {
+        Self {
+            dir,
+            mode,
+            cassettes: Default::default(),
+        }
+    }
+
+    /// Send `request` with `client` and record the response, or answer it from
+    /// the recording.
+    pub(crate) async fn send(
+        &self,
+        client: &reqwest::Client,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response> {
+        let name = fixture_name(&request);
+        let path = self.dir.join(format!("{}.json", name));
+
+        match self.mode {
+            FixtureMode::Replay => {
+                let mut cassettes = self.cassettes.lock().await;
+                if !cassettes.contains_key(&name) {
+                    let data = tokio::fs::read(&path).await.map_err(|e| {
+                        Error::Other(format!(
+                            "no fixture for {} {} ({}): {}",
+                            request.method(),
+                            path_and_query(request.url()),
+                            path.display(),
+                            e
+                        ))
+                    })?;
+                    let interactions = serde_json::from_slice(&data)?;
+                    cassettes.insert(
+                        name.clone(),
+                        Cassette {
+                            interactions,
+                            next: 0,
+                        },
+                    );
+                }
+                let cassette = cassettes.get_mut(&name).expect("cassette loaded above");
+                // Repeated requests get the recorded responses in order; the
+                // last one is served again once they run out
+                let index = cassette
+                    .next
+                    .min(cassette.interactions.len().saturating_sub(1));
+                cassette.next += 1;
+                let interaction = cassette
+                    .interactions
+                    .get(index)
+                    .ok_or_else(|| Error::Other(format!("fixture {} is empty", path.display())))?;
+                to_response(interaction, request.url().clone())
+            }
+            FixtureMode::Record => {
+                let method = request.method().to_string();
+                let url = request.url().clone();
+                let response = client.execute(request).await?;
+                let status = response.status().as_u16();
+                let headers = response
+                    .headers()
+                    .iter()
+                    .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+                    .filter_map(|(name, value)| {
+                        Some((name.to_string(), value.to_str().ok()?.to_string()))
+                    })
+                    .collect();
+                let bytes = response.bytes().await?;
+                let (body, base64) = match String::from_utf8(bytes.to_vec()) {
+                    Ok(text) => (text, false),
+                    Err(_) => (
+                        base64::engine::general_purpose::STANDARD.encode(&bytes),
+                        true,
+                    ),
+                };
+                let interaction = Interaction {
+                    method,
+                    path: path_and_query(&url),
+                    status,
+                    headers,
+                    body,
+                    base64,
+                };
+
+                // Locked only once the response is read, so concurrent
+                // requests are recorded as they are sent, not one at a time
+                let mut cassettes = self.cassettes.lock().await;
+                let cassette = cassettes.entry(name).or_default();
+                cassette.interactions.push(interaction.clone());
+                let io_error = |e: std::io::Error| {
+                    Error::Other(format!("cannot write {}: {}", path.display(), e))
+                };
+                tokio::fs::create_dir_all(&self.dir)
+                    .await
+                    .map_err(io_error)?;
+                tokio::fs::write(&path, serde_json::to_vec_pretty(&cassette.interactions)?)
+                    .await
+                    .map_err(io_error)?;
+                to_response(&interaction, url)
+            }
+        }
+    }
+}
+
+/// File name of the fixture for a request: method and path for readability,
+/// plus a hash of the method, path, query and body. Hosts and headers are left out so
+/// fixtures replay against any base URI and credentials. Multipart bodies are
+/// left out too, as their boundaries change with every request.
+fn fixture_name(request: &reqwest::Request) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(request.method().as_str());
+    hasher.update(path_and_query(request.url()));
+    let multipart = request
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("multipart/"));
+    if !multipart {
+        if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            // Re-serialized so that maps hash the same whatever their key order
+            match serde_json::from_slice::<serde_json::Value>(body) {
+                Ok(json) => hasher.update(json.to_string()),
+                Err(_) => hasher.update(body),
+            }
+        }
+    }
+    let hash = hex::encode(hasher.finalize());
+
+    let path: String = request
+        .url()
+        .path()
+        .trim_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}_{}_{}", request.method(), path, &hash[..12])
+}
+
+fn path_and_query(url: &url::Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+fn to_response(interaction: &Interaction, url: url::Url) -> Result<reqwest::Response> {
+    let body = if interaction.base64 {
+        base64::engine::general_purpose::STANDARD
+            .decode(&interaction.body)
+            .map_err(|e| Error::Other(format!("invalid fixture body: {}", e)))?
+    } else {
+        interaction.body.clone().into_bytes()
+    };
+    let mut builder = http::Response::builder()
+        .status(interaction.status)
+        .url(url);
+    for (name, value) in &interaction.headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(body)
+        .map_err(|e| Error::Other(format!("invalid fixture: {}", e)))?;
+    Ok(reqwest::Response::from(response))
+}
@@ -0,0 +1,154 @@
+//! Declarative agent provisioning.
+
+use crate::error::{Error, Result};
+use crate::models::{AgentChange, AgentConfig, AgentSpec, FleetPlan, FleetSpec};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+impl super::AGiXTSDK {
+    // ==================== Fleet ====================
+
+    /// Make the server's agents match `spec`: agents missing by name are
+    /// created with their settings, commands and training URLs, and existing
+    /// agents get the settings and commands that drifted from the spec.
+    ///
+    /// Agents, settings and commands the spec does not mention are left alone,
+    /// and training URLs are only learned on creation. With `dry_run` nothing is
+    /// changed and the returned plan lists what would be. A spec naming an
+    /// agent twice or listing an invalid training URL is rejected up front.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, FleetSpec};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let spec: FleetSpec = serde_json::from_str(&std::fs::read_to_string("agents.json").unwrap())?;
+    /// let plan = client.apply_fleet(&spec, true).await?;
+    /// for change in &plan.changes {
+    ///     println!("{:?}", change);
+    /// }
+    /// client.apply_fleet(&spec, false).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn apply_fleet(&self, spec: &FleetSpec, dry_run: bool) -> Result<FleetPlan> {
+        let mut names = HashSet::new();
+        if let Some(agent) = spec.agents.iter().find(|a| !names.insert(a.name.as_str())) {
+            return Err(Error::InvalidInput(format!(
+                "agent {} is listed more than once in the spec",
+                agent.name
+            )));
+        }
+
+        for agent in &spec.agents {
+            super::agents::validate_training_urls(&agent.training_urls)?;
+        }
+
+        let existing: HashMap<String, String> = self
+            .get_agents()
+            .await?
+            .into_iter()
+            .map(|agent| (agent.name, agent.id))
+            .collect();
+        let mut plan = FleetPlan {
+            dry_run,
+            changes: Vec::new(),
+        };
+        for agent in &spec.agents {
+            match existing.get(&agent.name) {
+                None => {
+                    if !dry_run {
+                        self.create_from_spec(agent).await?;
+                    }
+                    plan.changes.push(AgentChange::Create {
+                        agent: agent.name.clone(),
+                    });
+                }
+                Some(id) => {
+                    let config = self.get_agentconfig(id).await?;
+                    self.reconcile(id, agent, &config, dry_run, &mut plan.changes)
+                        .await?;
+                }
+            }
+        }
+        Ok(plan)
+    }
+
+    async fn create_from_spec(&self, agent: &AgentSpec) -> Result<()> {
+        let commands = agent
+            .commands
+            .iter()
+            .map(|(name, enabled)| (name.clone(), Value::Bool(*enabled)))
+            .collect();
+        self.add_agent(
+            &agent.name,
+            Some(agent.settings.clone()),
+            Some(commands),
+            Some(agent.training_urls.clone()),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn reconcile(
+        &self,
+        id: &str,
+        agent: &AgentSpec,
+        config: &AgentConfig,
+        dry_run: bool,
+        changes: &mut Vec<AgentChange>,
+    ) -> Result<()> {
+        let mut keys: Vec<&String> = agent
+            .settings
+            .iter()
+            .filter(|(key, value)| !config.settings.get(*key).is_some_and(|v| same(v, value)))
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort();
+        if !keys.is_empty() {
+            if !dry_run {
+                let settings = keys
+                    .iter()
+                    .map(|key| ((*key).clone(), agent.settings[*key].clone()))
+                    .collect();
+                self.update_agent_settings(id, settings, Some(&agent.name))
+                    .await?;
+            }
+            changes.push(AgentChange::UpdateSettings {
+                agent: agent.name.clone(),
+                keys: keys.into_iter().cloned().collect(),
+            });
+        }
+
+        let mut commands: Vec<(&String, bool)> = agent
+            .commands
+            .iter()
+            .filter(|(name, enabled)| config.command_enabled(name) != **enabled)
+            .map(|(name, enabled)| (name, *enabled))
+            .collect();
+        commands.sort();
+        if !commands.is_empty() {
+            if !dry_run {
+                for (name, enabled) in &commands {
+                    self.set_command_enabled(id, name, *enabled).await?;
+                }
+            }
+            changes.push(AgentChange::UpdateCommands {
+                agent: agent.name.clone(),
+                commands: commands.into_iter().map(|(name, _)| name.clone()).collect(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Whether a stored setting matches the spec. The server keeps most settings
+/// as strings, so `0.7` in the spec matches a stored `"0.7"`.
+fn same(stored: &Value, wanted: &Value) -> bool {
+    let scalar = |value: &Value| match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+        _ => None,
+    };
+    stored == wanted || matches!((scalar(stored), scalar(wanted)), (Some(a), Some(b)) if a == b)
+}
@@ -0,0 +1,103 @@
+//! GitHub repository learning and code review for CI bots.
+
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+
+impl super::AGiXTSDK {
+    // ==================== GitHub ====================
+
+    /// Teach an agent by ID the files of a GitHub repository, given as
+    /// `owner/repo` or a repository URL.
+    ///
+    /// Without `github_token` the server uses the agent's `GITHUB_USER` and
+    /// `GITHUB_API_KEY` settings, which public repositories do not need.
+    /// `github_branch` defaults to `main`.
+    pub async fn learn_github_repo(
+        &self,
+        agent_id: &str,
+        github_repo: &str,
+        github_branch: Option<&str>,
+        github_token: Option<&str>,
+        collection_number: Option<&str>,
+    ) -> Result<String> {
+        let request = self
+            .client
+            .post(format!("{}/agent/{}/learn/github", self.api_root, agent_id))
+            .json(&serde_json::json!({
+                "github_repo": github_repo,
+                "github_branch": github_branch.unwrap_or("main"),
+                "github_token": github_token,
+                "use_agent_settings": github_token.is_none(),
+                "collection_number": collection_number.unwrap_or("0"),
+            }));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct MessageResponse {
+            message: String,
+        }
+
+        let result: MessageResponse = text.json()?;
+        Ok(result.message)
+    }
+
+    /// Have an agent by ID review a pull request: the agent learns the
+    /// repository with [`learn_github_repo`](Self::learn_github_repo), using
+    /// its own GitHub settings, and then reviews `pr_diff` with that code as
+    /// context. Returns the review text.
+    ///
+    /// The review is not logged to any conversation.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let diff = std::process::Command::new("git")
+    ///     .args(["diff", "origin/main...HEAD"])
+    ///     .output()
+    ///     .unwrap()
+    ///     .stdout;
+    /// let review = client
+    ///     .code_review("agent-id", "AGiXT/rust-sdk", &String::from_utf8_lossy(&diff))
+    ///     .await?;
+    /// println!("{}", review);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn code_review(
+        &self,
+        agent_id: &str,
+        github_repo: &str,
+        pr_diff: &str,
+    ) -> Result<String> {
+        if pr_diff.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "the pull request diff is empty".to_string(),
+            ));
+        }
+        self.learn_github_repo(agent_id, github_repo, None, None, None)
+            .await?;
+
+        let user_input = format!(
+            "Review the following pull request to {}. Point out bugs, security \
+             problems and unclear code, naming the file and line of each finding, \
+             and end with a verdict: approve or request changes.\n\n```diff\n{}\n```",
+            github_repo,
+            pr_diff.trim_end()
+        );
+        let mut args = HashMap::new();
+        args.insert("user_input".to_string(), serde_json::json!(user_input));
+        args.insert("context_results".to_string(), serde_json::json!(10));
+        args.insert("log_user_input".to_string(), serde_json::json!(false));
+        args.insert("log_output".to_string(), serde_json::json!(false));
+        self.prompt_agent(agent_id, "Think About It", args).await
+    }
+}
@@ -0,0 +1,235 @@
+//! Conversation import from other chat products' data exports.
+
+use crate::error::{Error, Result};
+use crate::models::{messages_to_history, Message};
+use serde_json::Value;
+
+impl super::AGiXTSDK {
+    // ==================== Import ====================
+
+    /// Create AGiXT conversations for the agent from a ChatGPT data export
+    /// (`conversations.json`), returning the created conversations.
+    ///
+    /// The file may hold a list of conversations or a single one, either in
+    /// ChatGPT's tree format or as an OpenAI chat request with `messages`. Only
+    /// the visible user and assistant messages of the selected branch are
+    /// imported; the conversation title becomes the conversation name.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let created = client
+    ///     .import_conversation_from_openai_json("agent-id", "conversations.json".as_ref())
+    ///     .await?;
+    /// println!("imported {} conversations", created.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn import_conversation_from_openai_json(
+        &self,
+        agent_id: &str,
+        path: &std::path::Path,
+    ) -> Result<Vec<Value>> {
+        let data = tokio::fs::read(path)
+            .await
+            .map_err(|e| Error::Other(format!("cannot read {}: {}", path.display(), e)))?;
+        let conversations = parse_openai_export(&serde_json::from_slice(&data)?)?;
+        if conversations.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let agent_name = self.get_agentconfig(agent_id).await?.name;
+        let mut created = Vec::with_capacity(conversations.len());
+        for (title, messages) in conversations {
+            let history = messages_to_history(&messages, &agent_name);
+            created.push(
+                self.new_conversation(agent_id, &title, Some(history))
+                    .await?,
+            );
+        }
+        Ok(created)
+    }
+}
+
+/// Conversations of a ChatGPT export or OpenAI chat request, as titles and
+/// chat-completion messages.
+fn parse_openai_export(data: &Value) -> Result<Vec<(String, Vec<Message>)>> {
+    let conversations = match data {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![data],
+        _ => vec![],
+    };
+    conversations
+        .into_iter()
+        .map(|conversation| {
+            let title = conversation
+                .get("title")
+                .and_then(Value::as_str)
+                .filter(|title| !title.trim().is_empty())
+                .unwrap_or("Imported conversation")
+                .to_string();
+            let messages = if let Some(mapping) = conversation.get("mapping") {
+                branch_messages(conversation, mapping)
+            } else if let Some(messages) = conversation.get("messages") {
+                serde_json::from_value(messages.clone())?
+            } else {
+                return Err(Error::InvalidInput(format!(
+                    "conversation '{}' has neither `mapping` nor `messages`",
+                    title
+                )));
+            };
+            Ok((title, messages))
+        })
+        .collect()
+}
+
+/// Messages on the path from the root of a ChatGPT message tree to
+/// `current_node`, the branch shown in the ChatGPT UI. Without a current node
+/// every message is taken in creation order.
+fn branch_messages(conversation: &Value, mapping: &Value) -> Vec<Message> {
+    let nodes: Vec<&Value> = match conversation.get("current_node").and_then(Value::as_str) {
+        Some(current) => {
+            let mut path = Vec::new();
+            let mut node = mapping.get(current);
+            // Bounded by the node count so a malformed cycle cannot loop forever
+            let limit = mapping.as_object().map_or(0, |nodes| nodes.len());
+            while let Some(current) = node.filter(|_| path.len() < limit) {
+                path.push(current);
+                node = current
+                    .get("parent")
+                    .and_then(Value::as_str)
+                    .and_then(|parent| mapping.get(parent));
+            }
+            path.reverse();
+            path
+        }
+        None => {
+            let mut nodes: Vec<&Value> = mapping
+                .as_object()
+                .map(|nodes| nodes.values().collect())
+                .unwrap_or_default();
+            nodes.sort_by(|a, b| create_time(a).total_cmp(&create_time(b)));
+            nodes
+        }
+    };
+    nodes
+        .into_iter()
+        .filter_map(|node| export_message(node.get("message")?))
+        .collect()
+}
+
+fn create_time(node: &Value) -> f64 {
+    node.pointer("/message/create_time")
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0)
+}
+
+/// A visible user or assistant message of a ChatGPT export.
+fn export_message(message: &Value) -> Option<Message> {
+    let role = message.pointer("/author/role").and_then(Value::as_str)?;
+    if !matches!(role, "user" | "assistant") {
+        return None;
+    }
+    let hidden = message
+        .pointer("/metadata/is_visually_hidden_from_conversation")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if hidden {
+        return None;
+    }
+    let text = message
+        .pointer("/content/parts")
+        .and_then(Value::as_array)?
+        .iter()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.trim().is_empty() {
+        return None;
+    }
+    let mut converted = Message::new(role, text);
+    converted.timestamp = message
+        .get("create_time")
+        .and_then(Value::as_f64)
+        .and_then(|time| {
+            chrono::DateTime::from_timestamp(time.trunc() as i64, (time.fract() * 1e9) as u32)
+        })
+        .map(|time| time.to_rfc3339());
+    Some(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_chatgpt_export_follows_current_branch() {
+        let export = json!([{
+            "title": "Trip plan",
+            "current_node": "c",
+            "mapping": {
+                "root": {"id": "root", "message": null, "parent": null, "children": ["s"]},
+                "s": {"id": "s", "parent": "root", "children": ["a"], "message": {
+                    "author": {"role": "system"},
+                    "content": {"content_type": "text", "parts": [""]}
+                }},
+                "a": {"id": "a", "parent": "s", "children": ["b", "old"], "message": {
+                    "author": {"role": "user"},
+                    "create_time": 1700000000.5,
+                    "content": {"content_type": "text", "parts": ["Plan a trip"]}
+                }},
+                "old": {"id": "old", "parent": "a", "children": [], "message": {
+                    "author": {"role": "assistant"},
+                    "content": {"content_type": "text", "parts": ["Discarded answer"]}
+                }},
+                "b": {"id": "b", "parent": "a", "children": ["c"], "message": {
+                    "author": {"role": "assistant"},
+                    "content": {"content_type": "text", "parts": ["Where to?"]}
+                }},
+                "c": {"id": "c", "parent": "b", "children": [], "message": {
+                    "author": {"role": "user"},
+                    "content": {"content_type": "multimodal_text",
+                                "parts": [{"asset_pointer": "file-1"}, "Lisbon"]}
+                }}
+            }
+        }]);
+
+        let conversations = parse_openai_export(&export).unwrap();
+        assert_eq!(conversations.len(), 1);
+        let (title, messages) = &conversations[0];
+        assert_eq!(title, "Trip plan");
+        let texts: Vec<_> = messages
+            .iter()
+            .map(|message| (message.role.as_str(), message.text()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                ("user", "Plan a trip".to_string()),
+                ("assistant", "Where to?".to_string()),
+                ("user", "Lisbon".to_string()),
+            ]
+        );
+        assert!(messages[0]
+            .timestamp
+            .as_deref()
+            .unwrap()
+            .starts_with("2023-11-14T22:13:20.5"));
+    }
+
+    #[test]
+    fn test_parse_openai_chat_messages() {
+        let export = json!({"messages": [
+            {"role": "user", "content": "Hi"},
+            {"role": "assistant", "content": "Hello!"}
+        ]});
+        let conversations = parse_openai_export(&export).unwrap();
+        assert_eq!(conversations[0].0, "Imported conversation");
+        assert_eq!(conversations[0].1.len(), 2);
+
+        assert!(parse_openai_export(&json!([{"title": "Empty"}])).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Graceful shutdown: draining in-flight requests and stopping background work.
+
+use crate::error::{Error, Result};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// Shutdown state shared by all clones of a client.
+#[derive(Debug, Default)]
+pub(crate) struct Lifecycle {
+    /// Cancelled when shutdown starts: new requests are refused and background
+    /// workers stop
+    closing: CancellationToken,
+    /// Cancelled when the shutdown deadline passes, aborting in-flight requests
+    aborting: CancellationToken,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+/// Marks a request as in flight until dropped. Kept by the `Response` until
+/// its body is read.
+pub(crate) struct InFlight(Arc<Lifecycle>);
+
+impl Lifecycle {
+    /// Count a request as in flight, unless the client is shutting down.
+    pub(crate) fn enter(self: &Arc<Self>) -> Result<InFlight> {
+        // Counted before checking, so `shutdown` either sees the request or
+        // the request sees the shutdown
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlight(self.clone());
+        if self.closing.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(guard)
+    }
+
+    /// Token cancelled once the shutdown deadline passes.
+    pub(crate) fn aborting(&self) -> &CancellationToken {
+        &self.aborting
+    }
+
+    /// Wait until no request is in flight.
+    async fn drained(&self) {
+        loop {
+            let notified = self.idle.notified();
+            futures_util::pin_mut!(notified);
+            notified.as_mut().enable();
+            if self.in_flight.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl InFlight {
+    /// Run `future`, e.g. reading the response body, until the shutdown
+    /// deadline passes.
+    pub(crate) async fn abortable<F: Future>(&self, future: F) -> Result<F::Output> {
+        self.0
+            .aborting
+            .run_until_cancelled(future)
+            .await
+            .ok_or(Error::Cancelled)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl super::AGiXTSDK {
+    // ==================== Lifecycle ====================
+
+    /// Shut the client down for a service restart: refuse new requests, stop
+    /// background work and wait for the requests in flight to finish, including
+    /// reading their response bodies and streams.
+    ///
+    /// Applies to every clone of the client, including those made with
+    /// `with_options` and `as_user`. Requests started afterwards fail with
+    /// `Error::Cancelled`, which also ends polling helpers such as
+    /// `wait_for_task` at their next request. Realtime event streams end, and
+    /// ingestion workers report their queued jobs as failed with
+    /// `Error::Cancelled` after finishing the running ones.
+    ///
+    /// Without a `deadline`, waits as long as the requests in flight take.
+    /// With one, requests still running when it passes are aborted, failing
+    /// with `Error::Cancelled`, and `Error::ShutdownTimedOut` reports how many.
+    /// Pooled connections are closed once the last clone of the client is
+    /// dropped.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    ///
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) {
+    /// tokio::signal::ctrl_c().await.unwrap();
+    /// if let Err(e) = client.shutdown(Some(Duration::from_secs(10))).await {
+    ///     eprintln!("{}", e);
+    /// }
+    /// # }
+    /// ```
+    pub async fn shutdown(&self, deadline: Option<Duration>) -> Result<()> {
+        let lifecycle = &self.lifecycle;
+        lifecycle.closing.cancel();
+        let Some(deadline) = deadline else {
+            lifecycle.drained().await;
+            return Ok(());
+        };
+
+        let drained = lifecycle.drained();
+        let expired = super::sleep(deadline);
+        futures_util::pin_mut!(drained, expired);
+        if let futures_util::future::Either::Left(_) =
+            futures_util::future::select(drained, expired).await
+        {
+            return Ok(());
+        }
+        let aborted = lifecycle.in_flight.load(Ordering::SeqCst);
+        lifecycle.aborting.cancel();
+        lifecycle.drained().await;
+        Err(Error::ShutdownTimedOut { deadline, aborted })
+    }
+
+    /// Whether `shutdown` was called on this client or one of its clones.
+    pub fn is_shut_down(&self) -> bool {
+        self.lifecycle.closing.is_cancelled()
+    }
+
+    /// Resolves once the client starts shutting down, to stop background work.
+    pub(crate) fn closed(&self) -> impl Future<Output = ()> + Send + 'static {
+        let closing = self.lifecycle.closing.clone();
+        async move { closing.cancelled().await }
+    }
+}
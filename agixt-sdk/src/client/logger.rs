@@ -0,0 +1,117 @@
+//! Destinations for the client's verbose diagnostics.
+
+/// Receives the diagnostics printed by a verbose client, such as response
+/// bodies, retries and re-logins.
+///
+/// Set one with `AGiXTSDKBuilder::logger` to capture the output instead of
+/// printing it to stdout. Closures taking a `&str` implement this trait.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::AGiXTSDK;
+///
+/// let client = AGiXTSDK::builder()
+///     .verbose(true)
+///     .logger(|message: &str| eprintln!("[agixt] {}", message))
+///     .build()
+///     .unwrap();
+/// ```
+pub trait ResponseLogger: Send + Sync {
+    /// Record one diagnostic message. Messages may span several lines.
+    fn log(&self, message: &str);
+}
+
+impl<F: Fn(&str) + Send + Sync> ResponseLogger for F {
+    fn log(&self, message: &str) {
+        self(message)
+    }
+}
+
+/// Prints messages to stdout. This is the default logger.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutLogger;
+
+impl ResponseLogger for StdoutLogger {
+    fn log(&self, message: &str) {
+        println!("{}", message);
+    }
+}
+
+/// Emits messages as `tracing` debug events with the `agixt_sdk` target.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingLogger;
+
+#[cfg(feature = "tracing")]
+impl ResponseLogger for TracingLogger {
+    fn log(&self, message: &str) {
+        tracing::debug!(target: "agixt_sdk", "{}", message);
+    }
+}
+
+/// Appends messages to a file, one message per line group.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct FileLogger {
+    file: std::sync::Mutex<std::fs::File>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileLogger {
+    /// Open `path` for appending, creating it if needed.
+    pub fn append(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| crate::Error::Other(format!("cannot open {}: {}", path.display(), e)))?;
+        Ok(Self {
+            file: std::sync::Mutex::new(file),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ResponseLogger for FileLogger {
+    fn log(&self, message: &str) {
+        use std::io::Write;
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        // Diagnostics must never fail a request, so write errors are ignored
+        let _ = writeln!(file, "{}", message);
+    }
+}
+
+/// Sends messages to a channel, e.g. to show them in an application's UI.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::{AGiXTSDK, ChannelLogger};
+///
+/// let (logger, mut messages) = ChannelLogger::new();
+/// let client = AGiXTSDK::builder().verbose(true).logger(logger).build().unwrap();
+/// // ...
+/// while let Ok(message) = messages.try_recv() {
+///     println!("{}", message);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChannelLogger {
+    sender: tokio::sync::mpsc::UnboundedSender<String>,
+}
+
+impl ChannelLogger {
+    /// Create a logger and the receiver its messages are delivered to.
+    pub fn new() -> (Self, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+}
+
+impl ResponseLogger for ChannelLogger {
+    fn log(&self, message: &str) {
+        // Messages are dropped once the receiver is gone
+        let _ = self.sender.send(message.to_string());
+    }
+}
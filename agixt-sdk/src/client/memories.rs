@@ -0,0 +1,204 @@
+//! Agent memory export using /v1 endpoints with ID-based parameters.
+
+use crate::error::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::models::ExportFormat;
+use crate::models::Memory;
+
+impl super::AGiXTSDK {
+    // ==================== Memory Export ====================
+
+    /// Export every memory of an agent by ID.
+    pub async fn export_agent_memories(&self, agent_id: &str) -> Result<Vec<Memory>> {
+        let response = self.request_memory_export(agent_id).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum ExportResponse {
+            Wrapped { memories: Vec<Memory> },
+            List(Vec<Memory>),
+        }
+
+        Ok(match text.json()? {
+            ExportResponse::Wrapped { memories } | ExportResponse::List(memories) => memories,
+        })
+    }
+
+    /// Export every memory of an agent by ID to `path` as a JSON array or as
+    /// JSON Lines, returning the number of memories written.
+    ///
+    /// The export is spooled to a temporary file next to `path` and converted
+    /// one memory at a time, so large memory sets are never held in RAM.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, ExportFormat};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let count = client
+    ///     .export_memories_to_file("agent-id", "memories.jsonl".as_ref(), ExportFormat::Jsonl)
+    ///     .await?;
+    /// println!("backed up {} memories", count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn export_memories_to_file(
+        &self,
+        agent_id: &str,
+        path: &std::path::Path,
+        format: ExportFormat,
+    ) -> Result<u64> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut response = self.request_memory_export(agent_id).await?;
+        let spool = path.with_file_name(format!(
+            ".{}.download",
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("memories")
+        ));
+        let io_error = |e: std::io::Error| {
+            crate::Error::Other(format!("cannot write {}: {}", spool.display(), e))
+        };
+        let mut file = tokio::fs::File::create(&spool).await.map_err(io_error)?;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await.map_err(io_error)?;
+        }
+        file.flush().await.map_err(io_error)?;
+        drop(file);
+
+        let (source, target) = (spool.clone(), path.to_path_buf());
+        let written =
+            tokio::task::spawn_blocking(move || convert::convert(&source, &target, format))
+                .await
+                .map_err(|e| crate::Error::Other(format!("memory export failed: {}", e)));
+        let _ = tokio::fs::remove_file(&spool).await;
+        written?
+    }
+
+    async fn request_memory_export(&self, agent_id: &str) -> Result<super::Response> {
+        let request = self.client.get(format!(
+            "{}/agent/{}/memory/export",
+            self.api_root, agent_id
+        ));
+        self.execute(request).await
+    }
+}
+
+/// Streaming conversion of a spooled export into the requested format.
+#[cfg(not(target_arch = "wasm32"))]
+mod convert {
+    use crate::error::{Error, Result};
+    use crate::models::{ExportFormat, Memory};
+    use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess};
+    use std::io::{BufReader, BufWriter, Write};
+    use std::path::Path;
+
+    pub(super) fn convert(source: &Path, target: &Path, format: ExportFormat) -> Result<u64> {
+        let io_error = |path: &Path, e: std::io::Error| {
+            Error::Other(format!("cannot access {}: {}", path.display(), e))
+        };
+        let input = std::fs::File::open(source).map_err(|e| io_error(source, e))?;
+        let output = std::fs::File::create(target).map_err(|e| io_error(target, e))?;
+
+        let mut sink = Sink {
+            out: BufWriter::new(output),
+            format,
+            count: 0,
+        };
+        sink.begin().map_err(|e| io_error(target, e))?;
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(input));
+        (&mut sink).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        sink.end().map_err(|e| io_error(target, e))?;
+        Ok(sink.count)
+    }
+
+    /// Writes memories to the output as they are parsed.
+    struct Sink<W: Write> {
+        out: W,
+        format: ExportFormat,
+        count: u64,
+    }
+
+    impl<W: Write> Sink<W> {
+        fn begin(&mut self) -> std::io::Result<()> {
+            match self.format {
+                ExportFormat::Json => self.out.write_all(b"["),
+                ExportFormat::Jsonl => Ok(()),
+            }
+        }
+
+        fn write(&mut self, memory: &Memory) -> std::io::Result<()> {
+            match self.format {
+                ExportFormat::Json => {
+                    if self.count > 0 {
+                        self.out.write_all(b",")?;
+                    }
+                    self.out.write_all(b"\n  ")?;
+                }
+                ExportFormat::Jsonl => {}
+            }
+            serde_json::to_writer(&mut self.out, memory)?;
+            if self.format == ExportFormat::Jsonl {
+                self.out.write_all(b"\n")?;
+            }
+            self.count += 1;
+            Ok(())
+        }
+
+        fn end(&mut self) -> std::io::Result<()> {
+            if self.format == ExportFormat::Json {
+                self.out
+                    .write_all(if self.count > 0 { b"\n]\n" } else { b"]\n" })?;
+            }
+            self.out.flush()
+        }
+    }
+
+    /// Accepts either a list of memories or an object with a `memories` list.
+    impl<'de, W: Write> DeserializeSeed<'de> for &mut Sink<W> {
+        type Value = ();
+
+        fn deserialize<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> std::result::Result<(), D::Error> {
+            deserializer.deserialize_any(self)
+        }
+    }
+
+    impl<'de, W: Write> de::Visitor<'de> for &mut Sink<W> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a list of memories")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+            while let Some(memory) = seq.next_element::<Memory>()? {
+                self.write(&memory).map_err(de::Error::custom)?;
+            }
+            Ok(())
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+            while let Some(key) = map.next_key::<String>()? {
+                if key == "memories" {
+                    map.next_value_seed(&mut *self)?;
+                } else {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
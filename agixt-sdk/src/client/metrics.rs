@@ -0,0 +1,93 @@
+//! Request metrics reported to a user-supplied observer.
+
+use std::time::Duration;
+
+/// Outcome of a single HTTP request, as reported to a `MetricsObserver`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RequestMetrics {
+    /// HTTP method of the request.
+    pub method: reqwest::Method,
+    /// Request path with IDs replaced by `{id}`, e.g. `/v1/agent/{id}/prompt`,
+    /// so it can be used as a low-cardinality metric label.
+    pub endpoint: String,
+    /// Response status, or `None` if no response was received.
+    pub status: Option<u16>,
+    /// Time from sending the request until the response headers arrived or it failed.
+    pub latency: Duration,
+}
+
+impl RequestMetrics {
+    /// Whether the request received a successful (2xx) response.
+    pub fn is_success(&self) -> bool {
+        matches!(self.status, Some(200..=299))
+    }
+}
+
+/// Receives metrics for every request made by the client.
+///
+/// Implement this to forward request counts, latencies and error rates to a
+/// telemetry system such as Prometheus or OpenTelemetry.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::{AGiXTSDK, MetricsObserver, RequestMetrics};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// #[derive(Default)]
+/// struct ErrorCounter(AtomicU64);
+///
+/// impl MetricsObserver for ErrorCounter {
+///     fn record(&self, metrics: &RequestMetrics) {
+///         if !metrics.is_success() {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// let client = AGiXTSDK::builder().metrics(ErrorCounter::default()).build().unwrap();
+/// ```
+pub trait MetricsObserver: Send + Sync {
+    /// Called once per request after it completes or fails.
+    fn record(&self, metrics: &RequestMetrics);
+}
+
+/// Replace path segments that look like identifiers with `{id}`.
+pub(crate) fn endpoint_label(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            if is_identifier(segment) {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// UUIDs, numbers and other long alphanumeric tokens are treated as identifiers.
+fn is_identifier(segment: &str) -> bool {
+    !segment.is_empty()
+        && (segment.chars().all(|c| c.is_ascii_digit())
+            || uuid::Uuid::parse_str(segment).is_ok()
+            || (segment.len() >= 20 && segment.chars().all(|c| c.is_ascii_alphanumeric())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_label() {
+        assert_eq!(
+            endpoint_label("/v1/agent/0b9a4d9e-2f4c-4d3a-9d47-1f2e3c4b5a69/prompt"),
+            "/v1/agent/{id}/prompt"
+        );
+        assert_eq!(
+            endpoint_label("/v1/task/42/complete"),
+            "/v1/task/{id}/complete"
+        );
+        assert_eq!(endpoint_label("/v1/provider"), "/v1/provider");
+    }
+}
@@ -0,0 +1,37 @@
+//! Request/response interceptors registered on the client.
+
+use std::time::Duration;
+
+/// Hook into every HTTP request made by the client.
+///
+/// Interceptors run in registration order. Both methods have no-op defaults, so
+/// implementors only override what they need.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::{AGiXTSDK, Interceptor};
+/// use std::time::Duration;
+///
+/// struct Timing;
+///
+/// impl Interceptor for Timing {
+///     fn on_response(&self, method: &reqwest::Method, response: &reqwest::Response, elapsed: Duration) {
+///         println!("{} {} -> {} in {:?}", method, response.url(), response.status(), elapsed);
+///     }
+/// }
+///
+/// let client = AGiXTSDK::builder().interceptor(Timing).build().unwrap();
+/// ```
+pub trait Interceptor: Send + Sync {
+    /// Called before a request is sent. The request may be modified, e.g. to add headers.
+    fn on_request(&self, _request: &mut reqwest::Request) {}
+
+    /// Called when a response is received, with the time spent waiting for it.
+    fn on_response(
+        &self,
+        _method: &reqwest::Method,
+        _response: &reqwest::Response,
+        _elapsed: Duration,
+    ) {
+    }
+}
@@ -1,22 +1,308 @@
 //! AGiXT SDK client implementation using /v1 endpoints with ID-based parameters.
 
 mod agents;
+pub(crate) mod api;
+mod artifacts;
+mod audio;
+#[cfg(not(target_arch = "wasm32"))]
+mod backup;
+mod builder;
+mod cache;
+mod chat;
+#[cfg(feature = "compression")]
+mod compression;
 mod conversations;
+#[cfg(not(target_arch = "wasm32"))]
+mod download;
+mod failover;
+#[cfg(not(target_arch = "wasm32"))]
+mod fixtures;
+mod fleet;
+mod github;
+#[cfg(not(target_arch = "wasm32"))]
+mod images;
+#[cfg(not(target_arch = "wasm32"))]
+mod import;
+mod lifecycle;
+mod logger;
+mod memories;
+mod metrics;
+mod middleware;
+mod options;
+mod orchestrate;
+#[cfg(not(target_arch = "wasm32"))]
+mod prompt_sync;
 mod providers;
-
-use crate::error::Result;
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+mod rate_limit;
+mod raw;
+mod response;
+mod scheduler;
+mod stream;
+mod tasks;
+mod tools;
+mod training;
+mod transcript;
+mod usage;
+mod webhooks;
+mod workspace;
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+mod ws;
+
+pub use api::AgixtApi;
+pub use builder::AGiXTSDKBuilder;
+#[cfg(not(target_arch = "wasm32"))]
+pub use fixtures::FixtureMode;
+#[cfg(not(target_arch = "wasm32"))]
+pub use logger::FileLogger;
+#[cfg(feature = "tracing")]
+pub use logger::TracingLogger;
+pub use logger::{ChannelLogger, ResponseLogger, StdoutLogger};
+pub use metrics::{MetricsObserver, RequestMetrics};
+pub use middleware::Interceptor;
+pub use options::RequestOptions;
+pub use orchestrate::{AgentReply, Orchestration, OrchestrationMode, OrchestrationResult};
+pub use rate_limit::RateLimitInfo;
+pub use raw::RawClient;
+pub(crate) use response::{Response, Text};
+pub use scheduler::RequestPriority;
+pub use stream::{StreamEvent, StreamEvents, ToolCallDelta};
+pub use tools::ToolRegistry;
+#[cfg(not(target_arch = "wasm32"))]
+pub use transcript::JsonlTranscript;
+pub use transcript::{TranscriptEntry, TranscriptSink};
+pub use usage::UsageSummary;
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+pub use ws::{ConversationEvent, EventStream};
+
+use crate::error::{ErrorContext, Result};
+use crate::models::{
+    Agent, ChainProgress, ChainRun, Company, CompanyUser, Invitation, LoginOutcome, ServerInfo,
+    SessionState, SsoProvider, User, UserUpdate, UserWallet,
+};
+use crate::secret::Secret;
+use arc_swap::ArcSwap;
+use reqwest::header::{
+    HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_NONE_MATCH, USER_AGENT,
+};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::sync::{Arc, Mutex};
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use gloo_timers::future::sleep;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use tokio::time::sleep;
 
 /// AGiXT SDK client for interacting with the AGiXT API.
 #[derive(Clone)]
 pub struct AGiXTSDK {
     pub(crate) base_uri: String,
+    /// Fallback servers configured with `AGiXTSDKBuilder::base_uris`
+    pub(crate) endpoints: Option<Arc<failover::Endpoints>>,
+    /// `base_uri` followed by the route family of the API version, e.g. `/v1`
+    pub(crate) api_root: String,
     pub(crate) client: Arc<reqwest::Client>,
-    pub(crate) headers: Arc<Mutex<HeaderMap>>,
+    /// Headers sent with every request, swapped as a whole on change so
+    /// requests read them without locking
+    pub(crate) headers: Arc<ArcSwap<HeaderMap>>,
     pub(crate) verbose: bool,
+    /// Check agent settings before sending them, see `AGiXTSDKBuilder::validate_settings`
+    pub(crate) validate_settings: bool,
+    pub(crate) logger: Arc<dyn ResponseLogger>,
+    pub(crate) credentials: Option<Arc<Credentials>>,
+    /// Held while logging in again with `credentials`, so concurrent requests
+    /// rejected with the same expired token log in only once
+    pub(crate) relogin: Arc<tokio::sync::Mutex<()>>,
+    pub(crate) rate_limiter: Option<Arc<rate_limit::RateLimiter>>,
+    /// Concurrency cap, see `AGiXTSDKBuilder::max_concurrent_requests`
+    pub(crate) scheduler: Option<Arc<scheduler::Scheduler>>,
+    /// Shutdown state, see `AGiXTSDK::shutdown`
+    pub(crate) lifecycle: Arc<lifecycle::Lifecycle>,
+    pub(crate) last_rate_limit: Arc<Mutex<Option<RateLimitInfo>>>,
+    /// Request bodies at least this large are gzipped
+    #[cfg(feature = "compression")]
+    pub(crate) compress_requests_over: Option<usize>,
+    pub(crate) interceptors: Vec<Arc<dyn Interceptor>>,
+    pub(crate) metrics: Option<Arc<dyn MetricsObserver>>,
+    /// Audit log of prompts, set with `AGiXTSDKBuilder::transcript`
+    pub(crate) transcript: Option<Arc<dyn TranscriptSink>>,
+    pub(crate) cache: Option<Arc<cache::ResponseCache>>,
+    /// Token totals, when enabled with `AGiXTSDKBuilder::track_usage`
+    pub(crate) usage: Option<Arc<usage::UsageTracker>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fixtures: Option<Arc<fixtures::Fixtures>>,
+    /// Longest silence while waiting for a response, see
+    /// `AGiXTSDKBuilder::read_timeout`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) read_timeout: Option<std::time::Duration>,
+    pub(crate) deserialize_mode: DeserializeMode,
+    pub(crate) options: RequestOptions,
+}
+
+/// Route family of the AGiXT REST API, selected with `AGiXTSDKBuilder::api_version`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ApiVersion {
+    /// ID-based `/v1/...` routes served by current AGiXT releases
+    #[default]
+    V1,
+    /// Unversioned `/api/...` routes of older releases. Endpoints that only
+    /// exist under `/v1` answer 404 on such servers.
+    Legacy,
+}
+
+impl ApiVersion {
+    /// Path segment the routes of this version start with.
+    pub fn path(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/v1",
+            ApiVersion::Legacy => "/api",
+        }
+    }
+}
+
+/// How response bodies are mapped onto the SDK's types, selected with
+/// `AGiXTSDKBuilder::deserialize_mode`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeserializeMode {
+    /// Fields the SDK does not know are ignored and missing optional fields
+    /// are left empty, so responses of newer servers still parse
+    #[default]
+    Lenient,
+    /// Fields the SDK does not know are rejected, naming their path, so
+    /// changes to the server's response shapes show up immediately
+    Strict,
+}
+
+/// Login credentials used to transparently re-authenticate when a token expires.
+///
+/// AGiXT accounts sign in with their email and the TOTP secret of their
+/// authenticator app, see `Credentials::totp` (requires the `totp` feature).
+/// Servers with password login enabled also accept `Credentials::new`.
+#[derive(Clone)]
+pub struct Credentials {
+    pub(crate) login: CredentialLogin,
+}
+
+/// How stored credentials sign in.
+#[derive(Clone)]
+pub(crate) enum CredentialLogin {
+    /// Email and MFA secret, signing in with a freshly generated TOTP code
+    #[cfg(feature = "totp")]
+    Totp {
+        email: String,
+        totp_secret: Secret<String>,
+    },
+    Password {
+        username: String,
+        password: Secret<String>,
+        #[cfg(feature = "totp")]
+        totp_secret: Option<Secret<String>>,
+    },
+}
+
+impl Credentials {
+    /// Create credentials from an email and the base32 MFA secret from the
+    /// registration OTP URI; each re-login sends a freshly generated TOTP code.
+    #[cfg(feature = "totp")]
+    pub fn totp(email: impl Into<String>, totp_secret: impl Into<String>) -> Self {
+        Self {
+            login: CredentialLogin::Totp {
+                email: email.into(),
+                totp_secret: Secret::new(totp_secret.into()),
+            },
+        }
+    }
+
+    /// Create credentials from a username (or email) and password.
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            login: CredentialLogin::Password {
+                username: username.into(),
+                password: Secret::new(password.into()),
+                #[cfg(feature = "totp")]
+                totp_secret: None,
+            },
+        }
+    }
+
+    /// Attach the MFA secret of a password login so a fresh TOTP code is sent
+    /// with each re-login.
+    #[cfg(feature = "totp")]
+    pub fn with_totp_secret(mut self, secret: impl Into<String>) -> Self {
+        match &mut self.login {
+            CredentialLogin::Password { totp_secret, .. } => {
+                *totp_secret = Some(Secret::new(secret.into()))
+            }
+            CredentialLogin::Totp { totp_secret, .. } => *totp_secret = Secret::new(secret.into()),
+        }
+        self
+    }
+
+    /// The email or username the credentials sign in as.
+    pub(crate) fn identity(&self) -> &str {
+        match &self.login {
+            #[cfg(feature = "totp")]
+            CredentialLogin::Totp { email, .. } => email,
+            CredentialLogin::Password { username, .. } => username,
+        }
+    }
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("identity", &self.identity())
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+/// User-Agent sent unless overridden, so server operators can identify SDK traffic.
+const DEFAULT_USER_AGENT: &str = concat!("agixt-rust-sdk/", env!("CARGO_PKG_VERSION"));
+
+/// Header carrying the per-request correlation ID.
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Header letting the server recognize a retried request that creates a resource.
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Status, request ID and rate limit of a failed response, read before its body.
+fn error_metadata(
+    response: &Response,
+    request_id: &str,
+) -> (reqwest::StatusCode, String, Option<RateLimitInfo>) {
+    let request_id = response
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or(request_id)
+        .to_string();
+    (
+        response.status(),
+        request_id,
+        RateLimitInfo::from_headers(response.headers()),
+    )
+}
+
+/// Whether a 401 body says the token expired, as opposed to being invalid or
+/// lacking permission, which logging in again would not fix.
+fn is_token_expired(body: &str) -> bool {
+    body.to_lowercase().contains("expired")
+}
+
+/// Header used to scope requests to a company.
+const COMPANY_ID_HEADER: &str = "x-company-id";
+
+/// Build an Authorization header value from an API key, stripping any `Bearer ` prefix.
+fn authorization_value(api_key: &str) -> Option<HeaderValue> {
+    let api_key = api_key.replace("Bearer ", "").replace("bearer ", "");
+    HeaderValue::from_str(&api_key).ok()
+}
+
+/// Wait before retry number `attempt`: 250ms doubling up to 8s.
+pub(crate) fn retry_backoff(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(250 << attempt.saturating_sub(1).min(5))
 }
 
 impl AGiXTSDK {
@@ -29,27 +315,259 @@ impl AGiXTSDK {
     pub fn new(base_uri: Option<String>, api_key: Option<String>, verbose: bool) -> Self {
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(USER_AGENT, HeaderValue::from_static(DEFAULT_USER_AGENT));
 
-        if let Some(key) = api_key {
-            let api_key = key.replace("Bearer ", "").replace("bearer ", "");
-            if let Ok(value) = HeaderValue::from_str(&api_key) {
-                headers.insert(AUTHORIZATION, value);
-            }
+        if let Some(value) = api_key.as_deref().and_then(authorization_value) {
+            headers.insert(AUTHORIZATION, value);
         }
 
         let base_uri = base_uri.unwrap_or_else(|| "http://localhost:7437".to_string());
         let base_uri = base_uri.trim_end_matches('/').to_string();
 
         Self {
+            api_root: format!("{}{}", base_uri, ApiVersion::default().path()),
             base_uri,
+            endpoints: None,
             client: Arc::new(reqwest::Client::new()),
-            headers: Arc::new(Mutex::new(headers)),
+            headers: Arc::new(ArcSwap::from_pointee(headers)),
             verbose,
+            validate_settings: false,
+            logger: Arc::new(StdoutLogger),
+            credentials: None,
+            relogin: Arc::default(),
+            rate_limiter: None,
+            scheduler: None,
+            lifecycle: Arc::default(),
+            last_rate_limit: Arc::default(),
+            #[cfg(feature = "compression")]
+            compress_requests_over: None,
+            interceptors: Vec::new(),
+            metrics: None,
+            transcript: None,
+            cache: None,
+            usage: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            fixtures: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            read_timeout: None,
+            deserialize_mode: DeserializeMode::default(),
+            options: RequestOptions::default(),
+        }
+    }
+
+    /// The base URI requests are currently sent to: the one that served the
+    /// latest request when fallback servers are configured with
+    /// `AGiXTSDKBuilder::base_uris`, otherwise the base URI.
+    pub fn active_base_uri(&self) -> &str {
+        match &self.endpoints {
+            Some(endpoints) => endpoints.active(),
+            None => &self.base_uri,
         }
     }
 
+    /// Rate limit headers of the most recent response that carried any.
+    ///
+    /// Shared with clients created by `with_options`, so batch jobs can slow
+    /// down before the server starts rejecting requests.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// for agent in client.get_agents().await? {
+    ///     client.get_agentconfig(&agent.id).await?;
+    ///     if let Some(info) = client.last_rate_limit() {
+    ///         if info.remaining == Some(0) {
+    ///             tokio::time::sleep(info.reset.unwrap_or_default()).await;
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn last_rate_limit(&self) -> Option<RateLimitInfo> {
+        self.last_rate_limit
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Create a builder for configuring a client.
+    pub fn builder() -> AGiXTSDKBuilder {
+        AGiXTSDKBuilder::new()
+    }
+
+    /// Store credentials so the client can log in again when the server rejects
+    /// an expired token, instead of returning an authentication error.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(Arc::new(credentials));
+        self
+    }
+
+    /// Return a client that applies `options` to every request it makes.
+    ///
+    /// The returned client shares headers, credentials and rate limits with `self`,
+    /// so it is cheap to create for a single long-running or latency-sensitive call.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self {
+            options,
+            ..self.clone()
+        }
+    }
+
+    /// Return a client that acts as another user, sending `token` (an API key
+    /// or JWT) instead of this client's token.
+    ///
+    /// The returned client shares the connection pool, interceptors, rate
+    /// limiter and options with `self`, so a backend serving many users can
+    /// create one per incoming request instead of a full client per user. It
+    /// gets its own headers and response cache, so logging in, `set_api_key`
+    /// and cached responses on one user's client never affect another's.
+    /// Credentials stored with `with_credentials` are not carried over.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(service: AGiXTSDK, user_token: &str) -> agixt_sdk::Result<()> {
+    /// let user = service.as_user(user_token)?;
+    /// let agents = user.get_agents().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn as_user(&self, token: &str) -> Result<Self> {
+        let value = authorization_value(token).ok_or_else(|| {
+            crate::Error::InvalidInput("token contains invalid header characters".to_string())
+        })?;
+        let mut headers = HeaderMap::clone(&self.headers());
+        headers.insert(AUTHORIZATION, value);
+        Ok(Self {
+            headers: Arc::new(ArcSwap::from_pointee(headers)),
+            credentials: None,
+            last_rate_limit: Arc::default(),
+            cache: self
+                .cache
+                .as_ref()
+                .map(|cache| Arc::new(cache::ResponseCache::new(cache.ttl()))),
+            ..self.clone()
+        })
+    }
+
+    /// Low-level requests to endpoints the SDK does not wrap yet, with the
+    /// client's authentication, retries and error mapping.
+    pub fn raw(&self) -> RawClient<'_> {
+        RawClient::new(self)
+    }
+
     // ==================== Authentication ====================
 
+    /// Replace the API key or JWT token used for subsequent requests.
+    ///
+    /// A leading `Bearer ` prefix is stripped, matching `new`.
+    pub async fn set_api_key(&self, api_key: &str) -> Result<()> {
+        let value = authorization_value(api_key).ok_or_else(|| {
+            crate::Error::InvalidInput("API key contains invalid header characters".to_string())
+        })?;
+        self.update_headers(|headers| {
+            headers.insert(AUTHORIZATION, value.clone());
+        });
+        self.invalidate_cache();
+        Ok(())
+    }
+
+    /// Remove the stored API key so subsequent requests are unauthenticated.
+    pub async fn clear_api_key(&self) {
+        self.update_headers(|headers| {
+            headers.remove(AUTHORIZATION);
+        });
+        self.invalidate_cache();
+    }
+
+    /// Get the API key or JWT token currently sent with requests, if any.
+    pub async fn get_bearer_token(&self) -> Option<String> {
+        self.headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    }
+
+    /// Export the server URI, token and company of this client, e.g. to save
+    /// them when a desktop app closes.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, SessionState};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let saved = serde_json::to_string(&client.session())?;
+    /// // ... on the next start
+    /// let state: SessionState = serde_json::from_str(&saved)?;
+    /// let client = AGiXTSDK::builder().build()?.restore_session(state)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn session(&self) -> SessionState {
+        let headers = self.headers();
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(String::from)
+        };
+        SessionState {
+            base_uri: self.base_uri.clone(),
+            token: header(AUTHORIZATION.as_str()).map(Secret::new),
+            company_id: header(COMPANY_ID_HEADER),
+        }
+    }
+
+    /// Point this client at the server of a saved `session` and use its token
+    /// and company, keeping the rest of its configuration.
+    ///
+    /// The token is not checked; call `get_user` to find out whether it
+    /// is still valid. Fallback servers configured with
+    /// `AGiXTSDKBuilder::base_uris` are dropped.
+    pub fn restore_session(mut self, session: SessionState) -> Result<Self> {
+        let token = session
+            .token
+            .as_ref()
+            .map(|token| {
+                authorization_value(token.expose_secret()).ok_or_else(|| {
+                    crate::Error::InvalidInput("token contains invalid header characters".into())
+                })
+            })
+            .transpose()?;
+        let company = session
+            .company_id
+            .as_deref()
+            .map(|id| {
+                HeaderValue::from_str(id)
+                    .map_err(|_| crate::Error::InvalidInput(format!("Invalid company ID: {}", id)))
+            })
+            .transpose()?;
+
+        let base_uri = session.base_uri.trim_end_matches('/').to_string();
+        let route = self.api_root[self.base_uri.len()..].to_string();
+        self.api_root = format!("{}{}", base_uri, route);
+        self.base_uri = base_uri;
+        self.endpoints = None;
+        // Headers are shared with clones, so this client gets its own copy
+        let mut headers = HeaderMap::clone(&self.headers());
+        for (name, value) in [
+            (AUTHORIZATION.as_str(), token),
+            (COMPANY_ID_HEADER, company),
+        ] {
+            match value {
+                Some(value) => headers.insert(name, value),
+                None => headers.remove(name),
+            };
+        }
+        self.headers = Arc::new(ArcSwap::from_pointee(headers));
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+        Ok(self)
+    }
+
     /// Login with username/password authentication.
     ///
     /// # Arguments
@@ -58,8 +576,14 @@ impl AGiXTSDK {
     /// * `mfa_token` - Optional TOTP code if MFA is enabled
     ///
     /// # Returns
-    /// JWT token on success, or None on failure
-    pub async fn login(&self, username: &str, password: &str, mfa_token: Option<&str>) -> Result<Option<String>> {
+    /// JWT token on success, or None if the server sent no token. A rejected
+    /// login is an `Error::AuthError`, see [`Error::auth_error_kind`](crate::Error::auth_error_kind)
+    pub async fn login(
+        &self,
+        username: &str,
+        password: &str,
+        mfa_token: Option<&str>,
+    ) -> Result<Option<String>> {
         let mut payload = serde_json::json!({
             "username": username,
             "password": password,
@@ -68,31 +592,32 @@ impl AGiXTSDK {
             payload["mfa_token"] = serde_json::json!(token);
         }
 
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/login", self.base_uri))
-            .json(&payload)
-            .send()
-            .await?;
+            .post(format!("{}/login", self.api_root))
+            .json(&payload);
+        let response = self.dispatch(request).await?;
 
         let status = response.status();
+        let rate_limit = RateLimitInfo::from_headers(response.headers());
         let text = response.text().await?;
 
         if self.verbose {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
-        
+        if status.is_client_error() || status.is_server_error() {
+            return Err(crate::Error::auth(status.as_u16(), &text).with_rate_limit(rate_limit));
+        }
+
+        let json: serde_json::Value = text.json()?;
+
         // Check for token in response (new auth flow)
         if status.is_success() {
             if let Some(token) = json.get("token").and_then(|t| t.as_str()) {
-                let mut headers = self.headers.lock().await;
-                if let Ok(value) = HeaderValue::from_str(token) {
-                    headers.insert(AUTHORIZATION, value);
-                }
+                self.install_token(token);
                 if self.verbose {
-                    println!("Logged in successfully");
+                    self.log("Logged in successfully");
                 }
                 return Ok(Some(token.to_string()));
             }
@@ -108,39 +633,122 @@ impl AGiXTSDK {
     /// * `otp` - TOTP code from authenticator app
     ///
     /// # Returns
-    /// JWT token on success, or None on failure
-    pub async fn login_magic_link(&self, email: &str, otp: &str) -> Result<Option<String>> {
-        let response = self
+    /// `LoginOutcome::TokenIssued` if the server returned a token, which the
+    /// client now uses, or `LoginOutcome::MagicLinkSent` with the login link to
+    /// pass to [`complete_magic_link_login`](Self::complete_magic_link_login).
+    /// A rejected code or expired link is an `Error::AuthError`
+    pub async fn login_magic_link(&self, email: &str, otp: &str) -> Result<LoginOutcome> {
+        let request = self
             .client
-            .post(&format!("{}/v1/login/magic-link", self.base_uri))
+            .post(format!("{}/login/magic-link", self.api_root))
             .json(&serde_json::json!({
                 "email": email,
                 "token": otp,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.dispatch(request).await?;
 
         let status = response.status();
+        let rate_limit = RateLimitInfo::from_headers(response.headers());
         let text = response.text().await?;
 
         if self.verbose {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
-        
-        if let Some(detail) = json.get("detail").and_then(|d| d.as_str()) {
-            if detail.contains("?token=") {
-                let token = detail.split("token=").nth(1).unwrap_or_default();
-                let mut headers = self.headers.lock().await;
-                if let Ok(value) = HeaderValue::from_str(token) {
-                    headers.insert(AUTHORIZATION, value);
+        if status.is_client_error() || status.is_server_error() {
+            return Err(crate::Error::auth(status.as_u16(), &text).with_rate_limit(rate_limit));
+        }
+
+        let json: serde_json::Value = text.json()?;
+
+        if let Some(token) = json.get("token").and_then(|t| t.as_str()) {
+            self.install_token(token);
+            return Ok(LoginOutcome::TokenIssued {
+                token: Secret::new(token.to_string()),
+            });
+        }
+        let detail = json.get("detail").and_then(|d| d.as_str());
+        match detail {
+            Some(detail) if detail.contains("token=") => {
+                if self.verbose {
+                    self.log(&format!("Log in at {}", detail));
                 }
-                println!("Log in at {}", detail);
-                return Ok(Some(token.to_string()));
+                Ok(LoginOutcome::MagicLinkSent {
+                    url: detail.to_string(),
+                })
             }
+            _ => Ok(LoginOutcome::Failed {
+                message: detail.map(String::from),
+            }),
+        }
+    }
+
+    /// Finish a magic-link login: take the token from the login link, or the
+    /// bare token, and use it to authenticate the client's requests.
+    ///
+    /// # Arguments
+    /// * `url_or_token` - Login link from `LoginOutcome::MagicLinkSent` or the
+    ///   login email, or the token itself
+    ///
+    /// # Returns
+    /// The installed token
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, LoginOutcome};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// if let LoginOutcome::MagicLinkSent { url } =
+    ///     client.login_magic_link("user@example.com", "123456").await?
+    /// {
+    ///     client.complete_magic_link_login(&url)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn complete_magic_link_login(&self, url_or_token: &str) -> Result<String> {
+        let url_or_token = url_or_token.trim();
+        let token = match url::Url::parse(url_or_token) {
+            Ok(url) => url
+                .query_pairs()
+                .find(|(name, _)| name == "token")
+                .map(|(_, token)| token.into_owned())
+                .ok_or_else(|| {
+                    crate::Error::InvalidInput(format!("no token in login link {}", url))
+                })?,
+            Err(_) => url_or_token.to_string(),
+        };
+        if token.is_empty() || HeaderValue::from_str(&token).is_err() {
+            return Err(crate::Error::InvalidInput(
+                "the login token is empty or malformed".to_string(),
+            ));
+        }
+        self.install_token(&token);
+        Ok(token)
+    }
+
+    /// Login with email and the MFA secret, generating the current TOTP code locally.
+    /// A login link sent back by the server is completed right away.
+    ///
+    /// # Arguments
+    /// * `email` - User's email address
+    /// * `totp_secret` - Base32 MFA secret from the registration OTP URI
+    ///
+    /// # Returns
+    /// `LoginOutcome::TokenIssued` on success, or `LoginOutcome::Failed` if the
+    /// server sent neither a token nor a login link
+    #[cfg(feature = "totp")]
+    pub async fn login_with_secret(&self, email: &str, totp_secret: &str) -> Result<LoginOutcome> {
+        let otp = crate::totp::generate(totp_secret)?;
+        match self.login_magic_link(email, &otp).await? {
+            LoginOutcome::MagicLinkSent { url } => {
+                let token = self.complete_magic_link_login(&url)?;
+                Ok(LoginOutcome::TokenIssued {
+                    token: Secret::new(token),
+                })
+            }
+            outcome => Ok(outcome),
         }
-        Ok(None)
     }
 
     /// Register a new user with username/password authentication.
@@ -156,6 +764,7 @@ impl AGiXTSDK {
     ///
     /// # Returns
     /// Response JSON with user_id, username, token on success
+    #[allow(clippy::too_many_arguments)]
     pub async fn register_user(
         &self,
         email: &str,
@@ -180,12 +789,11 @@ impl AGiXTSDK {
             payload["organization_name"] = serde_json::json!(org);
         }
 
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/user", self.base_uri))
-            .json(&payload)
-            .send()
-            .await?;
+            .post(format!("{}/user", self.api_root))
+            .json(&payload);
+        let response = self.dispatch(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -194,17 +802,17 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
+        let json: serde_json::Value = text.json()?;
 
         // Auto-login if token is returned
         if status.is_success() {
             if let Some(token) = json.get("token").and_then(|t| t.as_str()) {
-                let mut headers = self.headers.lock().await;
-                if let Ok(value) = HeaderValue::from_str(token) {
-                    headers.insert(AUTHORIZATION, value);
-                }
+                self.install_token(token);
                 if self.verbose {
-                    println!("Registered and logged in as {}", json.get("username").and_then(|u| u.as_str()).unwrap_or(""));
+                    self.log(&format!(
+                        "Registered and logged in as {}",
+                        json.get("username").and_then(|u| u.as_str()).unwrap_or("")
+                    ));
                 }
             }
         }
@@ -217,12 +825,8 @@ impl AGiXTSDK {
     /// # Returns
     /// JSON with provisioning_uri, secret, and mfa_enabled status
     pub async fn get_mfa_setup(&self) -> Result<serde_json::Value> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/user/mfa/setup", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+        let request = self.client.get(format!("{}/user/mfa/setup", self.api_root));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -231,7 +835,7 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
+        let json: serde_json::Value = text.json()?;
         Ok(json)
     }
 
@@ -241,15 +845,16 @@ impl AGiXTSDK {
     /// * `mfa_token` - TOTP code from authenticator app to verify setup
     ///
     /// # Returns
-    /// Response JSON with success message
+    /// Response JSON with success message. A rejected code is an `Error::AuthError`
     pub async fn enable_mfa(&self, mfa_token: &str) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/user/mfa/enable", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({ "mfa_token": mfa_token }))
-            .send()
-            .await?;
+            .post(format!("{}/user/mfa/enable", self.api_root))
+            .json(&serde_json::json!({ "mfa_token": mfa_token }));
+        let response = self
+            .execute(request)
+            .await
+            .map_err(crate::Error::into_auth)?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -258,7 +863,7 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
+        let json: serde_json::Value = text.json()?;
         Ok(json)
     }
 
@@ -269,8 +874,13 @@ impl AGiXTSDK {
     /// * `mfa_token` - Current TOTP code (optional)
     ///
     /// # Returns
-    /// Response JSON with success message
-    pub async fn disable_mfa(&self, password: Option<&str>, mfa_token: Option<&str>) -> Result<serde_json::Value> {
+    /// Response JSON with success message. A rejected code or password is an
+    /// `Error::AuthError`
+    pub async fn disable_mfa(
+        &self,
+        password: Option<&str>,
+        mfa_token: Option<&str>,
+    ) -> Result<serde_json::Value> {
         let mut payload = serde_json::json!({});
         if let Some(p) = password {
             payload["password"] = serde_json::json!(p);
@@ -279,13 +889,14 @@ impl AGiXTSDK {
             payload["mfa_token"] = serde_json::json!(t);
         }
 
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/user/mfa/disable", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .json(&payload)
-            .send()
-            .await?;
+            .post(format!("{}/user/mfa/disable", self.api_root))
+            .json(&payload);
+        let response = self
+            .execute(request)
+            .await
+            .map_err(crate::Error::into_auth)?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -294,7 +905,7 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
+        let json: serde_json::Value = text.json()?;
         Ok(json)
     }
 
@@ -313,17 +924,15 @@ impl AGiXTSDK {
         new_password: &str,
         confirm_password: &str,
     ) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/user/password/change", self.base_uri))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/user/password/change", self.api_root))
             .json(&serde_json::json!({
                 "current_password": current_password,
                 "new_password": new_password,
                 "confirm_password": confirm_password,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -332,7 +941,7 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
+        let json: serde_json::Value = text.json()?;
         Ok(json)
     }
 
@@ -344,17 +953,19 @@ impl AGiXTSDK {
     ///
     /// # Returns
     /// Response JSON with success message
-    pub async fn set_password(&self, new_password: &str, confirm_password: &str) -> Result<serde_json::Value> {
-        let response = self
+    pub async fn set_password(
+        &self,
+        new_password: &str,
+        confirm_password: &str,
+    ) -> Result<serde_json::Value> {
+        let request = self
             .client
-            .post(&format!("{}/v1/user/password/set", self.base_uri))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/user/password/set", self.api_root))
             .json(&serde_json::json!({
                 "new_password": new_password,
                 "confirm_password": confirm_password,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -363,18 +974,17 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
+        let json: serde_json::Value = text.json()?;
         Ok(json)
     }
 
     /// Check if a user exists.
     pub async fn user_exists(&self, email: &str) -> Result<bool> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/user/exists", self.base_uri))
-            .query(&[("email", email)])
-            .send()
-            .await?;
+            .get(format!("{}/user/exists", self.api_root))
+            .query(&[("email", email)]);
+        let response = self.dispatch(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -383,19 +993,42 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
+        let json: serde_json::Value = text.json()?;
         Ok(json.as_bool().unwrap_or(false))
     }
 
-    /// Update user information.
-    pub async fn update_user(&self, updates: serde_json::Value) -> Result<serde_json::Value> {
-        let response = self
+    /// Update user information. Only the fields set on `updates` are sent; an
+    /// update that sets nothing, or sets a preference named like one of its
+    /// fields, is rejected with `Error::InvalidInput`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn example(client: &agixt_sdk::AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// use agixt_sdk::UserUpdate;
+    ///
+    /// let user = client
+    ///     .update_user(UserUpdate::new().first_name("Ada").timezone("Europe/London"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn update_user(&self, updates: UserUpdate) -> Result<User> {
+        if updates.is_empty() {
+            return Err(crate::Error::InvalidInput(
+                "user update sets no fields".to_string(),
+            ));
+        }
+        if let Some(key) = updates.conflicting_preference() {
+            return Err(crate::Error::InvalidInput(format!(
+                "user update sets `{}` as a preference; use UserUpdate::{} instead",
+                key, key
+            )));
+        }
+        let request = self
             .client
-            .put(&format!("{}/v1/user", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .json(&updates)
-            .send()
-            .await?;
+            .put(format!("{}/user", self.api_root))
+            .json(&updates);
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -404,18 +1037,13 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
-        Ok(json)
+        text.json()
     }
 
-    /// Get user information.
-    pub async fn get_user(&self) -> Result<serde_json::Value> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/user", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+    /// Get the current user's information.
+    pub async fn get_user(&self) -> Result<User> {
+        let request = self.client.get(format!("{}/user", self.api_root));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -424,20 +1052,88 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let json: serde_json::Value = serde_json::from_str(&text)?;
-        Ok(json)
+        text.json()
+    }
+
+    /// Get the current user's wallet address and, when the server permits it,
+    /// its private key.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let wallet = client.get_user_wallet().await?;
+    /// println!("address: {}", wallet.address);
+    /// if let Some(key) = &wallet.private_key {
+    ///     // The key is wiped from memory once `wallet` is dropped.
+    ///     assert!(!key.expose_secret().is_empty());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_user_wallet(&self) -> Result<UserWallet> {
+        let request = self.client.get(format!("{}/user/wallet", self.api_root));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Delete the current user's account.
+    ///
+    /// The stored Authorization header is cleared once the account is deleted.
+    pub async fn delete_user(&self) -> Result<String> {
+        let request = self.client.delete(format!("{}/user", self.api_root));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct MessageResponse {
+            message: String,
+        }
+
+        let result: MessageResponse = text.json()?;
+        self.clear_api_key().await;
+        Ok(result.message)
+    }
+
+    /// Log out the current session.
+    ///
+    /// The stored Authorization header is always cleared, even if the server
+    /// rejects the request (for example because the token already expired).
+    pub async fn logout(&self) -> Result<()> {
+        let request = self.client.post(format!("{}/logout", self.api_root));
+        let response = self.dispatch(request).await;
+        self.clear_api_key().await;
+
+        let response = response?;
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        Ok(())
     }
 
     // ==================== Chains ====================
 
     /// Get all chains. Returns list with chain IDs.
     pub async fn get_chains(&self) -> Result<Vec<serde_json::Value>> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/chains", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+        let request = self.client.get(format!("{}/chains", self.api_root));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -446,7 +1142,7 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let data: serde_json::Value = serde_json::from_str(&text)?;
+        let data: serde_json::Value = text.json()?;
         if let Some(arr) = data.as_array() {
             return Ok(arr.clone());
         }
@@ -468,12 +1164,10 @@ impl AGiXTSDK {
 
     /// Get a chain by ID.
     pub async fn get_chain(&self, chain_id: &str) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/chain/{}", self.base_uri, chain_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/chain/{}", self.api_root, chain_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -482,7 +1176,7 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let data: serde_json::Value = serde_json::from_str(&text)?;
+        let data: serde_json::Value = text.json()?;
         // Response is {chain_name: {chain_data}} - extract the chain data
         if let Some(obj) = data.as_object() {
             if obj.len() == 1 {
@@ -496,12 +1190,10 @@ impl AGiXTSDK {
 
     /// Get chain responses by ID.
     pub async fn get_chain_responses(&self, chain_id: &str) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/chain/{}/responses", self.base_uri, chain_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/chain/{}/responses", self.api_root, chain_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -515,18 +1207,69 @@ impl AGiXTSDK {
             chain: serde_json::Value,
         }
 
-        let result: ChainResponse = serde_json::from_str(&text)?;
+        let result: ChainResponse = text.json()?;
         Ok(result.chain)
     }
 
+    /// List the past runs of a chain by ID, with their timestamps and statuses.
+    pub async fn get_chain_runs(&self, chain_id: &str) -> Result<Vec<ChainRun>> {
+        let request = self
+            .client
+            .get(format!("{}/chain/{}/runs", self.api_root, chain_id));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum RunsResponse {
+            Wrapped { runs: Vec<ChainRun> },
+            List(Vec<ChainRun>),
+        }
+
+        Ok(match text.json()? {
+            RunsResponse::Wrapped { runs } | RunsResponse::List(runs) => runs,
+        })
+    }
+
+    /// Get one run of a chain by ID, including the output of each step.
+    pub async fn get_chain_run(&self, chain_id: &str, run_id: &str) -> Result<ChainRun> {
+        let request = self.client.get(format!(
+            "{}/chain/{}/runs/{}",
+            self.api_root, chain_id, run_id
+        ));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum RunResponse {
+            Wrapped { run: ChainRun },
+            Run(ChainRun),
+        }
+
+        Ok(match text.json()? {
+            RunResponse::Wrapped { run } | RunResponse::Run(run) => run,
+        })
+    }
+
     /// Get chain arguments by ID.
     pub async fn get_chain_args(&self, chain_id: &str) -> Result<Vec<String>> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/chain/{}/args", self.base_uri, chain_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/chain/{}/args", self.api_root, chain_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -535,7 +1278,7 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let data: Vec<String> = serde_json::from_str(&text)?;
+        let data: Vec<String> = text.json()?;
         Ok(data)
     }
 
@@ -549,19 +1292,17 @@ impl AGiXTSDK {
         from_step: Option<i32>,
         chain_args: Option<HashMap<String, serde_json::Value>>,
     ) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/chain/{}/run", self.base_uri, chain_id))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/chain/{}/run", self.api_root, chain_id))
             .json(&serde_json::json!({
                 "prompt": user_input,
                 "agent_override": agent_id.unwrap_or(""),
                 "all_responses": all_responses.unwrap_or(false),
                 "from_step": from_step.unwrap_or(1),
                 "chain_args": chain_args.unwrap_or_default(),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -570,7 +1311,22 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
+    }
+
+    /// Run a chain by ID with all its responses off and from the first step,
+    /// taking the chain arguments from the fields of a `Serialize` struct by
+    /// [`to_args`](crate::prompt::to_args).
+    pub async fn run_chain_with_args<T: serde::Serialize + ?Sized>(
+        &self,
+        chain_id: &str,
+        user_input: &str,
+        agent_id: Option<&str>,
+        chain_args: &T,
+    ) -> Result<serde_json::Value> {
+        let chain_args = crate::prompt::to_args(chain_args)?;
+        self.run_chain(chain_id, user_input, agent_id, None, None, Some(chain_args))
+            .await
     }
 
     /// Run a specific chain step by chain ID.
@@ -582,17 +1338,18 @@ impl AGiXTSDK {
         agent_id: Option<&str>,
         chain_args: Option<HashMap<String, serde_json::Value>>,
     ) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/chain/{}/run/step/{}", self.base_uri, chain_id, step_number))
-            .headers(self.headers.lock().await.clone())
+            .post(format!(
+                "{}/chain/{}/run/step/{}",
+                self.api_root, chain_id, step_number
+            ))
             .json(&serde_json::json!({
                 "prompt": user_input,
                 "agent_override": agent_id,
                 "chain_args": chain_args.unwrap_or_default(),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -601,18 +1358,85 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
+    }
+
+    /// Run a chain by ID one step at a time, reporting progress through `progress`.
+    ///
+    /// Each step is sent with `run_chain_step`, so the callback sees a
+    /// `StepStarted` before and a `StepCompleted` after every step. Returns the
+    /// response of the last step.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, ChainProgress};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let output = client
+    ///     .run_chain_with_progress("chain-id", "Write a report", None, None, &|event| match event {
+    ///         ChainProgress::StepStarted { step, total } => println!("step {}/{}", step, total),
+    ///         ChainProgress::StepCompleted { step, .. } => println!("step {} done", step),
+    ///         _ => {}
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_chain_with_progress(
+        &self,
+        chain_id: &str,
+        user_input: &str,
+        agent_id: Option<&str>,
+        chain_args: Option<HashMap<String, serde_json::Value>>,
+        progress: &(dyn Fn(ChainProgress) + Send + Sync),
+    ) -> Result<serde_json::Value> {
+        let chain = self.get_chain(chain_id).await?;
+        let mut steps: Vec<i32> = chain
+            .get("steps")
+            .and_then(|v| v.as_array())
+            .map(|steps| {
+                steps
+                    .iter()
+                    .filter_map(|step| {
+                        step.get("step")
+                            .or_else(|| step.get("step_number"))
+                            .and_then(|v| v.as_i64())
+                            .map(|n| n as i32)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        steps.sort_unstable();
+        if steps.is_empty() {
+            return Err(crate::Error::InvalidInput(format!(
+                "chain {} has no steps",
+                chain_id
+            )));
+        }
+
+        let total = steps.len();
+        let mut output = serde_json::Value::Null;
+        for step in steps {
+            progress(ChainProgress::StepStarted { step, total });
+            output = self
+                .run_chain_step(chain_id, step, user_input, agent_id, chain_args.clone())
+                .await?;
+            progress(ChainProgress::StepCompleted {
+                step,
+                total,
+                response: output.clone(),
+            });
+        }
+        Ok(output)
     }
 
     /// Create a new chain. Returns chain info with ID.
     pub async fn add_chain(&self, chain_name: &str) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/chain", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({ "chain_name": chain_name }))
-            .send()
-            .await?;
+            .post(format!("{}/chain", self.api_root))
+            .json(&serde_json::json!({ "chain_name": chain_name }));
+        let response = self.execute_create(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -621,21 +1445,19 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
     }
 
     /// Import a chain with steps.
     pub async fn import_chain(&self, chain_name: &str, steps: serde_json::Value) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/chain/import", self.base_uri))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/chain/import", self.api_root))
             .json(&serde_json::json!({
                 "chain_name": chain_name,
                 "steps": steps,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -649,19 +1471,56 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
+    /// Capture a conversation as a reusable chain: each user message becomes a
+    /// `Chat` prompt step run by `agent_name`, in conversation order.
+    ///
+    /// Agent replies and activity messages are not stored; running the chain
+    /// asks the agent again.
+    pub async fn chain_from_conversation(
+        &self,
+        conversation_id: &str,
+        chain_name: &str,
+        agent_name: &str,
+    ) -> Result<String> {
+        let history = self.get_full_conversation(conversation_id).await?;
+        let steps: Vec<serde_json::Value> = crate::models::history_to_messages(&history)
+            .iter()
+            .filter(|message| message.normalized_role() == "user")
+            .enumerate()
+            .map(|(index, message)| {
+                serde_json::json!({
+                    "step": index + 1,
+                    "agent_name": agent_name,
+                    "prompt_type": "Prompt",
+                    "prompt": {
+                        "prompt_name": "Chat",
+                        "user_input": message.text(),
+                    },
+                })
+            })
+            .collect();
+        if steps.is_empty() {
+            return Err(crate::Error::InvalidInput(format!(
+                "conversation {} has no user messages to turn into steps",
+                conversation_id
+            )));
+        }
+
+        self.import_chain(chain_name, serde_json::Value::Array(steps))
+            .await
+    }
+
     /// Rename a chain by ID.
     pub async fn rename_chain(&self, chain_id: &str, new_name: &str) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .put(&format!("{}/v1/chain/{}", self.base_uri, chain_id))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({ "new_name": new_name }))
-            .send()
-            .await?;
+            .put(format!("{}/chain/{}", self.api_root, chain_id))
+            .json(&serde_json::json!({ "new_name": new_name }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -675,18 +1534,16 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
     /// Delete a chain by ID.
     pub async fn delete_chain(&self, chain_id: &str) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .delete(&format!("{}/v1/chain/{}", self.base_uri, chain_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .delete(format!("{}/chain/{}", self.api_root, chain_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -700,7 +1557,7 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -713,18 +1570,16 @@ impl AGiXTSDK {
         prompt_type: &str,
         prompt: serde_json::Value,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/chain/{}/step", self.base_uri, chain_id))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/chain/{}/step", self.api_root, chain_id))
             .json(&serde_json::json!({
                 "step_number": step_number,
                 "agent_id": agent_id,
                 "prompt_type": prompt_type,
                 "prompt": prompt,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -738,7 +1593,7 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -751,18 +1606,19 @@ impl AGiXTSDK {
         prompt_type: &str,
         prompt: serde_json::Value,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .put(&format!("{}/v1/chain/{}/step/{}", self.base_uri, chain_id, step_number))
-            .headers(self.headers.lock().await.clone())
+            .put(format!(
+                "{}/chain/{}/step/{}",
+                self.api_root, chain_id, step_number
+            ))
             .json(&serde_json::json!({
                 "step_number": step_number,
                 "agent_id": agent_id,
                 "prompt_type": prompt_type,
                 "prompt": prompt,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -776,7 +1632,7 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -787,16 +1643,14 @@ impl AGiXTSDK {
         old_step_number: i32,
         new_step_number: i32,
     ) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .patch(&format!("{}/v1/chain/{}/step/move", self.base_uri, chain_id))
-            .headers(self.headers.lock().await.clone())
+            .patch(format!("{}/chain/{}/step/move", self.api_root, chain_id))
             .json(&serde_json::json!({
                 "old_step_number": old_step_number,
                 "new_step_number": new_step_number,
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -810,18 +1664,17 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
     /// Delete a chain step by chain ID.
     pub async fn delete_step(&self, chain_id: &str, step_number: i32) -> Result<String> {
-        let response = self
-            .client
-            .delete(&format!("{}/v1/chain/{}/step/{}", self.base_uri, chain_id, step_number))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+        let request = self.client.delete(format!(
+            "{}/chain/{}/step/{}",
+            self.api_root, chain_id, step_number
+        ));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -835,7 +1688,7 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -848,17 +1701,15 @@ impl AGiXTSDK {
         prompt: &str,
         prompt_category: Option<&str>,
     ) -> Result<serde_json::Value> {
-        let response = self
-            .client
-            .post(&format!("{}/v1/prompt", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({
-                "prompt_name": prompt_name,
-                "prompt": prompt,
-                "prompt_category": prompt_category.unwrap_or("Default"),
-            }))
-            .send()
-            .await?;
+        let request =
+            self.client
+                .post(format!("{}/prompt", self.api_root))
+                .json(&serde_json::json!({
+                    "prompt_name": prompt_name,
+                    "prompt": prompt,
+                    "prompt_category": prompt_category.unwrap_or("Default"),
+                }));
+        let response = self.execute_create(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -867,17 +1718,15 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
     }
 
     /// Get a prompt by ID.
     pub async fn get_prompt(&self, prompt_id: &str) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/prompt/{}", self.base_uri, prompt_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/prompt/{}", self.api_root, prompt_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -886,21 +1735,19 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
     }
 
     /// Get all prompts in a category.
-    pub async fn get_prompts(&self, prompt_category: Option<&str>) -> Result<Vec<serde_json::Value>> {
-        let response = self
+    pub async fn get_prompts(
+        &self,
+        prompt_category: Option<&str>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let request = self
             .client
-            .get(&format!("{}/v1/prompts", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .query(&[("prompt_category", prompt_category.unwrap_or("Default"))])
-            .send()
-            .await?;
-
-        let status = response.status();
-        let text = response.text().await?;
+            .get(format!("{}/prompts", self.api_root))
+            .query(&[("prompt_category", prompt_category.unwrap_or("Default"))]);
+        let (status, text) = self.execute_cached(request).await?;
 
         if self.verbose {
             self.parse_response(status, &text).await?;
@@ -911,31 +1758,28 @@ impl AGiXTSDK {
             prompts: Vec<serde_json::Value>,
         }
 
-        let result: PromptsResponse = serde_json::from_str(&text)?;
+        let result: PromptsResponse = text.json()?;
         Ok(result.prompts)
     }
 
     /// Get all global and user prompts with full details including IDs.
     pub async fn get_all_prompts(&self) -> Result<serde_json::Value> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/prompt/all", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
-
-        let status = response.status();
-        let text = response.text().await?;
+        let request = self.client.get(format!("{}/prompt/all", self.api_root));
+        let (status, text) = self.execute_cached(request).await?;
 
         if self.verbose {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
     }
 
     /// Get prompt ID by name. Returns None if not found.
-    pub async fn get_prompt_id_by_name(&self, prompt_name: &str, category: Option<&str>) -> Result<Option<String>> {
+    pub async fn get_prompt_id_by_name(
+        &self,
+        prompt_name: &str,
+        category: Option<&str>,
+    ) -> Result<Option<String>> {
         let prompts = self.get_prompts(category).await?;
         for prompt in prompts {
             if let Some(name) = prompt.get("name").and_then(|v| v.as_str()) {
@@ -949,15 +1793,10 @@ impl AGiXTSDK {
 
     /// Get all prompt categories with IDs.
     pub async fn get_prompt_categories(&self) -> Result<Vec<serde_json::Value>> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/prompt/categories", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
-
-        let status = response.status();
-        let text = response.text().await?;
+            .get(format!("{}/prompt/categories", self.api_root));
+        let (status, text) = self.execute_cached(request).await?;
 
         if self.verbose {
             self.parse_response(status, &text).await?;
@@ -968,18 +1807,19 @@ impl AGiXTSDK {
             categories: Vec<serde_json::Value>,
         }
 
-        let result: CategoriesResponse = serde_json::from_str(&text)?;
+        let result: CategoriesResponse = text.json()?;
         Ok(result.categories)
     }
 
     /// Get prompts by category ID.
-    pub async fn get_prompts_by_category_id(&self, category_id: &str) -> Result<Vec<serde_json::Value>> {
-        let response = self
+    pub async fn get_prompts_by_category_id(
+        &self,
+        category_id: &str,
+    ) -> Result<Vec<serde_json::Value>> {
+        let request = self
             .client
-            .get(&format!("{}/v1/prompt/category/{}", self.base_uri, category_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/prompt/category/{}", self.api_root, category_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -993,18 +1833,16 @@ impl AGiXTSDK {
             prompts: Vec<serde_json::Value>,
         }
 
-        let result: PromptsResponse = serde_json::from_str(&text)?;
+        let result: PromptsResponse = text.json()?;
         Ok(result.prompts)
     }
 
     /// Get prompt arguments by ID.
     pub async fn get_prompt_args(&self, prompt_id: &str) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/prompt/{}/args", self.base_uri, prompt_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/prompt/{}/args", self.api_root, prompt_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1018,18 +1856,16 @@ impl AGiXTSDK {
             prompt_args: serde_json::Value,
         }
 
-        let result: PromptArgsResponse = serde_json::from_str(&text)?;
+        let result: PromptArgsResponse = text.json()?;
         Ok(result.prompt_args)
     }
 
     /// Delete a prompt by ID.
     pub async fn delete_prompt(&self, prompt_id: &str) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .delete(&format!("{}/v1/prompt/{}", self.base_uri, prompt_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .delete(format!("{}/prompt/{}", self.api_root, prompt_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1043,19 +1879,17 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
     /// Update a prompt by ID.
     pub async fn update_prompt(&self, prompt_id: &str, prompt: &str) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .put(&format!("{}/v1/prompt/{}", self.base_uri, prompt_id))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({ "prompt": prompt }))
-            .send()
-            .await?;
+            .put(format!("{}/prompt/{}", self.api_root, prompt_id))
+            .json(&serde_json::json!({ "prompt": prompt }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1069,19 +1903,17 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
     /// Rename a prompt by ID.
     pub async fn rename_prompt(&self, prompt_id: &str, new_name: &str) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .patch(&format!("{}/v1/prompt/{}", self.base_uri, prompt_id))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({ "prompt_name": new_name }))
-            .send()
-            .await?;
+            .patch(format!("{}/prompt/{}", self.api_root, prompt_id))
+            .json(&serde_json::json!({ "prompt_name": new_name }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1095,20 +1927,16 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
     // ==================== Companies ====================
 
-    /// Get companies.
-    pub async fn get_companies(&self) -> Result<Vec<serde_json::Value>> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/companies", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+    /// Get companies the current user belongs to.
+    pub async fn get_companies(&self) -> Result<Vec<Company>> {
+        let request = self.client.get(format!("{}/companies", self.api_root));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1117,26 +1945,162 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let data: serde_json::Value = serde_json::from_str(&text)?;
-        if let Some(arr) = data.as_array() {
-            return Ok(arr.clone());
+        let data: serde_json::Value = text.json()?;
+        if data.is_array() {
+            return Ok(serde_json::from_value(data)?);
         }
-        if let Some(obj) = data.as_object() {
-            if let Some(companies) = obj.get("companies").and_then(|v| v.as_array()) {
-                return Ok(companies.clone());
-            }
+        if let Some(companies) = data.get("companies") {
+            return Ok(serde_json::from_value(companies.clone())?);
         }
         Ok(vec![])
     }
 
     /// Get company by ID.
-    pub async fn get_company(&self, company_id: &str) -> Result<serde_json::Value> {
-        let response = self
+    pub async fn get_company(&self, company_id: &str) -> Result<Company> {
+        let request = self
+            .client
+            .get(format!("{}/companies/{}", self.api_root, company_id));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Create a new company, optionally as a child of another company.
+    pub async fn create_company(
+        &self,
+        name: &str,
+        parent_company_id: Option<&str>,
+    ) -> Result<Company> {
+        let mut payload = serde_json::json!({ "name": name });
+        if let Some(parent) = parent_company_id {
+            payload["parent_company_id"] = serde_json::json!(parent);
+        }
+
+        let request = self
+            .client
+            .post(format!("{}/companies", self.api_root))
+            .json(&payload);
+        let response = self.execute_create(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Rename a company by ID.
+    pub async fn update_company(&self, company_id: &str, name: &str) -> Result<Company> {
+        let request = self
+            .client
+            .put(format!("{}/companies/{}", self.api_root, company_id))
+            .json(&serde_json::json!({ "name": name }));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Get the users belonging to a company by ID.
+    pub async fn get_company_users(&self, company_id: &str) -> Result<Vec<CompanyUser>> {
+        let request = self
+            .client
+            .get(format!("{}/companies/{}/users", self.api_root, company_id));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct UsersResponse {
+            users: Vec<CompanyUser>,
+        }
+
+        let result: UsersResponse = text.json()?;
+        Ok(result.users)
+    }
+
+    /// Get the agents belonging to a company by ID.
+    pub async fn get_company_agents(&self, company_id: &str) -> Result<Vec<Agent>> {
+        let request = self
+            .client
+            .get(format!("{}/companies/{}/agents", self.api_root, company_id));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum AgentsResponse {
+            Wrapped { agents: Vec<Agent> },
+            List(Vec<Agent>),
+        }
+
+        Ok(match text.json()? {
+            AgentsResponse::Wrapped { agents } | AgentsResponse::List(agents) => agents,
+        })
+    }
+
+    /// Get the default persona for agents of a company by ID.
+    pub async fn get_company_persona(&self, company_id: &str) -> Result<String> {
+        let request = self.client.get(format!(
+            "{}/companies/{}/persona",
+            self.api_root, company_id
+        ));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct PersonaResponse {
+            #[serde(default, alias = "message")]
+            persona: String,
+        }
+
+        let result: PersonaResponse = text.json()?;
+        Ok(result.persona)
+    }
+
+    /// Set the default persona for agents of a company by ID.
+    pub async fn update_company_persona(&self, company_id: &str, persona: &str) -> Result<String> {
+        let request = self
             .client
-            .get(&format!("{}/v1/company/{}", self.base_uri, company_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .put(format!(
+                "{}/companies/{}/persona",
+                self.api_root, company_id
+            ))
+            .json(&serde_json::json!({ "persona": persona }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1145,23 +2109,84 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        #[derive(serde::Deserialize)]
+        struct MessageResponse {
+            message: String,
+        }
+
+        let result: MessageResponse = text.json()?;
+        Ok(result.message)
+    }
+
+    /// Scope subsequent requests to a company, or clear the scope with `None`.
+    ///
+    /// The company ID is sent in the `X-Company-ID` header, which the server uses
+    /// to resolve tenant-specific agents, prompts and chains.
+    pub async fn set_company(&self, company_id: Option<&str>) -> Result<()> {
+        let value = company_id
+            .map(|id| {
+                HeaderValue::from_str(id)
+                    .map_err(|_| crate::Error::InvalidInput(format!("Invalid company ID: {}", id)))
+            })
+            .transpose()?;
+        self.update_headers(|headers| match &value {
+            Some(value) => {
+                headers.insert(COMPANY_ID_HEADER, value.clone());
+            }
+            None => {
+                headers.remove(COMPANY_ID_HEADER);
+            }
+        });
+        self.invalidate_cache();
+        Ok(())
     }
 
     // ==================== Invitations ====================
 
-    /// Create an invitation.
-    pub async fn create_invitation(&self, email: &str, role: Option<&str>) -> Result<serde_json::Value> {
-        let response = self
+    /// Invite a user by email, optionally into a specific company.
+    ///
+    /// # Arguments
+    /// * `email` - Email address of the invitee
+    /// * `role` - Role to grant (defaults to "user")
+    /// * `company_id` - Company to invite into (defaults to the current user's company)
+    pub async fn create_invitation(
+        &self,
+        email: &str,
+        role: Option<&str>,
+        company_id: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let mut payload = serde_json::json!({
+            "email": email,
+            "role": role.unwrap_or("user"),
+        });
+        if let Some(id) = company_id {
+            payload["company_id"] = serde_json::json!(id);
+        }
+
+        let request = self
             .client
-            .post(&format!("{}/v1/invitation", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({
-                "email": email,
-                "role": role.unwrap_or("user"),
-            }))
-            .send()
-            .await?;
+            .post(format!("{}/invitation", self.api_root))
+            .json(&payload);
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Get pending invitations, optionally filtered to a company.
+    pub async fn get_invitations(&self, company_id: Option<&str>) -> Result<Vec<Invitation>> {
+        let url = match company_id {
+            Some(id) => format!("{}/invitations/{}", self.api_root, id),
+            None => format!("{}/invitations", self.api_root),
+        };
+        let request = self.client.get(url);
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1170,17 +2195,21 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        #[derive(serde::Deserialize)]
+        struct InvitationsResponse {
+            invitations: Vec<Invitation>,
+        }
+
+        let result: InvitationsResponse = text.json()?;
+        Ok(result.invitations)
     }
 
     /// Delete an invitation.
     pub async fn delete_invitation(&self, invitation_id: &str) -> Result<String> {
-        let response = self
+        let request = self
             .client
-            .delete(&format!("{}/v1/invitation/{}", self.base_uri, invitation_id))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .delete(format!("{}/invitation/{}", self.api_root, invitation_id));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1194,7 +2223,7 @@ impl AGiXTSDK {
             message: String,
         }
 
-        let result: MessageResponse = serde_json::from_str(&text)?;
+        let result: MessageResponse = text.json()?;
         Ok(result.message)
     }
 
@@ -1202,12 +2231,8 @@ impl AGiXTSDK {
 
     /// Get OAuth2 providers.
     pub async fn get_oauth_providers(&self) -> Result<Vec<serde_json::Value>> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/oauth", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+        let request = self.client.get(format!("{}/oauth", self.api_root));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1216,38 +2241,129 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        let data: serde_json::Value = serde_json::from_str(&text)?;
+        let data: serde_json::Value = text.json()?;
         if let Some(arr) = data.as_array() {
             return Ok(arr.clone());
         }
         Ok(vec![])
     }
 
-    // ==================== Text to Speech ====================
+    /// Get the OAuth providers configured on the server, with what a login page
+    /// needs to render their sign-in buttons.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// for provider in client.get_sso_providers().await? {
+    ///     println!(
+    ///         "{}: {}?client_id={}&scope={}",
+    ///         provider.name,
+    ///         provider.authorize_url,
+    ///         provider.client_id,
+    ///         provider.scopes.join(" ")
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_sso_providers(&self) -> Result<Vec<SsoProvider>> {
+        let request = self.client.get(format!("{}/oauth", self.api_root));
+        let response = self.execute(request).await?;
 
-    /// Generate speech from text.
-    pub async fn text_to_speech(&self, text: &str, voice: Option<&str>) -> Result<Vec<u8>> {
-        let response = self
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum ProvidersResponse {
+            Wrapped { providers: Vec<SsoProvider> },
+            List(Vec<SsoProvider>),
+        }
+
+        Ok(match text.json()? {
+            ProvidersResponse::Wrapped { providers } | ProvidersResponse::List(providers) => {
+                providers
+            }
+        })
+    }
+
+    /// Complete an OAuth2 SSO sign-in (Google, Microsoft, GitHub, ...).
+    ///
+    /// # Arguments
+    /// * `provider` - OAuth provider name as returned by `get_oauth_providers`
+    /// * `code` - Authorization code received on the redirect
+    /// * `referrer` - Optional redirect URI used when requesting the code
+    ///
+    /// # Returns
+    /// JWT token on success, or None on failure. The token is stored in the client headers.
+    pub async fn oauth2_login(
+        &self,
+        provider: &str,
+        code: &str,
+        referrer: Option<&str>,
+    ) -> Result<Option<String>> {
+        let mut payload = serde_json::json!({ "code": code });
+        if let Some(r) = referrer {
+            payload["referrer"] = serde_json::json!(r);
+        }
+
+        let request = self
             .client
-            .post(&format!("{}/v1/audio/speech", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .json(&serde_json::json!({
-                "input": text,
-                "voice": voice.unwrap_or("default"),
-            }))
-            .send()
-            .await?;
+            .post(format!(
+                "{}/oauth2/{}",
+                self.api_root,
+                provider.to_lowercase()
+            ))
+            .json(&payload);
+        let response = self.dispatch(request).await?;
 
         let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
 
         if !status.is_success() {
-            let text = response.text().await?;
-            return Err(crate::Error::ApiError {
-                status: status.as_u16(),
-                message: text,
+            return Ok(None);
+        }
+
+        let json: serde_json::Value = text.json()?;
+
+        // The token is returned directly or embedded in a magic link
+        let token = json
+            .get("token")
+            .and_then(|t| t.as_str())
+            .map(String::from)
+            .or_else(|| {
+                json.get("detail")
+                    .and_then(|d| d.as_str())
+                    .and_then(|d| d.split("token=").nth(1))
+                    .map(String::from)
             });
+
+        if let Some(token) = &token {
+            self.install_token(token);
         }
+        Ok(token)
+    }
+
+    // ==================== Text to Speech ====================
 
+    /// Generate speech from text.
+    pub async fn text_to_speech(&self, text: &str, voice: Option<&str>) -> Result<Vec<u8>> {
+        let request = self
+            .client
+            .post(format!("{}/audio/speech", self.api_root))
+            .json(&serde_json::json!({
+                "input": text,
+                "voice": voice.unwrap_or("default"),
+            }));
+        let response = self.execute(request).await?;
         let bytes = response.bytes().await?;
         Ok(bytes.to_vec())
     }
@@ -1256,16 +2372,14 @@ impl AGiXTSDK {
 
     /// Generate an image.
     pub async fn generate_image(&self, prompt: &str, n: Option<i32>) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .post(&format!("{}/v1/images/generations", self.base_uri))
-            .headers(self.headers.lock().await.clone())
+            .post(format!("{}/images/generations", self.api_root))
             .json(&serde_json::json!({
                 "prompt": prompt,
                 "n": n.unwrap_or(1),
-            }))
-            .send()
-            .await?;
+            }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -1274,53 +2388,4673 @@ impl AGiXTSDK {
             self.parse_response(status, &text).await?;
         }
 
-        Ok(serde_json::from_str(&text)?)
+        text.json()
     }
 
-    // ==================== Utility ====================
+    // ==================== Cache ====================
 
-    /// Parse and log response if verbose mode is enabled.
-    pub(crate) async fn parse_response(
-        &self,
-        status: reqwest::StatusCode,
-        body: &str,
-    ) -> Result<()> {
-        println!("Status Code: {}", status);
-        println!("Response JSON:");
-        
-        if status.is_success() {
-            println!("{}", body);
-        } else {
-            println!("{}", body);
-            return Err(crate::Error::ApiError {
-                status: status.as_u16(),
-                message: body.to_string(),
-            });
+    /// Drop all cached responses so the next read goes to the server.
+    ///
+    /// The cache is also cleared automatically after any non-GET request and
+    /// when the API key or company changes.
+    pub fn invalidate_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
         }
-        println!("\n");
-        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // ==================== Health ====================
 
-    #[tokio::test]
-    async fn test_new_client() {
-        let client = AGiXTSDK::new(None, None, false);
-        assert_eq!(client.base_uri, "http://localhost:7437");
-        assert!(!client.verbose);
+    /// Check whether the server is up and answering requests.
+    ///
+    /// Returns `Ok(false)` when the server responds with an error status and
+    /// an error when it cannot be reached at all.
+    pub async fn health(&self) -> Result<bool> {
+        let request = self.client.get(format!("{}/health", self.base_uri));
+        let response = self.dispatch(request).await?;
+
+        let status = response.status();
+        if self.verbose {
+            let text = response.text().await?;
+            self.log_response(status, &text);
+        }
+        Ok(status.is_success())
     }
 
-    #[tokio::test]
-    async fn test_new_client_with_options() {
-        let client = AGiXTSDK::new(
-            Some("https://api.example.com/".to_string()),
-            Some("test-key".to_string()),
-            true,
-        );
-        assert_eq!(client.base_uri, "https://api.example.com");
-        assert!(client.verbose);
+    /// Get the server version and enabled features.
+    ///
+    /// Servers that predate the info endpoint answer 404; they are reported as
+    /// a `ServerInfo` without a version or features rather than as an error, so
+    /// callers can fall back to older endpoints.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let info = client.server_info().await?;
+    /// if info.supports("wallet") {
+    ///     println!("{}", client.get_user_wallet().await?.address);
+    /// } else {
+    ///     println!("AGiXT {} has no wallets", info.version.as_deref().unwrap_or("(unknown)"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn server_info(&self) -> Result<ServerInfo> {
+        let request = self.client.get(format!("{}/server/info", self.api_root));
+        let response = match self.execute(request).await {
+            Ok(response) => response,
+            Err(e) if e.status() == Some(404) => return Ok(ServerInfo::default()),
+            Err(e) => return Err(e),
+        };
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Poll `health` every `interval` until the server is ready, giving up after `timeout`.
+    ///
+    /// Useful for integration tests and container deployments where the client
+    /// may start before the server.
+    pub async fn wait_for_ready(
+        &self,
+        timeout: std::time::Duration,
+        interval: std::time::Duration,
+    ) -> Result<()> {
+        let started = web_time::Instant::now();
+        loop {
+            let last_error = match self.health().await {
+                Ok(true) => return Ok(()),
+                Ok(false) => "server reported unhealthy".to_string(),
+                Err(e) => e.to_string(),
+            };
+            if started.elapsed() + interval > timeout {
+                return Err(crate::Error::Other(format!(
+                    "AGiXT server at {} was not ready after {:?}: {}",
+                    self.base_uri, timeout, last_error
+                )));
+            }
+            sleep(interval).await;
+        }
+    }
+
+    // ==================== Utility ====================
+
+    /// Apply the client headers and interceptors to a request and send it, waiting
+    /// for a slot of the concurrency cap and the rate limiter if configured.
+    pub(crate) async fn dispatch(&self, request: reqwest::RequestBuilder) -> Result<Response> {
+        // Counted by `shutdown` until the response body is read
+        let in_flight = self.lifecycle.enter()?;
+        // Held until the response headers arrive
+        let _permit = match &self.scheduler {
+            Some(scheduler) => Some(
+                self.cancellable(scheduler.acquire(self.options.priority))
+                    .await?,
+            ),
+            None => None,
+        };
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+        let defaults = self.headers();
+        let mut request = request.build()?;
+        // Client-wide headers are defaults: a header set on the request itself,
+        // such as a multipart Content-Type, takes precedence
+        for name in defaults.keys() {
+            if !request.headers().contains_key(name) {
+                for value in defaults.get_all(name) {
+                    request.headers_mut().append(name.clone(), value.clone());
+                }
+            }
+        }
+        if !request.headers().contains_key(REQUEST_ID_HEADER) {
+            let request_id = uuid::Uuid::new_v4().to_string();
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                request.headers_mut().insert(REQUEST_ID_HEADER, value);
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(timeout) = self.options.timeout {
+            *request.timeout_mut() = Some(timeout);
+        }
+        #[cfg(feature = "compression")]
+        if let Some(min_size) = self.compress_requests_over {
+            compression::gzip_body(&mut request, min_size)?;
+        }
+        for interceptor in &self.interceptors {
+            interceptor.on_request(&mut request);
+        }
+
+        let method = request.method().clone();
+        if method != reqwest::Method::GET {
+            self.invalidate_cache();
+        }
+        let endpoint = self
+            .metrics
+            .as_ref()
+            .map(|_| metrics::endpoint_label(request.url().path()));
+        let started = web_time::Instant::now();
+        #[cfg(feature = "tracing")]
+        let result = {
+            use tracing::Instrument;
+            let span = tracing::debug_span!(
+                "agixt_request",
+                method = %method,
+                path = request.url().path(),
+                request_id = request
+                    .headers()
+                    .get(REQUEST_ID_HEADER)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default(),
+            );
+            self.cancellable(self.send(request).instrument(span))
+                .await?
+        };
+        #[cfg(not(feature = "tracing"))]
+        let result = self.cancellable(self.send(request)).await?;
+        let elapsed = started.elapsed();
+        if let (Some(observer), Some(endpoint)) = (&self.metrics, endpoint) {
+            observer.record(&RequestMetrics {
+                method: method.clone(),
+                endpoint,
+                status: result.as_ref().ok().map(|r| r.status().as_u16()),
+                latency: elapsed,
+            });
+        }
+        let response = result?;
+        if let Some(info) = RateLimitInfo::from_headers(response.headers()) {
+            *self
+                .last_rate_limit
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(info);
+        }
+        for interceptor in &self.interceptors {
+            interceptor.on_response(&method, &response, elapsed);
+        }
+        let response = Response::new(response, None, self.deserialize_mode).in_flight(in_flight);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(limit) = self.read_timeout {
+            return Ok(response.read_timeout(limit));
+        }
+        Ok(response)
+    }
+
+    /// Send a prepared request, failing when its response headers do not
+    /// arrive within the read timeout.
+    async fn send(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(limit) = self.read_timeout {
+            return tokio::time::timeout(limit, self.transmit(request))
+                .await
+                .unwrap_or_else(|_| Err(crate::Error::read_timed_out()));
+        }
+        self.transmit(request).await
+    }
+
+    /// Send a prepared request, or answer it from recorded fixtures.
+    async fn transmit(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(fixtures) = &self.fixtures {
+            return fixtures.send(&self.client, request).await;
+        }
+        match &self.endpoints {
+            Some(endpoints) => endpoints.send(&self.client, request).await,
+            None => Ok(self.client.execute(request).await?),
+        }
+    }
+
+    /// Run `future` unless the cancellation token of the options fires first,
+    /// or a shutdown deadline passes.
+    async fn cancellable<F: std::future::Future>(&self, future: F) -> Result<F::Output> {
+        let aborting = self.lifecycle.aborting();
+        let token = self.options.cancellation.as_ref();
+        if aborting.is_cancelled() || token.is_some_and(|token| token.is_cancelled()) {
+            return Err(crate::Error::Cancelled);
+        }
+        let cancelled = async {
+            let aborted = aborting.cancelled();
+            match token {
+                Some(token) => {
+                    let cancelled = token.cancelled();
+                    futures_util::pin_mut!(aborted, cancelled);
+                    futures_util::future::select(aborted, cancelled).await;
+                }
+                None => aborted.await,
+            }
+        };
+        futures_util::pin_mut!(future, cancelled);
+        match futures_util::future::select(future, cancelled).await {
+            futures_util::future::Either::Left((output, _)) => Ok(output),
+            futures_util::future::Either::Right(_) => Err(crate::Error::Cancelled),
+        }
+    }
+
+    /// Send an authenticated request.
+    ///
+    /// Each request carries an `X-Request-ID` (from `RequestOptions::request_id` or a
+    /// fresh UUID), and error responses are returned as `Error::ApiError` tagged with it.
+    /// If the server answers 401 and credentials were configured with
+    /// `with_credentials`, the client logs in again and retries the request once.
+    /// Retryable failures are sent again according to `RequestOptions::retries`,
+    /// all within `RequestOptions::deadline` when one is set.
+    ///
+    /// Errors, including those reading or decoding the response body, name the
+    /// method and path of the request.
+    pub(crate) async fn execute(&self, request: reqwest::RequestBuilder) -> Result<Response> {
+        let context = request
+            .try_clone()
+            .and_then(|r| r.build().ok())
+            .map(|r| ErrorContext::request(r.method().clone(), r.url().path()));
+        match self.send_with_retries(request).await {
+            Ok(response) => Ok(response.with_context(context)),
+            Err(e) => Err(e.with_context(context)),
+        }
+    }
+
+    /// Like `execute`, for POST requests that create a resource. With retries
+    /// enabled the same `Idempotency-Key` is sent on every attempt, so a
+    /// server that honors it creates the resource once even when a response
+    /// was lost and the request retried.
+    pub(crate) async fn execute_create(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<Response> {
+        let request = if self.options.max_retries > 0 {
+            request.header(IDEMPOTENCY_KEY_HEADER, uuid::Uuid::new_v4().to_string())
+        } else {
+            request
+        };
+        self.execute(request).await
+    }
+
+    /// Send a request, retrying retryable failures as configured in the options.
+    async fn send_with_retries(&self, request: reqwest::RequestBuilder) -> Result<Response> {
+        let request_id = self
+            .options
+            .request_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let mut request = request.header(REQUEST_ID_HEADER, request_id.as_str());
+        let started = web_time::Instant::now();
+        let mut attempts = 0;
+        let mut last_error = None;
+
+        loop {
+            attempts += 1;
+            let retry = if attempts <= self.options.max_retries {
+                request.try_clone()
+            } else {
+                None
+            };
+            let attempt = self.send_once(request, &request_id);
+            let result = match self.options.deadline {
+                None => attempt.await,
+                Some(deadline) => {
+                    let expired = sleep(deadline.saturating_sub(started.elapsed()));
+                    futures_util::pin_mut!(attempt, expired);
+                    match futures_util::future::select(attempt, expired).await {
+                        futures_util::future::Either::Left((result, _)) => result,
+                        futures_util::future::Either::Right(_) => {
+                            return Err(crate::Error::DeadlineExceeded {
+                                deadline,
+                                attempts,
+                                last_error,
+                            })
+                        }
+                    }
+                }
+            };
+            let error = match result {
+                Ok(response) => return Ok(response),
+                Err(e) => e,
+            };
+            let retry = match retry {
+                Some(retry) if error.is_retryable() => retry,
+                _ => return Err(error),
+            };
+
+            // Wait at least as long as the server asked in Retry-After
+            let backoff = match error.rate_limit().and_then(|info| info.retry_after) {
+                Some(retry_after) => retry_backoff(attempts).max(retry_after),
+                None => retry_backoff(attempts),
+            };
+            if let Some(deadline) = self.options.deadline {
+                if started.elapsed() + backoff >= deadline {
+                    return Err(crate::Error::DeadlineExceeded {
+                        deadline,
+                        attempts,
+                        last_error: Some(Box::new(error)),
+                    });
+                }
+            }
+            if self.verbose {
+                self.log(&format!("Retrying after {:?}: {}", backoff, error));
+            }
+            self.cancellable(sleep(backoff)).await?;
+            last_error = Some(Box::new(error));
+            request = retry;
+        }
+    }
+
+    /// Send a request once, logging in again when credentials are stored and
+    /// the server answers 401 because the token expired.
+    async fn send_once(
+        &self,
+        request: reqwest::RequestBuilder,
+        request_id: &str,
+    ) -> Result<Response> {
+        let retry = request.try_clone();
+        let sent_with = self.headers.load().get(AUTHORIZATION).cloned();
+        let response = self.dispatch(request).await?;
+
+        let (Some(retry), Some(credentials), reqwest::StatusCode::UNAUTHORIZED) =
+            (retry, self.credentials.clone(), response.status())
+        else {
+            return self.error_for_status(response, request_id).await;
+        };
+        let (status, request_id, rate_limit) = error_metadata(&response, request_id);
+        let body = response.text().await?;
+        if !is_token_expired(&body) {
+            return Err(self.status_error(status, &body, request_id, rate_limit));
+        }
+        if self
+            .reauthenticate(&credentials, sent_with)
+            .await?
+            .is_none()
+        {
+            return Err(self.status_error(status, &body, request_id, rate_limit));
+        }
+        let response = self.dispatch(retry).await?;
+        self.error_for_status(response, &request_id).await
+    }
+
+    /// Turn a 4xx/5xx response into `Error::ApiError`, keeping the request ID.
+    async fn error_for_status(&self, response: Response, request_id: &str) -> Result<Response> {
+        let status = response.status();
+        if !status.is_client_error() && !status.is_server_error() {
+            return Ok(response);
+        }
+        let (status, request_id, rate_limit) = error_metadata(&response, request_id);
+        let body = response.text().await?;
+        Err(self.status_error(status, &body, request_id, rate_limit))
+    }
+
+    /// Build the error for a failed response's body, logging it when verbose.
+    fn status_error(
+        &self,
+        status: reqwest::StatusCode,
+        body: &str,
+        request_id: String,
+        rate_limit: Option<RateLimitInfo>,
+    ) -> crate::Error {
+        if self.verbose {
+            self.log_response(status, body);
+        }
+        crate::Error::api(status.as_u16(), body)
+            .with_request_id(request_id)
+            .with_rate_limit(rate_limit)
+    }
+
+    /// Log in again using stored credentials, once for all requests that were
+    /// rejected with the same expired token.
+    ///
+    /// `sent_with` is the Authorization header the rejected request carried.
+    /// Requests waiting here while another one logs in find the header
+    /// changed afterwards and reuse the fresh token instead of logging in again.
+    async fn reauthenticate(
+        &self,
+        credentials: &Credentials,
+        sent_with: Option<HeaderValue>,
+    ) -> Result<Option<String>> {
+        let _relogin = self.relogin.lock().await;
+        let current = self.headers.load().get(AUTHORIZATION).cloned();
+        if current.is_some() && current != sent_with {
+            return Ok(current.and_then(|value| value.to_str().ok().map(str::to_string)));
+        }
+        if self.verbose {
+            self.log(&format!(
+                "Token expired, logging in again as {}",
+                credentials.identity()
+            ));
+        }
+        match &credentials.login {
+            #[cfg(feature = "totp")]
+            CredentialLogin::Totp { email, totp_secret } => Ok(self
+                .login_with_secret(email, totp_secret.expose_secret())
+                .await?
+                .token()
+                .map(str::to_string)),
+            CredentialLogin::Password {
+                username,
+                password,
+                #[cfg(feature = "totp")]
+                totp_secret,
+            } => {
+                #[cfg(feature = "totp")]
+                let mfa_token = match totp_secret {
+                    Some(secret) => Some(crate::totp::generate(secret.expose_secret())?),
+                    None => None,
+                };
+                #[cfg(not(feature = "totp"))]
+                let mfa_token: Option<String> = None;
+
+                self.login(username, password.expose_secret(), mfa_token.as_deref())
+                    .await
+            }
+        }
+    }
+
+    /// Send a GET request, answering it from the response cache when caching is enabled.
+    ///
+    /// Only successful responses are cached. Expired bodies that came with an
+    /// `ETag` are revalidated with `If-None-Match`, and served again when the
+    /// server answers `304 Not Modified`.
+    pub(crate) async fn execute_cached(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<(reqwest::StatusCode, Text)> {
+        let (Some(cache), Some(key)) = (
+            &self.cache,
+            request
+                .try_clone()
+                .and_then(|r| r.build().ok())
+                .map(|r| r.url().clone()),
+        ) else {
+            let response = self.execute(request).await?;
+            let status = response.status();
+            return Ok((status, response.text().await?));
+        };
+        let context = || ErrorContext::request(reqwest::Method::GET, key.path());
+        if let Some(body) = cache.get(key.as_str()) {
+            return Ok((
+                reqwest::StatusCode::OK,
+                Text::new(body, Some(context()), self.deserialize_mode),
+            ));
+        }
+
+        let etag = cache.etag(key.as_str());
+        let unconditional = request.try_clone();
+        let request = match &etag {
+            Some(etag) => request.header(IF_NONE_MATCH, etag.as_str()),
+            None => request,
+        };
+        let mut response = self.execute(request).await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(body) = cache.refresh(key.as_str()) {
+                return Ok((
+                    reqwest::StatusCode::OK,
+                    Text::new(body, Some(context()), self.deserialize_mode),
+                ));
+            }
+            // The cache was cleared meanwhile, so ask for the full body
+            if let Some(request) = unconditional {
+                response = self.execute(request).await?;
+            }
+        }
+        let status = response.status();
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let text = response.text().await?;
+        if status.is_success() {
+            cache.insert(key.to_string(), text.to_string(), etag);
+        }
+        Ok((status, text))
+    }
+
+    /// The headers sent with every request. Reading them never waits for
+    /// other requests.
+    pub(crate) fn headers(&self) -> arc_swap::Guard<Arc<HeaderMap>> {
+        self.headers.load()
+    }
+
+    /// Send `token` as the `Authorization` header of subsequent requests.
+    fn install_token(&self, token: &str) {
+        if let Ok(value) = HeaderValue::from_str(token) {
+            self.update_headers(|headers| {
+                headers.insert(AUTHORIZATION, value.clone());
+            });
+        }
+    }
+
+    /// Change the headers sent with every request, e.g. to store a new token.
+    /// Requests already being sent keep the headers they started with.
+    /// `update` may run more than once when called concurrently.
+    pub(crate) fn update_headers(&self, update: impl Fn(&mut HeaderMap)) {
+        self.headers.rcu(|headers| {
+            let mut headers = HeaderMap::clone(headers);
+            update(&mut headers);
+            headers
+        });
+    }
+
+    /// Parse and log response if verbose mode is enabled.
+    pub(crate) async fn parse_response(
+        &self,
+        status: reqwest::StatusCode,
+        body: &str,
+    ) -> Result<()> {
+        self.log_response(status, body);
+
+        if !status.is_success() {
+            return Err(crate::Error::api(status.as_u16(), body));
+        }
+        Ok(())
+    }
+
+    /// Log a response body, with credentials such as agent API key settings
+    /// kept out of the log.
+    fn log_response(&self, status: reqwest::StatusCode, body: &str) {
+        let redacted = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|mut json| crate::secret::redact_json(&mut json).then(|| json.to_string()));
+        self.log(&format!(
+            "Status Code: {}\nResponse JSON:\n{}\n",
+            status,
+            redacted.as_deref().unwrap_or(body)
+        ));
+    }
+
+    /// Send a verbose diagnostic message to the configured `ResponseLogger`.
+    pub(crate) fn log(&self, message: &str) {
+        self.logger.log(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_new_client() {
+        let client = AGiXTSDK::new(None, None, false);
+        assert_eq!(client.base_uri, "http://localhost:7437");
+        assert!(!client.verbose);
+        assert_eq!(
+            client.headers()[USER_AGENT],
+            concat!("agixt-rust-sdk/", env!("CARGO_PKG_VERSION"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_client_with_options() {
+        let client = AGiXTSDK::new(
+            Some("https://api.example.com/".to_string()),
+            Some("test-key".to_string()),
+            true,
+        );
+        assert_eq!(client.base_uri, "https://api.example.com");
+        assert!(client.verbose);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_rotation() {
+        let client = AGiXTSDK::new(None, Some("Bearer first".to_string()), false);
+        assert_eq!(client.get_bearer_token().await.as_deref(), Some("first"));
+
+        client.set_api_key("second").await.unwrap();
+        assert_eq!(client.get_bearer_token().await.as_deref(), Some("second"));
+
+        client.clear_api_key().await;
+        assert_eq!(client.get_bearer_token().await, None);
+        assert!(client.set_api_key("bad\nkey").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_interceptors_run_for_each_request() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Tagger(Arc<AtomicUsize>);
+
+        impl Interceptor for Tagger {
+            fn on_request(&self, request: &mut reqwest::Request) {
+                request
+                    .headers_mut()
+                    .insert("x-trace", HeaderValue::from_static("abc"));
+            }
+
+            fn on_response(
+                &self,
+                method: &reqwest::Method,
+                response: &reqwest::Response,
+                _elapsed: std::time::Duration,
+            ) {
+                assert_eq!(method, reqwest::Method::GET);
+                assert!(response.status().is_success());
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/chains")
+            .match_header("x-trace", "abc")
+            .with_body("[]")
+            .create_async()
+            .await;
+
+        let responses = Arc::new(AtomicUsize::new(0));
+        let client = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .interceptor(Tagger(responses.clone()))
+            .build()
+            .unwrap();
+        client.get_chains().await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(responses.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reauthenticates_on_401() {
+        let mut server = mockito::Server::new_async().await;
+        let expired = server
+            .mock("GET", "/v1/agent")
+            .match_header("authorization", "expired")
+            .with_status(401)
+            .with_body(r#"{"detail":"Token expired"}"#)
+            .create_async()
+            .await;
+        let login = server
+            .mock("POST", "/v1/login")
+            .with_body(r#"{"token":"fresh"}"#)
+            .create_async()
+            .await;
+        let agents = server
+            .mock("GET", "/v1/agent")
+            .match_header("authorization", "fresh")
+            .with_body(r#"{"agents":[{"id":"1","name":"XT"}]}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), Some("expired".to_string()), false)
+            .with_credentials(Credentials::new("user@example.com", "hunter2"));
+        let result = client.get_agents().await.unwrap();
+
+        assert_eq!(result.len(), 1);
+        expired.assert_async().await;
+        login.assert_async().await;
+        agents.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_request_options_timeout() {
+        // The listener never answers, so only the per-request timeout ends the call.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_uri = format!("http://{}", listener.local_addr().unwrap());
+
+        let client = AGiXTSDK::new(Some(base_uri), None, false);
+        let err = client
+            .with_options(RequestOptions::new().timeout(std::time::Duration::from_millis(100)))
+            .get_providers()
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.timeout_phase(), Some(crate::TimeoutPhase::Read));
+        assert!(client.options.timeout.is_none());
+
+        let client = AGiXTSDK::builder()
+            .base_uri(format!("http://{}", listener.local_addr().unwrap()))
+            .connect_timeout(std::time::Duration::from_secs(5))
+            .read_timeout(std::time::Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let started = std::time::Instant::now();
+        let err = client.get_providers().await.unwrap_err();
+        assert_eq!(err.timeout_phase(), Some(crate::TimeoutPhase::Read));
+        assert!(err.is_retryable());
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout_limits_silence_not_total_time() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Sends the body in parts 80ms apart, then stalls on the second request
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let _server = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                let body = br#"{"providers":["openai"]}"#;
+                let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n", body.len());
+                let _ = stream.write_all(head.as_bytes()).await;
+                if connections.is_empty() {
+                    for part in body.chunks(6) {
+                        sleep(std::time::Duration::from_millis(80)).await;
+                        let _ = stream.write_all(part).await;
+                    }
+                }
+                connections.push(stream);
+            }
+        });
+
+        let client = AGiXTSDK::builder()
+            .base_uri(url)
+            .read_timeout(std::time::Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let started = std::time::Instant::now();
+        assert_eq!(client.get_providers().await.unwrap(), vec!["openai"]);
+        assert!(started.elapsed() > std::time::Duration::from_millis(200));
+
+        let err = client.get_agents().await.unwrap_err();
+        assert_eq!(err.timeout_phase(), Some(crate::TimeoutPhase::Read));
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_ready() {
+        let mut server = mockito::Server::new_async().await;
+        let health = server
+            .mock("GET", "/health")
+            .with_body(r#"{"status":"UP"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let interval = std::time::Duration::from_millis(10);
+        client
+            .wait_for_ready(std::time::Duration::from_secs(1), interval)
+            .await
+            .unwrap();
+        health.assert_async().await;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = AGiXTSDK::new(Some(closed), None, false);
+        assert!(client
+            .wait_for_ready(std::time::Duration::from_millis(50), interval)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cached_get_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let providers = server
+            .mock("GET", "/v1/provider")
+            .with_body(r#"{"providers":[{"name":"openai"}]}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .cache_ttl(std::time::Duration::from_secs(60))
+            .build()
+            .unwrap();
+        assert_eq!(client.get_providers().await.unwrap().len(), 1);
+        assert_eq!(client.get_providers().await.unwrap().len(), 1);
+        client.invalidate_cache();
+        assert_eq!(client.get_providers().await.unwrap().len(), 1);
+
+        providers.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_metrics_observer_records_requests() {
+        struct Recorder(std::sync::Mutex<Vec<RequestMetrics>>);
+
+        impl MetricsObserver for Recorder {
+            fn record(&self, metrics: &RequestMetrics) {
+                self.0.lock().unwrap().push(metrics.clone());
+            }
+        }
+
+        let mut server = mockito::Server::new_async().await;
+        let _missing = server
+            .mock("DELETE", "/v1/task/17")
+            .with_status(404)
+            .with_body(r#"{"detail":"Task not found"}"#)
+            .create_async()
+            .await;
+
+        let recorder = Arc::new(Recorder(std::sync::Mutex::new(Vec::new())));
+        let mut client = AGiXTSDK::new(Some(server.url()), None, false);
+        client.metrics = Some(recorder.clone());
+        let _ = client.delete_task("17").await;
+
+        let recorded = recorder.0.lock().unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].method, reqwest::Method::DELETE);
+        assert_eq!(recorded[0].endpoint, "/v1/task/{id}");
+        assert_eq!(recorded[0].status, Some(404));
+        assert!(!recorded[0].is_success());
+    }
+
+    #[tokio::test]
+    async fn test_request_id_header_and_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let generated = server
+            .mock("GET", "/v1/provider")
+            .match_header(
+                "x-request-id",
+                mockito::Matcher::Regex("^[0-9a-f-]{36}$".to_string()),
+            )
+            .with_body(r#"{"providers":[]}"#)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("GET", "/v1/agent/missing")
+            .match_header("x-request-id", "req-123")
+            .with_status(404)
+            .with_body(r#"{"detail":"Agent not found"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        client.get_providers().await.unwrap();
+        let err = client
+            .with_options(RequestOptions::new().request_id("req-123"))
+            .get_agentconfig("missing")
+            .await
+            .unwrap_err();
+
+        assert!(err.is_not_found());
+        assert_eq!(
+            err.to_string(),
+            "GET /v1/agent/missing: API error (404): Agent not found (request id: req-123)"
+        );
+        generated.assert_async().await;
+        missing.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_learn_urls_concurrent() {
+        let mut server = mockito::Server::new_async().await;
+        let ok = server
+            .mock("POST", "/v1/agent/a1/learn/url")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"url":"https://ok.example"}"#.to_string(),
+            ))
+            .with_body(r#"{"message":"learned"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let failed = server
+            .mock("POST", "/v1/agent/a1/learn/url")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"url":"https://bad.example"}"#.to_string(),
+            ))
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let urls = vec![
+            "https://ok.example".to_string(),
+            "https://bad.example".to_string(),
+            "https://ok.example".to_string(),
+        ];
+        let results = client.learn_urls_concurrent("a1", &urls, None, 2).await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1].0, "https://bad.example");
+        assert!(results[1].1.is_err());
+        assert_eq!(results[0].1.as_deref().unwrap(), "learned");
+        assert_eq!(results[2].1.as_deref().unwrap(), "learned");
+        ok.assert_async().await;
+        failed.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_providers_with_settings() {
+        use crate::models::SettingType;
+
+        let mut server = mockito::Server::new_async().await;
+        let list = server
+            .mock("GET", "/v1/provider")
+            .with_body(
+                r#"{"providers": [
+                    {"name": "openai", "services": ["llm", "tts"], "settings": {
+                        "AI_MODEL": "gpt-4o",
+                        "MAX_TOKENS": {"type": "int", "default": 4096, "description": "Token limit"}
+                    }},
+                    {"name": "gpt4free", "services": ["llm"]}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let detail = server
+            .mock("GET", "/v1/provider/gpt4free")
+            .with_body(r#"{"settings": {"AI_TEMPERATURE": 0.7, "WAIT_BETWEEN_REQUESTS": 1}}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let providers = client.get_all_providers_with_settings().await.unwrap();
+
+        assert_eq!(providers.len(), 2);
+        let openai = &providers[0];
+        assert!(openai.supports("tts"));
+        let max_tokens = openai.setting("MAX_TOKENS").unwrap();
+        assert_eq!(max_tokens.setting_type, SettingType::Integer);
+        assert_eq!(max_tokens.default, serde_json::json!(4096));
+        assert_eq!(max_tokens.description.as_deref(), Some("Token limit"));
+        assert_eq!(
+            openai.setting("AI_MODEL").unwrap().setting_type,
+            SettingType::String
+        );
+
+        let gpt4free = &providers[1];
+        assert_eq!(gpt4free.services, vec!["llm"]);
+        let names: Vec<_> = gpt4free.settings.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["AI_TEMPERATURE", "WAIT_BETWEEN_REQUESTS"]);
+        assert_eq!(gpt4free.settings[0].setting_type, SettingType::Number);
+        list.assert_async().await;
+        detail.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_embedders() {
+        let mut server = mockito::Server::new_async().await;
+        let _list = server
+            .mock("GET", "/v1/provider")
+            .with_body(
+                r#"{"providers": [
+                    {"name": "openai", "supports_embeddings": true, "embedding_dimensions": 1536,
+                     "settings": {"EMBEDDING_MODEL": "text-embedding-3-small"}},
+                    {"name": "default", "services": ["llm", "embeddings"]},
+                    {"name": "anthropic", "services": ["llm"]}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let _default = server
+            .mock("GET", "/v1/provider/default")
+            .with_body(r#"{"provider": {"services": ["llm", "embeddings"], "settings": {"MAX_TOKENS": 256}}}"#)
+            .create_async()
+            .await;
+        let _openai = server
+            .mock("GET", "/v1/provider/openai")
+            .with_body(r#"{"services": ["llm", "embeddings"], "settings": {"OPENAI_API_KEY": ""}}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let embedders = client.get_embedders().await.unwrap();
+        assert_eq!(embedders.len(), 2);
+        assert_eq!(embedders[0].name, "openai");
+        assert_eq!(embedders[0].dimensions, Some(1536));
+        assert_eq!(embedders[0].settings[0].name, "EMBEDDING_MODEL");
+        assert_eq!(embedders[1].dimensions, None);
+
+        let details = client.get_embedding_providers_with_details().await.unwrap();
+        let names: Vec<_> = details.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["openai", "default"]);
+        assert!(details[1].setting("MAX_TOKENS").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_provider_models() {
+        let mut server = mockito::Server::new_async().await;
+        let listed = server
+            .mock("GET", "/v1/provider/openai/models")
+            .with_body(r#"{"models": ["gpt-4o", {"id": "gpt-4o-mini"}, {"name": "o3"}]}"#)
+            .create_async()
+            .await;
+        let unsupported = server
+            .mock("GET", "/v1/provider/gpt4free/models")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        assert_eq!(
+            client.get_provider_models("openai").await.unwrap(),
+            vec!["gpt-4o", "gpt-4o-mini", "o3"]
+        );
+        assert!(client
+            .get_provider_models("gpt4free")
+            .await
+            .unwrap()
+            .is_empty());
+        listed.assert_async().await;
+        unsupported.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_provider_settings() {
+        let mut server = mockito::Server::new_async().await;
+        let update = server
+            .mock("PUT", "/v1/provider/openai")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "settings": {"OPENAI_API_KEY": "sk-new"}
+            })))
+            .with_body(r#"{"message": "Provider settings updated."}"#)
+            .create_async()
+            .await;
+        let _unsupported = server
+            .mock("PUT", "/v1/provider/gpt4free")
+            .with_status(404)
+            .with_body(r#"{"detail": "Not Found"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let settings = HashMap::from([("OPENAI_API_KEY".to_string(), "sk-new".into())]);
+        assert_eq!(
+            client
+                .update_provider_settings("openai", settings.clone())
+                .await
+                .unwrap(),
+            "Provider settings updated."
+        );
+        let err = client
+            .update_provider_settings("gpt4free", settings)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(404));
+        update.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_run_with_tools() {
+        use crate::models::{ChatCompletions, Message};
+
+        let mut server = mockito::Server::new_async().await;
+        let final_answer = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex(
+                r#""role":"tool","content":"\{\\"temp\\":21\}","tool_call_id":"call_1""#
+                    .to_string(),
+            ))
+            .with_body(
+                r#"{"id": "2", "object": "chat.completion", "created": 0, "model": "agent",
+                    "choices": [{"index": 0, "finish_reason": "stop",
+                        "message": {"role": "assistant", "content": "It is 21 degrees."}}],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}}"#,
+            )
+            .create_async()
+            .await;
+        let tool_call = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex(r#""name":"get_weather""#.to_string()))
+            .with_body(
+                r#"{"id": "1", "object": "chat.completion", "created": 0, "model": "agent",
+                    "choices": [{"index": 0, "finish_reason": "tool_calls",
+                        "message": {"role": "assistant", "content": null, "tool_calls": [
+                            {"id": "call_1", "type": "function",
+                             "function": {"name": "get_weather", "arguments": "{\"city\":\"Oslo\"}"}}
+                        ]}}],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let tools = ToolRegistry::new().register(
+            "get_weather",
+            "Current weather",
+            serde_json::json!({"type": "object"}),
+            |args| async move {
+                assert_eq!(args["city"], "Oslo");
+                Ok(serde_json::json!({"temp": 21}))
+            },
+        );
+        let request = ChatCompletions {
+            model: "agent".to_string(),
+            messages: Some(vec![Message::user("Weather in Oslo?")]),
+            ..Default::default()
+        };
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let response = client.run_with_tools(&request, &tools).await.unwrap();
+
+        assert_eq!(response.choices[0].message.text(), "It is 21 degrees.");
+        tool_call.assert_async().await;
+        final_answer.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_typed_agent_responses() {
+        let mut server = mockito::Server::new_async().await;
+        let _created = server
+            .mock("POST", "/v1/agent")
+            .with_body(r#"{"message": "Agent added", "agent_id": "a1", "agent_name": "XT"}"#)
+            .create_async()
+            .await;
+        let _config = server
+            .mock("GET", "/v1/agent/a1")
+            .with_body(
+                r#"{"agent": {"id": "a1", "name": "XT", "company_id": "c1",
+                    "settings": {"provider": "openai"}, "commands": {"Web Search": true}}}"#,
+            )
+            .create_async()
+            .await;
+        let _missing_name = server
+            .mock("GET", "/v1/agent/broken")
+            .with_body(r#"{"agent": {"settings": {}}}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let created = client.add_agent("XT", None, None, None).await.unwrap();
+        assert_eq!(created.agent_id, "a1");
+        assert_eq!(created.agent_name.as_deref(), Some("XT"));
+
+        let config = client.get_agentconfig("a1").await.unwrap();
+        assert_eq!(config.setting("provider"), Some("openai"));
+        assert!(config.command_enabled("Web Search"));
+        assert_eq!(config.extra["company_id"], "c1");
+
+        let err = client.get_agentconfig("broken").await.unwrap_err();
+        assert!(matches!(err.root(), crate::Error::JsonError(_)));
+        let context = err.context().unwrap();
+        assert_eq!(context.method, Some(reqwest::Method::GET));
+        assert_eq!(context.endpoint.as_deref(), Some("/v1/agent/broken"));
+
+        // Calls through the trait also name the SDK method
+        let err = AgixtApi::get_agentconfig(&client, "broken")
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("get_agentconfig (GET /v1/agent/broken): JSON error"));
+    }
+
+    #[tokio::test]
+    async fn test_upload_file_to_workspace() {
+        let mut server = mockito::Server::new_async().await;
+        let upload = server
+            .mock("POST", "/v1/agent/a1/workspace/upload")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Regex("^multipart/form-data".to_string()),
+            )
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex(r#"filename="report.md""#.to_string()),
+                mockito::Matcher::Regex("# Report".to_string()),
+                mockito::Matcher::Regex(r#"name="path"\r\n\r\ndocs"#.to_string()),
+            ]))
+            .with_body(r#"{"message": "File uploaded to docs/report.md"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let message = client
+            .upload_file_to_workspace(
+                "a1",
+                crate::models::UploadSource::bytes("report.md", "# Report"),
+                Some("docs"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(message, "File uploaded to docs/report.md");
+        upload.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_workspace_listing_and_download() {
+        let mut server = mockito::Server::new_async().await;
+        let _list = server
+            .mock("GET", "/v1/agent/a1/workspace")
+            .with_body(
+                r#"{"files": [
+                    {"path": "docs", "name": "docs", "type": "directory"},
+                    "docs/report.md"
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let download = server
+            .mock("GET", "/v1/agent/a1/workspace/download")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "path".to_string(),
+                "docs/report.md".to_string(),
+            ))
+            .with_body("# Report")
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let files = client.list_workspace_files("a1").await.unwrap();
+        assert!(files[0].is_dir());
+        assert_eq!(files[1].name, "report.md");
+
+        let bytes = client
+            .download_workspace_file("a1", &files[1].path)
+            .await
+            .unwrap();
+        assert_eq!(bytes, b"# Report");
+
+        let local = std::env::temp_dir().join(format!("agixt-{}.md", uuid::Uuid::new_v4()));
+        let written = client
+            .download_workspace_file_to("a1", "docs/report.md", &local)
+            .await
+            .unwrap();
+        assert_eq!(written, 8);
+        assert_eq!(std::fs::read(&local).unwrap(), b"# Report");
+        std::fs::remove_file(&local).unwrap();
+        download.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_run_chain_with_progress() {
+        let mut server = mockito::Server::new_async().await;
+        let _chain = server
+            .mock("GET", "/v1/chain/c1")
+            .with_body(
+                r#"{"Report": {"id": "c1", "steps": [
+                    {"step": 2, "prompt_type": "Prompt"},
+                    {"step": 1, "prompt_type": "Prompt"}
+                ]}}"#,
+            )
+            .create_async()
+            .await;
+        let step1 = server
+            .mock("POST", "/v1/chain/c1/run/step/1")
+            .with_body(r#""outline""#)
+            .create_async()
+            .await;
+        let step2 = server
+            .mock("POST", "/v1/chain/c1/run/step/2")
+            .with_body(r#""report""#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let events = Mutex::new(Vec::new());
+        let output = client
+            .run_chain_with_progress("c1", "Write", None, None, &|event| {
+                events.lock().unwrap().push(event)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output, "report");
+        let events = events.into_inner().unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0], ChainProgress::StepStarted { step: 1, total: 2 });
+        assert_eq!(
+            events[3],
+            ChainProgress::StepCompleted {
+                step: 2,
+                total: 2,
+                response: serde_json::json!("report"),
+            }
+        );
+        step1.assert_async().await;
+        step2.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_request_cancellation() {
+        use std::time::Duration;
+        use tokio_util::sync::CancellationToken;
+
+        // A server that accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let _server = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+
+        let token = CancellationToken::new();
+        let client = AGiXTSDK::new(Some(url), None, false)
+            .with_options(RequestOptions::new().cancellation(token.clone()));
+        let canceller = tokio::spawn(async move {
+            sleep(Duration::from_millis(100)).await;
+            token.cancel();
+        });
+
+        let started = web_time::Instant::now();
+        let err = client.get_providers().await.unwrap_err();
+        assert!(err.is_cancelled());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        canceller.await.unwrap();
+
+        // Requests made after cancellation fail immediately
+        assert!(client.get_providers().await.unwrap_err().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server
+            .mock("GET", "/v1/provider")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+        let available = server
+            .mock("GET", "/v1/provider")
+            .with_status(200)
+            .with_body(r#"{"providers": ["openai"]}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false)
+            .with_options(RequestOptions::new().retries(2));
+        assert_eq!(client.get_providers().await.unwrap().len(), 1);
+        unavailable.assert_async().await;
+        available.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_retried_creation_reuses_idempotency_key() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut server = mockito::Server::new_async().await;
+        let keys = Arc::new(Mutex::new(Vec::new()));
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let (seen, count) = (keys.clone(), attempts.clone());
+        let _create = server
+            .mock("POST", "/v1/conversation")
+            .with_status_code_from_request(move |request| {
+                let key = request.header(IDEMPOTENCY_KEY_HEADER).first().cloned();
+                seen.lock()
+                    .unwrap()
+                    .push(key.map(|k| k.to_str().unwrap().to_string()));
+                if count.fetch_add(1, Ordering::SeqCst) == 0 {
+                    503
+                } else {
+                    200
+                }
+            })
+            .with_body(r#"{"id": "c1", "name": "Trip"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        client
+            .with_options(RequestOptions::new().retries(1))
+            .new_conversation("a1", "Trip", None)
+            .await
+            .unwrap();
+        let sent = keys.lock().unwrap().clone();
+        assert_eq!(sent.len(), 2);
+        assert!(sent[0].is_some());
+        assert_eq!(sent[0], sent[1]);
+
+        // Without retries there is nothing to deduplicate
+        client.new_conversation("a1", "Trip", None).await.unwrap();
+        assert_eq!(keys.lock().unwrap()[2], None);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_spans_retries() {
+        use std::time::Duration;
+
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server
+            .mock("GET", "/v1/provider")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+
+        // The second backoff (500ms) would overrun the budget
+        let client = AGiXTSDK::new(Some(server.url()), None, false).with_options(
+            RequestOptions::new()
+                .retries(5)
+                .deadline(Duration::from_millis(600)),
+        );
+        let started = web_time::Instant::now();
+        let err = client.get_providers().await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_millis(600));
+        assert!(err.is_timeout());
+        match err.into_root() {
+            crate::Error::DeadlineExceeded {
+                attempts,
+                last_error,
+                ..
+            } => {
+                assert_eq!(attempts, 2);
+                assert_eq!(last_error.unwrap().status(), Some(503));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        unavailable.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_text_to_speech_for_message() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/conversation/c1/tts/m1")
+            .with_status(200)
+            .with_body(r#"{"message": "http://localhost:7437/outputs/m1.wav"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let url = client.text_to_speech_for_message("c1", "m1").await.unwrap();
+        assert_eq!(url, "http://localhost:7437/outputs/m1.wav");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_extension_commands() {
+        let mut server = mockito::Server::new_async().await;
+        let extensions = server
+            .mock("GET", "/v1/agent/a1/extensions")
+            .with_status(200)
+            .with_body(
+                r#"{"extensions": [{"extension_name": "Web", "description": "", "commands": [
+                    {"friendly_name": "Web Search", "description": "", "command_args": {}},
+                    {"friendly_name": "Browse", "description": "", "command_args": {}}
+                ]}]}"#,
+            )
+            .create_async()
+            .await;
+        let commands = server
+            .mock("GET", "/v1/agent/a1/command")
+            .with_status(200)
+            .with_body(r#"{"commands": {"Web Search": true, "Browse": false}}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let catalogue = client.get_extension_commands("a1").await.unwrap();
+        assert_eq!(catalogue.len(), 1);
+        let enabled: Vec<_> = catalogue[0]
+            .commands
+            .iter()
+            .map(|c| (c.name.as_str(), c.enabled))
+            .collect();
+        assert_eq!(enabled, vec![("Web Search", true), ("Browse", false)]);
+        extensions.assert_async().await;
+        commands.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_copy_agent() {
+        let mut server = mockito::Server::new_async().await;
+        let source = server
+            .mock("GET", "/v1/agent/a1")
+            .with_status(200)
+            .with_body(
+                r#"{"agent": {"name": "template", "settings": {"provider": "openai"},
+                    "commands": {"Web Search": true}}}"#,
+            )
+            .create_async()
+            .await;
+        let created = server
+            .mock("POST", "/v1/agent")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "agent_name": "worker-1",
+                "settings": {"provider": "openai"},
+                "commands": {"Web Search": true},
+            })))
+            .with_status(200)
+            .with_body(r#"{"agent_id": "a2", "agent_name": "worker-1"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let agent = client.copy_agent("a1", "worker-1").await.unwrap();
+        assert_eq!(agent.agent_id, "a2");
+        source.assert_async().await;
+        created.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_message_feedback() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/conversation/c1/message/m1/feedback")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "positive": false,
+                "feedback": "Wrong date",
+            })))
+            .with_status(200)
+            .with_body(r#"{"message": "Feedback recorded"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let message = client
+            .negative_message_feedback("c1", "m1", Some("Wrong date"))
+            .await
+            .unwrap();
+        assert_eq!(message, "Feedback recorded");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_notifications() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/notifications")
+            .with_status(200)
+            .with_body(
+                r#"{"notifications": [{"conversation_id": "c1", "conversation_name": "Chat",
+                    "message_id": "m1", "role": "XT", "message": "Report ready",
+                    "timestamp": "2024-05-01T10:00:00"}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let notifications = client.get_notifications().await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].message_id.as_deref(), Some("m1"));
+        assert_eq!(notifications[0].role, "XT");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_conversation_id_by_name() {
+        let mut server = mockito::Server::new_async().await;
+        let list = server
+            .mock("GET", "/v1/conversations")
+            .with_status(200)
+            .with_body(
+                r#"{"conversations_with_ids": [{"id": "c1", "name": "Chat"},
+                    {"id": "c2", "name": "Support"}]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", "/v1/conversation/c2")
+            .with_status(200)
+            .with_body(r#"{"message": "Conversation deleted"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let id = client.get_conversation_id_by_name("Support").await.unwrap();
+        assert_eq!(id.as_deref(), Some("c2"));
+        assert!(client
+            .get_conversation_id_by_name("Missing")
+            .await
+            .unwrap()
+            .is_none());
+        client.delete_conversation("c2").await.unwrap();
+        list.assert_async().await;
+        delete.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_prompt_agent_with_files() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/agent/a1/prompt")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "prompt_name": "Think About It",
+                "prompt_args": {
+                    "user_input": "Summarize the attachment",
+                    "file_urls": ["data:text/plain;base64,aGk="],
+                },
+            })))
+            .with_status(200)
+            .with_body(r#"{"response": "It says hi."}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let mut args = HashMap::new();
+        args.insert(
+            "user_input".to_string(),
+            serde_json::json!("Summarize the attachment"),
+        );
+        let response = client
+            .prompt_agent_with_files(
+                "a1",
+                "Think About It",
+                args,
+                vec![crate::models::UploadSource::bytes("notes.txt", "hi")],
+            )
+            .await
+            .unwrap();
+        assert_eq!(response, "It says hi.");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_export_memories_to_file() {
+        use crate::models::{ExportFormat, Memory};
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/agent/a1/memory/export")
+            .with_status(200)
+            .with_body(
+                r#"{"message": "ok", "memories": [
+                    {"id": "m1", "additional_metadata": "first", "key": "e1"},
+                    {"id": "m2", "additional_metadata": "second", "key": "e2"}
+                ]}"#,
+            )
+            .expect(3)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let memories = client.export_agent_memories("a1").await.unwrap();
+        assert_eq!(memories.len(), 2);
+
+        let jsonl = std::env::temp_dir().join(format!("agixt-{}.jsonl", uuid::Uuid::new_v4()));
+        let count = client
+            .export_memories_to_file("a1", &jsonl, ExportFormat::Jsonl)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        let lines: Vec<Memory> = std::fs::read_to_string(&jsonl)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, memories);
+
+        let json = jsonl.with_extension("json");
+        client
+            .export_memories_to_file("a1", &json, ExportFormat::Json)
+            .await
+            .unwrap();
+        let array: Vec<Memory> =
+            serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+        assert_eq!(array, memories);
+
+        std::fs::remove_file(&jsonl).unwrap();
+        std::fs::remove_file(&json).unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_voice_chat() {
+        let mut server = mockito::Server::new_async().await;
+        let agent = server
+            .mock("GET", "/v1/agent/a1")
+            .with_status(200)
+            .with_body(r#"{"agent": {"name": "XT", "settings": {}, "commands": {}}}"#)
+            .create_async()
+            .await;
+        let transcription = server
+            .mock("POST", "/v1/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex("name=\"model\"\r\n\r\nXT".into()))
+            .with_status(200)
+            .with_body(r#"{"text": "What time is it?"}"#)
+            .create_async()
+            .await;
+        let chat = server
+            .mock("POST", "/v1/agent/a1/prompt")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "prompt_args": {"user_input": "What time is it?", "conversation_name": "c1"}
+            })))
+            .with_status(200)
+            .with_body(r#"{"response": "Noon."}"#)
+            .create_async()
+            .await;
+        let speech = server
+            .mock("POST", "/v1/audio/speech")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"input": "Noon."}),
+            ))
+            .with_status(200)
+            .with_body("RIFF")
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let reply = client
+            .voice_chat(
+                "a1",
+                crate::models::UploadSource::bytes("question.wav", "RIFF"),
+                "c1",
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.transcript, "What time is it?");
+        assert_eq!(reply.answer_text, "Noon.");
+        assert_eq!(reply.answer_audio, b"RIFF");
+        for mock in [agent, transcription, chat, speech] {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_audio_with_options() {
+        use crate::models::{TranscriptionOptions, TranscriptionWord, UploadSource};
+
+        let mut server = mockito::Server::new_async().await;
+        let verbose = server
+            .mock("POST", "/v1/audio/transcriptions")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("name=\"language\"\r\n\r\npt".into()),
+                mockito::Matcher::Regex("name=\"response_format\"\r\n\r\nverbose_json".into()),
+                mockito::Matcher::Regex(
+                    "name=\"timestamp_granularities\\[\\]\"\r\n\r\nword".into(),
+                ),
+            ]))
+            .with_body(
+                r#"{"text": "Olá mundo", "language": "portuguese", "duration": 1.5,
+                    "segments": [{"id": 0, "start": 0.0, "end": 1.5, "text": "Olá mundo"}],
+                    "words": [{"word": "Olá", "start": 0.0, "end": 0.6},
+                              {"word": "mundo", "start": 0.7, "end": 1.5}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let transcription = client
+            .transcribe_audio_with_options(
+                UploadSource::bytes("talk.wav", "RIFF"),
+                "XT",
+                TranscriptionOptions::new().language("pt").word_timestamps(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(transcription.text, "Olá mundo");
+        assert_eq!(transcription.duration, Some(1.5));
+        assert_eq!(transcription.segments[0].end, 1.5);
+        assert_eq!(
+            transcription.words[1],
+            TranscriptionWord {
+                word: "mundo".into(),
+                start: 0.7,
+                end: 1.5
+            }
+        );
+        verbose.assert_async().await;
+
+        let srt = "1\n00:00:00,000 --> 00:00:01,500\nOlá mundo\n";
+        let _srt = server
+            .mock("POST", "/v1/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex(
+                "name=\"response_format\"\r\n\r\nsrt".into(),
+            ))
+            .with_body(srt)
+            .create_async()
+            .await;
+        let transcription = client
+            .transcribe_audio_with_options(
+                UploadSource::bytes("talk.wav", "RIFF"),
+                "XT",
+                TranscriptionOptions::new().response_format("srt"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(transcription.text, srt);
+        assert!(transcription.segments.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_generate_image_to_file() {
+        use crate::models::{ImageFormat, ImageOptions};
+        use base64::Engine;
+
+        let png = b"\x89PNG\r\n\x1a\nrest".to_vec();
+        let mut server = mockito::Server::new_async().await;
+        let inline = server
+            .mock("POST", "/v1/images/generations")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "prompt": "A lighthouse", "response_format": "b64_json"
+            })))
+            .with_body(
+                serde_json::json!({"data": [{
+                    "b64_json": base64::engine::general_purpose::STANDARD.encode(&png)
+                }]})
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let linked = server
+            .mock("POST", "/v1/images/generations")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"prompt": "A harbor"}),
+            ))
+            .with_body(r#"{"data": [{"url": "/outputs/harbor.jpg"}]}"#)
+            .create_async()
+            .await;
+        let download = server
+            .mock("GET", "/outputs/harbor.jpg")
+            .match_header("authorization", "key")
+            .with_body(b"\xFF\xD8\xFFjpeg")
+            .create_async()
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("agixt-images-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let client = AGiXTSDK::new(Some(server.url()), Some("key".into()), false);
+
+        let image = client
+            .generate_image_to_file(
+                "A lighthouse",
+                &dir.join("lighthouse"),
+                ImageOptions::new().response_format("b64_json"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(image.path, dir.join("lighthouse.png"));
+        assert_eq!(image.format, ImageFormat::Png);
+        assert_eq!(std::fs::read(&image.path).unwrap(), png);
+
+        let image = client
+            .generate_image_to_file("A harbor", &dir.join("harbor.jpeg"), ImageOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(image.path, dir.join("harbor.jpeg"));
+        assert_eq!(image.format, ImageFormat::Jpeg);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        for mock in [inline, linked, download] {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_company_persona() {
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", "/v1/companies/co1/persona")
+            .with_status(200)
+            .with_body(r#"{"persona": "Formal and concise"}"#)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PUT", "/v1/companies/co1/persona")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"persona": "Friendly"}),
+            ))
+            .with_status(200)
+            .with_body(r#"{"message": "Persona updated"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        assert_eq!(
+            client.get_company_persona("co1").await.unwrap(),
+            "Formal and concise"
+        );
+        assert_eq!(
+            client
+                .update_company_persona("co1", "Friendly")
+                .await
+                .unwrap(),
+            "Persona updated"
+        );
+        get.assert_async().await;
+        update.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_company_agents() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/companies/co1/agents")
+            .with_status(200)
+            .with_body(r#"{"agents": [{"id": "a1", "name": "XT", "status": true}]}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let agents = client.get_company_agents("co1").await.unwrap();
+        assert_eq!(agents.len(), 1);
+        assert_eq!(agents[0].name, "XT");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_sso_providers() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/oauth")
+            .with_status(200)
+            .with_body(
+                r#"{"providers": [{"name": "github", "client_id": "abc",
+                    "scopes": "user:email read:user",
+                    "authorize": "https://github.com/login/oauth/authorize",
+                    "pkce_required": false}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let providers = client.get_sso_providers().await.unwrap();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].name, "github");
+        assert_eq!(providers[0].client_id, "abc");
+        assert_eq!(providers[0].scopes, vec!["user:email", "read:user"]);
+        assert_eq!(
+            providers[0].authorize_url,
+            "https://github.com/login/oauth/authorize"
+        );
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_user_wallet() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/user/wallet")
+            .with_status(200)
+            .with_body(r#"{"address": "9xQeWvG816bUx9EP", "private_key": "5Kb8kLf9zgWQ", "passphrase": null}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let wallet = client.get_user_wallet().await.unwrap();
+        assert_eq!(wallet.address, "9xQeWvG816bUx9EP");
+        assert_eq!(
+            wallet.private_key.as_ref().unwrap().expose_secret(),
+            "5Kb8kLf9zgWQ"
+        );
+        assert!(wallet.passphrase.is_none());
+        assert!(!format!("{:?}", wallet).contains("5Kb8kLf9zgWQ"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_new_conversation_message_returns_id() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/conversation/c1/message")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "role": "user",
+                "message": "Hello!",
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"message": "Message added.", "id": "m1", "timestamp": "2024-05-01T10:00:00"}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let created = client
+            .new_conversation_message("user", "Hello!", "c1")
+            .await
+            .unwrap();
+        assert_eq!(created.id, "m1");
+        assert_eq!(created.timestamp.as_deref(), Some("2024-05-01T10:00:00"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_server_info() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/server/info")
+            .with_status(200)
+            .with_body(r#"{"version": "1.7.0", "features": {"wallet": true, "sso": false}}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let info = client.server_info().await.unwrap();
+        assert_eq!(info.version.as_deref(), Some("1.7.0"));
+        assert!(info.supports("Wallet"));
+        assert!(!info.supports("sso"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_server_info_on_old_server() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/v1/server/info")
+            .with_status(404)
+            .with_body(r#"{"detail": "Not Found"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let info = client.server_info().await.unwrap();
+        assert_eq!(info, ServerInfo::default());
+        assert!(!info.supports("wallet"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_and_rate_limit_headers() {
+        use std::time::Duration;
+
+        let mut server = mockito::Server::new_async().await;
+        let limited = server
+            .mock("GET", "/v1/provider")
+            .with_status(429)
+            .with_header("retry-after", "1")
+            .with_header("x-ratelimit-remaining", "0")
+            .with_body(r#"{"detail": "Too many requests"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let available = server
+            .mock("GET", "/v1/provider")
+            .with_status(200)
+            .with_header("x-ratelimit-limit", "60")
+            .with_header("x-ratelimit-remaining", "59")
+            .with_body(r#"{"providers": ["openai"]}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let error = client.get_providers().await.unwrap_err();
+        let info = error.rate_limit().unwrap();
+        assert_eq!(info.retry_after, Some(Duration::from_secs(1)));
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(client.last_rate_limit().unwrap().remaining, Some(0));
+
+        let started = web_time::Instant::now();
+        let retrying = client.with_options(RequestOptions::new().retries(1));
+        assert_eq!(retrying.get_providers().await.unwrap().len(), 1);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(client.last_rate_limit().unwrap().limit, Some(60));
+        limited.assert_async().await;
+        available.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_conversation_metadata() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/v1/conversations")
+            .with_body(
+                r#"{"conversations": {"c1": {"name": "Support", "agent_id": "a1",
+                    "created_at": "2026-01-02T03:04:05", "updated_at": "2026-01-03T00:00:00",
+                    "summary": "Password reset", "has_notifications": false}}}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let metadata = client.get_conversation_metadata("c1").await.unwrap();
+        assert_eq!(metadata.id, "c1");
+        assert_eq!(metadata.name, "Support");
+        assert_eq!(metadata.agent_id.as_deref(), Some("a1"));
+        assert_eq!(metadata.updated_at.as_deref(), Some("2026-01-03T00:00:00"));
+        assert_eq!(metadata.summary.as_deref(), Some("Password reset"));
+
+        let err = client.get_conversation_metadata("c2").await.unwrap_err();
+        assert!(err.is_not_found());
+    }
+
+    #[tokio::test]
+    async fn test_get_conversations_paged() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/v1/conversations")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("limit".into(), "2".into()),
+                mockito::Matcher::UrlEncoded("page".into(), "1".into()),
+            ]))
+            .with_body(
+                r#"{"conversations": [{"id": "c1", "name": "One"}, {"id": "c2", "name": "Two"}],
+                    "total": 3}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let page = client.get_conversations(Some(2), Some(1)).await.unwrap();
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.items[1]["id"], "c2");
+        assert_eq!(page.total, Some(3));
+        assert!(page.has_more());
+    }
+
+    #[tokio::test]
+    async fn test_set_command_enabled() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("PATCH", "/v1/agent/a1/command")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "command_name": "Web Search",
+                "enable": false,
+            })))
+            .with_body(r#"{"message": "Command 'Web Search' disabled."}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let message = client
+            .set_command_enabled("a1", "Web Search", false)
+            .await
+            .unwrap();
+        assert_eq!(message, "Command 'Web Search' disabled.");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_in_conversation() {
+        let mut server = mockito::Server::new_async().await;
+        let logged = server
+            .mock("POST", "/v1/agent/a1/command")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "command_name": "Web Search",
+                "command_args": {"query": "AGiXT"},
+                "conversation_name": "c1",
+            })))
+            .with_body(r#"{"response": "3 results"}"#)
+            .create_async()
+            .await;
+        let unlogged = server
+            .mock("POST", "/v1/agent/a1/command")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "command_name": "Web Search",
+                "command_args": {"query": "AGiXT"},
+            })))
+            .with_body(r#"{"response": "3 results"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let args = HashMap::from([("query".to_string(), serde_json::json!("AGiXT"))]);
+        let output = client
+            .execute_command("a1", "Web Search", args.clone(), Some("c1"))
+            .await
+            .unwrap();
+        assert_eq!(output, "3 results");
+        client
+            .execute_command("a1", "Web Search", args, None)
+            .await
+            .unwrap();
+        logged.assert_async().await;
+        unlogged.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_export_fine_tuning_dataset() {
+        let mut server = mockito::Server::new_async().await;
+        let _history = server
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"conversation_history": [
+                    {"role": "USER", "content": "Reset my password"},
+                    {"role": "Support Bot", "content": "[ACTIVITY] Looking up account"},
+                    {"role": "Support Bot", "content": "Done."}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let _dpo = server
+            .mock("POST", "/v1/agent/a1/dpo")
+            .with_body(r#"{"prompt": "Hi", "chosen": "Hello!", "rejected": "What?"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let options = crate::FineTuneOptions::new().system_prompt("Be brief.");
+        let path = std::env::temp_dir().join(format!("agixt-{}.jsonl", uuid::Uuid::new_v4()));
+
+        let count = client
+            .export_fine_tuning_dataset(&["c1"], &path, &options)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        let line: serde_json::Value =
+            serde_json::from_str(std::fs::read_to_string(&path).unwrap().trim()).unwrap();
+        assert_eq!(
+            line,
+            serde_json::json!({"messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "Reset my password"},
+                {"role": "assistant", "content": "Done."},
+            ]})
+        );
+
+        let count = client
+            .export_dpo_dataset("a1", &["Hi"], &path, &options)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        let line: serde_json::Value =
+            serde_json::from_str(std::fs::read_to_string(&path).unwrap().trim()).unwrap();
+        assert_eq!(line["input"]["messages"][1]["content"], "Hi");
+        assert_eq!(line["preferred_output"][0]["content"], "Hello!");
+        assert_eq!(line["non_preferred_output"][0]["content"], "What?");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_batch_keeps_order() {
+        let mut server = mockito::Server::new_async().await;
+        for (input, status, answer) in [("one", 200, "1"), ("two", 500, ""), ("three", 200, "3")] {
+            server
+                .mock("POST", "/v1/chat/completions")
+                .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                    "messages": [{"role": "user", "content": input}],
+                })))
+                .with_status(status)
+                .with_body(
+                    serde_json::json!({
+                        "id": input,
+                        "object": "chat.completion",
+                        "created": 0,
+                        "model": "agent",
+                        "choices": [{
+                            "index": 0,
+                            "message": {"role": "assistant", "content": answer},
+                            "finish_reason": "stop",
+                        }],
+                        "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2},
+                    })
+                    .to_string(),
+                )
+                .create_async()
+                .await;
+        }
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let requests: Vec<_> = ["one", "two", "three"]
+            .iter()
+            .map(|input| {
+                crate::ChatCompletions::builder()
+                    .model("agent")
+                    .message("user", *input)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        let results = client.chat_completions_batch(&requests, 2).await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().choices[0].message.text(), "1");
+        assert_eq!(results[1].as_ref().unwrap_err().status(), Some(500));
+        assert_eq!(results[2].as_ref().unwrap().choices[0].message.text(), "3");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_all_pages() {
+        let mut server = mockito::Server::new_async().await;
+        let page = |page: &str| {
+            mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("limit".into(), "100".into()),
+                mockito::Matcher::UrlEncoded("page".into(), page.into()),
+            ])
+        };
+        let full_page: Vec<_> = (0..100)
+            .map(|i| serde_json::json!({"role": "user", "content": format!("m{}", i)}))
+            .collect();
+        let first = server
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(page("1"))
+            .with_body(serde_json::json!({"conversation_history": full_page}).to_string())
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(page("2"))
+            .with_body(r#"{"conversation_history": [{"role": "assistant", "content": "last"}]}"#)
+            .create_async()
+            .await;
+        let conversations = server
+            .mock("GET", "/v1/conversations")
+            .match_query(page("1"))
+            .with_body(r#"{"conversations": [{"id": "c1", "name": "One"}], "total": 1}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let history = client.get_full_conversation("c1").await.unwrap();
+        assert_eq!(history.len(), 101);
+        assert_eq!(history[100].text(), "last");
+        first.assert_async().await;
+        second.assert_async().await;
+
+        let all = client.get_all_conversations().await.unwrap();
+        assert_eq!(all.len(), 1);
+        conversations.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_modes() {
+        let mut server = mockito::Server::new_async().await;
+        let _drift = server
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"conversation_history": [{"role": "user", "content": "Hi", "rating": 5}],
+                    "next_page": null}"#,
+            )
+            .create_async()
+            .await;
+        let _broken = server
+            .mock("GET", "/v1/conversation/c2")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"conversation_history": [{"role": "user"}, {"role": 7}]}"#)
+            .create_async()
+            .await;
+
+        let lenient = AGiXTSDK::new(Some(server.url()), None, false);
+        let history = lenient.get_conversation("c1", None, None).await.unwrap();
+        assert_eq!(history[0].text(), "Hi");
+        let err = lenient
+            .get_conversation("c2", None, None)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("conversation_history[1].role"),
+            "{}",
+            err
+        );
+
+        let strict = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .deserialize_mode(crate::DeserializeMode::Strict)
+            .build()
+            .unwrap();
+        let err = strict.get_conversation("c1", None, None).await.unwrap_err();
+        assert!(matches!(err.root(), crate::Error::JsonError(_)));
+        assert!(
+            err.to_string().contains("conversation_history.0.rating"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_stream() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"stream": true}),
+            ))
+            .with_header("content-type", "text/event-stream")
+            .with_body(concat!(
+                "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+                "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"lo\"},\"finish_reason\":\"stop\"}]}\n\n",
+                "data: [DONE]\n\n",
+            ))
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let request = crate::ChatCompletions::builder()
+            .model("XT")
+            .message("user", "Hi")
+            .build()
+            .unwrap();
+        use futures_util::StreamExt;
+        let events: Vec<_> = client
+            .chat_completions_stream(&request)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        let text: String = events
+            .iter()
+            .filter_map(|event| match event {
+                Ok(crate::StreamEvent::ContentDelta { text, .. }) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hello");
+        assert_eq!(events.len(), 4);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_verbose_log_redacts_credentials() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/v1/agent/a1")
+            .with_body(
+                r#"{"agent": {"name": "XT", "settings": {"OPENAI_API_KEY": "sk-live-123",
+                    "provider": "openai"}}}"#,
+            )
+            .create_async()
+            .await;
+
+        let (logger, mut messages) = ChannelLogger::new();
+        let client = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .verbose(true)
+            .logger(logger)
+            .build()
+            .unwrap();
+        let config = client.get_agentconfig("a1").await.unwrap();
+        assert_eq!(config.setting("OPENAI_API_KEY"), Some("sk-live-123"));
+        let log = messages.try_recv().unwrap();
+        assert!(!log.contains("sk-live-123"), "{}", log);
+        assert!(log.contains("openai"));
+
+        // Error bodies are logged through the same redaction
+        let _error = server
+            .mock("GET", "/v1/agent/a2")
+            .with_status(422)
+            .with_body(r#"{"detail": "invalid settings", "OPENAI_API_KEY": "sk-live-456"}"#)
+            .create_async()
+            .await;
+        assert!(client.get_agentconfig("a2").await.is_err());
+        let log = messages.try_recv().unwrap();
+        assert!(log.starts_with("Status Code: 422"), "{}", log);
+        assert!(!log.contains("sk-live-456"), "{}", log);
+        assert!(log.contains("invalid settings"));
+    }
+
+    #[tokio::test]
+    async fn test_header_updates_are_shared() {
+        let client = AGiXTSDK::new(None, Some("old".to_string()), false);
+        let scoped = client.with_options(RequestOptions::new().retries(1));
+        let snapshot = client.headers();
+
+        client.set_api_key("new").await.unwrap();
+        client.set_company(Some("c1")).await.unwrap();
+        assert_eq!(scoped.get_bearer_token().await.as_deref(), Some("new"));
+        assert_eq!(scoped.headers()[COMPANY_ID_HEADER], "c1");
+        // Requests that already read the headers keep their version
+        assert_eq!(snapshot[AUTHORIZATION], "old");
+        assert!(!snapshot.contains_key(COMPANY_ID_HEADER));
+    }
+
+    #[tokio::test]
+    async fn test_session_round_trip() {
+        let mut server = mockito::Server::new_async().await;
+        let user = server
+            .mock("GET", "/api/user")
+            .match_header("authorization", "token")
+            .match_header(COMPANY_ID_HEADER, "c1")
+            .with_body(r#"{"id": "u1", "email": "user@example.com"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(
+            Some(format!("{}/", server.url())),
+            Some("token".into()),
+            false,
+        );
+        client.set_company(Some("c1")).await.unwrap();
+        let saved = serde_json::to_string(&client.session()).unwrap();
+        assert!(format!("{:?}", client.session()).contains("Secret([REDACTED])"));
+
+        let state: SessionState = serde_json::from_str(&saved).unwrap();
+        let restored = AGiXTSDK::builder()
+            .api_version(ApiVersion::Legacy)
+            .build()
+            .unwrap()
+            .restore_session(state)
+            .unwrap();
+        assert_eq!(restored.get_user().await.unwrap().email, "user@example.com");
+        user.assert_async().await;
+
+        let logged_out = restored
+            .restore_session(SessionState {
+                base_uri: server.url(),
+                ..SessionState::default()
+            })
+            .unwrap();
+        assert_eq!(logged_out.get_bearer_token().await, None);
+        assert!(logged_out.session().company_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_conversation_with_query() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("limit".into(), "3".into()),
+                mockito::Matcher::UrlEncoded("page".into(), "2".into()),
+            ]))
+            .match_body(mockito::Matcher::Missing)
+            .with_body(
+                r#"{"conversation_history": [
+                    {"role": "USER", "content": "first"},
+                    {"role": "XT", "content": "reply"},
+                    {"role": "USER", "content": "second"}]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let query = crate::HistoryQuery::new()
+            .limit(3)
+            .page(2)
+            .newest_first()
+            .role("user");
+        let history = client.get_conversation_with("c1", &query).await.unwrap();
+        let texts: Vec<_> = history.iter().map(|message| message.text()).collect();
+        assert_eq!(texts, vec!["second", "first"]);
+
+        let query = crate::HistoryQuery::new()
+            .limit(3)
+            .page(2)
+            .role("assistant");
+        let history = client.get_conversation_with("c1", &query).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].role, "XT");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_user_sends_only_set_fields() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("PUT", "/v1/user")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "timezone": "Europe/London",
+                "theme": "dark",
+            })))
+            .with_body(r#"{"id": "u1", "email": "ada@example.com", "timezone": "Europe/London"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let user = client
+            .update_user(
+                UserUpdate::new()
+                    .timezone("Europe/London")
+                    .preference("theme", "dark"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(user.timezone.as_deref(), Some("Europe/London"));
+        mock.assert_async().await;
+
+        let err = client.update_user(UserUpdate::new()).await.unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidInput(_)));
+
+        let err = client
+            .update_user(
+                UserUpdate::new()
+                    .timezone("Europe/London")
+                    .preference("timezone", "UTC"),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("UserUpdate::timezone"));
+    }
+
+    #[tokio::test]
+    async fn test_prompt_agent_stream_reports_activities() {
+        use futures_util::StreamExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut server = mockito::Server::new_async().await;
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = polls.clone();
+        let _history = server
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(mockito::Matcher::Any)
+            .with_body_from_request(move |_| {
+                let mut history = vec![serde_json::json!({"role": "USER", "content": "Earlier"})];
+                if counter.fetch_add(1, Ordering::SeqCst) > 0 {
+                    history.push(serde_json::json!({"role": "USER", "content": "Plan a trip"}));
+                    history.push(serde_json::json!({
+                        "role": "XT", "content": "[ACTIVITY] Searching the web"
+                    }));
+                    history.push(serde_json::json!({"role": "XT", "content": "Go to Lisbon."}));
+                }
+                serde_json::json!({"conversation_history": history})
+                    .to_string()
+                    .into()
+            })
+            .create_async()
+            .await;
+        let _prompt = server
+            .mock("POST", "/v1/agent/a1/prompt")
+            .with_body(r#"{"response": "Go to Lisbon."}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let events: Vec<_> = client
+            .instruct_stream(
+                "a1",
+                "Plan a trip",
+                "c1",
+                std::time::Duration::from_millis(10),
+            )
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            events,
+            vec![
+                crate::StreamEvent::Activity {
+                    text: "Searching the web".into(),
+                    subactivity: false
+                },
+                crate::StreamEvent::ContentDelta {
+                    index: 0,
+                    text: "Go to Lisbon.".into()
+                },
+                crate::StreamEvent::Done {
+                    finish_reason: Some("stop".into()),
+                    usage: None
+                },
+            ]
+        );
+        assert!(polls.load(Ordering::SeqCst) >= 2);
+
+        let err = client
+            .prompt_agent_stream(
+                "a1",
+                "Chat",
+                HashMap::new(),
+                std::time::Duration::from_secs(1),
+            )
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, crate::Error::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn test_chain_from_conversation() {
+        let mut server = mockito::Server::new_async().await;
+        let _history = server
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"conversation_history": [
+                    {"role": "USER", "content": "Find flights to Lisbon"},
+                    {"role": "XT", "content": "[ACTIVITY] Searching the web"},
+                    {"role": "XT", "content": "Here are three flights."},
+                    {"role": "USER", "content": "Book the cheapest"},
+                    {"role": "XT", "content": "Booked."}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let import = server
+            .mock("POST", "/v1/chain/import")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "chain_name": "Book a trip",
+                "steps": [
+                    {"step": 1, "agent_name": "XT", "prompt_type": "Prompt",
+                     "prompt": {"prompt_name": "Chat", "user_input": "Find flights to Lisbon"}},
+                    {"step": 2, "agent_name": "XT", "prompt_type": "Prompt",
+                     "prompt": {"prompt_name": "Chat", "user_input": "Book the cheapest"}}
+                ]
+            })))
+            .with_body(r#"{"message": "Chain imported."}"#)
+            .create_async()
+            .await;
+        let _empty = server
+            .mock("GET", "/v1/conversation/c2")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"conversation_history": []}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        assert_eq!(
+            client
+                .chain_from_conversation("c1", "Book a trip", "XT")
+                .await
+                .unwrap(),
+            "Chain imported."
+        );
+        import.assert_async().await;
+
+        let err = client
+            .chain_from_conversation("c2", "Nothing", "XT")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn test_chain_runs() {
+        let mut server = mockito::Server::new_async().await;
+        let runs = server
+            .mock("GET", "/v1/chain/ch1/runs")
+            .with_body(
+                r#"{"runs": [
+                    {"run_id": "r1", "created_at": "2024-01-01T00:00:00Z", "status": "completed"},
+                    {"run_id": "r2", "created_at": "2024-01-02T00:00:00Z", "status": "failed"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let run = server
+            .mock("GET", "/v1/chain/ch1/runs/r1")
+            .with_body(
+                r#"{"id": "r1", "status": "completed", "responses": [
+                    {"step": 1, "output": "Found three flights."},
+                    {"step": 2, "output": {"booked": true}}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let listed = client.get_chain_runs("ch1").await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[1].id, "r2");
+        assert_eq!(listed[1].status.as_deref(), Some("failed"));
+        assert_eq!(listed[0].timestamp.as_deref(), Some("2024-01-01T00:00:00Z"));
+
+        let detail = client.get_chain_run("ch1", "r1").await.unwrap();
+        assert_eq!(detail.steps.len(), 2);
+        assert_eq!(detail.steps[1].step_number, 2);
+        assert_eq!(
+            detail.steps[1].response,
+            serde_json::json!({"booked": true})
+        );
+        runs.assert_async().await;
+        run.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_prompts_from_dir() {
+        let mut server = mockito::Server::new_async().await;
+        let _list = server
+            .mock("GET", "/v1/prompts")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "prompt_category".into(),
+                "Support".into(),
+            ))
+            .with_body(
+                r#"{"prompts": [
+                    {"id": "p1", "name": "Greeting", "content": "Hello {user}!\n"},
+                    {"id": "p2", "name": "Refund", "content": "Old refund policy"},
+                    {"id": "p3", "name": "Legacy"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let _legacy = server
+            .mock("GET", "/v1/prompt/p3")
+            .with_body(r#"{"prompt": {"name": "Legacy", "content": "Unused"}}"#)
+            .create_async()
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("agixt-prompts-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Greeting.txt"), "Hello {user}!\n").unwrap();
+        std::fs::write(dir.join("Refund.md"), "New refund policy").unwrap();
+        std::fs::write(dir.join("Escalate.txt"), "Escalate to {team}").unwrap();
+        std::fs::write(dir.join("notes.json"), "{}").unwrap();
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let plan = client
+            .sync_prompts_from_dir(&dir, "Support", true)
+            .await
+            .unwrap();
+        assert!(plan.dry_run);
+        assert_eq!(plan.created, vec!["Escalate"]);
+        assert_eq!(plan.updated, vec!["Refund"]);
+        assert_eq!(plan.deleted, vec!["Legacy"]);
+        assert_eq!(plan.unchanged, vec!["Greeting"]);
+
+        let create = server
+            .mock("POST", "/v1/prompt")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "prompt_name": "Escalate",
+                "prompt": "Escalate to {team}",
+                "prompt_category": "Support"
+            })))
+            .with_body(r#"{"id": "p4"}"#)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PUT", "/v1/prompt/p2")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"prompt": "New refund policy"}),
+            ))
+            .with_body(r#"{"message": "Prompt updated."}"#)
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", "/v1/prompt/p3")
+            .with_body(r#"{"message": "Prompt deleted."}"#)
+            .create_async()
+            .await;
+        let applied = client
+            .sync_prompts_from_dir(&dir, "Support", false)
+            .await
+            .unwrap();
+        assert_eq!(applied.created, plan.created);
+        for mock in [create, update, delete] {
+            mock.assert_async().await;
+        }
+
+        std::fs::write(dir.join("Greeting.md"), "Hi").unwrap();
+        let err = client
+            .sync_prompts_from_dir(&dir, "Support", true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidInput(_)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_apply_fleet() {
+        use crate::models::{AgentChange, FleetSpec};
+
+        let mut server = mockito::Server::new_async().await;
+        let _agents = server
+            .mock("GET", "/v1/agent")
+            .with_body(r#"{"agents": [{"id": "a1", "name": "Support"}]}"#)
+            .create_async()
+            .await;
+        let _config = server
+            .mock("GET", "/v1/agent/a1")
+            .with_body(
+                r#"{"agent": {"name": "Support", "settings": {
+                    "provider": "openai", "AI_MODEL": "gpt-3.5-turbo", "AI_TEMPERATURE": "0.7"
+                }, "commands": {"Web Search": false, "Read File": true}}}"#,
+            )
+            .create_async()
+            .await;
+        let spec: FleetSpec = serde_json::from_value(serde_json::json!({"agents": [
+            {"name": "Support",
+             "settings": {"provider": "openai", "AI_MODEL": "gpt-4o", "AI_TEMPERATURE": 0.7},
+             "commands": {"Web Search": true, "Read File": true}},
+            {"name": "Research", "settings": {"provider": "anthropic"},
+             "training_urls": ["https://docs.example.com"]}
+        ]}))
+        .unwrap();
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let plan = client.apply_fleet(&spec, true).await.unwrap();
+        assert_eq!(
+            plan.changes,
+            vec![
+                AgentChange::UpdateSettings {
+                    agent: "Support".into(),
+                    keys: vec!["AI_MODEL".into()]
+                },
+                AgentChange::UpdateCommands {
+                    agent: "Support".into(),
+                    commands: vec!["Web Search".into()]
+                },
+                AgentChange::Create {
+                    agent: "Research".into()
+                },
+            ]
+        );
+
+        let settings = server
+            .mock("PUT", "/v1/agent/a1")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "settings": {"AI_MODEL": "gpt-4o"}
+            })))
+            .with_body(r#"{"message": "Agent updated."}"#)
+            .create_async()
+            .await;
+        let command = server
+            .mock("PATCH", "/v1/agent/a1/command")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "command_name": "Web Search", "enable": true
+            })))
+            .with_body(r#"{"message": "Command enabled."}"#)
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/v1/agent")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "agent_name": "Research",
+                "training_urls": ["https://docs.example.com"]
+            })))
+            .with_body(r#"{"id": "a2", "name": "Research"}"#)
+            .create_async()
+            .await;
+        let applied = client.apply_fleet(&spec, false).await.unwrap();
+        assert_eq!(applied.changes, plan.changes);
+        for mock in [settings, command, create] {
+            mock.assert_async().await;
+        }
+
+        let duplicated = FleetSpec {
+            agents: vec![spec.agents[0].clone(), spec.agents[0].clone()],
+        };
+        let err = client.apply_fleet(&duplicated, true).await.unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn test_summarize_conversation() {
+        let mut server = mockito::Server::new_async().await;
+        let _history = server
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"conversation_history": [
+                    {"role": "USER", "content": "Find flights to Lisbon"},
+                    {"role": "XT", "content": "[ACTIVITY] Searching the web"},
+                    {"role": "XT", "content": "Here are three flights."}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let prompt = server
+            .mock("POST", "/v1/agent/a1/prompt")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::PartialJson(serde_json::json!({
+                    "prompt_args": {"log_user_input": false, "log_output": false}
+                })),
+                mockito::Matcher::Regex("user: Find flights to Lisbon".into()),
+            ]))
+            .with_body(
+                serde_json::json!({"response": "```json\n{\"title\": \"Lisbon flights\", \"summary\": \"The user looked for flights.\"}\n```"})
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let summary = client.summarize_conversation("a1", "c1").await.unwrap();
+        assert_eq!(summary.title, "Lisbon flights");
+        assert_eq!(summary.summary, "The user looked for flights.");
+        prompt.assert_async().await;
+
+        let _plain = server
+            .mock("POST", "/v1/agent/a2/prompt")
+            .with_body(r##"{"response": "# Lisbon flights\nThe user looked for flights."}"##)
+            .create_async()
+            .await;
+        let summary = client.summarize_conversation("a2", "c1").await.unwrap();
+        assert_eq!(summary.title, "Lisbon flights");
+        assert_eq!(summary.summary, "The user looked for flights.");
+    }
+
+    #[tokio::test]
+    async fn test_download_conversation_artifacts() {
+        let mut server = mockito::Server::new_async().await;
+        let history = server
+            .mock("GET", "/v1/conversation/conv-1")
+            .match_query(mockito::Matcher::Any)
+            .with_body(format!(
+                r#"{{"conversation_history": [
+                    {{"role": "USER", "content": "Chart [this](/outputs/a1/ignored.csv)"}},
+                    {{"id": "m2", "role": "XT", "content": "![Sales]({}/outputs/a1/c1/chart.png) and [data](/outputs/a1/c1/sales.csv?v=2)"}}
+                ]}}"#,
+                server.url()
+            ))
+            .create_async()
+            .await;
+        let chart = server
+            .mock("GET", "/outputs/a1/c1/chart.png")
+            .match_header("authorization", "key")
+            .with_body(b"png")
+            .create_async()
+            .await;
+        let csv = server
+            .mock("GET", "/outputs/a1/c1/sales.csv?v=2")
+            .match_header("authorization", "key")
+            .with_body("region,total")
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), Some("key".into()), false);
+        let messages = client.get_conversation("conv-1", None, None).await.unwrap();
+        let artifacts = crate::models::extract_artifacts(&messages);
+        assert_eq!(artifacts.len(), 2);
+        assert_eq!(artifacts[0].kind, crate::models::ArtifactKind::Image);
+        assert_eq!(artifacts[0].message_id.as_deref(), Some("m2"));
+        assert_eq!(artifacts[1].label, "data");
+        assert_eq!(artifacts[1].file_name(), "sales.csv");
+
+        let dir = std::env::temp_dir().join(format!("agixt-artifacts-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(
+            client.download_artifact(&artifacts[0]).await.unwrap(),
+            b"png"
+        );
+        let path = client
+            .download_artifact_to(&artifacts[1], &dir)
+            .await
+            .unwrap();
+        assert_eq!(path, dir.join("sales.csv"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "region,total");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        history.assert_async().await;
+        chart.assert_async().await;
+        csv.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_ask_uses_throwaway_conversation() {
+        let conversation = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+        let mut server = mockito::Server::new_async().await;
+        let seen = conversation.clone();
+        let completion = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "XT",
+                "messages": [{"role": "user", "content": "Capital of Portugal?"}]
+            })))
+            .with_body_from_request(move |request| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                *seen.lock().unwrap() = body["user"].as_str().unwrap().to_string();
+                br#"{"id": "1", "object": "chat.completion", "created": 0, "model": "XT",
+                    "choices": [{"index": 0, "finish_reason": "stop",
+                        "message": {"role": "assistant", "content": "Lisbon."}}],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}}"#
+                    .to_vec()
+            })
+            .create_async()
+            .await;
+        let seen = conversation.clone();
+        let conversations = server
+            .mock("GET", "/v1/conversations")
+            .with_body_from_request(move |_| {
+                serde_json::json!({"conversations": [
+                    {"id": "c1", "name": "Other"},
+                    {"id": "c2", "name": *seen.lock().unwrap()}
+                ]})
+                .to_string()
+                .into_bytes()
+            })
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", "/v1/conversation/c2")
+            .with_body(r#"{"message": "Conversation deleted"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        assert_eq!(
+            client.ask("XT", "Capital of Portugal?").await.unwrap(),
+            "Lisbon."
+        );
+        assert!(conversation.lock().unwrap().starts_with("ask-"));
+        completion.assert_async().await;
+        conversations.assert_async().await;
+        delete.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_training_urls_are_validated() {
+        let mut server = mockito::Server::new_async().await;
+        let learn = server
+            .mock("POST", "/v1/agent/a1/learn/url")
+            .match_body(mockito::Matcher::Regex(
+                "docs.example.com/(intro|api)".to_string(),
+            ))
+            .with_body(r#"{"message": "Agent learned the content from the url."}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+
+        let urls = vec![
+            "https://docs.example.com/intro".to_string(),
+            "https://docs.example.com/api".to_string(),
+        ];
+        let messages = client.add_training_urls("a1", &urls).await.unwrap();
+        assert_eq!(messages.len(), 2);
+
+        // Nothing is sent when any URL is invalid
+        for url in ["docs.example.com", "ftp://docs.example.com/file"] {
+            let urls = vec![
+                "https://docs.example.com/intro".to_string(),
+                url.to_string(),
+            ];
+            let err = client.add_training_urls("a1", &urls).await.unwrap_err();
+            assert!(matches!(err, crate::Error::InvalidInput(_)), "{}", err);
+            assert!(err.to_string().contains(url));
+            let err = client
+                .add_agent("XT", None, None, Some(urls))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, crate::Error::InvalidInput(_)), "{}", err);
+        }
+        learn.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_raw_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", "/v1/agent/a1/schedules")
+            .match_header("authorization", "key")
+            .with_body(r#"{"schedules": []}"#)
+            .create_async()
+            .await;
+        let post = server
+            .mock("POST", "/v1/agent/a1/schedules")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"cron": "0 9 * * 1"}),
+            ))
+            .with_body(r#"{"id": "s1"}"#)
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", "/v1/agent/a1/schedules/s1")
+            .with_status(204)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("PUT", "/v1/agent/a1/schedules/s2")
+            .with_status(404)
+            .with_body(r#"{"detail": "Schedule not found"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), Some("key".into()), false);
+        let raw = client.raw();
+        assert_eq!(
+            raw.get("/agent/a1/schedules").await.unwrap(),
+            serde_json::json!({"schedules": []})
+        );
+        let created = raw
+            .post(
+                "agent/a1/schedules",
+                &serde_json::json!({"cron": "0 9 * * 1"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(created["id"], "s1");
+        assert_eq!(
+            raw.delete("/agent/a1/schedules/s1").await.unwrap(),
+            serde_json::Value::Null
+        );
+        let err = raw
+            .put("/agent/a1/schedules/s2", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(404));
+        assert!(err.to_string().contains("Schedule not found"), "{}", err);
+
+        get.assert_async().await;
+        post.assert_async().await;
+        delete.assert_async().await;
+        missing.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_code_review() {
+        let mut server = mockito::Server::new_async().await;
+        let learn = server
+            .mock("POST", "/v1/agent/a1/learn/github")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "github_repo": "AGiXT/rust-sdk",
+                "github_branch": "main",
+                "github_token": null,
+                "use_agent_settings": true,
+                "collection_number": "0",
+            })))
+            .with_body(r#"{"message": "Agent learned the content from the GitHub Repository."}"#)
+            .create_async()
+            .await;
+        let review = server
+            .mock("POST", "/v1/agent/a1/prompt")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::PartialJson(serde_json::json!({
+                    "prompt_name": "Think About It",
+                    "prompt_args": {"context_results": 10, "log_output": false},
+                })),
+                mockito::Matcher::Regex(r"```diff\\n-old\\n\+new\\n```".to_string()),
+            ]))
+            .with_body(r#"{"response": "Looks good. Verdict: approve"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let err = client
+            .code_review("a1", "AGiXT/rust-sdk", " \n")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidInput(_)));
+        let text = client
+            .code_review("a1", "AGiXT/rust-sdk", "-old\n+new\n")
+            .await
+            .unwrap();
+        assert_eq!(text, "Looks good. Verdict: approve");
+        learn.assert_async().await;
+        review.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_typed_retries_parse_failures() {
+        use crate::models::{ChatCompletions, ResponseFormat};
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct City {
+            name: String,
+            population: u64,
+        }
+
+        let answer = |content: &str| {
+            serde_json::json!({"id": "1", "object": "chat.completion", "created": 0,
+                "model": "XT", "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2},
+                "choices": [{"index": 0, "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": content}}]})
+            .to_string()
+        };
+        let mut server = mockito::Server::new_async().await;
+        let retry = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("could not be parsed".to_string()))
+            .with_body(answer(
+                "```json\n{\"name\": \"Lisbon\", \"population\": 545000}\n```",
+            ))
+            .create_async()
+            .await;
+        let first = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "response_format": {"type": "json_object"},
+                "messages": [{"role": "user", "content": "Largest city of Portugal"}]
+            })))
+            .with_body(answer("The largest city is Lisbon."))
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let request = ChatCompletions::builder()
+            .model("XT")
+            .message("user", "Largest city of Portugal")
+            .build()
+            .unwrap();
+        let city: City = client.chat_completions_typed(&request, 2).await.unwrap();
+        assert_eq!(
+            city,
+            City {
+                name: "Lisbon".to_string(),
+                population: 545000
+            }
+        );
+        first.assert_async().await;
+        retry.assert_async().await;
+
+        // Without retries the parse error is returned
+        let request = ChatCompletions::builder()
+            .model("XT")
+            .message("user", "Largest city of Portugal")
+            .response_format(ResponseFormat::JsonObject)
+            .build()
+            .unwrap();
+        let err = client
+            .chat_completions_typed::<City>(&request, 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::JsonError(_)), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_set_provider_fallback() {
+        use crate::models::ProviderFallback;
+
+        let mut server = mockito::Server::new_async().await;
+        let providers = server
+            .mock("GET", "/v1/provider")
+            .with_body(r#"{"providers": ["openai", {"name": "anthropic"}]}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PUT", "/v1/agent/a1")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "settings": {
+                    "provider": "openai",
+                    "FALLBACK_PROVIDERS": "anthropic",
+                    "ROTATE_PROVIDERS_ON_ERROR": "true"
+                }
+            })))
+            .with_body(r#"{"message": "Agent updated"}"#)
+            .create_async()
+            .await;
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+
+        let fallback = ProviderFallback::new("openai")
+            .fallback("anthropic")
+            .rotate_on_error(true);
+        client.set_provider_fallback("a1", &fallback).await.unwrap();
+
+        let err = client
+            .set_provider_fallback("a1", &fallback.clone().fallback("openai"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("more than once"), "{}", err);
+        let err = client
+            .set_provider_fallback("a1", &ProviderFallback::new("openai").fallback("gpt4free"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidInput(_)));
+        assert!(
+            err.to_string().contains("gpt4free is not available"),
+            "{}",
+            err
+        );
+
+        providers.assert_async().await;
+        update.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_login_failures_are_auth_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let locked = server
+            .mock("POST", "/v1/login")
+            .with_status(429)
+            .with_header("retry-after", "60")
+            .with_body(r#"{"detail":"Too many failed login attempts"}"#)
+            .create_async()
+            .await;
+        let expired = server
+            .mock("POST", "/v1/login/magic-link")
+            .with_status(401)
+            .with_body(r#"{"detail":"Magic link has expired"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let error = client
+            .login("user@example.com", "hunter2", None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.auth_error_kind(),
+            Some(crate::AuthErrorKind::TooManyAttempts)
+        );
+        assert!(error.rate_limit().is_some());
+
+        let error = client
+            .login_magic_link("user@example.com", "123456")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.auth_error_kind(),
+            Some(crate::AuthErrorKind::ExpiredMagicLink)
+        );
+        assert_eq!(error.status(), Some(401));
+
+        locked.assert_async().await;
+        expired.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_complete_magic_link_login() {
+        let mut server = mockito::Server::new_async().await;
+        let link = server
+            .mock("POST", "/v1/login/magic-link")
+            .with_body(
+                r#"{"detail":"https://agixt.example.com/?email=user@example.com&token=jwt-123"}"#,
+            )
+            .create_async()
+            .await;
+        let agents = server
+            .mock("GET", "/v1/agent")
+            .match_header("authorization", "jwt-123")
+            .with_body(r#"{"agents":[]}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let outcome = client
+            .login_magic_link("user@example.com", "123456")
+            .await
+            .unwrap();
+        let LoginOutcome::MagicLinkSent { url } = outcome else {
+            panic!("expected a login link, got {:?}", outcome);
+        };
+        assert_eq!(client.complete_magic_link_login(&url).unwrap(), "jwt-123");
+        client.get_agents().await.unwrap();
+
+        assert_eq!(
+            client.complete_magic_link_login(" jwt-456 ").unwrap(),
+            "jwt-456"
+        );
+        assert!(matches!(
+            client.complete_magic_link_login("https://agixt.example.com/?email=x"),
+            Err(crate::Error::InvalidInput(_))
+        ));
+        link.assert_async().await;
+        agents.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_conversation_attachments() {
+        let mut server = mockito::Server::new_async().await;
+        let history = server
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"conversation_history":[
+                    {"id":"m1","role":"user","content":"Review [spec.pdf](/outputs/a1/c1/spec.pdf)"},
+                    {"id":"m2","role":"XT","content":"Done: ![chart](/outputs/a1/c1/chart.png) and [spec.pdf](/outputs/a1/c1/spec.pdf)"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let spec = server
+            .mock("HEAD", "/outputs/a1/c1/spec.pdf")
+            .with_header("content-length", "2048")
+            .create_async()
+            .await;
+        let chart = server
+            .mock("HEAD", "/outputs/a1/c1/chart.png")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let attachments = client.get_conversation_attachments("c1").await.unwrap();
+
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0].name, "spec.pdf");
+        assert_eq!(attachments[0].size, Some(2048));
+        assert_eq!(attachments[0].role, "user");
+        assert_eq!(attachments[0].message_id.as_deref(), Some("m1"));
+        assert_eq!(attachments[1].kind, crate::ArtifactKind::Image);
+        assert_eq!(attachments[1].size, None);
+        history.assert_async().await;
+        spec.assert_async().await;
+        chart.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_usage_tracking() {
+        let mut server = mockito::Server::new_async().await;
+        let stream = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"stream": true}),
+            ))
+            .with_header("content-type", "text/event-stream")
+            .with_body(concat!(
+                "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"},\"finish_reason\":\"stop\"}],",
+                "\"usage\":{\"prompt_tokens\":5,\"completion_tokens\":1,\"total_tokens\":6}}\n\n",
+                "data: [DONE]\n\n",
+            ))
+            .create_async()
+            .await;
+        let completion = server
+            .mock("POST", "/v1/chat/completions")
+            .with_body(
+                r#"{"id": "1", "object": "chat.completion", "created": 0, "model": "XT",
+                    "choices": [{"index": 0, "finish_reason": "stop",
+                        "message": {"role": "assistant", "content": "Hello"}}],
+                    "usage": {"prompt_tokens": 10, "completion_tokens": 4, "total_tokens": 14}}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .track_usage(true)
+            .build()
+            .unwrap();
+        let request = crate::ChatCompletions::builder()
+            .model("XT")
+            .message("user", "Hi")
+            .build()
+            .unwrap();
+        client.chat_completions(&request).await.unwrap();
+        client.clone().chat_completions(&request).await.unwrap();
+        use futures_util::StreamExt;
+        let _: Vec<_> = client
+            .chat_completions_stream(&request)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        let usage = client.usage_summary().unwrap();
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (25, 9, 34)
+        );
+        assert_eq!(usage.responses, 3);
+        assert_eq!(client.reset_usage(), Some(usage));
+        assert_eq!(client.usage_summary(), Some(crate::UsageSummary::default()));
+        assert_eq!(
+            AGiXTSDK::new(Some(server.url()), None, false).usage_summary(),
+            None
+        );
+        stream.assert_async().await;
+        completion.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_html_response_is_api_error() {
+        let mut server = mockito::Server::new_async().await;
+        let login_page = server
+            .mock("GET", "/v1/agent")
+            .with_header("content-type", "text/html; charset=utf-8")
+            .with_body(
+                "<!DOCTYPE html><html><head><title>Sign in - Access Proxy</title></head></html>",
+            )
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let error = client.get_agents().await.unwrap_err();
+        assert!(matches!(
+            error.root(),
+            crate::Error::ApiError { status: 200, .. }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "GET /v1/agent: API error (200): expected JSON but got text/html; charset=utf-8: \
+             Sign in - Access Proxy"
+        );
+        login_page.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_account_backup_round_trip() {
+        let mut source = mockito::Server::new_async().await;
+        let _agents = source
+            .mock("GET", "/v1/agent")
+            .with_body(r#"{"agents": [{"id": "a1", "name": "Helper"}]}"#)
+            .create_async()
+            .await;
+        let _config = source
+            .mock("GET", "/v1/agent/a1")
+            .with_body(
+                r#"{"agent": {"name": "Helper", "settings": {"provider": "openai"},
+                    "commands": {"Web Search": true}}}"#,
+            )
+            .create_async()
+            .await;
+        let _categories = source
+            .mock("GET", "/v1/prompt/categories")
+            .with_body(r#"{"categories": [{"id": "cat1", "name": "Default"}]}"#)
+            .create_async()
+            .await;
+        let _prompts = source
+            .mock("GET", "/v1/prompts")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "prompt_category".into(),
+                "Default".into(),
+            ))
+            .with_body(r#"{"prompts": [{"id": "p1", "name": "Greet", "content": "Hi {name}"}]}"#)
+            .create_async()
+            .await;
+        let _chains = source
+            .mock("GET", "/v1/chains")
+            .with_body(r#"[{"id": "ch1", "name": "Flow"}]"#)
+            .create_async()
+            .await;
+        let _chain = source
+            .mock("GET", "/v1/chain/ch1")
+            .with_body(r#"{"id": "ch1", "name": "Flow", "steps": [{"step": 1}]}"#)
+            .create_async()
+            .await;
+        let _conversations = source
+            .mock("GET", "/v1/conversations")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"conversations": [{"id": "c1", "name": "Planning", "agent_id": "a1"}],
+                    "total": 1}"#,
+            )
+            .create_async()
+            .await;
+        let _history = source
+            .mock("GET", "/v1/conversation/c1")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"conversation_history": [{"role": "user", "content": "Hello"}]}"#)
+            .create_async()
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("agixt-backup-{}", uuid::Uuid::new_v4()));
+        let client = AGiXTSDK::new(Some(source.url()), None, false);
+        let exported = client.export_account(&dir).await.unwrap();
+        assert_eq!(
+            (
+                exported.agents,
+                exported.prompts,
+                exported.chains,
+                exported.conversations
+            ),
+            (1, 1, 1, 1)
+        );
+        assert!(dir.join("manifest.json").exists());
+
+        let mut target = mockito::Server::new_async().await;
+        let _no_agents = target
+            .mock("GET", "/v1/agent")
+            .with_body(r#"{"agents": []}"#)
+            .create_async()
+            .await;
+        let import_agent = target
+            .mock("POST", "/v1/agent/import")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "agent_name": "Helper",
+                "settings": {"provider": "openai"},
+            })))
+            .with_body(r#"{"id": "a9", "name": "Helper"}"#)
+            .create_async()
+            .await;
+        let _no_prompts = target
+            .mock("GET", "/v1/prompts")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"prompts": []}"#)
+            .create_async()
+            .await;
+        let add_prompt = target
+            .mock("POST", "/v1/prompt")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "prompt_name": "Greet",
+                "prompt": "Hi {name}",
+            })))
+            .with_body(r#"{"id": "p9"}"#)
+            .create_async()
+            .await;
+        let _no_chains = target
+            .mock("GET", "/v1/chains")
+            .with_body("[]")
+            .create_async()
+            .await;
+        let import_chain = target
+            .mock("POST", "/v1/chain/import")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "chain_name": "Flow",
+                "steps": [{"step": 1}],
+            })))
+            .with_body(r#"{"message": "Chain imported"}"#)
+            .create_async()
+            .await;
+        let _no_conversations = target
+            .mock("GET", "/v1/conversations")
+            .with_body(r#"{"conversations_with_ids": []}"#)
+            .create_async()
+            .await;
+        let new_conversation = target
+            .mock("POST", "/v1/conversation")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "conversation_name": "Planning",
+                "agent_id": "a9",
+            })))
+            .with_body(r#"{"id": "c9"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(target.url()), None, false);
+        let imported = client.import_account(&dir).await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(imported.source, source.url());
+        assert_eq!(
+            (
+                imported.agents,
+                imported.prompts,
+                imported.chains,
+                imported.conversations
+            ),
+            (1, 1, 1, 1)
+        );
+        import_agent.assert_async().await;
+        add_prompt.assert_async().await;
+        import_chain.assert_async().await;
+        new_conversation.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_with_progress_resumes() {
+        let mut server = mockito::Server::new_async().await;
+        let resumed = server
+            .mock("GET", "/v1/agent/a1/workspace/download")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "path".into(),
+                "report.txt".into(),
+            ))
+            .match_header("range", "bytes=6-")
+            .with_status(206)
+            .with_header("content-range", "bytes 6-10/11")
+            .with_body("world")
+            .create_async()
+            .await;
+        let full = server
+            .mock("GET", "/files/logo.txt")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_body("logo")
+            .create_async()
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("agixt-download-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("report.txt");
+        std::fs::write(dir.join("report.txt.part"), "hello ").unwrap();
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let seen = std::sync::Mutex::new(Vec::new());
+        let size = client
+            .download_workspace_file_with_progress("a1", "report.txt", &path, &|progress| {
+                seen.lock().unwrap().push(progress)
+            })
+            .await
+            .unwrap();
+        assert_eq!(size, 11);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world");
+        assert!(!dir.join("report.txt.part").exists());
+        let seen = seen.into_inner().unwrap();
+        assert_eq!(seen.first().unwrap().received, 6);
+        assert_eq!(seen.last().unwrap().fraction(), Some(1.0));
+
+        let logo = dir.join("logo.txt");
+        let size = client
+            .download_url_to("/files/logo.txt", &logo, &|_| {})
+            .await
+            .unwrap();
+        assert_eq!(size, 4);
+        assert_eq!(std::fs::read_to_string(&logo).unwrap(), "logo");
+        std::fs::remove_dir_all(&dir).unwrap();
+        resumed.assert_async().await;
+        full.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_url() {
+        let mut server = mockito::Server::new_async().await;
+        let audio = server
+            .mock("GET", "/episodes/42.mp3")
+            .match_query(mockito::Matcher::Any)
+            .with_body("ID3 audio bytes")
+            .create_async()
+            .await;
+        let transcribe = server
+            .mock("POST", "/v1/audio/transcriptions")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex(r#"filename="42.mp3""#.to_string()),
+                mockito::Matcher::Regex("ID3 audio bytes".to_string()),
+            ]))
+            .with_body(r#"{"text": "Welcome to episode 42."}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let url = format!("{}/episodes/42.mp3?token=abc", server.url());
+        let text = client.transcribe_url(&url, "XT").await.unwrap();
+        assert_eq!(text, "Welcome to episode 42.");
+        audio.assert_async().await;
+        transcribe.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_typed_prompt_and_chain_args() {
+        #[derive(serde::Serialize)]
+        struct ReportArgs {
+            topic: &'static str,
+            max_words: u32,
+            audience: Option<&'static str>,
+        }
+
+        let mut server = mockito::Server::new_async().await;
+        let prompt = server
+            .mock("POST", "/v1/agent/a1/prompt")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "prompt_name": "Report",
+                "prompt_args": {"topic": "Solar", "max_words": "300"},
+            })))
+            .with_body(r#"{"response": "Solar is bright."}"#)
+            .create_async()
+            .await;
+        let chain = server
+            .mock("POST", "/v1/chain/c1/run")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "chain_args": {"topic": "Solar", "max_words": "300"},
+            })))
+            .with_body(r#""done""#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let args = ReportArgs {
+            topic: "Solar",
+            max_words: 300,
+            audience: None,
+        };
+        let answer = client
+            .prompt_agent_with_args("a1", "Report", &args)
+            .await
+            .unwrap();
+        assert_eq!(answer, "Solar is bright.");
+        let output = client
+            .run_chain_with_args("c1", "Go", None, &args)
+            .await
+            .unwrap();
+        assert_eq!(output, "done");
+        prompt.assert_async().await;
+        chain.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_etag_revalidation() {
+        let mut server = mockito::Server::new_async().await;
+        let full = server
+            .mock("GET", "/v1/provider")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", r#"W/"v1""#)
+            .with_body(r#"{"providers":[{"name":"openai"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let unchanged = server
+            .mock("GET", "/v1/provider")
+            .match_header("if-none-match", r#"W/"v1""#)
+            .with_status(304)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .etag_cache(true)
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            let providers = client.get_providers().await.unwrap();
+            assert_eq!(providers[0]["name"], "openai");
+        }
+        full.assert_async().await;
+        unchanged.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_as_user_overrides_token() {
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for (token, provider) in [("alice-token", "openai"), ("bob-token", "anthropic")] {
+            mocks.push(
+                server
+                    .mock("GET", "/v1/provider")
+                    .match_header("authorization", token)
+                    .with_body(format!(r#"{{"providers":[{{"name":"{}"}}]}}"#, provider))
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let service = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .api_key("service-token")
+            .cache_ttl(std::time::Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let alice = service.as_user("Bearer alice-token").unwrap();
+        let bob = service.as_user("bob-token").unwrap();
+        for _ in 0..2 {
+            assert_eq!(alice.get_providers().await.unwrap()[0]["name"], "openai");
+            assert_eq!(bob.get_providers().await.unwrap()[0]["name"], "anthropic");
+        }
+        assert_eq!(
+            service.get_bearer_token().await.as_deref(),
+            Some("service-token")
+        );
+        assert!(Arc::ptr_eq(&service.client, &alice.client));
+        assert!(service.as_user("bad\ntoken").is_err());
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcript_records_prompts() {
+        let mut server = mockito::Server::new_async().await;
+        let ok = server
+            .mock("POST", "/v1/agent/agent-1/prompt")
+            .with_body(r#"{"response": "Hello!"}"#)
+            .create_async()
+            .await;
+        let failed = server
+            .mock("POST", "/v1/agent/agent-2/prompt")
+            .with_status(500)
+            .with_body(r#"{"detail": "provider down"}"#)
+            .create_async()
+            .await;
+
+        let path = std::env::temp_dir().join(format!("agixt-{}.jsonl", uuid::Uuid::new_v4()));
+        let client = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .transcript(JsonlTranscript::append(&path).unwrap())
+            .build()
+            .unwrap();
+        let mut args = HashMap::new();
+        args.insert("user_input".to_string(), serde_json::json!("Hi"));
+        args.insert("api_key".to_string(), serde_json::json!("sk-live-123"));
+        assert_eq!(
+            client
+                .prompt_agent("agent-1", "Chat", args.clone())
+                .await
+                .unwrap(),
+            "Hello!"
+        );
+        assert!(client.prompt_agent("agent-2", "Chat", args).await.is_err());
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!log.contains("sk-live-123"));
+        let entries: Vec<serde_json::Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["kind"], "prompt");
+        assert_eq!(entries[0]["agent"], "agent-1");
+        assert_eq!(entries[0]["request"]["prompt_args"]["user_input"], "Hi");
+        assert_eq!(entries[0]["response"], "Hello!");
+        assert!(entries[0].get("error").is_none());
+        assert_eq!(entries[1]["agent"], "agent-2");
+        assert!(entries[1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("provider down"));
+        ok.assert_async().await;
+        failed.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_validate_agent_settings() {
+        let mut server = mockito::Server::new_async().await;
+        let openai = server
+            .mock("GET", "/v1/provider/openai")
+            .with_body(
+                r#"{"provider": {"name": "openai", "settings":
+                    {"OPENAI_API_KEY": "", "OPENAI_MODEL": "gpt-4o"}}}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let elevenlabs = server
+            .mock("GET", "/v1/provider/elevenlabs")
+            .with_body(r#"{"name": "elevenlabs", "settings": {"ELEVENLABS_API_KEY": ""}}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let created = server
+            .mock("POST", "/v1/agent")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .validate_settings(true)
+            .build()
+            .unwrap();
+        let settings: HashMap<String, serde_json::Value> = HashMap::from([
+            ("provider".to_string(), "openai".into()),
+            ("OPENAI_MODLE".to_string(), "gpt-4o-mini".into()),
+            ("tts_provider".to_string(), "elevenlabs".into()),
+            ("ELEVENLABS_API_KEY".to_string(), "el-key".into()),
+            ("mode".to_string(), "prompt".into()),
+        ]);
+        let validation = client.validate_agent_settings(&settings).await.unwrap();
+        assert_eq!(validation.provider.as_deref(), Some("openai"));
+        assert_eq!(validation.missing, ["OPENAI_API_KEY"]);
+        assert_eq!(validation.unknown, ["OPENAI_MODLE"]);
+
+        let err = client
+            .add_agent("XT", Some(settings), None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidInput(_)));
+        assert!(err.to_string().contains("missing OPENAI_API_KEY"));
+        assert!(client
+            .validate_agent_settings(&HashMap::new())
+            .await
+            .unwrap()
+            .missing
+            .contains(&"provider".to_string()));
+        openai.assert_async().await;
+        elevenlabs.assert_async().await;
+        created.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_task() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let mut server = mockito::Server::new_async().await;
+        let polls = Arc::new(AtomicUsize::new(0));
+        let count = polls.clone();
+        let _task = server
+            .mock("GET", "/v1/task/t1")
+            .with_body_from_request(move |_| {
+                let status = match count.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => "running",
+                    _ => "failed",
+                };
+                serde_json::json!({"task": {
+                    "id": "t1", "title": "Train", "status": status,
+                    "output": "dataset is empty"
+                }})
+                .to_string()
+                .into()
+            })
+            .create_async()
+            .await;
+        let _stuck = server
+            .mock("GET", "/v1/task/t2")
+            .with_body(r#"{"task": {"id": "t2", "title": "Plan", "completed": false}}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let outcome = client
+            .wait_for_task("t1", Duration::from_millis(10), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(!outcome.is_completed());
+        assert_eq!(outcome.output(), "dataset is empty");
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+
+        let err = client
+            .wait_for_task("t2", Duration::from_millis(10), Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout());
+        assert!(matches!(
+            err,
+            crate::Error::DeadlineExceeded {
+                deadline,
+                attempts: 2..,
+                last_error: None,
+            } if deadline == Duration::from_millis(50)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_new_conversation_stores_user_role() {
+        let mut server = mockito::Server::new_async().await;
+        let create = server
+            .mock("POST", "/v1/conversation")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "conversation_content": [
+                    {"role": "USER", "content": "Hi"},
+                    {"role": "XT", "content": "Hello!"}
+                ]
+            })))
+            .with_body(r#"{"id": "c1", "name": "Greeting"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        use crate::models::{messages_to_history, Message};
+
+        let history =
+            messages_to_history(&[Message::user("Hi"), Message::assistant("Hello!")], "XT");
+        client
+            .new_conversation("a1", "Greeting", Some(history))
+            .await
+            .unwrap();
+        create.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_providers_by_service() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/providers/service/embeddings")
+            .with_body(r#"{"providers": ["openai", "default"]}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let providers = client
+            .get_providers_by_service(crate::models::ProviderService::Embeddings)
+            .await
+            .unwrap();
+        assert_eq!(providers, ["openai", "default"]);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_requests_queues_by_priority() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/provider")
+            .with_body(r#"{"providers": []}"#)
+            .expect(4)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .max_concurrent_requests(1)
+            .build()
+            .unwrap();
+        let background =
+            client.with_options(RequestOptions::new().priority(RequestPriority::Background));
+        let (a, b, c, d) = tokio::join!(
+            background.get_providers(),
+            client.get_providers(),
+            background.get_providers(),
+            client.get_providers(),
+        );
+        for result in [a, b, c, d] {
+            assert!(result.is_ok());
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_options() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/agent/a1/prompt")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "prompt_name": "Chat",
+                "prompt_args": {
+                    "user_input": "News?",
+                    "conversation_name": "c1",
+                    "context_results": 8,
+                    "disable_memory": true,
+                    "websearch": true,
+                    "websearch_depth": 2,
+                    "browse_links": false,
+                    "injected_memories": 3,
+                    "shots": 2
+                }
+            })))
+            .with_body(r#"{"response": "Here is the news"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let options = crate::models::PromptOptions::new()
+            .context_results(8)
+            .websearch(true)
+            .websearch_depth(2)
+            .browse_links(false)
+            .injected_memories(3)
+            .shots(2);
+        let answer = client
+            .chat_with_options("a1", "News?", "c1", &options)
+            .await
+            .unwrap();
+        assert_eq!(answer, "Here is the news");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_and_aborts() {
+        // A server that accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let _server = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+
+        let client = AGiXTSDK::new(Some(url), None, false);
+        let pending = tokio::spawn({
+            let client = client.clone();
+            async move { client.get_providers().await }
+        });
+        sleep(std::time::Duration::from_millis(100)).await;
+
+        let err = client
+            .shutdown(Some(std::time::Duration::from_millis(100)))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::ShutdownTimedOut { aborted: 1, .. }
+        ));
+        assert!(pending.await.unwrap().unwrap_err().is_cancelled());
+        assert!(client.is_shut_down());
+        assert!(client
+            .with_options(RequestOptions::new())
+            .get_providers()
+            .await
+            .unwrap_err()
+            .is_cancelled());
+
+        // Nothing in flight: returns right away
+        let idle = AGiXTSDK::new(None, None, false);
+        idle.shutdown(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_new_conversation_from_template() {
+        let mut server = mockito::Server::new_async().await;
+        let create = server
+            .mock("POST", "/v1/conversation")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "conversation_name": "Welcome",
+                "agent_id": "a1",
+                "conversation_content": [
+                    {"role": "system", "content": "Be brief."},
+                    {"role": "assistant", "content": "Hi!"}
+                ]
+            })))
+            .with_body(r#"{"id": "c1", "name": "Welcome"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let template = crate::ConversationTemplate::new("onboarding", "Welcome")
+            .system_prompt("Be brief.")
+            .message(crate::models::Message::assistant("Hi!"))
+            .metadata("category", "support");
+        let conversation = client
+            .new_conversation_from_template("a1", &template)
+            .await
+            .unwrap();
+        assert_eq!(conversation["id"], "c1");
+        create.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_orchestrate() {
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("POST", "/v1/agent/a1/prompt")
+            .with_body(r#"{"response": "Use Rust"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let failing = server
+            .mock("POST", "/v1/agent/a2/prompt")
+            .with_status(500)
+            .with_body(r#"{"detail": "Provider down"}"#)
+            .create_async()
+            .await;
+        let judge = server
+            .mock("POST", "/v1/agent/j1/prompt")
+            .match_body(mockito::Matcher::Regex(
+                "Answer 1:\\\\nUse Rust".to_string(),
+            ))
+            .with_body(r#"{"response": "Rust, definitely"}"#)
+            .create_async()
+            .await;
+        let second = server
+            .mock("POST", "/v1/agent/a3/prompt")
+            .match_body(mockito::Matcher::Regex(
+                "The previous agent answered:\\\\nUse Rust".to_string(),
+            ))
+            .with_body(r#"{"response": "Use Rust, with tokio"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let panel = Orchestration::parallel(["a1", "a2"]).judge("j1");
+        let result = client.orchestrate("Which language?", &panel).await.unwrap();
+        assert_eq!(result.replies.len(), 2);
+        assert_eq!(result.replies[1].agent_id, "a2");
+        assert_eq!(
+            result.replies[1].result.as_ref().unwrap_err().status(),
+            Some(500)
+        );
+        assert_eq!(result.answers().get("a1"), Some(&"Use Rust"));
+        assert_eq!(result.answer(), Some("Rust, definitely"));
+
+        let pipeline = Orchestration::pipeline(["a1", "a3"]);
+        let result = client
+            .orchestrate("Which language?", &pipeline)
+            .await
+            .unwrap();
+        assert!(result.verdict.is_none());
+        assert_eq!(result.answer(), Some("Use Rust, with tokio"));
+
+        first.assert_async().await;
+        failing.assert_async().await;
+        judge.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_client_reads_fixtures_in_strict_mode() {
+        use crate::models::{fixtures, ChatCompletions, Role};
+
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for fixture in fixtures::ALL {
+            mocks.push(
+                server
+                    .mock(fixture.method, fixture.path)
+                    .match_query(mockito::Matcher::Any)
+                    .with_body(fixture.body)
+                    .expect_at_least(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let client = AGiXTSDK::builder()
+            .base_uri(server.url())
+            .deserialize_mode(crate::DeserializeMode::Strict)
+            .build()
+            .unwrap();
+        assert_eq!(client.get_agents().await.unwrap().len(), 2);
+        let created = client
+            .add_agent("Researcher", None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(created.agent_name.as_deref(), Some("Researcher"));
+        let config = client.get_agentconfig(fixtures::AGENT_ID).await.unwrap();
+        assert_eq!(config.name, "AGiXT");
+        let extensions = client
+            .get_extension_commands(fixtures::AGENT_ID)
+            .await
+            .unwrap();
+        assert_eq!(extensions[0].name, "Web Search");
+        assert!(extensions[0].commands[0].enabled);
+        assert!(!extensions[1].commands[0].enabled);
+        let triple = client
+            .get_dpo_response(
+                fixtures::AGENT_ID,
+                "How do I reset my password?",
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(triple.rejected, "I don't know.");
+        let files = client
+            .list_workspace_files(fixtures::AGENT_ID)
+            .await
+            .unwrap();
+        assert_eq!(files[1].kind.as_deref(), Some("directory"));
+        let memories = client
+            .get_agent_memories(fixtures::AGENT_ID, "contact", None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(memories[0].relevance_score, Some(0.91));
+        let history = client
+            .get_conversation(fixtures::CONVERSATION_ID, None, None)
+            .await
+            .unwrap();
+        assert_eq!(history[1].role, Role::Agent("AGiXT".into()));
+        let metadata = client
+            .get_conversation_metadata(fixtures::CONVERSATION_ID)
+            .await
+            .unwrap();
+        assert_eq!(metadata.name, "Password reset");
+        assert!(!client
+            .get_conversations(None, None)
+            .await
+            .unwrap()
+            .items
+            .is_empty());
+        let message = client
+            .new_conversation_message("user", "Hello", fixtures::CONVERSATION_ID)
+            .await
+            .unwrap();
+        assert_eq!(message.id, "e5f7a9b1-3c6e-4d8f-9a2b-4c6e8a0b2d4f");
+        assert_eq!(client.get_notifications().await.unwrap().len(), 1);
+        assert_eq!(client.get_user().await.unwrap().companies.len(), 1);
+        let wallet = client.get_user_wallet().await.unwrap();
+        assert!(wallet.address.starts_with("7EcD"));
+        assert!(wallet.private_key.is_some());
+        assert_eq!(
+            client.get_chain_runs(fixtures::CHAIN_ID).await.unwrap()[1]
+                .status
+                .as_deref(),
+            Some("failed")
+        );
+        let run = client
+            .get_chain_run(fixtures::CHAIN_ID, fixtures::CHAIN_RUN_ID)
+            .await
+            .unwrap();
+        assert_eq!(run.steps[1].response["tickets"], 12);
+        assert_eq!(client.get_companies().await.unwrap().len(), 2);
+        let company = client.get_company(fixtures::COMPANY_ID).await.unwrap();
+        assert_eq!(company.agents.map(|agents| agents.len()), Some(1));
+        assert_eq!(
+            client
+                .get_company_agents(fixtures::COMPANY_ID)
+                .await
+                .unwrap()[0]
+                .name,
+            "AGiXT"
+        );
+        assert_eq!(
+            client
+                .get_company_users(fixtures::COMPANY_ID)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(!client.get_invitations(None).await.unwrap()[0].is_accepted);
+        assert_eq!(client.get_sso_providers().await.unwrap()[0].name, "google");
+        assert!(client.server_info().await.unwrap().supports("tasks"));
+        assert!(client.get_tasks().await.unwrap()[1].completed);
+        let task = client.get_task(fixtures::TASK_ID).await.unwrap();
+        assert_eq!(task.status.as_deref(), Some("running"));
+        let provider = client.get_provider_settings("openai").await.unwrap();
+        assert!(provider.supports("embeddings"));
+        assert_eq!(
+            provider.setting("MAX_TOKENS").unwrap().setting_type,
+            crate::models::SettingType::Integer
+        );
+        let embedders = client.get_embedders().await.unwrap();
+        assert_eq!(embedders.len(), 1);
+        assert_eq!(embedders[0].dimensions, Some(1536));
+        let webhooks = client.get_webhooks().await.unwrap();
+        assert!(webhooks[0].secret.is_some());
+        assert!(!webhooks[1].active);
+        let request = ChatCompletions::builder()
+            .model("AGiXT")
+            .message("user", "What is the capital of France?")
+            .build()
+            .unwrap();
+        let response = client.chat_completions(&request).await.unwrap();
+        assert_eq!(response.usage.total_tokens, 32);
+
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reauth_only_on_expired_token() {
+        let mut server = mockito::Server::new_async().await;
+        let rejected = server
+            .mock("GET", "/v1/agent")
+            .with_status(401)
+            .with_body(r#"{"detail":"Invalid token"}"#)
+            .create_async()
+            .await;
+        let login = server
+            .mock("POST", "/v1/login")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), Some("revoked".to_string()), false)
+            .with_credentials(Credentials::new("user@example.com", "hunter2"));
+        let err = client.get_agents().await.unwrap_err();
+
+        assert_eq!(err.status(), Some(401));
+        rejected.assert_async().await;
+        login.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reauth_logs_in_once() {
+        let mut server = mockito::Server::new_async().await;
+        let expired = server
+            .mock("GET", "/v1/agent")
+            .match_header("authorization", "expired")
+            .with_status(401)
+            .with_body(r#"{"detail":"Signature has expired"}"#)
+            .expect_at_least(1)
+            .create_async()
+            .await;
+        let login = server
+            .mock("POST", "/v1/login")
+            .with_body(r#"{"token":"fresh"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let agents = server
+            .mock("GET", "/v1/agent")
+            .match_header("authorization", "fresh")
+            .with_body(r#"{"agents":[{"id":"1","name":"XT"}]}"#)
+            .expect(4)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), Some("expired".to_string()), false)
+            .with_credentials(Credentials::new("user@example.com", "hunter2"));
+        let results = futures_util::future::join_all((0..4).map(|_| client.get_agents())).await;
+
+        assert!(results.iter().all(|result| result.is_ok()));
+        expired.assert_async().await;
+        login.assert_async().await;
+        agents.assert_async().await;
+    }
+
+    #[cfg(feature = "totp")]
+    #[tokio::test]
+    async fn test_reauth_with_totp_credentials() {
+        let mut server = mockito::Server::new_async().await;
+        let expired = server
+            .mock("GET", "/v1/agent")
+            .match_header("authorization", "expired")
+            .with_status(401)
+            .with_body(r#"{"detail":"Token expired"}"#)
+            .create_async()
+            .await;
+        let login = server
+            .mock("POST", "/v1/login/magic-link")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"email": "user@example.com"}),
+            ))
+            .with_body(r#"{"token":"fresh"}"#)
+            .create_async()
+            .await;
+        let agents = server
+            .mock("GET", "/v1/agent")
+            .match_header("authorization", "fresh")
+            .with_body(r#"{"agents":[]}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), Some("expired".to_string()), false)
+            .with_credentials(Credentials::totp("user@example.com", "JBSWY3DPEHPK3PXP"));
+        assert!(client.get_agents().await.unwrap().is_empty());
+
+        expired.assert_async().await;
+        login.assert_async().await;
+        agents.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_response_body() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // A server that sends the headers, then stalls in the middle of the body
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let _server = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\n{")
+                    .await;
+                connections.push(stream);
+            }
+        });
+
+        let client = AGiXTSDK::new(Some(url), None, false);
+        let pending = tokio::spawn({
+            let client = client.clone();
+            async move { client.get_providers().await }
+        });
+        sleep(std::time::Duration::from_millis(100)).await;
+
+        let err = client
+            .shutdown(Some(std::time::Duration::from_millis(100)))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::ShutdownTimedOut { aborted: 1, .. }
+        ));
+        assert!(pending.await.unwrap().unwrap_err().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_company_routes() {
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", "/v1/companies/co1")
+            .with_body(r#"{"id": "co1", "name": "Example Inc"}"#)
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/v1/companies")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"name": "Labs", "parent_company_id": "co1"}),
+            ))
+            .with_body(r#"{"id": "co2", "name": "Labs", "parent_company_id": "co1"}"#)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PUT", "/v1/companies/co2")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"name": "Research"}),
+            ))
+            .with_body(r#"{"id": "co2", "name": "Research", "parent_company_id": "co1"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        assert_eq!(client.get_company("co1").await.unwrap().name, "Example Inc");
+        let company = client.create_company("Labs", Some("co1")).await.unwrap();
+        assert_eq!(company.parent_company_id.as_deref(), Some("co1"));
+        let company = client
+            .update_company(&company.id, "Research")
+            .await
+            .unwrap();
+        assert_eq!(company.name, "Research");
+
+        get.assert_async().await;
+        create.assert_async().await;
+        update.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_user() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("DELETE", "/v1/user")
+            .match_header("authorization", "token")
+            .with_body(r#"{"message": "User deleted successfully."}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), Some("token".to_string()), false);
+        let message = client.delete_user().await.unwrap();
+        assert_eq!(message, "User deleted successfully.");
+        assert!(!client.headers().contains_key(AUTHORIZATION));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_logout() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/logout")
+            .match_header("authorization", "token")
+            .with_body(r#"{"message": "Logged out"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), Some("token".to_string()), false);
+        client.logout().await.unwrap();
+        assert!(!client.headers().contains_key(AUTHORIZATION));
+        mock.assert_async().await;
+
+        // The token is dropped even when the server rejects the logout
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/logout")
+            .with_status(401)
+            .with_body(r#"{"detail": "Token expired"}"#)
+            .create_async()
+            .await;
+        let client = AGiXTSDK::new(Some(server.url()), Some("expired".to_string()), false);
+        client.logout().await.unwrap();
+        assert!(!client.headers().contains_key(AUTHORIZATION));
+        mock.assert_async().await;
     }
 }
@@ -0,0 +1,143 @@
+//! Per-call request options.
+
+use super::RequestPriority;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Options applied to the requests of a client returned by `AGiXTSDK::with_options`.
+///
+/// # Example
+/// ```rust,no_run
+/// use agixt_sdk::{AGiXTSDK, RequestOptions};
+/// use std::time::Duration;
+///
+/// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+/// // Chains may run for minutes; allow them more time than the client default.
+/// let result = client
+///     .with_options(RequestOptions::new().timeout(Duration::from_secs(600)))
+///     .run_chain("chain-id", "Summarize the report", None, None, None, None)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Timeout for each request, overriding the client-wide timeout.
+    /// Not supported on `wasm32`.
+    pub timeout: Option<Duration>,
+    /// Correlation ID sent as `X-Request-ID`; a fresh UUID is used when unset.
+    pub request_id: Option<String>,
+    /// Token that aborts in-flight requests with `Error::Cancelled` when cancelled.
+    pub cancellation: Option<CancellationToken>,
+    /// How many times a request that failed with a retryable error is sent again.
+    pub max_retries: u32,
+    /// Wall-clock budget for each call, including retries and the waits between them.
+    pub deadline: Option<Duration>,
+    /// Queue position when the client caps concurrent requests.
+    pub priority: RequestPriority,
+}
+
+impl RequestOptions {
+    /// Create options that leave every setting at the client default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the timeout for each request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send `request_id` as the `X-Request-ID` header, e.g. to propagate the ID
+    /// of an incoming request to AGiXT.
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Abort requests when `token` is cancelled, e.g. from a "Stop" button.
+    ///
+    /// Requests already in flight are dropped and return `Error::Cancelled`, as
+    /// do requests started after cancellation. Calls made of several requests,
+    /// such as `run_chain_with_progress`, stop at the next request.
+    ///
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, RequestOptions};
+    /// use tokio_util::sync::CancellationToken;
+    ///
+    /// # async fn run(client: AGiXTSDK) {
+    /// let stop = CancellationToken::new();
+    /// let cancellable = client.with_options(RequestOptions::new().cancellation(stop.clone()));
+    /// let answer = tokio::spawn(async move {
+    ///     cancellable.chat("agent-id", "Write a novel", "conversation-id", None).await
+    /// });
+    /// stop.cancel();
+    /// assert!(answer.await.unwrap().unwrap_err().is_cancelled());
+    /// # }
+    /// ```
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Retry requests that fail with a retryable error (see `Error::is_retryable`)
+    /// up to `max_retries` times, with exponential backoff starting at 250ms.
+    ///
+    /// Requests whose body cannot be replayed, such as streamed uploads, are
+    /// never retried. Requests creating agents, conversations, messages and
+    /// other resources carry an `Idempotency-Key` header that stays the same
+    /// across attempts, so servers honoring it don't create duplicates.
+    pub fn retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Bound each call, retries and backoff included, to `deadline`.
+    ///
+    /// A call that runs out of budget returns `Error::DeadlineExceeded` with the
+    /// number of attempts made and the error of the last one. Unlike `timeout`,
+    /// this also works on `wasm32`.
+    ///
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, RequestOptions};
+    /// use std::time::Duration;
+    ///
+    /// # async fn run(client: AGiXTSDK) {
+    /// let client = client.with_options(
+    ///     RequestOptions::new()
+    ///         .retries(3)
+    ///         .deadline(Duration::from_secs(2)),
+    /// );
+    /// if let Err(e) = client.get_providers().await {
+    ///     if let agixt_sdk::Error::DeadlineExceeded { attempts, .. } = e.root() {
+    ///         eprintln!("gave up after {} attempts", attempts)
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Queue requests behind those of a higher priority when the client caps
+    /// concurrent requests with `AGiXTSDKBuilder::max_concurrent_requests`.
+    ///
+    /// Without a cap, requests are sent right away whatever their priority.
+    ///
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, RequestOptions, RequestPriority};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// // Ingestion waits whenever a chat is queued on the same client
+    /// let ingest = client.with_options(RequestOptions::new().priority(RequestPriority::Background));
+    /// ingest.learn_text("agent-id", "notes", "A long document", None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
@@ -0,0 +1,239 @@
+//! Sending one user input to several agents and combining their replies.
+
+use crate::error::Result;
+use crate::models::PromptOptions;
+use futures_util::future::join_all;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How the agents of an `Orchestration` receive the user input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OrchestrationMode {
+    /// Every agent answers the input at the same time.
+    #[default]
+    Parallel,
+    /// Agents answer one after another, each given the input and the reply
+    /// of the agent before it, e.g. draft, critique, polish. Stops at the
+    /// first agent that fails.
+    Pipeline,
+}
+
+/// Agents to send one input to with `AGiXTSDK::orchestrate`, and how.
+///
+/// # Example
+/// ```rust,no_run
+/// use agixt_sdk::{AGiXTSDK, Orchestration};
+///
+/// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+/// let panel = Orchestration::parallel(["researcher-id", "skeptic-id", "optimist-id"])
+///     .judge("editor-id");
+/// let result = client.orchestrate("Should we adopt Rust for our backend?", &panel).await?;
+/// for reply in &result.replies {
+///     match &reply.result {
+///         Ok(answer) => println!("{}: {}", reply.agent_id, answer),
+///         Err(e) => eprintln!("{} failed: {}", reply.agent_id, e),
+///     }
+/// }
+/// println!("Verdict: {}", result.answer().unwrap_or_default());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Orchestration {
+    /// Agent IDs, in pipeline order
+    pub agents: Vec<String>,
+    pub mode: OrchestrationMode,
+    /// Agent ID of the agent that picks or merges the best reply
+    pub judge: Option<String>,
+    /// Conversation ID the prompts are logged to; none when unset
+    pub conversation_id: Option<String>,
+    /// Context options for every agent and the judge
+    pub options: PromptOptions,
+}
+
+impl Orchestration {
+    /// Send the input to every agent at once.
+    pub fn parallel<I, S>(agents: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            agents: agents.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Pass the input through the agents in order.
+    pub fn pipeline<I, S>(agents: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            mode: OrchestrationMode::Pipeline,
+            ..Self::parallel(agents)
+        }
+    }
+
+    /// Have the agent `agent_id` pick or merge the best of the replies.
+    pub fn judge(mut self, agent_id: impl Into<String>) -> Self {
+        self.judge = Some(agent_id.into());
+        self
+    }
+
+    /// Log the prompts to a conversation by ID.
+    pub fn conversation(mut self, conversation_id: impl Into<String>) -> Self {
+        self.conversation_id = Some(conversation_id.into());
+        self
+    }
+
+    /// Set the context options of every prompt.
+    pub fn options(mut self, options: PromptOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+/// The reply of one agent of an orchestration.
+#[derive(Debug)]
+pub struct AgentReply {
+    pub agent_id: String,
+    /// The agent's answer, or why it failed
+    pub result: Result<String>,
+    /// Time the agent took to answer
+    pub latency: Duration,
+}
+
+/// Replies collected by `AGiXTSDK::orchestrate`.
+#[derive(Debug, Default)]
+pub struct OrchestrationResult {
+    /// One reply per agent that was asked, in the order of `Orchestration::agents`
+    pub replies: Vec<AgentReply>,
+    /// The judge's pick or merge of the replies, when a judge was set and at
+    /// least one agent answered
+    pub verdict: Option<String>,
+}
+
+impl OrchestrationResult {
+    /// The final answer: the judge's verdict, or else the last successful
+    /// reply, which for a completed pipeline is that of its last agent.
+    pub fn answer(&self) -> Option<&str> {
+        self.verdict.as_deref().or_else(|| {
+            self.replies
+                .iter()
+                .rev()
+                .find_map(|reply| reply.result.as_deref().ok())
+        })
+    }
+
+    /// The agents' successful answers by agent ID.
+    pub fn answers(&self) -> HashMap<&str, &str> {
+        self.replies
+            .iter()
+            .filter_map(|reply| Some((reply.agent_id.as_str(), reply.result.as_deref().ok()?)))
+            .collect()
+    }
+}
+
+impl super::AGiXTSDK {
+    // ==================== Orchestration ====================
+
+    /// Send `input` to several agents, in parallel or as a pipeline, and
+    /// collect their replies, optionally letting a judge agent pick or merge
+    /// the best one.
+    ///
+    /// A failing agent does not fail the call; its reply holds the error.
+    /// Only a failing judge makes the call return an error.
+    pub async fn orchestrate(
+        &self,
+        input: &str,
+        orchestration: &Orchestration,
+    ) -> Result<OrchestrationResult> {
+        let mut result = OrchestrationResult::default();
+        match orchestration.mode {
+            OrchestrationMode::Parallel => {
+                let replies: Vec<_> = orchestration
+                    .agents
+                    .iter()
+                    .map(|agent_id| self.ask_agent(agent_id, input.to_string(), orchestration))
+                    .collect();
+                result.replies = join_all(replies).await;
+            }
+            OrchestrationMode::Pipeline => {
+                let mut previous: Option<String> = None;
+                for agent_id in &orchestration.agents {
+                    let prompt = match &previous {
+                        Some(reply) => format!(
+                            "{}\n\nThe previous agent answered:\n{}\n\nBuild on that answer \
+                             to respond to the request above.",
+                            input, reply
+                        ),
+                        None => input.to_string(),
+                    };
+                    let reply = self.ask_agent(agent_id, prompt, orchestration).await;
+                    previous = reply.result.as_ref().ok().cloned();
+                    result.replies.push(reply);
+                    if previous.is_none() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let answers: Vec<&str> = result
+            .replies
+            .iter()
+            .filter_map(|reply| reply.result.as_deref().ok())
+            .collect();
+        if let (Some(judge), false) = (&orchestration.judge, answers.is_empty()) {
+            let numbered: Vec<String> = answers
+                .iter()
+                .enumerate()
+                .map(|(i, answer)| format!("Answer {}:\n{}", i + 1, answer))
+                .collect();
+            let prompt = format!(
+                "Several assistants answered the request below. Reply with the best \
+                 answer to the request, picking one of theirs or merging their strengths. \
+                 Reply only with that answer.\n\nRequest:\n{}\n\n{}",
+                input,
+                numbered.join("\n\n")
+            );
+            result.verdict = Some(self.ask_agent(judge, prompt, orchestration).await.result?);
+        }
+        Ok(result)
+    }
+
+    async fn ask_agent(
+        &self,
+        agent_id: &str,
+        input: String,
+        orchestration: &Orchestration,
+    ) -> AgentReply {
+        let mut args = HashMap::new();
+        args.insert("user_input".to_string(), serde_json::json!(input));
+        args.insert("disable_memory".to_string(), serde_json::json!(true));
+        match &orchestration.conversation_id {
+            Some(conversation_id) => {
+                args.insert(
+                    "conversation_name".to_string(),
+                    serde_json::json!(conversation_id),
+                );
+            }
+            None => {
+                args.insert("log_user_input".to_string(), serde_json::json!(false));
+                args.insert("log_output".to_string(), serde_json::json!(false));
+            }
+        }
+        let started = web_time::Instant::now();
+        let result = self
+            .prompt_agent_with_options(agent_id, "Chat", args, &orchestration.options)
+            .await;
+        AgentReply {
+            agent_id: agent_id.to_string(),
+            result,
+            latency: started.elapsed(),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Prompt library sync from a local directory.
+
+use crate::error::{Error, Result};
+use crate::models::PromptSyncReport;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+impl super::AGiXTSDK {
+    // ==================== Prompt Sync ====================
+
+    /// Make the prompts of `category` match the `.txt` and `.md` files in
+    /// `dir`, so a prompt library can be kept in version control.
+    ///
+    /// Each file is a prompt named after the file without its extension.
+    /// Prompts missing on the server are created, those whose text differs are
+    /// updated and prompts of the category without a file are deleted. With
+    /// `dry_run` nothing is changed and the report lists what would be.
+    /// Trailing whitespace is ignored when comparing.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let plan = client
+    ///     .sync_prompts_from_dir("prompts/support".as_ref(), "Support", true)
+    ///     .await?;
+    /// println!("would delete {:?}", plan.deleted);
+    /// client
+    ///     .sync_prompts_from_dir("prompts/support".as_ref(), "Support", false)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sync_prompts_from_dir(
+        &self,
+        dir: &Path,
+        category: &str,
+        dry_run: bool,
+    ) -> Result<PromptSyncReport> {
+        let local = read_prompt_dir(dir).await?;
+
+        let mut remote = BTreeMap::new();
+        for prompt in self.get_prompts(Some(category)).await? {
+            let (Some(name), Some(id)) = (
+                prompt.get("name").and_then(|v| v.as_str()),
+                prompt.get("id").and_then(|v| v.as_str()),
+            ) else {
+                continue;
+            };
+            let text = match prompt_text(&prompt) {
+                Some(text) => text,
+                None => prompt_text(&self.get_prompt(id).await?).unwrap_or_default(),
+            };
+            remote.insert(name.to_string(), (id.to_string(), text));
+        }
+
+        let mut report = PromptSyncReport {
+            dry_run,
+            ..PromptSyncReport::default()
+        };
+        for (name, text) in &local {
+            match remote.get(name) {
+                None => {
+                    if !dry_run {
+                        self.add_prompt(name, text, Some(category)).await?;
+                    }
+                    report.created.push(name.clone());
+                }
+                Some((_, current)) if current.trim_end() == text => {
+                    report.unchanged.push(name.clone());
+                }
+                Some((id, _)) => {
+                    if !dry_run {
+                        self.update_prompt(id, text).await?;
+                    }
+                    report.updated.push(name.clone());
+                }
+            }
+        }
+        for (name, (id, _)) in &remote {
+            if !local.contains_key(name) {
+                if !dry_run {
+                    self.delete_prompt(id).await?;
+                }
+                report.deleted.push(name.clone());
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Prompt texts by name from the `.txt` and `.md` files directly in `dir`.
+async fn read_prompt_dir(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let io_error = |path: &Path, e: std::io::Error| {
+        Error::Other(format!("cannot read {}: {}", path.display(), e))
+    };
+    let mut entries = tokio::fs::read_dir(dir)
+        .await
+        .map_err(|e| io_error(dir, e))?;
+    let mut prompts = BTreeMap::new();
+    while let Some(entry) = entries.next_entry().await.map_err(|e| io_error(dir, e))? {
+        let path = entry.path();
+        let is_prompt = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("txt") | Some("md")
+        );
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if !is_prompt || !path.is_file() {
+            continue;
+        }
+        let text = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| io_error(&path, e))?;
+        if prompts
+            .insert(name.to_string(), text.trim_end().to_string())
+            .is_some()
+        {
+            return Err(Error::InvalidInput(format!(
+                "prompt {} has both a .txt and a .md file in {}",
+                name,
+                dir.display()
+            )));
+        }
+    }
+    Ok(prompts)
+}
+
+/// The text of a prompt, from a listing entry or a `get_prompt` response.
+pub(super) fn prompt_text(prompt: &Value) -> Option<String> {
+    let prompt = prompt
+        .get("prompt")
+        .filter(|v| v.is_object())
+        .unwrap_or(prompt);
+    ["content", "prompt", "prompt_content"]
+        .iter()
+        .find_map(|field| prompt.get(field).and_then(|v| v.as_str()))
+        .map(str::to_string)
+}
@@ -1,29 +1,47 @@
 //! Provider operations using /v1 endpoints.
 
 use crate::error::Result;
-use std::collections::HashMap;
+use crate::models::{
+    Embedder, ProviderDetails, ProviderFallback, ProviderService, SettingsValidation,
+};
+use std::collections::{BTreeSet, HashMap};
+
+/// Agent settings that belong to no provider.
+const GENERAL_AGENT_SETTINGS: &[&str] = &[
+    "mode",
+    "persona",
+    "prompt_name",
+    "prompt_category",
+    "command_name",
+    "command_args",
+    "chain_name",
+    "chain_args",
+    "helper_agent_name",
+    "AUTONOMOUS_EXECUTION",
+    "analyze_user_input",
+    "websearch",
+    "websearch_depth",
+    "WEBSEARCH_TIMEOUT",
+    "context_results",
+    "conversation_results",
+    "FALLBACK_PROVIDERS",
+    "ROTATE_PROVIDERS_ON_ERROR",
+];
 
 impl super::AGiXTSDK {
     // ==================== Providers ====================
 
     /// Get list of available providers.
     pub async fn get_providers(&self) -> Result<Vec<serde_json::Value>> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/provider", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
-
-        let status = response.status();
-        let text = response.text().await?;
+        let request = self.client.get(format!("{}/provider", self.api_root));
+        let (status, text) = self.execute_cached(request).await?;
 
         if self.verbose {
             self.parse_response(status, &text).await?;
         }
 
         // Handle both list (v1) and dict (legacy) responses
-        let data: serde_json::Value = serde_json::from_str(&text)?;
+        let data: serde_json::Value = text.json()?;
         if let Some(arr) = data.as_array() {
             return Ok(arr.clone());
         }
@@ -35,23 +53,23 @@ impl super::AGiXTSDK {
         Ok(vec![])
     }
 
-    /// Get providers by service type.
-    pub async fn get_providers_by_service(&self, service: &str) -> Result<Vec<serde_json::Value>> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/providers/service/{}", self.base_uri, service))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
-
-        let status = response.status();
-        let text = response.text().await?;
+    /// Get the providers offering `service`.
+    pub async fn get_providers_by_service(
+        &self,
+        service: ProviderService,
+    ) -> Result<Vec<serde_json::Value>> {
+        let request = self.client.get(format!(
+            "{}/providers/service/{}",
+            self.api_root,
+            service.as_str()
+        ));
+        let (status, text) = self.execute_cached(request).await?;
 
         if self.verbose {
             self.parse_response(status, &text).await?;
         }
 
-        let data: serde_json::Value = serde_json::from_str(&text)?;
+        let data: serde_json::Value = text.json()?;
         if let Some(arr) = data.as_array() {
             return Ok(arr.clone());
         }
@@ -63,14 +81,41 @@ impl super::AGiXTSDK {
         Ok(vec![])
     }
 
-    /// Get settings for a specific provider.
-    pub async fn get_provider_settings(&self, provider_name: &str) -> Result<HashMap<String, serde_json::Value>> {
-        let response = self
+    /// Get a provider's services and typed settings schema.
+    pub async fn get_provider_settings(&self, provider_name: &str) -> Result<ProviderDetails> {
+        let request = self
             .client
-            .get(&format!("{}/v1/provider/{}", self.base_uri, provider_name))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+            .get(format!("{}/provider/{}", self.api_root, provider_name));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        let data: serde_json::Value = text.json()?;
+        // The payload is either the provider object itself or wrapped in "provider"
+        let provider = data.get("provider").unwrap_or(&data);
+        Ok(ProviderDetails::from_value(provider_name, provider))
+    }
+
+    /// Update a provider's server-wide settings, e.g. to rotate its API key or
+    /// change default models for every agent at once.
+    ///
+    /// Servers without central provider settings answer with an API error
+    /// (usually 404); update the agents' settings instead.
+    pub async fn update_provider_settings(
+        &self,
+        provider_name: &str,
+        settings: HashMap<String, serde_json::Value>,
+    ) -> Result<String> {
+        let request = self
+            .client
+            .put(format!("{}/provider/{}", self.api_root, provider_name))
+            .json(&serde_json::json!({ "settings": settings }));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -80,63 +125,234 @@ impl super::AGiXTSDK {
         }
 
         #[derive(serde::Deserialize)]
-        struct SettingsResponse {
-            settings: HashMap<String, serde_json::Value>,
+        struct MessageResponse {
+            message: String,
+        }
+
+        let result: MessageResponse = text.json()?;
+        Ok(result.message)
+    }
+
+    /// Get every provider together with its settings schema.
+    ///
+    /// Providers whose list entry carries no settings are fetched individually.
+    pub async fn get_all_providers_with_settings(&self) -> Result<Vec<ProviderDetails>> {
+        let mut providers = Vec::new();
+        for provider in self.get_providers().await? {
+            let details = match provider.as_str() {
+                Some(name) => self.get_provider_settings(name).await?,
+                None => {
+                    let mut details = ProviderDetails::from_value("", &provider);
+                    if provider.get("settings").is_none() && !details.name.is_empty() {
+                        let fetched = self.get_provider_settings(&details.name).await?;
+                        details.settings = fetched.settings;
+                        if details.services.is_empty() {
+                            details.services = fetched.services;
+                        }
+                    }
+                    details
+                }
+            };
+            providers.push(details);
+        }
+        Ok(providers)
+    }
+
+    /// Check agent settings against the settings schemas of the providers
+    /// they name, before creating or updating an agent with them.
+    ///
+    /// Settings are `missing` when the agent's `provider` has no default for
+    /// them and they are absent or empty, as API keys usually are. They are
+    /// `unknown` when none of the providers named by `provider`, the
+    /// `*_provider` settings or the fallbacks knows them and they are not
+    /// general agent settings such as `mode` or `persona`. Auxiliary providers
+    /// the server does not know are ignored.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AGiXTSDK;
+    /// use std::collections::HashMap;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let settings = HashMap::from([
+    ///     ("provider".to_string(), "openai".into()),
+    ///     ("OPENAI_MODEL".to_string(), "gpt-4o".into()),
+    /// ]);
+    /// let validation = client.validate_agent_settings(&settings).await?;
+    /// if !validation.is_valid() {
+    ///     eprintln!("{}", validation);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn validate_agent_settings(
+        &self,
+        settings: &HashMap<String, serde_json::Value>,
+    ) -> Result<SettingsValidation> {
+        let text = |key: &str| settings.get(key).and_then(|v| v.as_str());
+        let Some(provider) = text("provider").filter(|p| !p.is_empty()) else {
+            return Ok(SettingsValidation {
+                missing: vec!["provider".to_string()],
+                ..SettingsValidation::default()
+            });
+        };
+
+        let mut validation = SettingsValidation {
+            provider: Some(provider.to_string()),
+            ..SettingsValidation::default()
+        };
+        let details = self.get_provider_settings(provider).await?;
+        for setting in &details.settings {
+            let supplied = settings.get(&setting.name).is_some_and(|v| !is_blank(v));
+            if is_blank(&setting.default) && !supplied {
+                validation.missing.push(setting.name.clone());
+            }
+        }
+
+        let mut known: BTreeSet<&str> = GENERAL_AGENT_SETTINGS.iter().copied().collect();
+        known.extend(details.settings.iter().map(|s| s.name.as_str()));
+        let mut others: BTreeSet<&str> = settings
+            .iter()
+            .filter(|(key, _)| is_provider_key(key))
+            .filter_map(|(_, value)| value.as_str())
+            .collect();
+        let fallback = ProviderFallback::from_settings(settings);
+        others.extend(
+            fallback
+                .iter()
+                .flat_map(|f| f.fallbacks.iter().map(String::as_str)),
+        );
+        others.remove(provider);
+        let mut schemas = Vec::new();
+        for name in others {
+            if name.is_empty() || name.eq_ignore_ascii_case("none") {
+                continue;
+            }
+            match self.get_provider_settings(name).await {
+                Ok(details) => schemas.push(details),
+                Err(e) if e.status() == Some(404) => {}
+                Err(e) => return Err(e),
+            }
         }
+        known.extend(
+            schemas
+                .iter()
+                .flat_map(|details| details.settings.iter().map(|s| s.name.as_str())),
+        );
+        validation.unknown = settings
+            .keys()
+            .filter(|key| !is_provider_key(key) && !known.contains(key.as_str()))
+            .cloned()
+            .collect();
+        validation.unknown.sort();
+        Ok(validation)
+    }
 
-        let result: SettingsResponse = serde_json::from_str(&text)?;
-        Ok(result.settings)
+    /// Fail with `Error::InvalidInput` when `validate_agent_settings` finds
+    /// problems, if the client was built with `validate_settings(true)`.
+    pub(crate) async fn check_agent_settings(
+        &self,
+        settings: &HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        if !self.validate_settings {
+            return Ok(());
+        }
+        let validation = self.validate_agent_settings(settings).await?;
+        if validation.is_valid() {
+            Ok(())
+        } else {
+            Err(crate::Error::InvalidInput(validation.to_string()))
+        }
+    }
+
+    /// Get the models a provider offers, for populating model pickers.
+    ///
+    /// Returns an empty list when the server does not expose model listing for
+    /// the provider.
+    pub async fn get_provider_models(&self, provider_name: &str) -> Result<Vec<String>> {
+        let request = self.client.get(format!(
+            "{}/provider/{}/models",
+            self.api_root, provider_name
+        ));
+        let (status, text) = match self.execute_cached(request).await {
+            Ok(response) => response,
+            Err(e) if e.status() == Some(404) => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        // Accept a bare list or {"models": [...]}, with entries given either as
+        // strings or as objects carrying an "id" or "name"
+        let data: serde_json::Value = text.json()?;
+        let models = data
+            .as_array()
+            .or_else(|| data.get("models").and_then(|v| v.as_array()))
+            .map(|models| {
+                models
+                    .iter()
+                    .filter_map(|model| {
+                        model
+                            .as_str()
+                            .or_else(|| model.get("id").and_then(|v| v.as_str()))
+                            .or_else(|| model.get("name").and_then(|v| v.as_str()))
+                            .map(str::to_string)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(models)
     }
 
     /// Get list of embedding providers.
     pub async fn get_embed_providers(&self) -> Result<Vec<String>> {
         let providers = self.get_providers().await?;
         let mut embed_providers = Vec::new();
-        
+
         for provider in providers {
             if let Some(obj) = provider.as_object() {
-                if obj.get("supports_embeddings").and_then(|v| v.as_bool()).unwrap_or(false) {
+                if obj
+                    .get("supports_embeddings")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false)
+                {
                     if let Some(name) = obj.get("name").and_then(|v| v.as_str()) {
                         embed_providers.push(name.to_string());
                     }
                 }
             }
         }
-        
+
         Ok(embed_providers)
     }
 
-    /// Get details of all embedders.
-    pub async fn get_embedders(&self) -> Result<HashMap<String, serde_json::Value>> {
+    /// Get the providers that compute embeddings, with their vector size
+    /// where the server reports it.
+    pub async fn get_embedders(&self) -> Result<Vec<Embedder>> {
         let providers = self.get_providers().await?;
-        let mut embedders = HashMap::new();
-        
-        for provider in providers {
-            if let Some(obj) = provider.as_object() {
-                if obj.get("supports_embeddings").and_then(|v| v.as_bool()).unwrap_or(false) {
-                    if let Some(name) = obj.get("name").and_then(|v| v.as_str()) {
-                        embedders.insert(name.to_string(), provider.clone());
-                    }
-                }
-            }
+        Ok(providers.iter().filter_map(Embedder::from_value).collect())
+    }
+
+    /// Get the full settings schema of every provider that computes
+    /// embeddings.
+    pub async fn get_embedding_providers_with_details(&self) -> Result<Vec<ProviderDetails>> {
+        let mut providers = Vec::new();
+        for embedder in self.get_embedders().await? {
+            providers.push(self.get_provider_settings(&embedder.name).await?);
         }
-        
-        Ok(embedders)
+        Ok(providers)
     }
 
     // ==================== Extensions ====================
 
     /// Get extension settings.
     pub async fn get_extension_settings(&self) -> Result<serde_json::Value> {
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/v1/extensions/settings", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
-
-        let status = response.status();
-        let text = response.text().await?;
+            .get(format!("{}/extensions/settings", self.api_root));
+        let (status, text) = self.execute_cached(request).await?;
 
         if self.verbose {
             self.parse_response(status, &text).await?;
@@ -147,27 +363,20 @@ impl super::AGiXTSDK {
             extension_settings: serde_json::Value,
         }
 
-        let result: ExtensionSettingsResponse = serde_json::from_str(&text)?;
+        let result: ExtensionSettingsResponse = text.json()?;
         Ok(result.extension_settings)
     }
 
     /// Get all available extensions.
     pub async fn get_extensions(&self) -> Result<Vec<serde_json::Value>> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/extensions", self.base_uri))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
-
-        let status = response.status();
-        let text = response.text().await?;
+        let request = self.client.get(format!("{}/extensions", self.api_root));
+        let (status, text) = self.execute_cached(request).await?;
 
         if self.verbose {
             self.parse_response(status, &text).await?;
         }
 
-        let data: serde_json::Value = serde_json::from_str(&text)?;
+        let data: serde_json::Value = text.json()?;
         if let Some(arr) = data.as_array() {
             return Ok(arr.clone());
         }
@@ -181,12 +390,11 @@ impl super::AGiXTSDK {
 
     /// Get arguments for a command.
     pub async fn get_command_args(&self, command_name: &str) -> Result<serde_json::Value> {
-        let response = self
-            .client
-            .get(&format!("{}/v1/extensions/{}/args", self.base_uri, command_name))
-            .headers(self.headers.lock().await.clone())
-            .send()
-            .await?;
+        let request = self.client.get(format!(
+            "{}/extensions/{}/args",
+            self.api_root, command_name
+        ));
+        let response = self.execute(request).await?;
 
         let status = response.status();
         let text = response.text().await?;
@@ -200,7 +408,20 @@ impl super::AGiXTSDK {
             command_args: serde_json::Value,
         }
 
-        let result: CommandArgsResponse = serde_json::from_str(&text)?;
+        let result: CommandArgsResponse = text.json()?;
         Ok(result.command_args)
     }
 }
+
+/// Settings naming a provider, such as `provider` or `tts_provider`.
+fn is_provider_key(key: &str) -> bool {
+    key == "provider" || key.to_ascii_lowercase().ends_with("_provider")
+}
+
+fn is_blank(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => true,
+        serde_json::Value::String(text) => text.is_empty(),
+        _ => false,
+    }
+}
@@ -0,0 +1,172 @@
+//! Client-side token-bucket rate limiting shared by all requests of a client,
+//! and the server's rate limit headers.
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::sync::Mutex;
+use std::time::Duration;
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Token bucket that refills at a fixed rate up to a burst capacity.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `requests_per_second` on average with bursts of `burst`.
+    pub(crate) fn new(requests_per_second: f64, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            requests_per_second,
+            burst,
+            state: Mutex::new(BucketState {
+                tokens: burst,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until a request may be sent, then consume one token.
+    pub(crate) async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * self.requests_per_second).min(self.burst);
+                state.last_refill = now;
+
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - state.tokens) / self.requests_per_second)
+            };
+            super::sleep(wait).await;
+        }
+    }
+}
+
+/// Rate limit state reported by the server in response headers.
+///
+/// Available from `AGiXTSDK::last_rate_limit` after any response and from
+/// `Error::rate_limit` on failed requests, typically with status 429.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// Requests allowed per window (`X-RateLimit-Limit`)
+    pub limit: Option<u64>,
+    /// Requests left in the current window (`X-RateLimit-Remaining`)
+    pub remaining: Option<u64>,
+    /// Time until the window resets (`X-RateLimit-Reset`)
+    pub reset: Option<Duration>,
+    /// How long the server asked the client to wait (`Retry-After`)
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Read the rate limit headers of a response, returning `None` when it has none.
+    ///
+    /// Both the `X-RateLimit-*` and the unprefixed `RateLimit-*` spellings are
+    /// understood. Resets may be given in seconds or as a Unix timestamp, and
+    /// `Retry-After` in seconds or as an HTTP date.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .get(format!("x-ratelimit-{}", name))
+                .or_else(|| headers.get(format!("ratelimit-{}", name)))
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+        };
+        let info = Self {
+            limit: header("limit").and_then(|v| v.parse().ok()),
+            remaining: header("remaining").and_then(|v| v.parse().ok()),
+            reset: header("reset").and_then(parse_reset),
+            retry_after: headers
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_retry_after),
+        };
+        (info != Self::default()).then_some(info)
+    }
+}
+
+/// Values this large are Unix timestamps rather than a number of seconds.
+const TIMESTAMP_THRESHOLD: f64 = 1_000_000_000.0;
+
+fn parse_reset(value: &str) -> Option<Duration> {
+    let seconds: f64 = value.parse().ok()?;
+    if !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    if seconds >= TIMESTAMP_THRESHOLD {
+        return Some(until_unix_time(seconds));
+    }
+    Some(Duration::from_secs_f64(seconds))
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(until_unix_time(date.timestamp() as f64))
+}
+
+fn until_unix_time(timestamp: f64) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    Duration::from_secs_f64((timestamp - now).max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_burst_then_throttle() {
+        let limiter = RateLimiter::new(20.0, 2);
+        let start = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(start.elapsed() < Duration::from_millis(40));
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[test]
+    fn test_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(RateLimitInfo::from_headers(&headers), None);
+
+        headers.insert("x-ratelimit-limit", "60".parse().unwrap());
+        headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+        headers.insert("x-ratelimit-reset", "30".parse().unwrap());
+        headers.insert(RETRY_AFTER, "12".parse().unwrap());
+        let info = RateLimitInfo::from_headers(&headers).unwrap();
+        assert_eq!(info.limit, Some(60));
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(info.reset, Some(Duration::from_secs(30)));
+        assert_eq!(info.retry_after, Some(Duration::from_secs(12)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        let info = RateLimitInfo::from_headers(&headers).unwrap();
+        assert_eq!(info.retry_after, Some(Duration::ZERO));
+    }
+}
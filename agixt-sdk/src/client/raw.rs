@@ -0,0 +1,90 @@
+//! Requests to endpoints the SDK does not wrap yet.
+
+use super::AGiXTSDK;
+use crate::error::Result;
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Low-level access to the REST API, returned by [`AGiXTSDK::raw`].
+///
+/// Requests go through the same pipeline as the SDK's own methods: headers
+/// and authentication, retries and request options, interceptors, rate
+/// limiting and error mapping. Paths are relative to the API root, e.g.
+/// `/agent` for `https://agixt.example.com/v1/agent`. Responses are returned
+/// as JSON, with an empty body as `Value::Null`.
+///
+/// # Example
+/// ```rust,no_run
+/// use agixt_sdk::AGiXTSDK;
+/// use serde_json::json;
+///
+/// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+/// let created = client
+///     .raw()
+///     .post("/agent/agent-id/schedules", &json!({"cron": "0 9 * * 1"}))
+///     .await?;
+/// println!("{}", created["id"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct RawClient<'a> {
+    sdk: &'a AGiXTSDK,
+}
+
+impl<'a> RawClient<'a> {
+    pub(crate) fn new(sdk: &'a AGiXTSDK) -> Self {
+        Self { sdk }
+    }
+
+    /// Send a GET request.
+    pub async fn get(&self, path: &str) -> Result<Value> {
+        self.request(Method::GET, path, None::<&Value>).await
+    }
+
+    /// Send a POST request with a JSON body.
+    pub async fn post<B: Serialize + ?Sized>(&self, path: &str, body: &B) -> Result<Value> {
+        self.request(Method::POST, path, Some(body)).await
+    }
+
+    /// Send a PUT request with a JSON body.
+    pub async fn put<B: Serialize + ?Sized>(&self, path: &str, body: &B) -> Result<Value> {
+        self.request(Method::PUT, path, Some(body)).await
+    }
+
+    /// Send a DELETE request.
+    pub async fn delete(&self, path: &str) -> Result<Value> {
+        self.request(Method::DELETE, path, None::<&Value>).await
+    }
+
+    /// Send a request with any method and an optional JSON body.
+    pub async fn request<B: Serialize + ?Sized>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<Value> {
+        let sdk = self.sdk;
+        let mut request = sdk.client.request(
+            method,
+            format!("{}/{}", sdk.api_root, path.trim_start_matches('/')),
+        );
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = sdk.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if sdk.verbose {
+            sdk.parse_response(status, &text).await?;
+        }
+
+        if text.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        text.json()
+    }
+}
@@ -0,0 +1,272 @@
+//! Responses that remember the request they answer, so body and JSON errors
+//! name the endpoint that produced them.
+
+use super::lifecycle::InFlight;
+use super::DeserializeMode;
+use crate::error::{ErrorContext, Result};
+use serde::de::{DeserializeOwned, Error as _};
+use std::time::Duration;
+
+/// Response returned by `AGiXTSDK::dispatch`, and by `AGiXTSDK::execute`
+/// once its status was checked.
+pub(crate) struct Response {
+    inner: reqwest::Response,
+    context: Option<ErrorContext>,
+    mode: DeserializeMode,
+    /// Keeps the request counted by `AGiXTSDK::shutdown` until the body is read
+    in_flight: Option<InFlight>,
+    /// Longest wait for each part of the body
+    read_timeout: Option<Duration>,
+}
+
+impl Response {
+    pub(crate) fn new(
+        inner: reqwest::Response,
+        context: Option<ErrorContext>,
+        mode: DeserializeMode,
+    ) -> Self {
+        Self {
+            inner,
+            context,
+            mode,
+            in_flight: None,
+            read_timeout: None,
+        }
+    }
+
+    /// Count the request as in flight until the response is dropped.
+    pub(crate) fn in_flight(mut self, in_flight: InFlight) -> Self {
+        self.in_flight = Some(in_flight);
+        self
+    }
+
+    /// Fail reading the body when no part of it arrives within `limit`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn read_timeout(mut self, limit: Duration) -> Self {
+        self.read_timeout = Some(limit);
+        self
+    }
+
+    /// Name the request in body and JSON errors.
+    pub(crate) fn with_context(mut self, context: Option<ErrorContext>) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub(crate) fn status(&self) -> reqwest::StatusCode {
+        self.inner.status()
+    }
+
+    pub(crate) fn headers(&self) -> &reqwest::header::HeaderMap {
+        self.inner.headers()
+    }
+
+    /// Read the whole body as text.
+    pub(crate) async fn text(self) -> Result<Text> {
+        let Self {
+            inner,
+            context,
+            mode,
+            in_flight,
+            read_timeout,
+        } = self;
+        let read = Text::read(inner, mode, read_timeout);
+        let result = match &in_flight {
+            Some(in_flight) => in_flight.abortable(read).await.and_then(|text| text),
+            None => read.await,
+        };
+        let mut text = result.map_err(|e| e.with_context(context.clone()))?;
+        text.context = context;
+        Ok(text)
+    }
+
+    /// Read the whole body as bytes.
+    pub(crate) async fn bytes(self) -> Result<Vec<u8>> {
+        let Self {
+            inner,
+            context,
+            in_flight,
+            read_timeout,
+            ..
+        } = self;
+        let read = read_body(inner, read_timeout);
+        let result = match &in_flight {
+            Some(in_flight) => in_flight.abortable(read).await.and_then(|bytes| bytes),
+            None => read.await,
+        };
+        result.map_err(|e| e.with_context(context))
+    }
+
+    /// Read the next chunk of the body, or `None` at its end.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) async fn chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        let read = next_chunk(&mut self.inner, self.read_timeout);
+        let chunk = match &self.in_flight {
+            Some(in_flight) => in_flight.abortable(read).await.and_then(|chunk| chunk),
+            None => read.await,
+        };
+        chunk.map_err(|e| e.with_context(self.context.clone()))
+    }
+}
+
+/// Read the next chunk of a body, failing when it takes longer than
+/// `read_timeout`.
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+async fn next_chunk(
+    response: &mut reqwest::Response,
+    read_timeout: Option<Duration>,
+) -> Result<Option<Vec<u8>>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(limit) = read_timeout {
+        return match tokio::time::timeout(limit, response.chunk()).await {
+            Ok(chunk) => Ok(chunk?.map(|chunk| chunk.to_vec())),
+            Err(_) => Err(crate::Error::read_timed_out()),
+        };
+    }
+    Ok(response.chunk().await?.map(|chunk| chunk.to_vec()))
+}
+
+/// Read a whole body, with `read_timeout` applied to each chunk.
+async fn read_body(
+    mut response: reqwest::Response,
+    read_timeout: Option<Duration>,
+) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    while let Some(chunk) = next_chunk(&mut response, read_timeout).await? {
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Body of a `Response`, read as text.
+pub(crate) struct Text {
+    text: String,
+    status: reqwest::StatusCode,
+    content_type: Option<String>,
+    context: Option<ErrorContext>,
+    mode: DeserializeMode,
+}
+
+impl Text {
+    /// Text read outside of `Response::text`, e.g. from the response cache.
+    pub(crate) fn new(text: String, context: Option<ErrorContext>, mode: DeserializeMode) -> Self {
+        Self {
+            text,
+            status: reqwest::StatusCode::OK,
+            content_type: None,
+            context,
+            mode,
+        }
+    }
+
+    /// Read the body of a response.
+    async fn read(
+        response: reqwest::Response,
+        mode: DeserializeMode,
+        read_timeout: Option<Duration>,
+    ) -> Result<Self> {
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let text = match read_timeout {
+            // Chunk by chunk, so a server that stops sending is noticed
+            Some(_) => {
+                String::from_utf8_lossy(&read_body(response, read_timeout).await?).into_owned()
+            }
+            None => response.text().await?,
+        };
+        Ok(Self {
+            text,
+            status,
+            content_type,
+            context: None,
+            mode,
+        })
+    }
+
+    /// Deserialize the body, naming the endpoint if it is not the expected JSON
+    /// and the field that did not match.
+    ///
+    /// A body that is not JSON at all, such as the HTML error page of a reverse
+    /// proxy, is reported as an `ApiError` with an excerpt of the page.
+    pub(crate) fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        deserialize(&self.text, self.mode).map_err(|e| {
+            let error = if self.looks_like_json() {
+                crate::Error::from(e)
+            } else {
+                crate::Error::non_json(
+                    self.status.as_u16(),
+                    self.content_type.as_deref(),
+                    &self.text,
+                )
+            };
+            error.with_context(self.context.clone())
+        })
+    }
+
+    /// Whether the body was meant to be JSON, going by its content type or,
+    /// without one, by how it starts.
+    fn looks_like_json(&self) -> bool {
+        if let Some(content_type) = &self.content_type {
+            if content_type.contains("json") {
+                return true;
+            }
+        }
+        let body = self.text.trim_start();
+        match body.chars().next() {
+            None => true,
+            Some('{' | '[' | '"' | '-' | '0'..='9') => true,
+            Some(_) => ["true", "false", "null"]
+                .iter()
+                .any(|literal| body.starts_with(literal)),
+        }
+    }
+}
+
+/// Deserialize `text` in `mode`. Mismatched values are reported with the path
+/// of the field, e.g. `conversation_history[2].content: invalid type: ...`.
+fn deserialize<T: DeserializeOwned>(text: &str, mode: DeserializeMode) -> serde_json::Result<T> {
+    let mut json = serde_json::Deserializer::from_str(text);
+    let mut track = serde_path_to_error::Track::new();
+    let mut unknown = Vec::new();
+    let mut record_unknown = |path: serde_ignored::Path<'_>| unknown.push(path.to_string());
+    let result = match mode {
+        DeserializeMode::Lenient => T::deserialize(serde_path_to_error::Deserializer::new(
+            &mut json, &mut track,
+        )),
+        DeserializeMode::Strict => T::deserialize(serde_path_to_error::Deserializer::new(
+            serde_ignored::Deserializer::new(&mut json, &mut record_unknown),
+            &mut track,
+        )),
+    };
+    let value = match result {
+        Ok(value) => value,
+        Err(e) if e.is_data() => {
+            let path = track.path().to_string();
+            return Err(match path.as_str() {
+                "." => e,
+                _ => serde_json::Error::custom(format!("{}: {}", path, e)),
+            });
+        }
+        Err(e) => return Err(e),
+    };
+    json.end()?;
+    match unknown.first() {
+        None => Ok(value),
+        Some(path) => Err(serde_json::Error::custom(format!(
+            "unknown field `{}` in strict mode",
+            path
+        ))),
+    }
+}
+
+impl std::ops::Deref for Text {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
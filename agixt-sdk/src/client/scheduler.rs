@@ -0,0 +1,169 @@
+//! Concurrency cap for outgoing requests that serves interactive requests
+//! before background ones.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Queue position of a request when the client caps concurrent requests with
+/// `AGiXTSDKBuilder::max_concurrent_requests`; set it with `RequestOptions::priority`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RequestPriority {
+    /// Requests a user is waiting for, such as chats. Served first.
+    #[default]
+    Interactive,
+    /// Bulk work such as memory ingestion, sent when no interactive request waits.
+    Background,
+}
+
+/// Semaphore handing free slots to waiting interactive requests before
+/// background ones, each in arrival order.
+#[derive(Debug)]
+pub(crate) struct Scheduler {
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    available: usize,
+    interactive: VecDeque<oneshot::Sender<Permit>>,
+    background: VecDeque<oneshot::Sender<Permit>>,
+}
+
+/// A slot of the scheduler, given back to the next waiter when dropped.
+#[derive(Debug)]
+pub(crate) struct Permit {
+    scheduler: Option<Arc<Scheduler>>,
+}
+
+impl Scheduler {
+    /// Create a scheduler letting at most `max_concurrent` requests through at once.
+    pub(crate) fn new(max_concurrent: usize) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(State {
+                available: max_concurrent,
+                interactive: VecDeque::new(),
+                background: VecDeque::new(),
+            }),
+        })
+    }
+
+    /// Wait for a free slot, behind every waiting request of the same or a
+    /// higher priority.
+    pub(crate) async fn acquire(self: &Arc<Self>, priority: RequestPriority) -> Permit {
+        let receiver = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let ahead = match priority {
+                RequestPriority::Interactive => state.interactive.len(),
+                RequestPriority::Background => state.interactive.len() + state.background.len(),
+            };
+            if state.available > 0 && ahead == 0 {
+                state.available -= 1;
+                return Permit {
+                    scheduler: Some(self.clone()),
+                };
+            }
+            let (sender, receiver) = oneshot::channel();
+            match priority {
+                RequestPriority::Interactive => state.interactive.push_back(sender),
+                RequestPriority::Background => state.background.push_back(sender),
+            }
+            receiver
+        };
+        // The sender is only dropped after a permit was sent on it; a permit
+        // sent to a waiter that gave up is dropped with the channel, which
+        // hands it on to the next one
+        receiver.await.unwrap_or(Permit { scheduler: None })
+    }
+
+    /// Hand a slot to the next waiter, or mark it free if none is waiting.
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let next = match state.interactive.pop_front() {
+                Some(sender) => sender,
+                None => match state.background.pop_front() {
+                    Some(sender) => sender,
+                    None => {
+                        state.available += 1;
+                        return;
+                    }
+                },
+            };
+            let permit = Permit {
+                scheduler: Some(self.clone()),
+            };
+            match next.send(permit) {
+                Ok(()) => return,
+                // The waiter gave up; disarm the permit so dropping it doesn't
+                // release again while the lock is held
+                Err(mut permit) => permit.scheduler = None,
+            }
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.scheduler.take() {
+            scheduler.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_interactive_requests_go_first() {
+        let scheduler = Scheduler::new(1);
+        let held = scheduler.acquire(RequestPriority::Background).await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut waiters = Vec::new();
+        for (name, priority) in [
+            ("background", RequestPriority::Background),
+            ("interactive", RequestPriority::Interactive),
+        ] {
+            let scheduler = scheduler.clone();
+            let order = order.clone();
+            waiters.push(tokio::spawn(async move {
+                let _permit = scheduler.acquire(priority).await;
+                order.lock().unwrap().push(name);
+            }));
+            tokio::task::yield_now().await;
+        }
+
+        drop(held);
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), ["interactive", "background"]);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_waiter_passes_its_slot_on() {
+        let scheduler = Scheduler::new(1);
+        let held = scheduler.acquire(RequestPriority::Interactive).await;
+
+        let abandoned = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move {
+                scheduler.acquire(RequestPriority::Interactive).await;
+            }
+        });
+        tokio::task::yield_now().await;
+        abandoned.abort();
+        let _ = abandoned.await;
+
+        drop(held);
+        let next = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            scheduler.acquire(RequestPriority::Background),
+        )
+        .await;
+        assert!(next.is_ok());
+    }
+}
@@ -0,0 +1,400 @@
+//! Server-sent events of the streaming chat and chain endpoints.
+
+use crate::error::Result;
+use crate::models::Usage;
+use futures_util::stream::Stream;
+use serde_json::Value;
+use std::pin::Pin;
+
+/// Stream of events from a streaming endpoint. It ends when the server closes
+/// the response; `StreamEvent::Done` may arrive more than once before that,
+/// e.g. for the finish reason and for the final `[DONE]` marker.
+pub type StreamEvents = Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send>>;
+
+/// Event of a streamed chat completion or chain run.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum StreamEvent {
+    /// More text of the answer for the choice at `index`
+    ContentDelta { index: u32, text: String },
+    /// A piece of a tool call; see `ToolCallDelta`
+    ToolCallDelta(ToolCallDelta),
+    /// The agent reported what it is working on (`[ACTIVITY]` and
+    /// `[SUBACTIVITY]` messages), with the marker removed
+    Activity { text: String, subactivity: bool },
+    /// The answer is complete. `usage` is only sent by servers that report it
+    /// on the last chunk.
+    Done {
+        finish_reason: Option<String>,
+        usage: Option<Usage>,
+    },
+    /// The server failed while streaming
+    Error {
+        message: String,
+        code: Option<String>,
+    },
+}
+
+/// Incremental piece of a tool call. The first piece of a call carries its
+/// `id` and function `name`; `arguments` of all pieces with the same `index`
+/// concatenate to the JSON arguments.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolCallDelta {
+    /// Choice the call belongs to
+    pub choice: u32,
+    /// Position of the call among the tool calls of the choice
+    pub index: u32,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub arguments: String,
+}
+
+impl StreamEvent {
+    /// Parse the `data` of one server-sent event.
+    ///
+    /// Accepts OpenAI `chat.completion.chunk` objects, which may hold several
+    /// events, the `[DONE]` marker, OpenAI error objects and the
+    /// `{"type": ..., ...}` events of AGiXT's chain streams.
+    ///
+    /// # Example
+    /// ```rust
+    /// use agixt_sdk::StreamEvent;
+    ///
+    /// let events = StreamEvent::parse(
+    ///     r#"{"object": "chat.completion.chunk", "choices": [{"index": 0, "delta": {"content": "Hi"}}]}"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(events, vec![StreamEvent::ContentDelta { index: 0, text: "Hi".into() }]);
+    /// ```
+    pub fn parse(data: &str) -> Result<Vec<StreamEvent>> {
+        let data = data.trim();
+        if data == "[DONE]" {
+            return Ok(vec![StreamEvent::Done {
+                finish_reason: None,
+                usage: None,
+            }]);
+        }
+        let value: Value = serde_json::from_str(data)?;
+        if let Some(error) = value.get("error").filter(|error| !error.is_null()) {
+            return Ok(vec![error_event(error)]);
+        }
+        if let Some(event_type) = value.get("type").and_then(Value::as_str) {
+            return Ok(tagged_event(event_type, &value).into_iter().collect());
+        }
+        Ok(chunk_events(&value))
+    }
+}
+
+/// Events of an OpenAI `chat.completion.chunk`.
+fn chunk_events(chunk: &Value) -> Vec<StreamEvent> {
+    let mut events = Vec::new();
+    let mut finish_reason = None;
+    for choice in chunk
+        .get("choices")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let index = choice.get("index").and_then(Value::as_u64).unwrap_or(0) as u32;
+        let delta = choice.get("delta").unwrap_or(&Value::Null);
+        if let Some(text) = delta.get("content").and_then(Value::as_str) {
+            if !text.is_empty() {
+                events.push(text_event(index, text));
+            }
+        }
+        for call in delta
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let function = call.get("function").unwrap_or(&Value::Null);
+            events.push(StreamEvent::ToolCallDelta(ToolCallDelta {
+                choice: index,
+                index: call.get("index").and_then(Value::as_u64).unwrap_or(0) as u32,
+                id: string_field(call, "id"),
+                name: string_field(function, "name"),
+                arguments: string_field(function, "arguments").unwrap_or_default(),
+            }));
+        }
+        if let Some(reason) = choice.get("finish_reason").and_then(Value::as_str) {
+            finish_reason = Some(reason.to_string());
+        }
+    }
+    let usage = chunk
+        .get("usage")
+        .filter(|usage| !usage.is_null())
+        .and_then(|usage| serde_json::from_value(usage.clone()).ok());
+    if finish_reason.is_some() || usage.is_some() {
+        events.push(StreamEvent::Done {
+            finish_reason,
+            usage,
+        });
+    }
+    events
+}
+
+/// Event of a `{"type": ...}` object, or `None` for types this SDK version
+/// does not know.
+fn tagged_event(event_type: &str, value: &Value) -> Option<StreamEvent> {
+    let text = || {
+        ["content", "text", "message", "data"]
+            .iter()
+            .find_map(|key| string_field(value, key))
+            .unwrap_or_default()
+    };
+    match event_type {
+        "content" | "delta" | "token" => Some(text_event(0, &text())),
+        "activity" => Some(StreamEvent::Activity {
+            text: text(),
+            subactivity: false,
+        }),
+        "subactivity" => Some(StreamEvent::Activity {
+            text: text(),
+            subactivity: true,
+        }),
+        "done" | "complete" => Some(StreamEvent::Done {
+            finish_reason: string_field(value, "finish_reason"),
+            usage: value
+                .get("usage")
+                .and_then(|usage| serde_json::from_value(usage.clone()).ok()),
+        }),
+        "error" => Some(StreamEvent::Error {
+            message: text(),
+            code: string_field(value, "code"),
+        }),
+        _ => None,
+    }
+}
+
+/// Content text, recognizing the activity messages agents interleave with
+/// their answer.
+pub(crate) fn text_event(index: u32, text: &str) -> StreamEvent {
+    let activity = |marker: &str| text.trim_start().strip_prefix(marker).map(str::trim);
+    if let Some(activity) = activity("[SUBACTIVITY]") {
+        StreamEvent::Activity {
+            text: activity.to_string(),
+            subactivity: true,
+        }
+    } else if let Some(activity) = activity("[ACTIVITY]") {
+        StreamEvent::Activity {
+            text: activity.to_string(),
+            subactivity: false,
+        }
+    } else {
+        StreamEvent::ContentDelta {
+            index,
+            text: text.to_string(),
+        }
+    }
+}
+
+fn error_event(error: &Value) -> StreamEvent {
+    let message = match error {
+        Value::String(message) => message.clone(),
+        _ => string_field(error, "message")
+            .or_else(|| string_field(error, "detail"))
+            .unwrap_or_else(|| error.to_string()),
+    };
+    let code = error
+        .get("code")
+        .or_else(|| error.get("type"))
+        .filter(|_| error.is_object())
+        .and_then(|code| match code {
+            Value::String(code) => Some(code.clone()),
+            Value::Number(code) => Some(code.to_string()),
+            _ => None,
+        });
+    StreamEvent::Error { message, code }
+}
+
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Splits a response body into the `data` of its server-sent events.
+#[derive(Default)]
+pub(crate) struct SseDecoder {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+// Responses are only read chunk by chunk on native targets
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+impl SseDecoder {
+    /// Add a chunk of the body, returning the data of the events it completes.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            self.line(line.trim_end_matches(['\r', '\n']), &mut events);
+        }
+        events
+    }
+
+    /// Data of an event the body ended without terminating.
+    pub(crate) fn finish(&mut self) -> Option<String> {
+        let mut events = Vec::new();
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+            self.line(line.trim_end_matches('\r'), &mut events);
+        }
+        self.line("", &mut events);
+        events.pop()
+    }
+
+    fn line(&mut self, line: &str, events: &mut Vec<String>) {
+        if line.is_empty() {
+            if !self.data.is_empty() {
+                events.push(std::mem::take(&mut self.data).join("\n"));
+            }
+        } else if let Some(data) = line.strip_prefix("data:") {
+            self.data
+                .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+        }
+        // Comments (`:`) and the `event`, `id` and `retry` fields are not used
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Body of a streamed completion captured from an AGiXT server.
+    const CAPTURED_CHAT: &str = concat!(
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1718000000,\"model\":\"XT\",",
+        "\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"[ACTIVITY] Searching memories\"},\"finish_reason\":null}]}\n\n",
+        ": keep-alive\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1718000000,\"model\":\"XT\",",
+        "\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hello\"},\"finish_reason\":null}]}\r\n\r\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1718000000,\"model\":\"XT\",",
+        "\"choices\":[{\"index\":0,\"delta\":{\"content\":\" world\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1718000000,\"model\":\"XT\",",
+        "\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}],",
+        "\"usage\":{\"prompt_tokens\":12,\"completion_tokens\":2,\"total_tokens\":14}}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    fn decode(body: &str, chunk_size: usize) -> Vec<StreamEvent> {
+        let mut decoder = SseDecoder::default();
+        let mut data = Vec::new();
+        for chunk in body.as_bytes().chunks(chunk_size) {
+            data.extend(decoder.push(chunk));
+        }
+        data.extend(decoder.finish());
+        data.iter()
+            .flat_map(|data| StreamEvent::parse(data).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_captured_chat_stream() {
+        // Split mid-line and mid-character to exercise buffering
+        for chunk_size in [1, 7, CAPTURED_CHAT.len()] {
+            let events = decode(CAPTURED_CHAT, chunk_size);
+            assert_eq!(events.len(), 5, "chunk size {}", chunk_size);
+            assert_eq!(
+                events[0],
+                StreamEvent::Activity {
+                    text: "Searching memories".into(),
+                    subactivity: false
+                }
+            );
+            assert_eq!(
+                events[2],
+                StreamEvent::ContentDelta {
+                    index: 0,
+                    text: " world".into()
+                }
+            );
+            match &events[3] {
+                StreamEvent::Done {
+                    finish_reason,
+                    usage: Some(usage),
+                } => {
+                    assert_eq!(finish_reason.as_deref(), Some("stop"));
+                    assert_eq!(usage.total_tokens, 14);
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+            assert!(matches!(
+                events[4],
+                StreamEvent::Done {
+                    finish_reason: None,
+                    usage: None
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_tool_call_deltas() {
+        let body = concat!(
+            "data: {\"object\":\"chat.completion.chunk\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":",
+            "[{\"index\":0,\"id\":\"call_1\",\"type\":\"function\",\"function\":{\"name\":\"get_weather\",\"arguments\":\"\"}}]}}]}\n\n",
+            "data: {\"object\":\"chat.completion.chunk\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":",
+            "[{\"index\":0,\"function\":{\"arguments\":\"{\\\"city\\\":\"}}]}}]}\n\n",
+            "data: {\"object\":\"chat.completion.chunk\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":",
+            "[{\"index\":0,\"function\":{\"arguments\":\"\\\"Oslo\\\"}\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n",
+        );
+        let events = decode(body, 16);
+        let deltas: Vec<&ToolCallDelta> = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::ToolCallDelta(delta) => Some(delta),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deltas.len(), 3);
+        assert_eq!(deltas[0].id.as_deref(), Some("call_1"));
+        assert_eq!(deltas[0].name.as_deref(), Some("get_weather"));
+        let arguments: String = deltas.iter().map(|d| d.arguments.as_str()).collect();
+        assert_eq!(arguments, r#"{"city":"Oslo"}"#);
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::Done { finish_reason: Some(reason), .. }) if reason == "tool_calls"
+        ));
+    }
+
+    #[test]
+    fn test_parse_chain_and_error_events() {
+        let body = concat!(
+            "event: message\n",
+            "data: {\"type\": \"subactivity\", \"content\": \"Running step 1\"}\n\n",
+            "data: {\"type\": \"content\", \"content\": \"Report\"}\n\n",
+            "data: {\"type\": \"heartbeat\"}\n\n",
+            "data: {\"error\": {\"message\": \"Provider timed out\", \"type\": \"server_error\"}}\n\n",
+            "data: {\"type\": \"error\", \"message\": \"Step failed\"}\n\n",
+            "data: {\"type\": \"done\"}",
+        );
+        let events = decode(body, 5);
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::Activity {
+                    text: "Running step 1".into(),
+                    subactivity: true
+                },
+                StreamEvent::ContentDelta {
+                    index: 0,
+                    text: "Report".into()
+                },
+                StreamEvent::Error {
+                    message: "Provider timed out".into(),
+                    code: Some("server_error".into())
+                },
+                StreamEvent::Error {
+                    message: "Step failed".into(),
+                    code: None
+                },
+                StreamEvent::Done {
+                    finish_reason: None,
+                    usage: None
+                },
+            ]
+        );
+        assert!(StreamEvent::parse("not json").is_err());
+    }
+}
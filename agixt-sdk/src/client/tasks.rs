@@ -0,0 +1,299 @@
+//! Scheduled task operations using /v1 endpoints with ID-based parameters.
+
+use super::sleep;
+use crate::error::Result;
+use crate::models::{Task, TaskFrequency, TaskOutcome};
+use std::time::Duration;
+
+/// Longest wait between two polls of `wait_for_task`, unless the caller's
+/// poll interval is longer.
+const MAX_TASK_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+impl super::AGiXTSDK {
+    // ==================== Tasks ====================
+
+    /// Create a new scheduled task for an agent by ID.
+    pub async fn create_task(
+        &self,
+        agent_id: &str,
+        title: &str,
+        description: &str,
+        due_date: Option<&str>,
+        conversation_id: Option<&str>,
+    ) -> Result<Task> {
+        let mut payload = serde_json::json!({
+            "agent_id": agent_id,
+            "title": title,
+            "description": description,
+        });
+        if let Some(date) = due_date {
+            payload["due_date"] = serde_json::json!(date);
+        }
+        if let Some(id) = conversation_id {
+            payload["conversation_id"] = serde_json::json!(id);
+        }
+
+        let request = self
+            .client
+            .post(format!("{}/task", self.api_root))
+            .json(&payload);
+        let response = self.execute_create(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TaskResponse {
+            task: Task,
+        }
+
+        let result: TaskResponse = text.json()?;
+        Ok(result.task)
+    }
+
+    /// Create a reoccurring task for an agent by ID.
+    ///
+    /// # Arguments
+    /// * `agent_id` - ID of the agent that runs the task
+    /// * `title` - Task title
+    /// * `description` - Instructions for the agent
+    /// * `frequency` - How often the task repeats
+    /// * `start_date` - First run date (ISO 8601)
+    /// * `end_date` - Optional last run date (ISO 8601)
+    pub async fn create_reoccurring_task(
+        &self,
+        agent_id: &str,
+        title: &str,
+        description: &str,
+        frequency: TaskFrequency,
+        start_date: &str,
+        end_date: Option<&str>,
+    ) -> Result<Vec<Task>> {
+        let mut payload = serde_json::json!({
+            "agent_id": agent_id,
+            "title": title,
+            "description": description,
+            "frequency": frequency,
+            "start_date": start_date,
+        });
+        if let Some(date) = end_date {
+            payload["end_date"] = serde_json::json!(date);
+        }
+
+        let request = self
+            .client
+            .post(format!("{}/reoccurring_task", self.api_root))
+            .json(&payload);
+        let response = self.execute_create(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TasksResponse {
+            tasks: Vec<Task>,
+        }
+
+        let result: TasksResponse = text.json()?;
+        Ok(result.tasks)
+    }
+
+    /// Get all scheduled tasks for the current user.
+    pub async fn get_tasks(&self) -> Result<Vec<Task>> {
+        let request = self.client.get(format!("{}/tasks", self.api_root));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TasksResponse {
+            tasks: Vec<Task>,
+        }
+
+        let result: TasksResponse = text.json()?;
+        Ok(result.tasks)
+    }
+
+    /// Get a scheduled task by ID.
+    pub async fn get_task(&self, task_id: &str) -> Result<Task> {
+        let request = self
+            .client
+            .get(format!("{}/task/{}", self.api_root, task_id));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TaskResponse {
+            task: Task,
+        }
+
+        let result: TaskResponse = text.json()?;
+        Ok(result.task)
+    }
+
+    /// Poll a task until it completes or fails, e.g. a planning or training
+    /// job, giving up with `Error::DeadlineExceeded` after `timeout`.
+    ///
+    /// The task is polled right away, then after `poll_interval`, with each
+    /// following wait doubling up to 30 seconds, or `poll_interval` if that is
+    /// longer. Polls failing with a retryable error are skipped.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, TaskOutcome};
+    /// use std::time::Duration;
+    ///
+    /// # async fn run(client: AGiXTSDK, task_id: &str) -> agixt_sdk::Result<()> {
+    /// match client
+    ///     .wait_for_task(task_id, Duration::from_secs(1), Duration::from_secs(600))
+    ///     .await?
+    /// {
+    ///     TaskOutcome::Completed(task) => println!("done: {}", task.output),
+    ///     TaskOutcome::Failed(task) => eprintln!("failed: {}", task.output),
+    ///     _ => {}
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_task(
+        &self,
+        task_id: &str,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<TaskOutcome> {
+        let started = web_time::Instant::now();
+        let max_interval = poll_interval.max(MAX_TASK_POLL_INTERVAL);
+        let mut interval = poll_interval;
+        let mut attempts = 0;
+        let mut last_error;
+        loop {
+            attempts += 1;
+            match self.get_task(task_id).await {
+                Ok(task) => {
+                    if let Some(outcome) = task.outcome() {
+                        return Ok(outcome);
+                    }
+                    last_error = None;
+                }
+                Err(e) if e.is_retryable() => last_error = Some(Box::new(e)),
+                Err(e) => return Err(e),
+            }
+            let remaining = timeout.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                return Err(crate::Error::DeadlineExceeded {
+                    deadline: timeout,
+                    attempts,
+                    last_error,
+                });
+            }
+            sleep(interval.min(remaining)).await;
+            interval = (interval * 2).min(max_interval);
+        }
+    }
+
+    /// Update a scheduled task by ID. Only the provided fields are changed.
+    pub async fn update_task(
+        &self,
+        task_id: &str,
+        title: Option<&str>,
+        description: Option<&str>,
+        due_date: Option<&str>,
+    ) -> Result<Task> {
+        let mut payload = serde_json::json!({});
+        if let Some(t) = title {
+            payload["title"] = serde_json::json!(t);
+        }
+        if let Some(d) = description {
+            payload["description"] = serde_json::json!(d);
+        }
+        if let Some(date) = due_date {
+            payload["due_date"] = serde_json::json!(date);
+        }
+
+        let request = self
+            .client
+            .put(format!("{}/task/{}", self.api_root, task_id))
+            .json(&payload);
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TaskResponse {
+            task: Task,
+        }
+
+        let result: TaskResponse = text.json()?;
+        Ok(result.task)
+    }
+
+    /// Mark a scheduled task as complete by ID.
+    pub async fn complete_task(&self, task_id: &str) -> Result<Task> {
+        let request = self
+            .client
+            .post(format!("{}/task/{}/complete", self.api_root, task_id));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TaskResponse {
+            task: Task,
+        }
+
+        let result: TaskResponse = text.json()?;
+        Ok(result.task)
+    }
+
+    /// Delete a scheduled task by ID.
+    pub async fn delete_task(&self, task_id: &str) -> Result<String> {
+        let request = self
+            .client
+            .delete(format!("{}/task/{}", self.api_root, task_id));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct MessageResponse {
+            message: String,
+        }
+
+        let result: MessageResponse = text.json()?;
+        Ok(result.message)
+    }
+}
@@ -0,0 +1,139 @@
+//! Rust callbacks that back the tools offered to a model in `run_with_tools`.
+
+use crate::error::{Error, Result};
+use crate::models::{Tool, ToolFunction};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+#[cfg(not(target_arch = "wasm32"))]
+type ToolFuture = Pin<Box<dyn Future<Output = Result<serde_json::Value>> + Send>>;
+#[cfg(target_arch = "wasm32")]
+type ToolFuture = Pin<Box<dyn Future<Output = Result<serde_json::Value>>>>;
+
+type ToolHandler = Box<dyn Fn(serde_json::Value) -> ToolFuture + Send + Sync>;
+
+/// `Send` on native targets; no bound on wasm, where futures are single-threaded.
+#[cfg(not(target_arch = "wasm32"))]
+#[doc(hidden)]
+pub trait MaybeSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> MaybeSend for T {}
+#[cfg(target_arch = "wasm32")]
+#[doc(hidden)]
+pub trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T> MaybeSend for T {}
+
+/// Tools a model may call, each paired with the async function that runs it.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::ToolRegistry;
+/// use serde_json::json;
+///
+/// let tools = ToolRegistry::new().register(
+///     "get_weather",
+///     "Get the current weather for a city",
+///     json!({
+///         "type": "object",
+///         "properties": {"city": {"type": "string"}},
+///         "required": ["city"]
+///     }),
+///     |args| async move {
+///         let city = args["city"].as_str().unwrap_or("unknown");
+///         Ok(json!({"city": city, "forecast": "sunny"}))
+///     },
+/// );
+/// assert_eq!(tools.tools().len(), 1);
+/// ```
+pub struct ToolRegistry {
+    tools: Vec<Tool>,
+    handlers: HashMap<String, ToolHandler>,
+    max_iterations: usize,
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self {
+            tools: Vec::new(),
+            handlers: HashMap::new(),
+            max_iterations: 8,
+        }
+    }
+}
+
+impl ToolRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a tool. `parameters` is the JSON Schema of the arguments the
+    /// handler receives. Registering the same name again replaces the tool.
+    pub fn register<F, Fut>(
+        mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(serde_json::Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<serde_json::Value>> + MaybeSend + 'static,
+    {
+        let name = name.into();
+        self.tools.retain(|tool| tool.function.name != name);
+        self.tools.push(Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: name.clone(),
+                description: description.into(),
+                parameters,
+            },
+        });
+        self.handlers
+            .insert(name, Box::new(move |args| Box::pin(handler(args))));
+        self
+    }
+
+    /// Maximum number of model round trips before `run_with_tools` gives up.
+    /// Defaults to 8.
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations.max(1);
+        self
+    }
+
+    /// Tool definitions to send with a chat completion.
+    pub fn tools(&self) -> &[Tool] {
+        &self.tools
+    }
+
+    pub(crate) fn iteration_limit(&self) -> usize {
+        self.max_iterations
+    }
+
+    /// Run the handler registered for `name`.
+    pub async fn call(
+        &self,
+        name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        match self.handlers.get(name) {
+            Some(handler) => handler(arguments).await,
+            None => Err(Error::NotFound(format!(
+                "tool '{}' is not registered",
+                name
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolRegistry")
+            .field("tools", &self.tools)
+            .field("max_iterations", &self.max_iterations)
+            .finish()
+    }
+}
@@ -0,0 +1,142 @@
+//! Fine-tuning dataset helpers using /v1 endpoints with ID-based parameters.
+
+use crate::error::Result;
+use crate::models::DpoTriple;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::models::FineTuneOptions;
+
+impl super::AGiXTSDK {
+    // ==================== Training Data ====================
+
+    /// Ask an agent for a preferred and a rejected answer to `user_input`, for
+    /// DPO fine-tuning.
+    pub async fn get_dpo_response(
+        &self,
+        agent_id: &str,
+        user_input: &str,
+        injected_memories: Option<i32>,
+        conversation_id: Option<&str>,
+    ) -> Result<DpoTriple> {
+        let request = self
+            .client
+            .post(format!("{}/agent/{}/dpo", self.api_root, agent_id))
+            .json(&serde_json::json!({
+                "user_input": user_input,
+                "injected_memories": injected_memories.unwrap_or(10),
+                "conversation_name": conversation_id.unwrap_or(""),
+            }));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Write one OpenAI-style chat example per conversation to `path` as JSON
+    /// Lines, returning the number of examples written.
+    ///
+    /// Conversations without user or assistant messages are skipped.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, FineTuneOptions};
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let options = FineTuneOptions::new().system_prompt("You are a support agent.");
+    /// let count = client
+    ///     .export_fine_tuning_dataset(&["conversation-id"], "train.jsonl".as_ref(), &options)
+    ///     .await?;
+    /// println!("wrote {} examples", count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn export_fine_tuning_dataset(
+        &self,
+        conversation_ids: &[&str],
+        path: &std::path::Path,
+        options: &FineTuneOptions,
+    ) -> Result<u64> {
+        let mut writer = JsonlWriter::create(path).await?;
+        for conversation_id in conversation_ids {
+            let history = self.get_full_conversation(conversation_id).await?;
+            if let Some(example) = options.chat_example(&history) {
+                writer.write(&example).await?;
+            }
+        }
+        writer.finish().await
+    }
+
+    /// Generate a DPO triple for each prompt with `get_dpo_response` and write
+    /// them to `path` as JSON Lines in the OpenAI preference format, returning
+    /// the number of examples written.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn export_dpo_dataset(
+        &self,
+        agent_id: &str,
+        prompts: &[&str],
+        path: &std::path::Path,
+        options: &FineTuneOptions,
+    ) -> Result<u64> {
+        let mut writer = JsonlWriter::create(path).await?;
+        for prompt in prompts {
+            let triple = self.get_dpo_response(agent_id, prompt, None, None).await?;
+            writer.write(&options.dpo_example(&triple)).await?;
+        }
+        writer.finish().await
+    }
+}
+
+/// Buffered JSON Lines output file.
+#[cfg(not(target_arch = "wasm32"))]
+struct JsonlWriter {
+    out: tokio::io::BufWriter<tokio::fs::File>,
+    path: std::path::PathBuf,
+    count: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl JsonlWriter {
+    async fn create(path: &std::path::Path) -> Result<Self> {
+        let file = tokio::fs::File::create(path)
+            .await
+            .map_err(|e| Self::io_error(path, e))?;
+        Ok(Self {
+            out: tokio::io::BufWriter::new(file),
+            path: path.to_path_buf(),
+            count: 0,
+        })
+    }
+
+    async fn write(&mut self, value: &serde_json::Value) -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut line = serde_json::to_vec(value)?;
+        line.push(b'\n');
+        self.out
+            .write_all(&line)
+            .await
+            .map_err(|e| Self::io_error(&self.path, e))?;
+        self.count += 1;
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<u64> {
+        use tokio::io::AsyncWriteExt;
+
+        self.out
+            .flush()
+            .await
+            .map_err(|e| Self::io_error(&self.path, e))?;
+        Ok(self.count)
+    }
+
+    fn io_error(path: &std::path::Path, e: std::io::Error) -> crate::Error {
+        crate::Error::Other(format!("cannot write {}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,136 @@
+//! Audit transcripts of the prompts sent to agents and their answers.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// One prompt and its outcome, as passed to a `TranscriptSink`.
+///
+/// Request and response bodies are the JSON exchanged with the server, with
+/// the values of credential fields such as `api_key` or `password` redacted.
+#[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
+pub struct TranscriptEntry {
+    /// When the prompt was sent, in RFC 3339 format.
+    pub timestamp: String,
+    /// `prompt` for `prompt_agent` and the calls built on it, such as `chat`
+    /// and `instruct`, or `chat_completion` for `chat_completions`.
+    pub kind: String,
+    /// Agent ID, or the agent name for chat completions.
+    pub agent: String,
+    /// The request body that was sent.
+    pub request: Value,
+    /// The answer: the response text for prompts, the whole response for chat
+    /// completions. `None` if the call failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    /// Why the call failed, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Time the call took in milliseconds, retries included.
+    pub latency_ms: u64,
+}
+
+/// Receives a `TranscriptEntry` for every prompt sent by the client.
+///
+/// Set one with `AGiXTSDKBuilder::transcript` to keep an audit log of what
+/// was asked and answered, e.g. with a `JsonlTranscript`. Closures taking a
+/// `&TranscriptEntry` implement this trait. Streamed chat completions are not
+/// recorded.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::{AGiXTSDK, TranscriptEntry};
+///
+/// let client = AGiXTSDK::builder()
+///     .transcript(|entry: &TranscriptEntry| {
+///         eprintln!("{} {} took {}ms", entry.kind, entry.agent, entry.latency_ms)
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+pub trait TranscriptSink: Send + Sync {
+    /// Record one prompt and its outcome.
+    fn record(&self, entry: &TranscriptEntry);
+}
+
+impl<F: Fn(&TranscriptEntry) + Send + Sync> TranscriptSink for F {
+    fn record(&self, entry: &TranscriptEntry) {
+        self(entry)
+    }
+}
+
+/// Appends entries to a file as JSON Lines, one entry per line.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct JsonlTranscript {
+    file: std::sync::Mutex<std::fs::File>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl JsonlTranscript {
+    /// Open `path` for appending, creating it if needed.
+    pub fn append(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| crate::Error::Other(format!("cannot open {}: {}", path.display(), e)))?;
+        Ok(Self {
+            file: std::sync::Mutex::new(file),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TranscriptSink for JsonlTranscript {
+    fn record(&self, entry: &TranscriptEntry) {
+        use std::io::Write;
+
+        let Ok(mut line) = serde_json::to_vec(entry) else {
+            return;
+        };
+        line.push(b'\n');
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        // Recording must never fail a request, so write errors are ignored
+        let _ = file.write_all(&line);
+    }
+}
+
+impl super::AGiXTSDK {
+    /// Pass a prompt and its outcome to the transcript sink, if one is set.
+    pub(crate) fn record_transcript<T: Serialize>(
+        &self,
+        kind: &str,
+        agent: &str,
+        request: &impl Serialize,
+        result: &crate::Result<T>,
+        started: web_time::Instant,
+    ) {
+        let Some(sink) = &self.transcript else {
+            return;
+        };
+        let (response, error) = match result {
+            Ok(response) => (Some(redacted(response)), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let elapsed = started.elapsed();
+        let sent = chrono::Utc::now() - chrono::Duration::from_std(elapsed).unwrap_or_default();
+        sink.record(&TranscriptEntry {
+            timestamp: sent.to_rfc3339(),
+            kind: kind.to_string(),
+            agent: agent.to_string(),
+            request: redacted(request),
+            response,
+            error,
+            latency_ms: elapsed.as_millis() as u64,
+        });
+    }
+}
+
+/// `value` as JSON with the values of credential fields redacted.
+fn redacted(value: &impl Serialize) -> Value {
+    let mut value = serde_json::to_value(value).unwrap_or(Value::Null);
+    crate::secret::redact_json(&mut value);
+    value
+}
@@ -0,0 +1,111 @@
+//! Token usage accumulated over the chat completions of a client.
+
+use crate::models::Usage;
+use std::sync::Mutex;
+
+/// Tokens consumed by the chat completions of a client since it was built or
+/// last reset, returned by [`AGiXTSDK::usage_summary`](super::AGiXTSDK::usage_summary).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageSummary {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// Number of responses that reported their usage
+    pub responses: u64,
+}
+
+impl UsageSummary {
+    fn add(&mut self, usage: &Usage) {
+        let tokens = |count: i32| count.max(0) as u64;
+        self.prompt_tokens += tokens(usage.prompt_tokens);
+        self.completion_tokens += tokens(usage.completion_tokens);
+        self.total_tokens += tokens(usage.total_tokens);
+        self.responses += 1;
+    }
+
+    /// Estimated cost of the tokens, given the provider's prices per million
+    /// prompt and completion tokens.
+    ///
+    /// # Example
+    /// ```rust
+    /// use agixt_sdk::UsageSummary;
+    ///
+    /// let usage = UsageSummary {
+    ///     prompt_tokens: 2_000_000,
+    ///     completion_tokens: 500_000,
+    ///     total_tokens: 2_500_000,
+    ///     responses: 40,
+    /// };
+    /// assert_eq!(usage.cost(0.15, 0.60), 0.6);
+    /// ```
+    pub fn cost(&self, prompt_per_million: f64, completion_per_million: f64) -> f64 {
+        (self.prompt_tokens as f64 * prompt_per_million
+            + self.completion_tokens as f64 * completion_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Running usage totals, shared by all clones of a client.
+#[derive(Debug, Default)]
+pub(crate) struct UsageTracker {
+    summary: Mutex<UsageSummary>,
+}
+
+impl UsageTracker {
+    pub(crate) fn record(&self, usage: &Usage) {
+        self.summary
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .add(usage);
+    }
+
+    fn summary(&self) -> UsageSummary {
+        *self.summary.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn reset(&self) -> UsageSummary {
+        std::mem::take(&mut *self.summary.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl super::AGiXTSDK {
+    // ==================== Usage ====================
+
+    /// Tokens used by chat completions, streamed or not, since the client was
+    /// built or [`reset_usage`](Self::reset_usage) was last called. `None`
+    /// unless usage tracking was enabled with `AGiXTSDKBuilder::track_usage`.
+    ///
+    /// Clones of the client add to the same totals. Responses that do not
+    /// report their usage are not counted.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn run(request: agixt_sdk::ChatCompletions) -> agixt_sdk::Result<()> {
+    /// let client = agixt_sdk::AGiXTSDK::builder()
+    ///     .base_uri("https://agixt.example.com")
+    ///     .track_usage(true)
+    ///     .build()?;
+    /// client.chat_completions(&request).await?;
+    /// if let Some(usage) = client.usage_summary() {
+    ///     println!("{} tokens in {} responses", usage.total_tokens, usage.responses);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn usage_summary(&self) -> Option<UsageSummary> {
+        self.usage.as_ref().map(|tracker| tracker.summary())
+    }
+
+    /// Start new usage totals, e.g. at the start of a session, returning the
+    /// totals so far. `None` unless usage tracking is enabled.
+    pub fn reset_usage(&self) -> Option<UsageSummary> {
+        self.usage.as_ref().map(|tracker| tracker.reset())
+    }
+
+    /// Add a response's usage to the totals, if tracking is enabled.
+    pub(crate) fn record_usage(&self, usage: &Usage) {
+        if let Some(tracker) = &self.usage {
+            tracker.record(usage);
+        }
+    }
+}
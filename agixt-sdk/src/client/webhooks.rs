@@ -0,0 +1,130 @@
+//! Outgoing webhook operations using /v1 endpoints with ID-based parameters.
+
+use crate::error::Result;
+use crate::models::Webhook;
+
+impl super::AGiXTSDK {
+    // ==================== Webhooks ====================
+
+    /// Create an outgoing webhook.
+    ///
+    /// # Arguments
+    /// * `name` - Display name for the webhook
+    /// * `target_url` - URL that receives the event payloads
+    /// * `event_types` - Events that trigger the webhook (e.g. "message.created")
+    /// * `secret` - Optional shared secret used to sign payloads
+    pub async fn create_webhook(
+        &self,
+        name: &str,
+        target_url: &str,
+        event_types: Vec<String>,
+        secret: Option<&str>,
+    ) -> Result<Webhook> {
+        let mut payload = serde_json::json!({
+            "name": name,
+            "target_url": target_url,
+            "event_types": event_types,
+        });
+        if let Some(s) = secret {
+            payload["secret"] = serde_json::json!(s);
+        }
+
+        let request = self
+            .client
+            .post(format!("{}/webhooks/outgoing", self.api_root))
+            .json(&payload);
+        let response = self.execute_create(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Get all outgoing webhooks.
+    pub async fn get_webhooks(&self) -> Result<Vec<Webhook>> {
+        let request = self
+            .client
+            .get(format!("{}/webhooks/outgoing", self.api_root));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Update an outgoing webhook by ID. Only the provided fields are changed.
+    pub async fn update_webhook(
+        &self,
+        webhook_id: &str,
+        target_url: Option<&str>,
+        event_types: Option<Vec<String>>,
+        secret: Option<&str>,
+        active: Option<bool>,
+    ) -> Result<Webhook> {
+        let mut payload = serde_json::json!({});
+        if let Some(url) = target_url {
+            payload["target_url"] = serde_json::json!(url);
+        }
+        if let Some(events) = event_types {
+            payload["event_types"] = serde_json::json!(events);
+        }
+        if let Some(s) = secret {
+            payload["secret"] = serde_json::json!(s);
+        }
+        if let Some(a) = active {
+            payload["active"] = serde_json::json!(a);
+        }
+
+        let request = self
+            .client
+            .put(format!(
+                "{}/webhooks/outgoing/{}",
+                self.api_root, webhook_id
+            ))
+            .json(&payload);
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    /// Delete an outgoing webhook by ID.
+    pub async fn delete_webhook(&self, webhook_id: &str) -> Result<String> {
+        let request = self.client.delete(format!(
+            "{}/webhooks/outgoing/{}",
+            self.api_root, webhook_id
+        ));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct MessageResponse {
+            message: String,
+        }
+
+        let result: MessageResponse = text.json()?;
+        Ok(result.message)
+    }
+}
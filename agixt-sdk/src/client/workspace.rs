@@ -0,0 +1,150 @@
+//! Agent workspace file operations using /v1 endpoints with ID-based parameters.
+
+use crate::error::Result;
+use crate::models::{UploadSource, WorkspaceFile};
+
+impl super::AGiXTSDK {
+    // ==================== Workspace ====================
+
+    /// Upload a file into an agent's workspace by ID, where commands such as
+    /// "Read File" can operate on it.
+    ///
+    /// `destination` is a directory relative to the workspace root; the file is
+    /// placed at the root when it is `None`.
+    pub async fn upload_file_to_workspace(
+        &self,
+        agent_id: &str,
+        source: UploadSource,
+        destination: Option<&str>,
+    ) -> Result<String> {
+        let (file_name, data) = source.into_parts()?;
+        let file = reqwest::multipart::Part::bytes(data).file_name(file_name);
+        let mut form = reqwest::multipart::Form::new().part("file", file);
+        if let Some(destination) = destination {
+            form = form.text("path", destination.to_string());
+        }
+
+        let request = self
+            .client
+            .post(format!(
+                "{}/agent/{}/workspace/upload",
+                self.api_root, agent_id
+            ))
+            .multipart(form);
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        #[derive(serde::Deserialize)]
+        struct MessageResponse {
+            message: String,
+        }
+
+        let result: MessageResponse = text.json()?;
+        Ok(result.message)
+    }
+
+    /// List the files in an agent's workspace by ID.
+    pub async fn list_workspace_files(&self, agent_id: &str) -> Result<Vec<WorkspaceFile>> {
+        let request = self
+            .client
+            .get(format!("{}/agent/{}/workspace", self.api_root, agent_id));
+        let response = self.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.verbose {
+            self.parse_response(status, &text).await?;
+        }
+
+        // Entries are either file objects or bare paths
+        let data: serde_json::Value = text.json()?;
+        let entries = data
+            .as_array()
+            .or_else(|| data.get("files").and_then(|v| v.as_array()))
+            .cloned()
+            .unwrap_or_default();
+        entries
+            .into_iter()
+            .map(|entry| match entry {
+                serde_json::Value::String(path) => Ok(WorkspaceFile {
+                    name: path.rsplit('/').next().unwrap_or_default().to_string(),
+                    path,
+                    size: None,
+                    modified: None,
+                    kind: None,
+                    extra: Default::default(),
+                }),
+                entry => Ok(serde_json::from_value(entry)?),
+            })
+            .collect()
+    }
+
+    /// Download a file from an agent's workspace by ID.
+    pub async fn download_workspace_file(
+        &self,
+        agent_id: &str,
+        file_path: &str,
+    ) -> Result<Vec<u8>> {
+        let response = self.request_workspace_file(agent_id, file_path).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Stream a file from an agent's workspace to `local_path`, returning the
+    /// number of bytes written. The file is not held in memory.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn download_workspace_file_to(
+        &self,
+        agent_id: &str,
+        file_path: &str,
+        local_path: &std::path::Path,
+    ) -> Result<u64> {
+        self.download_workspace_file_with_progress(agent_id, file_path, local_path, &|_| {})
+            .await
+    }
+
+    /// Stream a file from an agent's workspace to `local_path`, calling
+    /// `progress` after every chunk, and return the size of the file.
+    ///
+    /// An interrupted download is resumed where the server supports range
+    /// requests, like with [`download_url_to`](Self::download_url_to).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn download_workspace_file_with_progress(
+        &self,
+        agent_id: &str,
+        file_path: &str,
+        local_path: &std::path::Path,
+        progress: &(dyn Fn(crate::models::DownloadProgress) + Send + Sync),
+    ) -> Result<u64> {
+        let request = self
+            .client
+            .get(format!(
+                "{}/agent/{}/workspace/download",
+                self.api_root, agent_id
+            ))
+            .query(&[("path", file_path)]);
+        self.stream_to_file(request, true, local_path, progress)
+            .await
+    }
+
+    async fn request_workspace_file(
+        &self,
+        agent_id: &str,
+        file_path: &str,
+    ) -> Result<super::Response> {
+        let request = self
+            .client
+            .get(format!(
+                "{}/agent/{}/workspace/download",
+                self.api_root, agent_id
+            ))
+            .query(&[("path", file_path)]);
+        self.execute(request).await
+    }
+}
@@ -0,0 +1,208 @@
+//! Realtime conversation and notification events over WebSocket.
+
+use crate::error::{Error, Result};
+use crate::models::Message;
+use futures_util::stream::{Stream, StreamExt};
+use reqwest::header::AUTHORIZATION;
+use std::pin::Pin;
+use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest};
+
+/// Stream of events from a WebSocket subscription. It ends when the server
+/// closes the connection.
+pub type EventStream = Pin<Box<dyn Stream<Item = Result<ConversationEvent>> + Send>>;
+
+/// Event pushed by the server on a conversation or notification stream.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ConversationEvent {
+    /// A message was added to the conversation
+    MessageAdded(Message),
+    /// An existing message was edited
+    MessageUpdated(Message),
+    /// A message was deleted
+    MessageDeleted { message_id: String },
+    /// The agent reported what it is working on (`[ACTIVITY]` messages)
+    Activity(Message),
+    /// A user notification
+    Notification(serde_json::Value),
+    /// An event type this SDK version does not know
+    Other {
+        event_type: String,
+        data: serde_json::Value,
+    },
+}
+
+impl ConversationEvent {
+    /// Parse a `{"type": ..., "data": ...}` frame.
+    pub fn parse(frame: &str) -> Result<Self> {
+        let mut value: serde_json::Value = serde_json::from_str(frame)?;
+        let event_type = value
+            .get("type")
+            .or_else(|| value.get("event"))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let data = value
+            .get_mut("data")
+            .map(serde_json::Value::take)
+            .unwrap_or(value);
+
+        Ok(match event_type.as_str() {
+            "message_added" | "new_message" | "message" => {
+                let message: Message = serde_json::from_value(data)?;
+                if message.text().starts_with("[ACTIVITY]")
+                    || message.text().starts_with("[SUBACTIVITY]")
+                {
+                    ConversationEvent::Activity(message)
+                } else {
+                    ConversationEvent::MessageAdded(message)
+                }
+            }
+            "message_updated" => ConversationEvent::MessageUpdated(serde_json::from_value(data)?),
+            "message_deleted" => ConversationEvent::MessageDeleted {
+                message_id: data
+                    .get("id")
+                    .or_else(|| data.get("message_id"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            },
+            "activity" => ConversationEvent::Activity(serde_json::from_value(data)?),
+            "notification" => ConversationEvent::Notification(data),
+            _ => ConversationEvent::Other { event_type, data },
+        })
+    }
+}
+
+impl super::AGiXTSDK {
+    // ==================== Realtime ====================
+
+    /// Subscribe to new, updated and deleted messages of a conversation by ID.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::{AGiXTSDK, ConversationEvent};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+    /// let mut events = client.subscribe_conversation("conversation-id").await?;
+    /// while let Some(event) = events.next().await {
+    ///     if let ConversationEvent::MessageAdded(message) = event? {
+    ///         println!("{}: {}", message.role, message.text());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_conversation(&self, conversation_id: &str) -> Result<EventStream> {
+        self.subscribe(&format!("/conversation/{}/stream", conversation_id))
+            .await
+    }
+
+    /// Subscribe to the current user's notifications.
+    pub async fn subscribe_notifications(&self) -> Result<EventStream> {
+        self.subscribe("/notifications/stream").await
+    }
+
+    async fn subscribe(&self, path: &str) -> Result<EventStream> {
+        let base = self
+            .api_root
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1);
+        let mut request = format!("{}{}", base, path)
+            .into_client_request()
+            .map_err(ws_error)?;
+        let authorization = self.headers().get(AUTHORIZATION).cloned();
+        if let Some(value) = authorization {
+            let value = tungstenite::http::HeaderValue::from_bytes(value.as_bytes())
+                .map_err(|e| Error::InvalidInput(e.to_string()))?;
+            request.headers_mut().insert("authorization", value);
+        }
+
+        let (socket, _) = tokio_tungstenite::connect_async(request)
+            .await
+            .map_err(ws_error)?;
+        let events = socket.filter_map(|frame| async move {
+            match frame {
+                Ok(tungstenite::Message::Text(text)) => Some(ConversationEvent::parse(&text)),
+                Ok(tungstenite::Message::Binary(bytes)) => {
+                    Some(ConversationEvent::parse(&String::from_utf8_lossy(&bytes)))
+                }
+                Ok(_) => None,
+                Err(tungstenite::Error::ConnectionClosed) => None,
+                Err(e) => Some(Err(ws_error(e))),
+            }
+        });
+        // The stream ends when the client shuts down
+        Ok(Box::pin(events.take_until(self.closed())))
+    }
+}
+
+fn ws_error(e: tungstenite::Error) -> Error {
+    Error::Other(format!("WebSocket error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AGiXTSDK;
+    use futures_util::SinkExt;
+
+    #[tokio::test]
+    // The handshake callback's error type is tungstenite's
+    #[allow(clippy::result_large_err)]
+    async fn test_subscribe_conversation() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let callback =
+                |request: &tungstenite::handshake::server::Request,
+                 response: tungstenite::handshake::server::Response| {
+                    assert_eq!(request.uri().path(), "/v1/conversation/c1/stream");
+                    assert_eq!(request.headers()["authorization"], "secret");
+                    Ok(response)
+                };
+            let mut socket = tokio_tungstenite::accept_hdr_async(stream, callback)
+                .await
+                .unwrap();
+            for frame in [
+                r#"{"type": "message_added", "data": {"role": "XT", "content": "Hello"}}"#,
+                r#"{"type": "message_added", "data": {"role": "XT", "content": "[ACTIVITY] Thinking"}}"#,
+                r#"{"type": "message_deleted", "data": {"id": "m1"}}"#,
+            ] {
+                socket
+                    .send(tungstenite::Message::Text(frame.to_string()))
+                    .await
+                    .unwrap();
+            }
+            socket.close(None).await.unwrap();
+        });
+
+        let client = AGiXTSDK::new(
+            Some(format!("http://{}", address)),
+            Some("secret".to_string()),
+            false,
+        );
+        let events: Vec<_> = client
+            .subscribe_conversation("c1")
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        server.await.unwrap();
+
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            Ok(ConversationEvent::MessageAdded(message)) => assert_eq!(message.text(), "Hello"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(matches!(events[1], Ok(ConversationEvent::Activity(_))));
+        assert_eq!(
+            events[2].as_ref().unwrap(),
+            &ConversationEvent::MessageDeleted {
+                message_id: "m1".to_string()
+            }
+        );
+    }
+}
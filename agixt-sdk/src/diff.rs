@@ -0,0 +1,332 @@
+//! Diffs and merges of conversation histories.
+//!
+//! A conversation forked with `fork_conversation` shares the history of its
+//! parent up to the fork point, after which both may go on separately. These
+//! functions compare two histories message by message and bring the messages
+//! of one into the other where that is unambiguous, e.g. to show a fork next
+//! to its parent or to keep the answers of a fork the user liked better.
+//!
+//! Messages are compared by role and text; IDs and timestamps are ignored,
+//! since a fork gives its copies of the parent's messages new ones.
+
+use crate::models::Message;
+
+/// One message that differs between two histories.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MessageChange {
+    /// The message at `index` in the other history is missing from the base.
+    Added { index: usize, message: Message },
+    /// The message at `index` in the base history is missing from the other.
+    Removed { index: usize, message: Message },
+}
+
+/// Differences between a base history and another, from [`diff_conversations`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConversationDiff {
+    /// Number of leading messages both histories share, i.e. the fork point
+    pub common_prefix: usize,
+    /// Changes in history order, removals before additions at the same place
+    pub changes: Vec<MessageChange>,
+}
+
+impl ConversationDiff {
+    /// Whether both histories hold the same messages.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Messages only the other history has.
+    pub fn added(&self) -> impl Iterator<Item = &Message> {
+        self.changes.iter().filter_map(|change| match change {
+            MessageChange::Added { message, .. } => Some(message),
+            MessageChange::Removed { .. } => None,
+        })
+    }
+
+    /// Messages only the base history has.
+    pub fn removed(&self) -> impl Iterator<Item = &Message> {
+        self.changes.iter().filter_map(|change| match change {
+            MessageChange::Removed { message, .. } => Some(message),
+            MessageChange::Added { .. } => None,
+        })
+    }
+}
+
+/// Messages both histories have at the same place, each with its own ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// Position of the conflict in the merged history, where the base's
+    /// messages were kept
+    pub index: usize,
+    /// The base's messages at this place
+    pub base: Vec<Message>,
+    /// The other history's messages at this place
+    pub other: Vec<Message>,
+}
+
+/// Result of [`merge_conversations`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConversationMerge {
+    /// The base history with the other's non-conflicting messages added
+    pub messages: Vec<Message>,
+    /// Places where both histories went on differently
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl ConversationMerge {
+    /// Whether every message of the other history could be merged.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Compare `other` against `base`, e.g. a fork against its parent.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::diff::diff_conversations;
+/// use agixt_sdk::Message;
+///
+/// let parent = [Message::user("Hi"), Message::assistant("Hello!")];
+/// let fork = [Message::user("Hi"), Message::assistant("Hey there!")];
+/// let diff = diff_conversations(&parent, &fork);
+/// assert_eq!(diff.common_prefix, 1);
+/// assert_eq!(diff.added().next().unwrap().text(), "Hey there!");
+/// ```
+pub fn diff_conversations(base: &[Message], other: &[Message]) -> ConversationDiff {
+    let mut diff = ConversationDiff {
+        common_prefix: common_prefix(base, other),
+        changes: Vec::new(),
+    };
+    for hunk in hunks(base, other) {
+        if let Hunk::Changed {
+            base_start,
+            removed,
+            other_start,
+            added,
+        } = hunk
+        {
+            for (i, message) in removed.iter().enumerate() {
+                diff.changes.push(MessageChange::Removed {
+                    index: base_start + i,
+                    message: message.clone(),
+                });
+            }
+            for (i, message) in added.iter().enumerate() {
+                diff.changes.push(MessageChange::Added {
+                    index: other_start + i,
+                    message: message.clone(),
+                });
+            }
+        }
+    }
+    diff
+}
+
+/// Add the messages of `other` that `base` lacks to `base`.
+///
+/// Messages are never dropped: where `other` only has extra messages, they are
+/// inserted at their place; where `base` only has extra messages, they are
+/// kept. Where both have different messages at the same place, as when a fork
+/// and its parent both went on after the fork point, the base's messages are
+/// kept and the place is reported as a [`MergeConflict`].
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::diff::merge_conversations;
+/// use agixt_sdk::Message;
+///
+/// let parent = [Message::user("Hi"), Message::assistant("Hello!")];
+/// let fork = [
+///     Message::user("Hi"),
+///     Message::assistant("Hello!"),
+///     Message::user("Tell me a joke"),
+/// ];
+/// let merged = merge_conversations(&parent, &fork);
+/// assert!(merged.is_clean());
+/// assert_eq!(merged.messages.len(), 3);
+/// ```
+pub fn merge_conversations(base: &[Message], other: &[Message]) -> ConversationMerge {
+    let mut merge = ConversationMerge::default();
+    for hunk in hunks(base, other) {
+        match hunk {
+            Hunk::Same(messages) => merge.messages.extend_from_slice(messages),
+            Hunk::Changed {
+                removed: [], added, ..
+            } => merge.messages.extend_from_slice(added),
+            Hunk::Changed { removed, added, .. } => {
+                if !added.is_empty() {
+                    merge.conflicts.push(MergeConflict {
+                        index: merge.messages.len(),
+                        base: removed.to_vec(),
+                        other: added.to_vec(),
+                    });
+                }
+                merge.messages.extend_from_slice(removed);
+            }
+        }
+    }
+    merge
+}
+
+/// Runs of messages two histories share, and the runs between them.
+enum Hunk<'a> {
+    Same(&'a [Message]),
+    Changed {
+        base_start: usize,
+        removed: &'a [Message],
+        other_start: usize,
+        added: &'a [Message],
+    },
+}
+
+fn same(a: &Message, b: &Message) -> bool {
+    a.normalized_role() == b.normalized_role() && a.text() == b.text()
+}
+
+fn common_prefix(base: &[Message], other: &[Message]) -> usize {
+    base.iter()
+        .zip(other)
+        .take_while(|(a, b)| same(a, b))
+        .count()
+}
+
+/// Split two histories into hunks along their longest common subsequence.
+fn hunks<'a>(base: &'a [Message], other: &'a [Message]) -> Vec<Hunk<'a>> {
+    // The shared prefix and suffix are split off first, so the quadratic
+    // table only covers the part where the histories differ
+    let prefix = common_prefix(base, other);
+    let suffix = base[prefix..]
+        .iter()
+        .rev()
+        .zip(other[prefix..].iter().rev())
+        .take_while(|(a, b)| same(a, b))
+        .count();
+    let (a, b) = (
+        &base[prefix..base.len() - suffix],
+        &other[prefix..other.len() - suffix],
+    );
+
+    // lengths[i][j]: longest common subsequence of a[i..] and b[j..]
+    let mut lengths = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if same(&a[i], &b[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    if prefix > 0 {
+        hunks.push(Hunk::Same(&base[..prefix]));
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let (start_i, start_j) = (i, j);
+        while i < a.len() && j < b.len() && same(&a[i], &b[j]) {
+            i += 1;
+            j += 1;
+        }
+        if i > start_i {
+            hunks.push(Hunk::Same(&a[start_i..i]));
+            continue;
+        }
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && same(&a[i], &b[j]) {
+                break;
+            }
+            if j == b.len() || (i < a.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        hunks.push(Hunk::Changed {
+            base_start: prefix + start_i,
+            removed: &a[start_i..i],
+            other_start: prefix + start_j,
+            added: &b[start_j..j],
+        });
+    }
+    if suffix > 0 {
+        hunks.push(Hunk::Same(&base[base.len() - suffix..]));
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(messages: &[Message]) -> Vec<String> {
+        messages.iter().map(Message::text).collect()
+    }
+
+    #[test]
+    fn test_diff_fork_against_parent() {
+        let parent = [
+            Message::user("Hi"),
+            Message::assistant("Hello!"),
+            Message::user("Weather?"),
+            Message::assistant("Sunny."),
+        ];
+        let fork = [
+            Message::user("Hi"),
+            Message::new("XT", "Hello!"),
+            Message::user("Weather?"),
+            Message::assistant("Rainy."),
+            Message::user("Umbrella?"),
+        ];
+        let diff = diff_conversations(&parent, &fork);
+        assert_eq!(diff.common_prefix, 3);
+        assert_eq!(
+            diff.changes,
+            [
+                MessageChange::Removed {
+                    index: 3,
+                    message: parent[3].clone()
+                },
+                MessageChange::Added {
+                    index: 3,
+                    message: fork[3].clone()
+                },
+                MessageChange::Added {
+                    index: 4,
+                    message: fork[4].clone()
+                },
+            ]
+        );
+        assert!(diff_conversations(&parent, &parent).is_empty());
+    }
+
+    #[test]
+    fn test_merge_inserts_and_reports_conflicts() {
+        let base = [
+            Message::user("a"),
+            Message::assistant("b"),
+            Message::user("c"),
+            Message::assistant("d"),
+        ];
+        let other = [
+            Message::user("a"),
+            Message::assistant("x"),
+            Message::assistant("b"),
+            Message::user("c"),
+            Message::assistant("e"),
+        ];
+        let merged = merge_conversations(&base, &other);
+        assert_eq!(texts(&merged.messages), ["a", "x", "b", "c", "d"]);
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].index, 4);
+        assert_eq!(texts(&merged.conflicts[0].base), ["d"]);
+        assert_eq!(texts(&merged.conflicts[0].other), ["e"]);
+
+        let merged = merge_conversations(&base[..2], &base);
+        assert!(merged.is_clean());
+        assert_eq!(merged.messages, base);
+    }
+}
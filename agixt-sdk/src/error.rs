@@ -1,58 +1,922 @@
 //! Error types for the AGiXT SDK.
 
-use std::fmt;
+use crate::client::RateLimitInfo;
+use serde::Deserialize;
 
 /// Error types for AGiXT SDK operations.
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
     /// Error from the HTTP client
-    RequestError(reqwest::Error),
+    #[error("Request error: {0}")]
+    RequestError(#[source] reqwest::Error),
+    /// The request timed out; `phase` tells whether connecting to the server
+    /// or waiting for its response took too long. `source` is the HTTP
+    /// client's error, or `None` when `AGiXTSDKBuilder::read_timeout` ran out.
+    #[error("Request timed out {phase}{}", timeout_source_suffix(.source))]
+    Timeout {
+        phase: TimeoutPhase,
+        #[source]
+        source: Option<reqwest::Error>,
+    },
+    /// The connection to the server could not be established
+    #[error("Connection error: {0}")]
+    Connection(#[source] reqwest::Error),
     /// Error parsing JSON
-    JsonError(serde_json::Error),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
     /// Error from the AGiXT API
+    #[error("API error ({status}): {message}{}", request_id_suffix(.details))]
     ApiError {
         status: u16,
         message: String,
+        details: ApiErrorDetails,
+    },
+    /// The server rejected the request body or parameters (422), with one
+    /// entry per offending field
+    #[error("Validation error (422): {}{}", field_error_summary(.errors), request_id_suffix(.details))]
+    Validation {
+        errors: Vec<FieldError>,
+        details: ApiErrorDetails,
+    },
+    /// A login or MFA request was rejected; `kind` tells why
+    #[error("Authentication error ({status}): {message}{}", request_id_suffix(.details))]
+    AuthError {
+        kind: AuthErrorKind,
+        status: u16,
+        message: String,
+        details: ApiErrorDetails,
     },
-    /// Error with authentication
-    AuthError(String),
     /// Invalid input parameters
+    #[error("Invalid input: {0}")]
     InvalidInput(String),
     /// Resource not found
+    #[error("Not found: {0}")]
     NotFound(String),
+    /// The request was aborted through its cancellation token, or the client
+    /// was shut down
+    #[error("Request cancelled")]
+    Cancelled,
+    /// The call ran out of its `RequestOptions::deadline` budget, counting
+    /// retries, or `wait_for_task` its timeout, counting polls
+    #[error("Deadline of {deadline:?} exceeded after {attempts} attempt(s)")]
+    DeadlineExceeded {
+        deadline: std::time::Duration,
+        attempts: u32,
+        /// Error of the last attempt that completed before the deadline, if any
+        #[source]
+        last_error: Option<Box<Error>>,
+    },
+    /// `AGiXTSDK::shutdown` aborted the requests still in flight when its
+    /// deadline passed
+    #[error("{aborted} request(s) still in flight after {deadline:?} were aborted")]
+    ShutdownTimedOut {
+        deadline: std::time::Duration,
+        aborted: usize,
+    },
     /// Generic error for other cases
+    #[error("Error: {0}")]
     Other(String),
+    /// Another error, together with the SDK operation and endpoint that failed.
+    ///
+    /// The predicates and accessors of `Error` look through this wrapper; match
+    /// on `root()` to inspect the underlying error.
+    #[error("{context}: {source}")]
+    Context {
+        context: Box<ErrorContext>,
+        #[source]
+        source: Box<Error>,
+    },
 }
 
-impl std::error::Error for Error {}
+/// Stage of a request that ran out of time, for `Error::Timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TimeoutPhase {
+    /// No connection to the server within the connect timeout; the host may
+    /// be down or unreachable.
+    Connect,
+    /// Connected, but the response did not arrive in time.
+    Read,
+}
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl std::fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::RequestError(e) => write!(f, "Request error: {}", e),
-            Error::JsonError(e) => write!(f, "JSON error: {}", e),
-            Error::ApiError { status, message } => {
-                write!(f, "API error ({}): {}", status, message)
+            TimeoutPhase::Connect => write!(f, "while connecting"),
+            TimeoutPhase::Read => write!(f, "waiting for the response"),
+        }
+    }
+}
+
+/// Why a login or MFA request was rejected, classified from the server's
+/// status code and error detail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuthErrorKind {
+    /// Wrong username, email or password.
+    InvalidCredentials,
+    /// The account has MFA enabled and no TOTP code was sent.
+    MfaRequired,
+    /// The TOTP code was wrong or no longer valid.
+    InvalidOtp,
+    /// The magic link or its token has expired or was already used.
+    ExpiredMagicLink,
+    /// The account is locked or suspended.
+    AccountLocked,
+    /// Too many failed attempts; wait before trying again.
+    TooManyAttempts,
+    /// Any other authentication failure.
+    Other,
+}
+
+impl AuthErrorKind {
+    /// Classify a rejected login from its status code and error message.
+    pub(crate) fn classify(status: u16, message: &str) -> Self {
+        let message = message.to_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| message.contains(word));
+        if status == 429 || mentions(&["too many", "rate limit", "attempts"]) {
+            AuthErrorKind::TooManyAttempts
+        } else if status == 423 || mentions(&["locked", "suspended"]) {
+            AuthErrorKind::AccountLocked
+        } else if mentions(&["mfa", "otp", "two-factor", "2fa", "authenticator"]) {
+            if mentions(&["required", "missing"]) {
+                AuthErrorKind::MfaRequired
+            } else {
+                AuthErrorKind::InvalidOtp
             }
-            Error::AuthError(msg) => write!(f, "Authentication error: {}", msg),
-            Error::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
-            Error::NotFound(msg) => write!(f, "Not found: {}", msg),
-            Error::Other(msg) => write!(f, "Error: {}", msg),
+        } else if mentions(&["expired", "magic link", "already used"]) {
+            AuthErrorKind::ExpiredMagicLink
+        } else if matches!(status, 401 | 403)
+            || mentions(&["invalid", "incorrect", "password", "not found"])
+        {
+            AuthErrorKind::InvalidCredentials
+        } else {
+            AuthErrorKind::Other
+        }
+    }
+}
+
+/// The call an `Error::Context` was raised by.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// SDK method that failed, such as `add_agent`. Known for calls made
+    /// through the `AgixtApi` trait and the blocking client.
+    pub operation: Option<&'static str>,
+    /// HTTP method of the failed request.
+    pub method: Option<reqwest::Method>,
+    /// Path of the failed request, such as `/v1/agent`.
+    pub endpoint: Option<String>,
+}
+
+impl ErrorContext {
+    /// Context of a request to `endpoint`.
+    pub(crate) fn request(method: reqwest::Method, endpoint: impl Into<String>) -> Self {
+        Self {
+            operation: None,
+            method: Some(method),
+            endpoint: Some(endpoint.into()),
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let request = match (&self.method, &self.endpoint) {
+            (Some(method), Some(endpoint)) => Some(format!("{} {}", method, endpoint)),
+            (None, Some(endpoint)) => Some(endpoint.clone()),
+            _ => None,
+        };
+        match (self.operation, request) {
+            (Some(operation), Some(request)) => write!(f, "{} ({})", operation, request),
+            (Some(operation), None) => f.write_str(operation),
+            (None, Some(request)) => f.write_str(&request),
+            (None, None) => f.write_str("request failed"),
+        }
+    }
+}
+
+impl Error {
+    /// Build an `ApiError` from a response status and body, parsing the JSON
+    /// error payload when there is one. A 422 listing field errors becomes a
+    /// `Validation` error.
+    pub(crate) fn api(status: u16, body: &str) -> Self {
+        let details = ApiErrorDetails::parse(body);
+        if status == 422 {
+            let errors = FieldError::parse_all(body);
+            if !errors.is_empty() {
+                return Error::Validation { errors, details };
+            }
+        }
+        let message = details.detail.clone().unwrap_or_else(|| body_excerpt(body));
+        Error::ApiError {
+            status,
+            message,
+            details,
+        }
+    }
+
+    /// Build an `ApiError` for a response whose body is not JSON, such as the
+    /// HTML error page of a reverse proxy, quoting an excerpt of the body.
+    pub(crate) fn non_json(status: u16, content_type: Option<&str>, body: &str) -> Self {
+        Error::ApiError {
+            status,
+            message: format!(
+                "expected JSON but got {}: {}",
+                content_type.unwrap_or("a non-JSON body"),
+                body_excerpt(body)
+            ),
+            details: ApiErrorDetails::default(),
+        }
+    }
+
+    /// Build the `Timeout` error of a server that stayed silent for longer
+    /// than `AGiXTSDKBuilder::read_timeout`.
+    pub(crate) fn read_timed_out() -> Self {
+        Error::Timeout {
+            phase: TimeoutPhase::Read,
+            source: None,
+        }
+    }
+
+    /// Build an `AuthError` for a rejected login from the response status and body.
+    pub(crate) fn auth(status: u16, body: &str) -> Self {
+        Error::api(status, body).into_auth()
+    }
+
+    /// Turn an `ApiError` of a login or MFA request into an `AuthError`.
+    /// Validation errors, missing resources and server errors are kept as they are.
+    pub(crate) fn into_auth(self) -> Self {
+        match self {
+            Error::ApiError {
+                status,
+                message,
+                details,
+            } if matches!(status, 400 | 401 | 403 | 423 | 429) => Error::AuthError {
+                kind: AuthErrorKind::classify(status, &message),
+                status,
+                message,
+                details,
+            },
+            Error::Context { context, source } => Error::Context {
+                context,
+                source: Box::new(source.into_auth()),
+            },
+            error => error,
+        }
+    }
+
+    /// Attach the correlation ID of the failed request to an `ApiError`, unless
+    /// the server already reported one in the error body.
+    pub(crate) fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        if let Error::ApiError { details, .. }
+        | Error::AuthError { details, .. }
+        | Error::Validation { details, .. } = &mut self
+        {
+            if details.request_id.is_none() {
+                details.request_id = Some(request_id.into());
+            }
+        }
+        self
+    }
+
+    /// Attach the rate limit headers of the failed response to an `ApiError`.
+    pub(crate) fn with_rate_limit(mut self, rate_limit: Option<RateLimitInfo>) -> Self {
+        if let Error::ApiError { details, .. }
+        | Error::AuthError { details, .. }
+        | Error::Validation { details, .. } = &mut self
+        {
+            details.rate_limit = rate_limit.map(Box::new);
+        }
+        self
+    }
+
+    /// Wrap the error with the request it was raised by. An error that already
+    /// has a context keeps it.
+    pub(crate) fn with_context(self, context: Option<ErrorContext>) -> Self {
+        match (self, context) {
+            (error @ Error::Context { .. }, _) | (error, None) => error,
+            (error, Some(context)) => Error::Context {
+                context: Box::new(context),
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// Record the SDK method that raised the error.
+    pub(crate) fn with_operation(self, operation: &'static str) -> Self {
+        match self {
+            Error::Context {
+                mut context,
+                source,
+            } => {
+                context.operation.get_or_insert(operation);
+                Error::Context { context, source }
+            }
+            error => Error::Context {
+                context: Box::new(ErrorContext {
+                    operation: Some(operation),
+                    ..ErrorContext::default()
+                }),
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// The underlying error, without the operation and endpoint context.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) {
+    /// match client.get_providers().await {
+    ///     Err(e) => match e.root() {
+    ///         agixt_sdk::Error::JsonError(json) => {
+    ///             eprintln!("unexpected response from {:?}: {}", e.context(), json)
+    ///         }
+    ///         other => eprintln!("{}", other),
+    ///     },
+    ///     Ok(providers) => println!("{:?}", providers),
+    /// }
+    /// # }
+    /// ```
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root(),
+            error => error,
+        }
+    }
+
+    /// Consume the error, returning the underlying error without its context.
+    pub fn into_root(self) -> Error {
+        match self {
+            Error::Context { source, .. } => source.into_root(),
+            error => error,
+        }
+    }
+
+    /// The SDK operation and endpoint that failed, when known.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// HTTP status code associated with this error, if any.
+    pub fn status(&self) -> Option<u16> {
+        match self.root() {
+            Error::ApiError { status, .. } | Error::AuthError { status, .. } => Some(*status),
+            Error::Validation { .. } => Some(422),
+            Error::RequestError(e)
+            | Error::Timeout {
+                source: Some(e), ..
+            }
+            | Error::Connection(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// Whether the requested resource does not exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.root(), Error::NotFound(_)) || self.status() == Some(404)
+    }
+
+    /// Whether the request was rejected for missing or invalid credentials.
+    pub fn is_auth_error(&self) -> bool {
+        matches!(self.root(), Error::AuthError { .. })
+            || matches!(self.status(), Some(401) | Some(403))
+    }
+
+    /// Why a login or MFA request was rejected, for `AuthError`s.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use agixt_sdk::AuthErrorKind;
+    ///
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) {
+    /// if let Err(e) = client.login("user@example.com", "hunter2", None).await {
+    ///     let message = match e.auth_error_kind() {
+    ///         Some(AuthErrorKind::MfaRequired) => "Enter the code from your authenticator app",
+    ///         Some(AuthErrorKind::InvalidOtp) => "That code is not valid",
+    ///         Some(AuthErrorKind::AccountLocked) => "This account is locked",
+    ///         Some(AuthErrorKind::TooManyAttempts) => "Too many attempts, try again later",
+    ///         _ => "Wrong email or password",
+    ///     };
+    ///     eprintln!("{}", message);
+    /// }
+    /// # }
+    /// ```
+    pub fn auth_error_kind(&self) -> Option<AuthErrorKind> {
+        match self.root() {
+            Error::AuthError { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Whether the request was aborted through `RequestOptions::cancellation`.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.root(), Error::Cancelled)
+    }
+
+    /// Whether the request timed out, either on a single attempt or by running
+    /// out of its overall deadline.
+    pub fn is_timeout(&self) -> bool {
+        matches!(
+            self.root(),
+            Error::Timeout { .. } | Error::DeadlineExceeded { .. }
+        )
+    }
+
+    /// Whether a timed out attempt was still connecting or already waiting
+    /// for the response, for `Timeout` errors.
+    pub fn timeout_phase(&self) -> Option<TimeoutPhase> {
+        match self.root() {
+            Error::Timeout { phase, .. } => Some(*phase),
+            _ => None,
+        }
+    }
+
+    /// Whether the failure is transient and the request may succeed if retried.
+    ///
+    /// Timeouts, connection failures, rate limiting (429) and gateway or server
+    /// unavailability (502, 503, 504) are retryable; everything else is not.
+    pub fn is_retryable(&self) -> bool {
+        match self.root() {
+            Error::Timeout { .. } | Error::Connection(_) => true,
+            _ => matches!(self.status(), Some(408 | 429 | 502 | 503 | 504)),
+        }
+    }
+
+    /// Rate limit headers of the failed response, such as how long to wait
+    /// before retrying a 429.
+    pub fn rate_limit(&self) -> Option<&RateLimitInfo> {
+        self.details()?.rate_limit.as_deref()
+    }
+
+    /// Structured error payload returned by the server, for `ApiError`s,
+    /// `AuthError`s and `Validation` errors.
+    pub fn details(&self) -> Option<&ApiErrorDetails> {
+        match self.root() {
+            Error::ApiError { details, .. }
+            | Error::AuthError { details, .. }
+            | Error::Validation { details, .. } => Some(details),
+            _ => None,
+        }
+    }
+
+    /// The fields the server rejected, for `Validation` errors; empty otherwise.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn run(client: agixt_sdk::AGiXTSDK) {
+    /// if let Err(e) = client.add_agent("", None, None, None).await {
+    ///     for error in e.field_errors() {
+    ///         eprintln!("{}: {}", error.field(), error.msg);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn field_errors(&self) -> &[FieldError] {
+        match self.root() {
+            Error::Validation { errors, .. } => errors,
+            _ => &[],
+        }
+    }
+}
+
+/// Longest body excerpt quoted in error messages, in characters.
+const EXCERPT_CHARS: usize = 200;
+
+/// A short, single-line excerpt of an error body for messages: the title of
+/// an HTML page, or else its text without markup, truncated.
+fn body_excerpt(body: &str) -> String {
+    let lower = body.to_ascii_lowercase();
+    let title = lower.find("<title").and_then(|start| {
+        let open = start + lower[start..].find('>')? + 1;
+        let close = open + lower[open..].find("</title")?;
+        Some(&body[open..close])
+    });
+    let text = match title {
+        Some(title) if !title.trim().is_empty() => title.to_string(),
+        _ if body.trim_start().starts_with('<') => {
+            let mut text = String::new();
+            let mut in_tag = false;
+            for c in body.chars() {
+                match c {
+                    '<' => in_tag = true,
+                    '>' if in_tag => {
+                        in_tag = false;
+                        text.push(' ');
+                    }
+                    c if !in_tag => text.push(c),
+                    _ => {}
+                }
+            }
+            text
+        }
+        _ => body.to_string(),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+fn field_error_summary(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| match error.field().as_str() {
+            "" => error.msg.clone(),
+            field => format!("{}: {}", field, error.msg),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn timeout_source_suffix(source: &Option<reqwest::Error>) -> String {
+    match source {
+        Some(e) => format!(": {}", e),
+        None => String::new(),
+    }
+}
+
+fn request_id_suffix(details: &ApiErrorDetails) -> String {
+    match &details.request_id {
+        Some(id) => format!(" (request id: {})", id),
+        None => String::new(),
+    }
+}
+
+/// One rejected field of a FastAPI-style validation error, from `Error::Validation`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FieldError {
+    /// Where the field is, e.g. `["body", "agent_settings", "provider"]`;
+    /// list positions are numbers
+    #[serde(default)]
+    pub loc: Vec<serde_json::Value>,
+    /// What is wrong with it, e.g. `Field required`
+    #[serde(default)]
+    pub msg: String,
+    /// Machine-readable error type, e.g. `missing` or `string_type`
+    #[serde(default, rename = "type")]
+    pub kind: String,
+}
+
+impl FieldError {
+    /// Dotted path of the field without the part of the request it is in,
+    /// e.g. `agent_settings.provider` or `messages.0.role`.
+    pub fn field(&self) -> String {
+        let path = match self.loc.first().and_then(|part| part.as_str()) {
+            Some("body" | "query" | "path" | "header" | "cookie") => &self.loc[1..],
+            _ => &self.loc[..],
+        };
+        path.iter()
+            .map(|part| match part {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Whether the field was required but missing.
+    pub fn is_missing(&self) -> bool {
+        self.kind == "missing"
+    }
+
+    /// The field errors of an error body whose `detail` is a list of them.
+    fn parse_all(body: &str) -> Vec<Self> {
+        #[derive(Deserialize)]
+        struct RawError {
+            detail: Vec<serde_json::Value>,
+        }
+
+        match serde_json::from_str::<RawError>(body) {
+            Ok(raw) => raw
+                .detail
+                .into_iter()
+                .filter_map(|item| serde_json::from_value(item).ok())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// Fields parsed from an AGiXT error response body.
+///
+/// AGiXT returns FastAPI-style errors: `{"detail": "..."}` for most failures and
+/// `{"detail": [{"loc": [...], "msg": "...", "type": "missing"}]}` for validation
+/// errors. Fields that are absent from the body are left empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApiErrorDetails {
+    /// Human-readable error description.
+    pub detail: Option<String>,
+    /// Request fields reported as missing by the server's validation.
+    pub missing_fields: Vec<String>,
+    /// Request identifier reported by the server, or else the `X-Request-ID`
+    /// the client sent. Useful for finding the request in server logs.
+    pub request_id: Option<String>,
+    /// Rate limit headers of the error response, if it had any.
+    pub rate_limit: Option<Box<RateLimitInfo>>,
+}
+
+impl ApiErrorDetails {
+    /// Parse an error body, returning empty details if it is not JSON.
+    pub fn parse(body: &str) -> Self {
+        #[derive(Deserialize)]
+        struct RawError {
+            #[serde(default)]
+            detail: Option<serde_json::Value>,
+            #[serde(default)]
+            message: Option<String>,
+            #[serde(default)]
+            missing_fields: Vec<String>,
+            #[serde(default)]
+            request_id: Option<String>,
+        }
+
+        #[derive(Deserialize)]
+        struct ValidationIssue {
+            #[serde(default)]
+            loc: Vec<serde_json::Value>,
+            #[serde(default)]
+            msg: Option<String>,
+            #[serde(default, rename = "type")]
+            kind: Option<String>,
+        }
+
+        let raw: RawError = match serde_json::from_str(body) {
+            Ok(raw) => raw,
+            Err(_) => return Self::default(),
+        };
+
+        let mut missing_fields = raw.missing_fields;
+        let detail = match raw.detail {
+            Some(serde_json::Value::String(detail)) => Some(detail),
+            Some(serde_json::Value::Array(items)) => {
+                let issues: Vec<ValidationIssue> = items
+                    .into_iter()
+                    .filter_map(|item| serde_json::from_value(item).ok())
+                    .collect();
+                for issue in &issues {
+                    if issue.kind.as_deref() == Some("missing") {
+                        if let Some(field) = issue.loc.last() {
+                            missing_fields.push(match field {
+                                serde_json::Value::String(s) => s.clone(),
+                                other => other.to_string(),
+                            });
+                        }
+                    }
+                }
+                let messages: Vec<&str> = issues.iter().filter_map(|i| i.msg.as_deref()).collect();
+                (!messages.is_empty()).then(|| messages.join("; "))
+            }
+            Some(serde_json::Value::Null) | None => None,
+            Some(other) => Some(other.to_string()),
+        };
+
+        Self {
+            detail: detail.or(raw.message),
+            missing_fields,
+            request_id: raw.request_id,
+            rate_limit: None,
         }
     }
 }
 
 impl From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            #[cfg(not(target_arch = "wasm32"))]
+            let phase = if err.is_connect() {
+                TimeoutPhase::Connect
+            } else {
+                TimeoutPhase::Read
+            };
+            #[cfg(target_arch = "wasm32")]
+            let phase = TimeoutPhase::Read;
+            return Error::Timeout {
+                phase,
+                source: Some(err),
+            };
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if err.is_connect() {
+            return Error::Connection(err);
+        }
         Error::RequestError(err)
     }
 }
 
-impl From<serde_json::Error> for Error {
-    fn from(err: serde_json::Error) -> Self {
-        Error::JsonError(err)
+/// Result type alias using the AGiXT Error type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Return values of SDK methods whose errors can be tagged with the method name.
+pub(crate) trait WithOperation {
+    fn with_operation(self, operation: &'static str) -> Self;
+}
+
+impl<T> WithOperation for Result<T> {
+    fn with_operation(self, operation: &'static str) -> Self {
+        self.map_err(|e| e.with_operation(operation))
+    }
+}
+
+/// Per-item results of batch methods such as `chat_completions_batch`.
+impl<T> WithOperation for Vec<Result<T>> {
+    fn with_operation(self, operation: &'static str) -> Self {
+        self.into_iter()
+            .map(|result| result.with_operation(operation))
+            .collect()
     }
 }
 
-/// Result type alias using the AGiXT Error type.
-pub type Result<T> = std::result::Result<T, Error>;
+/// Per-item results of batch methods such as `learn_urls_concurrent`.
+impl<K, T> WithOperation for Vec<(K, Result<T>)> {
+    fn with_operation(self, operation: &'static str) -> Self {
+        self.into_iter()
+            .map(|(key, result)| (key, result.with_operation(operation)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_detail_string() {
+        let error = Error::api(404, r#"{"detail": "Agent not found", "request_id": "abc"}"#);
+        assert_eq!(
+            error.to_string(),
+            "API error (404): Agent not found (request id: abc)"
+        );
+        assert_eq!(error.details().unwrap().request_id.as_deref(), Some("abc"));
+        let error = error.with_request_id("ignored");
+        assert_eq!(error.details().unwrap().request_id.as_deref(), Some("abc"));
+        assert!(error.is_not_found());
+        assert!(!error.is_auth_error());
+    }
+
+    #[test]
+    fn test_parse_validation_errors() {
+        let body = r#"{"detail": [
+            {"loc": ["body", "agent_name"], "msg": "Field required", "type": "missing"},
+            {"loc": ["body", "settings"], "msg": "Input should be a dict", "type": "dict_type"}
+        ]}"#;
+        let details = ApiErrorDetails::parse(body);
+        assert_eq!(details.missing_fields, vec!["agent_name"]);
+        assert_eq!(
+            details.detail.as_deref(),
+            Some("Field required; Input should be a dict")
+        );
+    }
+
+    #[test]
+    fn test_validation_error() {
+        let body = r#"{"detail": [
+            {"loc": ["body", "agent_name"], "msg": "Field required", "type": "missing"},
+            {"loc": ["body", "messages", 1, "role"], "msg": "Input should be a valid string", "type": "string_type"}
+        ]}"#;
+        let error = Error::api(422, body)
+            .with_request_id("req-1")
+            .with_context(Some(ErrorContext::default()));
+        assert_eq!(error.status(), Some(422));
+        let fields: Vec<String> = error.field_errors().iter().map(FieldError::field).collect();
+        assert_eq!(fields, ["agent_name", "messages.1.role"]);
+        assert!(error.field_errors()[0].is_missing());
+        assert_eq!(error.details().unwrap().missing_fields, ["agent_name"]);
+        assert_eq!(
+            error.root().to_string(),
+            "Validation error (422): agent_name: Field required; \
+             messages.1.role: Input should be a valid string (request id: req-1)"
+        );
+
+        // A 422 without field errors stays an ApiError
+        let error = Error::api(422, r#"{"detail": "Unprocessable"}"#);
+        assert!(matches!(error, Error::ApiError { status: 422, .. }));
+        assert!(error.field_errors().is_empty());
+    }
+
+    #[test]
+    fn test_non_json_body() {
+        let error = Error::api(401, "Unauthorized");
+        assert_eq!(error.details(), Some(&ApiErrorDetails::default()));
+        assert_eq!(error.status(), Some(401));
+        assert!(error.is_auth_error());
+        assert!(!error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_connection_error_is_retryable() {
+        // Nothing listens on port 1, so the connection is refused.
+        let err = reqwest::get("http://127.0.0.1:1").await.unwrap_err();
+        let error = Error::from(err);
+        assert!(matches!(error, Error::Connection(_)));
+        assert!(error.is_retryable());
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(Error::api(503, "").is_retryable());
+    }
+
+    #[test]
+    fn test_context() {
+        let error = Error::api(404, r#"{"detail": "Agent not found"}"#)
+            .with_context(Some(ErrorContext::request(
+                reqwest::Method::POST,
+                "/v1/agent",
+            )))
+            .with_operation("add_agent");
+        assert_eq!(
+            error.to_string(),
+            "add_agent (POST /v1/agent): API error (404): Agent not found"
+        );
+        assert_eq!(error.context().unwrap().operation, Some("add_agent"));
+        assert!(error.is_not_found());
+        assert!(matches!(error.root(), Error::ApiError { status: 404, .. }));
+
+        let error = Error::Cancelled.with_operation("chat");
+        assert_eq!(error.to_string(), "chat: Request cancelled");
+        assert!(error.is_cancelled());
+        assert!(matches!(error.into_root(), Error::Cancelled));
+    }
+
+    #[test]
+    fn test_auth_error_kinds() {
+        let cases = [
+            (
+                401,
+                r#"{"detail": "Invalid username or password"}"#,
+                AuthErrorKind::InvalidCredentials,
+            ),
+            (
+                401,
+                r#"{"detail": "MFA token required"}"#,
+                AuthErrorKind::MfaRequired,
+            ),
+            (
+                401,
+                r#"{"detail": "Invalid MFA token"}"#,
+                AuthErrorKind::InvalidOtp,
+            ),
+            (
+                401,
+                r#"{"detail": "Magic link has expired"}"#,
+                AuthErrorKind::ExpiredMagicLink,
+            ),
+            (
+                403,
+                r#"{"detail": "Account locked"}"#,
+                AuthErrorKind::AccountLocked,
+            ),
+            (
+                429,
+                r#"{"detail": "Slow down"}"#,
+                AuthErrorKind::TooManyAttempts,
+            ),
+            (
+                400,
+                r#"{"detail": "Too many login attempts"}"#,
+                AuthErrorKind::TooManyAttempts,
+            ),
+            (400, "Bad request", AuthErrorKind::Other),
+        ];
+        for (status, body, kind) in cases {
+            let error = Error::auth(status, body);
+            assert_eq!(error.auth_error_kind(), Some(kind), "{}", body);
+            assert_eq!(error.status(), Some(status));
+            assert!(error.is_auth_error());
+        }
+
+        let error = Error::auth(422, r#"{"detail": "Field required"}"#);
+        assert!(matches!(error, Error::ApiError { status: 422, .. }));
+        assert_eq!(error.auth_error_kind(), None);
+
+        let error = Error::api(401, r#"{"detail": "Invalid MFA token"}"#)
+            .with_operation("enable_mfa")
+            .into_auth();
+        assert_eq!(error.auth_error_kind(), Some(AuthErrorKind::InvalidOtp));
+        assert_eq!(
+            error.to_string(),
+            "enable_mfa: Authentication error (401): Invalid MFA token"
+        );
+    }
+
+    #[test]
+    fn test_html_error_pages() {
+        let page = "<html><head><title>502 Bad Gateway</title></head>\n\
+                    <body><center><h1>502 Bad Gateway</h1></center><hr>nginx</body></html>";
+        let error = Error::api(502, page);
+        assert_eq!(error.to_string(), "API error (502): 502 Bad Gateway");
+        assert!(error.is_retryable());
+
+        let error = Error::api(500, "<p>Internal\n   Server Error</p>");
+        assert_eq!(error.to_string(), "API error (500): Internal Server Error");
+
+        let error = Error::non_json(200, Some("text/plain"), &"x".repeat(500));
+        let message = error.to_string();
+        assert!(message.starts_with("API error (200): expected JSON but got text/plain: xxx"));
+        assert!(message.ends_with("..."));
+        assert!(message.len() < 300);
+    }
+}
@@ -0,0 +1,404 @@
+//! Background ingestion of documents into agent memory.
+//!
+//! A [`Worker`] takes learn jobs from a queue and sends them to the server in a
+//! background task, a few at a time, retrying transient failures. Services that
+//! keep streaming documents into memory can submit jobs as they arrive and
+//! follow the outcome of each on the event channel.
+
+use crate::client::{retry_backoff, AGiXTSDK};
+use crate::error::{Error, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Semaphore};
+
+/// Something to learn, with the agent that learns it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Job {
+    /// Learn text with `learn_text`
+    Text {
+        agent_id: String,
+        user_input: String,
+        text: String,
+        collection_number: Option<String>,
+    },
+    /// Learn the page at a URL with `learn_url`
+    Url {
+        agent_id: String,
+        url: String,
+        collection_number: Option<String>,
+    },
+    /// Learn a base64-encoded file with `learn_file`
+    File {
+        agent_id: String,
+        file_name: String,
+        file_content: String,
+        collection_number: Option<String>,
+    },
+}
+
+impl Job {
+    /// Learn `text`, described to the agent by `user_input`.
+    pub fn text(
+        agent_id: impl Into<String>,
+        user_input: impl Into<String>,
+        text: impl Into<String>,
+    ) -> Self {
+        Job::Text {
+            agent_id: agent_id.into(),
+            user_input: user_input.into(),
+            text: text.into(),
+            collection_number: None,
+        }
+    }
+
+    /// Learn the page at `url`.
+    pub fn url(agent_id: impl Into<String>, url: impl Into<String>) -> Self {
+        Job::Url {
+            agent_id: agent_id.into(),
+            url: url.into(),
+            collection_number: None,
+        }
+    }
+
+    /// Learn a file, given its name and base64-encoded content.
+    pub fn file(
+        agent_id: impl Into<String>,
+        file_name: impl Into<String>,
+        file_content: impl Into<String>,
+    ) -> Self {
+        Job::File {
+            agent_id: agent_id.into(),
+            file_name: file_name.into(),
+            file_content: file_content.into(),
+            collection_number: None,
+        }
+    }
+
+    /// Store what is learned in the given memory collection.
+    pub fn collection(mut self, collection_number: impl Into<String>) -> Self {
+        let collection = match &mut self {
+            Job::Text {
+                collection_number, ..
+            }
+            | Job::Url {
+                collection_number, ..
+            }
+            | Job::File {
+                collection_number, ..
+            } => collection_number,
+        };
+        *collection = Some(collection_number.into());
+        self
+    }
+
+    async fn run(&self, client: &AGiXTSDK) -> Result<String> {
+        match self {
+            Job::Text {
+                agent_id,
+                user_input,
+                text,
+                collection_number,
+            } => {
+                client
+                    .learn_text(agent_id, user_input, text, collection_number.as_deref())
+                    .await
+            }
+            Job::Url {
+                agent_id,
+                url,
+                collection_number,
+            } => {
+                client
+                    .learn_url(agent_id, url, collection_number.as_deref())
+                    .await
+            }
+            Job::File {
+                agent_id,
+                file_name,
+                file_content,
+                collection_number,
+            } => {
+                client
+                    .learn_file(
+                        agent_id,
+                        file_name,
+                        file_content,
+                        collection_number.as_deref(),
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+/// Outcome of a job, delivered on the worker's event channel.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IngestionEvent {
+    /// The server learned the job's content
+    Completed {
+        /// ID returned by `Worker::submit`
+        id: u64,
+        job: Job,
+        /// The server's confirmation message
+        message: String,
+        attempts: u32,
+    },
+    /// The job failed with a permanent error, or still failed after the
+    /// last attempt
+    Failed {
+        id: u64,
+        job: Job,
+        error: Error,
+        attempts: u32,
+    },
+}
+
+/// Queue of learn jobs processed by a background task.
+///
+/// Jobs run concurrently up to the worker's limit. A job that fails with a
+/// retryable error (see `Error::is_retryable`) is tried again after a backoff,
+/// up to `max_attempts` times in all. When the client is shut down with
+/// `AGiXTSDK::shutdown`, the worker stops taking jobs and reports the queued
+/// ones as failed with `Error::Cancelled`. Must be created inside a Tokio runtime.
+///
+/// # Example
+/// ```rust,no_run
+/// use agixt_sdk::ingestion::{IngestionEvent, Job, Worker};
+/// use agixt_sdk::AGiXTSDK;
+///
+/// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+/// let (worker, mut events) = Worker::spawn(client, 4, 3);
+/// worker.submit(Job::url("agent-id", "https://docs.example.com/guide"))?;
+/// worker.submit(Job::text("agent-id", "Release notes", "Version 2 adds...").collection("2"))?;
+///
+/// while let Some(event) = events.recv().await {
+///     match event {
+///         IngestionEvent::Completed { id, .. } => println!("job {} learned", id),
+///         IngestionEvent::Failed { id, error, .. } => eprintln!("job {} failed: {}", id, error),
+///         _ => {}
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Worker {
+    jobs: mpsc::UnboundedSender<(u64, Job)>,
+    next_id: AtomicU64,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Worker {
+    /// Start a worker that runs up to `concurrency` jobs at a time, trying each
+    /// at most `max_attempts` times. Returns the worker and the receiver of
+    /// its events, one per job.
+    pub fn spawn(
+        client: AGiXTSDK,
+        concurrency: usize,
+        max_attempts: u32,
+    ) -> (Self, mpsc::UnboundedReceiver<IngestionEvent>) {
+        let (jobs, queue) = mpsc::unbounded_channel();
+        let (events, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(process(
+            client,
+            queue,
+            events,
+            concurrency.max(1),
+            max_attempts.max(1),
+        ));
+        let worker = Self {
+            jobs,
+            next_id: AtomicU64::new(1),
+            task,
+        };
+        (worker, receiver)
+    }
+
+    /// Queue a job, returning the ID its event will carry.
+    pub fn submit(&self, job: Job) -> Result<u64> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.jobs
+            .send((id, job))
+            .map_err(|_| Error::Other("the ingestion worker has stopped".to_string()))?;
+        Ok(id)
+    }
+
+    /// Stop accepting jobs and wait until the queued and running ones are done.
+    /// The event channel closes once their events are delivered.
+    pub async fn shutdown(self) {
+        drop(self.jobs);
+        let _ = self.task.await;
+    }
+}
+
+/// Run queued jobs until the worker is shut down, then wait for the running ones.
+async fn process(
+    client: AGiXTSDK,
+    mut queue: mpsc::UnboundedReceiver<(u64, Job)>,
+    events: mpsc::UnboundedSender<IngestionEvent>,
+    concurrency: usize,
+    max_attempts: u32,
+) {
+    let slots = Arc::new(Semaphore::new(concurrency));
+    let closed = client.closed();
+    tokio::pin!(closed);
+    loop {
+        let next = tokio::select! {
+            biased;
+            _ = &mut closed => None,
+            next = queue.recv() => next,
+        };
+        let Some((id, job)) = next else {
+            break;
+        };
+        let Ok(slot) = slots.clone().acquire_owned().await else {
+            return;
+        };
+        let client = client.clone();
+        let events = events.clone();
+        tokio::spawn(async move {
+            let event = run_job(&client, id, job, max_attempts).await;
+            // Events are dropped once the receiver is gone
+            let _ = events.send(event);
+            drop(slot);
+        });
+    }
+    // Jobs still queued when the client shut down are not sent
+    queue.close();
+    while let Ok((id, job)) = queue.try_recv() {
+        let _ = events.send(IngestionEvent::Failed {
+            id,
+            job,
+            error: Error::Cancelled,
+            attempts: 0,
+        });
+    }
+    let _ = slots.acquire_many(concurrency as u32).await;
+}
+
+async fn run_job(client: &AGiXTSDK, id: u64, job: Job, max_attempts: u32) -> IngestionEvent {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let error = match job.run(client).await {
+            Ok(message) => {
+                return IngestionEvent::Completed {
+                    id,
+                    job,
+                    message,
+                    attempts,
+                }
+            }
+            Err(error) => error,
+        };
+        if attempts >= max_attempts || !error.is_retryable() {
+            return IngestionEvent::Failed {
+                id,
+                job,
+                error,
+                attempts,
+            };
+        }
+        let backoff = match error.rate_limit().and_then(|info| info.retry_after) {
+            Some(retry_after) => retry_backoff(attempts).max(retry_after),
+            None => retry_backoff(attempts),
+        };
+        tokio::time::sleep(backoff).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_worker_retries_and_reports() {
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server
+            .mock("POST", "/v1/agent/a1/learn/url")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let url = server
+            .mock("POST", "/v1/agent/a1/learn/url")
+            .with_body(r#"{"message": "Learned"}"#)
+            .create_async()
+            .await;
+        let text = server
+            .mock("POST", "/v1/agent/a1/learn/text")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"collection_number": "2"}),
+            ))
+            .with_status(422)
+            .with_body(r#"{"detail": "Text is empty"}"#)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let (worker, mut events) = Worker::spawn(client, 2, 3);
+        let url_job = worker
+            .submit(Job::url("a1", "https://example.com"))
+            .unwrap();
+        let text_job = worker
+            .submit(Job::text("a1", "Notes", "").collection("2"))
+            .unwrap();
+        worker.shutdown().await;
+
+        let mut received = Vec::new();
+        while let Some(event) = events.recv().await {
+            received.push(event);
+        }
+        assert_eq!(received.len(), 2);
+        for event in received {
+            match event {
+                IngestionEvent::Completed {
+                    id,
+                    message,
+                    attempts,
+                    ..
+                } => {
+                    assert_eq!(id, url_job);
+                    assert_eq!(message, "Learned");
+                    assert_eq!(attempts, 2);
+                }
+                IngestionEvent::Failed {
+                    id,
+                    error,
+                    attempts,
+                    ..
+                } => {
+                    assert_eq!(id, text_job);
+                    assert_eq!(error.status(), Some(422));
+                    assert_eq!(attempts, 1);
+                }
+            }
+        }
+        unavailable.assert_async().await;
+        url.assert_async().await;
+        text.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_worker_stops_on_client_shutdown() {
+        let client = AGiXTSDK::new(Some("http://127.0.0.1:1".to_string()), None, false);
+        let (worker, mut events) = Worker::spawn(client.clone(), 1, 3);
+        worker.submit(Job::text("a1", "Notes", "one")).unwrap();
+        worker.submit(Job::text("a1", "Notes", "two")).unwrap();
+        client.shutdown(None).await.unwrap();
+
+        for _ in 0..2 {
+            match events.recv().await.unwrap() {
+                IngestionEvent::Failed {
+                    error, attempts, ..
+                } => {
+                    assert!(error.is_cancelled());
+                    assert_eq!(attempts, 0);
+                }
+                event => panic!("unexpected event {:?}", event),
+            }
+        }
+        assert!(worker.submit(Job::text("a1", "Notes", "three")).is_err());
+    }
+}
@@ -10,6 +10,8 @@
 //! - Type-safe request and response handling
 //! - Comprehensive error handling
 //! - ID-based resource management (agents, conversations, chains, prompts)
+//! - OpenAI-style client in [`openai_compat`] for code migrating from OpenAI
+//! - Runs in the browser when compiled for `wasm32-unknown-unknown`
 //!
 //! ## Example
 //!
@@ -30,8 +32,8 @@
 //!     println!("Available providers: {:?}", providers);
 //!
 //!     // Create a new agent and get its ID
-//!     let agent_result = client.add_agent("my_agent", None, None, None).await?;
-//!     let agent_id = agent_result["id"].as_str().unwrap();
+//!     let agent = client.add_agent("my_agent", None, None, None).await?;
+//!     let agent_id = agent.agent_id.as_str();
 //!     println!("Created agent with ID: {}", agent_id);
 //!
 //!     // Create a new conversation with the agent
@@ -56,16 +58,25 @@
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let client = AGiXTSDK::new(None, None, false);
 //!
-//!     // Register a new user
-//!     let otp_uri = client.register_user(
-//!         "user@example.com",
-//!         "John",
-//!         "Doe"
-//!     ).await?;
-//!     println!("Registration successful. OTP URI: {}", otp_uri);
-//!
-//!     // Login with email and OTP
-//!     if let Some(token) = client.login("user@example.com", "123456").await? {
+//!     // Register a new user; the returned token is used for later requests
+//!     let user = client
+//!         .register_user(
+//!             "user@example.com",
+//!             "correct-horse-battery",
+//!             "correct-horse-battery",
+//!             Some("John"),
+//!             Some("Doe"),
+//!             None,
+//!             None,
+//!         )
+//!         .await?;
+//!     println!("Registered user: {}", user["user_id"]);
+//!
+//!     // Log in with username and password, plus a TOTP code if MFA is enabled
+//!     if let Some(token) = client
+//!         .login("user@example.com", "correct-horse-battery", Some("123456"))
+//!         .await?
+//!     {
 //!         println!("Login successful! Token: {}", token);
 //!     }
 //!
@@ -110,14 +121,62 @@
 //! }
 //! ```
 
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
 pub mod client;
+pub mod diff;
 pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ingestion;
 pub mod models;
+pub mod openai_compat;
+pub mod prompt;
+pub mod secret;
+pub mod session;
+pub mod similarity;
+pub mod templates;
+#[cfg(all(feature = "test-harness", not(target_arch = "wasm32")))]
+pub mod test_harness;
+#[cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+pub mod testing;
+#[cfg(feature = "tokens")]
+pub mod tokens;
+#[cfg(feature = "totp")]
+pub mod totp;
+pub mod webhooks;
 
-pub use client::AGiXTSDK;
-pub use error::{Error, Result};
+#[cfg(feature = "tracing")]
+pub use client::TracingLogger;
+pub use client::{
+    AGiXTSDK, AGiXTSDKBuilder, AgentReply, AgixtApi, ApiVersion, ChannelLogger, Credentials,
+    DeserializeMode, Interceptor, MetricsObserver, Orchestration, OrchestrationMode,
+    OrchestrationResult, RateLimitInfo, RawClient, RequestMetrics, RequestOptions, RequestPriority,
+    ResponseLogger, StdoutLogger, StreamEvent, StreamEvents, ToolCallDelta, ToolRegistry,
+    TranscriptEntry, TranscriptSink, UsageSummary,
+};
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+pub use client::{ConversationEvent, EventStream};
+#[cfg(not(target_arch = "wasm32"))]
+pub use client::{FileLogger, FixtureMode, JsonlTranscript};
+pub use error::{
+    ApiErrorDetails, AuthErrorKind, Error, ErrorContext, FieldError, Result, TimeoutPhase,
+};
 pub use models::{
-    Agent, Chain, ChainStep, ChatCompletions, ChatResponse, Choice, Company, ContentPart,
-    Conversation, Extension, ExtensionCommand, FileUrl, ImageUrl, Message, MessageContent,
-    Prompt, Provider, Tool, ToolFunction, Usage, User,
+    Activity, ActivityStep, Agent, AgentChange, AgentConfig, AgentCreated, AgentSpec, Artifact,
+    ArtifactKind, Attachment, AudioUrl, BackupManifest, Chain, ChainProgress, ChainRun,
+    ChainRunStep, ChainStep, ChatCompletions, ChatCompletionsBuilder, ChatResponse, Choice,
+    Company, CompanyUser, ContentPart, Conversation, ConversationMetadata, ConversationSummary,
+    ConversationTurn, DownloadProgress, DpoTriple, Embedder, ExecuteCommand, ExecuteCommandBuilder,
+    ExportFormat, Extension, ExtensionCommand, FileUrl, FineTuneOptions, FleetPlan, FleetSpec,
+    FunctionCall, GeneratedImage, HistoryQuery, ImageFormat, ImageOptions, ImageUrl, Invitation,
+    JsonSchemaFormat, LoginOutcome, Memory, Message, MessageContent, MessageCreated, Notification,
+    Page, Prompt, PromptOptions, PromptSyncReport, Provider, ProviderDetails, ProviderFallback,
+    ProviderService, ProviderSetting, ResponseFormat, Role, ServerInfo, SessionState, SettingType,
+    SettingsValidation, SortOrder, SsoProvider, Task, TaskFrequency, TaskOutcome, Tool, ToolCall,
+    ToolFunction, Transcription, TranscriptionOptions, TranscriptionSegment, TranscriptionWord,
+    UploadSource, Usage, User, UserUpdate, UserWallet, VideoUrl, VoiceReply, Webhook,
+    WorkspaceFile,
 };
+pub use prompt::{to_args, PromptTemplate};
+pub use secret::{is_secret_setting, Secret};
+pub use templates::ConversationTemplate;
@@ -0,0 +1,224 @@
+//! Sample server responses for the endpoints read into a typed model.
+//!
+//! Each [`Fixture`] is a JSON body captured from an AGiXT server, with the
+//! method and path it answers. The SDK's tests check that the client reads
+//! every field of them, and that models which serialize write them back
+//! unchanged; downstream crates can serve them from a mock server, such as
+//! `testing::FakeAgixtServer`, to test their own integration code without a
+//! running AGiXT.
+//!
+//! Audio, image and streaming responses are not covered, nor are results the
+//! SDK assembles from several requests, such as `get_conversation_attachments`,
+//! `compare_conversations` or `export_account`.
+//!
+//! # Example
+//! ```rust
+//! use agixt_sdk::models::{fixtures, Agent};
+//!
+//! let mut body = fixtures::AGENTS.json();
+//! let agents: Vec<Agent> = serde_json::from_value(body["agents"].take()).unwrap();
+//! assert_eq!(agents[0].name, "AGiXT");
+//! ```
+
+/// A captured response body and the request it answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixture {
+    /// Identifier of the fixture, e.g. `agents`
+    pub name: &'static str,
+    /// HTTP method of the request, e.g. `GET`
+    pub method: &'static str,
+    /// Request path including the API version, e.g. `/v1/agent`
+    pub path: &'static str,
+    /// JSON response body
+    pub body: &'static str,
+}
+
+impl Fixture {
+    /// The response body parsed as JSON.
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(self.body).expect("fixtures are valid JSON")
+    }
+}
+
+/// Agent ID used throughout the fixtures.
+pub const AGENT_ID: &str = "5f1c2a9e-8d3b-4f6a-9c7e-2b4d6a8f0e13";
+/// Conversation ID used throughout the fixtures.
+pub const CONVERSATION_ID: &str = "1d3f5b7e-9a2c-4e6f-8b1d-3f5a7c9e1b3d";
+/// Company ID used throughout the fixtures.
+pub const COMPANY_ID: &str = "c0a8012e-4b5d-4e6f-8a9b-1c2d3e4f5a6b";
+/// Chain ID the `chain_runs` fixture answers for.
+pub const CHAIN_ID: &str = "6a8c0e2f-4b7d-4f9a-8c1e-5d7f9b1d3f5a";
+/// Run of [`CHAIN_ID`] the `chain_run` fixture answers for.
+pub const CHAIN_RUN_ID: &str = "b3d5f7a9-1c4e-4a6b-8d0f-2a4c6e8b0d2f";
+/// Task ID the `task` fixture answers for.
+pub const TASK_ID: &str = "f6b8d0e2-4a7c-4e9f-8b2d-5c7e9a1b3d5f";
+
+macro_rules! fixtures {
+    ($($(#[$doc:meta])* $const:ident => $name:literal, $method:literal, $path:expr;)*) => {
+        $(
+            $(#[$doc])*
+            pub const $const: Fixture = Fixture {
+                name: $name,
+                method: $method,
+                path: $path,
+                body: include_str!(concat!("fixtures/", $name, ".json")),
+            };
+        )*
+
+        /// Every fixture, in the order they are declared.
+        pub const ALL: &[Fixture] = &[$($const),*];
+    };
+}
+
+fixtures! {
+    /// `get_agents`
+    AGENTS => "agents", "GET", "/v1/agent";
+    /// `add_agent`
+    AGENT_CREATED => "agent_created", "POST", "/v1/agent";
+    /// `get_agentconfig` for [`AGENT_ID`]
+    AGENT_CONFIG => "agent_config", "GET",
+        "/v1/agent/5f1c2a9e-8d3b-4f6a-9c7e-2b4d6a8f0e13";
+    /// `get_agent_extensions` for [`AGENT_ID`], read by `get_extension_commands`
+    EXTENSIONS => "extensions", "GET",
+        "/v1/agent/5f1c2a9e-8d3b-4f6a-9c7e-2b4d6a8f0e13/extensions";
+    /// `get_commands` for [`AGENT_ID`], read by `get_extension_commands`
+    COMMANDS => "commands", "GET",
+        "/v1/agent/5f1c2a9e-8d3b-4f6a-9c7e-2b4d6a8f0e13/command";
+    /// `get_dpo_response` for [`AGENT_ID`]
+    DPO => "dpo", "POST",
+        "/v1/agent/5f1c2a9e-8d3b-4f6a-9c7e-2b4d6a8f0e13/dpo";
+    /// `list_workspace_files` for [`AGENT_ID`]
+    WORKSPACE_FILES => "workspace_files", "GET",
+        "/v1/agent/5f1c2a9e-8d3b-4f6a-9c7e-2b4d6a8f0e13/workspace";
+    /// `get_agent_memories` for [`AGENT_ID`]
+    AGENT_MEMORIES => "agent_memories", "POST",
+        "/v1/agent/5f1c2a9e-8d3b-4f6a-9c7e-2b4d6a8f0e13/memory/query";
+    /// `get_conversation` for [`CONVERSATION_ID`]
+    CONVERSATION => "conversation", "GET",
+        "/v1/conversation/1d3f5b7e-9a2c-4e6f-8b1d-3f5a7c9e1b3d";
+    /// `new_conversation_message` for [`CONVERSATION_ID`]
+    MESSAGE_CREATED => "message_created", "POST",
+        "/v1/conversation/1d3f5b7e-9a2c-4e6f-8b1d-3f5a7c9e1b3d/message";
+    /// `get_conversations` and `get_conversation_metadata`
+    CONVERSATIONS => "conversations", "GET", "/v1/conversations";
+    /// `get_notifications`
+    NOTIFICATIONS => "notifications", "GET", "/v1/notifications";
+    /// `get_user`
+    USER => "user", "GET", "/v1/user";
+    /// `get_user_wallet`
+    USER_WALLET => "user_wallet", "GET", "/v1/user/wallet";
+    /// `get_chain_runs` for [`CHAIN_ID`]
+    CHAIN_RUNS => "chain_runs", "GET",
+        "/v1/chain/6a8c0e2f-4b7d-4f9a-8c1e-5d7f9b1d3f5a/runs";
+    /// `get_chain_run` for [`CHAIN_ID`] and [`CHAIN_RUN_ID`]
+    CHAIN_RUN => "chain_run", "GET",
+        "/v1/chain/6a8c0e2f-4b7d-4f9a-8c1e-5d7f9b1d3f5a/runs/b3d5f7a9-1c4e-4a6b-8d0f-2a4c6e8b0d2f";
+    /// `get_companies`
+    COMPANIES => "companies", "GET", "/v1/companies";
+    /// `get_company` for [`COMPANY_ID`]
+    COMPANY => "company", "GET",
+        "/v1/companies/c0a8012e-4b5d-4e6f-8a9b-1c2d3e4f5a6b";
+    /// `get_company_agents` for [`COMPANY_ID`]
+    COMPANY_AGENTS => "company_agents", "GET",
+        "/v1/companies/c0a8012e-4b5d-4e6f-8a9b-1c2d3e4f5a6b/agents";
+    /// `get_company_users` for [`COMPANY_ID`]
+    COMPANY_USERS => "company_users", "GET",
+        "/v1/companies/c0a8012e-4b5d-4e6f-8a9b-1c2d3e4f5a6b/users";
+    /// `get_invitations` without a company
+    INVITATIONS => "invitations", "GET", "/v1/invitations";
+    /// `get_sso_providers`
+    SSO_PROVIDERS => "sso_providers", "GET", "/v1/oauth";
+    /// `server_info`
+    SERVER_INFO => "server_info", "GET", "/v1/server/info";
+    /// `get_tasks`
+    TASKS => "tasks", "GET", "/v1/tasks";
+    /// `get_task` for [`TASK_ID`]
+    TASK => "task", "GET", "/v1/task/f6b8d0e2-4a7c-4e9f-8b2d-5c7e9a1b3d5f";
+    /// `get_providers`, read by `get_embedders`
+    PROVIDERS => "providers", "GET", "/v1/provider";
+    /// `get_provider_settings` for `openai`
+    PROVIDER => "provider", "GET", "/v1/provider/openai";
+    /// `get_webhooks`
+    WEBHOOKS => "webhooks", "GET", "/v1/webhooks/outgoing";
+    /// `chat_completions`
+    CHAT_COMPLETION => "chat_completion", "POST", "/v1/chat/completions";
+}
+
+/// The fixture called `name`, if there is one.
+pub fn find(name: &str) -> Option<Fixture> {
+    ALL.iter().find(|fixture| fixture.name == name).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{DeserializeMode, Text};
+    use crate::models::*;
+    use serde::{de::DeserializeOwned, Serialize};
+
+    /// Read the model at `pointer` of the fixture's body in strict mode, so
+    /// fields the model doesn't know fail the test, and check that writing it
+    /// back gives the captured JSON.
+    fn round_trip<T: DeserializeOwned + Serialize>(fixture: Fixture, pointer: &str) {
+        let captured = fixture
+            .json()
+            .pointer(pointer)
+            .unwrap_or_else(|| panic!("{}: nothing at {}", fixture.name, pointer))
+            .clone();
+        let model: T = Text::new(captured.to_string(), None, DeserializeMode::Strict)
+            .json()
+            .unwrap_or_else(|e| panic!("{}: {}", fixture.name, e));
+        assert_eq!(
+            serde_json::to_value(&model).unwrap(),
+            captured,
+            "{} does not round-trip",
+            fixture.name
+        );
+    }
+
+    #[test]
+    fn test_fixtures_round_trip() {
+        round_trip::<Vec<Agent>>(AGENTS, "/agents");
+        round_trip::<AgentCreated>(AGENT_CREATED, "");
+        round_trip::<AgentConfig>(AGENT_CONFIG, "/agent");
+        round_trip::<DpoTriple>(DPO, "");
+        round_trip::<Vec<WorkspaceFile>>(WORKSPACE_FILES, "/files");
+        round_trip::<Vec<Memory>>(AGENT_MEMORIES, "/memories");
+        round_trip::<Vec<Message>>(CONVERSATION, "/conversation_history");
+        round_trip::<MessageCreated>(MESSAGE_CREATED, "");
+        round_trip::<Vec<ConversationMetadata>>(CONVERSATIONS, "/conversations");
+        round_trip::<Vec<Notification>>(NOTIFICATIONS, "/notifications");
+        round_trip::<User>(USER, "");
+        round_trip::<Vec<ChainRun>>(CHAIN_RUNS, "/runs");
+        round_trip::<ChainRun>(CHAIN_RUN, "/run");
+        round_trip::<Vec<Company>>(COMPANIES, "");
+        round_trip::<Company>(COMPANY, "");
+        round_trip::<Vec<Agent>>(COMPANY_AGENTS, "/agents");
+        round_trip::<Vec<CompanyUser>>(COMPANY_USERS, "/users");
+        round_trip::<Vec<Invitation>>(INVITATIONS, "/invitations");
+        round_trip::<Vec<SsoProvider>>(SSO_PROVIDERS, "/providers");
+        round_trip::<ServerInfo>(SERVER_INFO, "");
+        round_trip::<Vec<Task>>(TASKS, "/tasks");
+        round_trip::<Task>(TASK, "/task");
+        round_trip::<Vec<Webhook>>(WEBHOOKS, "");
+        round_trip::<ChatResponse>(CHAT_COMPLETION, "");
+    }
+
+    #[test]
+    fn test_fixture_table() {
+        for (i, fixture) in ALL.iter().enumerate() {
+            assert!(ALL[i + 1..].iter().all(|f| f.name != fixture.name));
+            assert!(fixture.path.starts_with("/v1/"));
+            fixture.json();
+        }
+        assert_eq!(find("tasks"), Some(TASKS));
+        assert!(AGENT_CONFIG.path.ends_with(AGENT_ID));
+        assert!(CONVERSATION.path.ends_with(CONVERSATION_ID));
+        assert!(COMPANY_USERS.path.contains(COMPANY_ID));
+        assert!(CHAIN_RUNS.path.contains(CHAIN_ID));
+        assert!(CHAIN_RUN.path.ends_with(CHAIN_RUN_ID));
+        assert!(TASK.path.ends_with(TASK_ID));
+        assert!(COMPANY.path.ends_with(COMPANY_ID));
+        assert!(find("unknown").is_none());
+    }
+}
@@ -1,7 +1,12 @@
 //! Model types for the AGiXT SDK.
 
+use crate::secret::{RedactedSettings, Secret};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
 
 /// Chat completion request for OpenAI-compatible API.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,14 +53,134 @@ pub struct ChatCompletions {
     /// The conversation ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
+    /// Format the answer must follow, e.g. JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+/// Sender of a message.
+///
+/// The standard roles are parsed case-insensitively, so the `USER` of stored
+/// conversation history is `Role::User`. Any other role is the name of the
+/// agent that wrote the message, as conversation history stores agent
+/// replies, and is kept as is. Defaults to `Assistant` where the server
+/// leaves the role out.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::Role;
+///
+/// assert_eq!(Role::from("USER"), Role::User);
+/// assert_eq!(Role::from("XT"), Role::Agent("XT".to_string()));
+/// assert_eq!(Role::from("XT").normalized(), Role::Assistant);
+/// assert_eq!(Role::Assistant, "assistant");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Role {
+    System,
+    User,
+    #[default]
+    Assistant,
+    /// Result of a tool call
+    Tool,
+    /// Reply of the agent with this name, in conversation history
+    Agent(String),
+}
+
+impl Role {
+    /// The role as sent to the server: `system`, `user`, `assistant`, `tool`
+    /// or the agent name.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+            Role::Agent(name) => name,
+        }
+    }
+
+    /// The role in chat-completion form, with agent replies as `Assistant`.
+    pub fn normalized(&self) -> Role {
+        match self {
+            Role::Agent(_) => Role::Assistant,
+            role => role.clone(),
+        }
+    }
+}
+
+impl From<&str> for Role {
+    fn from(role: &str) -> Self {
+        if role.eq_ignore_ascii_case("system") {
+            Role::System
+        } else if role.eq_ignore_ascii_case("user") {
+            Role::User
+        } else if role.eq_ignore_ascii_case("assistant") {
+            Role::Assistant
+        } else if role.eq_ignore_ascii_case("tool") {
+            Role::Tool
+        } else {
+            Role::Agent(role.to_string())
+        }
+    }
+}
+
+impl From<String> for Role {
+    fn from(role: String) -> Self {
+        match Role::from(role.as_str()) {
+            Role::Agent(_) => Role::Agent(role),
+            standard => standard,
+        }
+    }
+}
+
+impl From<&String> for Role {
+    fn from(role: &String) -> Self {
+        Role::from(role.as_str())
+    }
+}
+
+impl PartialEq<str> for Role {
+    fn eq(&self, other: &str) -> bool {
+        match self {
+            Role::Agent(name) => name == other,
+            role => role.as_str().eq_ignore_ascii_case(other),
+        }
+    }
+}
+
+impl PartialEq<&str> for Role {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Role {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Role {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Role::from(String::deserialize(deserializer)?))
+    }
 }
 
 /// Message in a chat conversation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Message {
-    /// The role of the message sender (user, assistant, system)
-    pub role: String,
-    /// The content of the message
+    /// The role of the message sender
+    pub role: Role,
+    /// The content of the message; empty text when the server sends `null`,
+    /// as it does for assistant messages that only call tools
+    #[serde(default, deserialize_with = "deserialize_content")]
     pub content: MessageContent,
     /// Optional message ID
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -63,10 +188,68 @@ pub struct Message {
     /// Optional timestamp
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamp: Option<String>,
+    /// Tools the assistant asked to call
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For `role: "tool"` messages, the ID of the call this message answers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+fn deserialize_content<'de, D>(deserializer: D) -> Result<MessageContent, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<MessageContent>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// A tool invocation requested by the model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    /// The type of tool (usually "function")
+    #[serde(rename = "type", default = "default_tool_type")]
+    pub call_type: String,
+    pub function: FunctionCall,
+}
+
+/// Function name and arguments of a tool call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    /// JSON-encoded arguments, as sent by the model
+    #[serde(default, deserialize_with = "deserialize_arguments")]
+    pub arguments: String,
+}
+
+impl FunctionCall {
+    /// Parse the arguments as JSON.
+    pub fn arguments_json(&self) -> crate::Result<serde_json::Value> {
+        if self.arguments.trim().is_empty() {
+            return Ok(serde_json::json!({}));
+        }
+        Ok(serde_json::from_str(&self.arguments)?)
+    }
+}
+
+/// Accept arguments either as a JSON-encoded string or as a JSON object.
+fn deserialize_arguments<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => s,
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    })
+}
+
+fn default_tool_type() -> String {
+    "function".to_string()
 }
 
 /// Content of a message, can be text or structured.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum MessageContent {
     /// Text content
@@ -76,7 +259,7 @@ pub enum MessageContent {
 }
 
 /// Part of structured message content.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ContentPart {
     /// Optional text content
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -87,20 +270,462 @@ pub struct ContentPart {
     /// Optional file URL
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_url: Option<FileUrl>,
+    /// Optional audio URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_url: Option<AudioUrl>,
+    /// Optional video URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video_url: Option<VideoUrl>,
+}
+
+impl Message {
+    /// Create a message with the given role.
+    pub fn new(role: impl Into<Role>, content: impl Into<MessageContent>) -> Self {
+        Self {
+            role: role.into(),
+            content: content.into(),
+            id: None,
+            timestamp: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    /// Create a `role: "tool"` message carrying the result of a tool call.
+    pub fn tool(tool_call_id: impl Into<String>, content: impl Into<MessageContent>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::new(Role::Tool, content)
+        }
+    }
+
+    /// Create a user message from text or content parts.
+    ///
+    /// # Example
+    /// ```rust
+    /// use agixt_sdk::{ContentPart, Message};
+    ///
+    /// let question = Message::user("What is in this image?")
+    ///     .with_part(ContentPart::image_from_bytes("image/png", b"..."));
+    /// let instructions = Message::system("Answer in one sentence.");
+    /// # let _ = (question, instructions);
+    /// ```
+    pub fn user(content: impl Into<MessageContent>) -> Self {
+        Self::new(Role::User, content)
+    }
+
+    /// Create a system message.
+    pub fn system(content: impl Into<MessageContent>) -> Self {
+        Self::new(Role::System, content)
+    }
+
+    /// Create an assistant message.
+    pub fn assistant(content: impl Into<MessageContent>) -> Self {
+        Self::new(Role::Assistant, content)
+    }
+
+    /// The role in chat-completion form: `user`, `assistant`, `system` or `tool`.
+    ///
+    /// Conversation history stores user messages as `USER` and agent replies
+    /// under the agent's name; any role that is not a standard one is treated
+    /// as the assistant.
+    pub fn normalized_role(&self) -> &str {
+        match self.role {
+            Role::Agent(_) => "assistant",
+            ref role => role.as_str(),
+        }
+    }
+
+    /// The message's text, joining the text parts of structured content.
+    pub fn text(&self) -> String {
+        match &self.content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Structured(parts) => parts
+                .iter()
+                .filter_map(|part| part.text.as_deref())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    /// Append a content part, turning text content into structured content.
+    pub fn with_part(mut self, part: ContentPart) -> Self {
+        match &mut self.content {
+            MessageContent::Structured(parts) => parts.push(part),
+            MessageContent::Text(text) => {
+                let text = ContentPart::text(std::mem::take(text));
+                self.content = MessageContent::Structured(vec![text, part]);
+            }
+        }
+        self
+    }
+}
+
+/// Convert stored conversation history (roles `USER` and the agent's name) into
+/// chat-completion messages (roles `user` and `assistant`).
+///
+/// Activity log entries (`[ACTIVITY]`, `[SUBACTIVITY]`) are dropped, as are
+/// message IDs and timestamps, so the result can be replayed through
+/// `chat_completions`.
+pub fn history_to_messages(history: &[Message]) -> Vec<Message> {
+    history
+        .iter()
+        .filter(|message| !is_activity(message))
+        .map(|message| Message {
+            id: None,
+            timestamp: None,
+            ..Message::new(message.normalized_role(), message.content.clone())
+        })
+        .collect()
+}
+
+/// Convert chat-completion messages back into conversation history, as accepted
+/// by `new_conversation`. Assistant messages are attributed to `agent_name`.
+pub fn messages_to_history(messages: &[Message], agent_name: &str) -> Vec<Message> {
+    messages
+        .iter()
+        .map(|message| {
+            let role = match message.role.normalized() {
+                Role::Assistant => Role::Agent(agent_name.to_string()),
+                role => role,
+            };
+            Message {
+                role,
+                ..message.clone()
+            }
+        })
+        .collect()
+}
+
+/// One exchange of a conversation: the user's message, the activities the
+/// agent logged while working on it and its final answer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConversationTurn {
+    /// The user message that started the turn; `None` for agent messages
+    /// logged before any user input
+    pub prompt: Option<Message>,
+    /// Activities in the order they were logged
+    pub activities: Vec<Activity>,
+    /// The agent's final answer, if it has been written yet
+    pub answer: Option<Message>,
+}
+
+/// An `[ACTIVITY]` entry with the `[SUBACTIVITY]` steps logged under it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Activity {
+    /// Message ID, which subactivities use to refer to their activity
+    pub id: Option<String>,
+    /// Tag following the marker, e.g. `ERROR` in `[ACTIVITY][ERROR]`
+    pub tag: Option<String>,
+    /// The activity text without markers
+    pub text: String,
+    /// When the activity was logged
+    pub timestamp: Option<String>,
+    /// Subactivities in the order they were logged
+    pub steps: Vec<ActivityStep>,
+}
+
+/// A `[SUBACTIVITY]` entry.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ActivityStep {
+    /// Message ID
+    pub id: Option<String>,
+    /// Tag following the parent reference, e.g. `EXECUTION` in
+    /// `[SUBACTIVITY][<id>][EXECUTION]`
+    pub tag: Option<String>,
+    /// The step text without markers
+    pub text: String,
+    /// When the step was logged
+    pub timestamp: Option<String>,
+}
+
+impl Activity {
+    /// Whether the activity or one of its steps reports an error.
+    pub fn has_error(&self) -> bool {
+        is_error_tag(&self.tag) || self.steps.iter().any(|step| is_error_tag(&step.tag))
+    }
+}
+
+fn is_error_tag(tag: &Option<String>) -> bool {
+    tag.as_deref()
+        .is_some_and(|tag| tag.eq_ignore_ascii_case("error"))
+}
+
+/// Group stored conversation history into turns of user prompt, activities
+/// and final answer, e.g. to render collapsible progress views.
+///
+/// `[SUBACTIVITY][<id>]` entries are attached to the activity with that
+/// message ID, falling back to the latest activity of the turn; a subactivity
+/// logged before any activity becomes an activity of its own. An agent message after the
+/// answer starts a new turn without a prompt.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::models::parse_activities;
+/// use agixt_sdk::Message;
+///
+/// let history = vec![
+///     Message::new("USER", "What's the weather in Lisbon?"),
+///     Message::new("XT", "[ACTIVITY] Searching the web"),
+///     Message::new("XT", "[SUBACTIVITY][EXECUTION] Fetched 3 results"),
+///     Message::new("XT", "Sunny, 24°C."),
+/// ];
+/// let turns = parse_activities(&history);
+/// assert_eq!(turns.len(), 1);
+/// assert_eq!(turns[0].activities[0].text, "Searching the web");
+/// assert_eq!(turns[0].activities[0].steps[0].tag.as_deref(), Some("EXECUTION"));
+/// assert_eq!(turns[0].answer.as_ref().unwrap().text(), "Sunny, 24°C.");
+/// ```
+pub fn parse_activities(history: &[Message]) -> Vec<ConversationTurn> {
+    let mut turns: Vec<ConversationTurn> = Vec::new();
+    for message in history {
+        let text = match &message.content {
+            MessageContent::Text(text) => text.trim_start(),
+            MessageContent::Structured(_) => "",
+        };
+        if message.normalized_role() == "user" {
+            turns.push(ConversationTurn {
+                prompt: Some(message.clone()),
+                ..ConversationTurn::default()
+            });
+            continue;
+        }
+        if turns.last().is_none_or(|turn| turn.answer.is_some()) {
+            turns.push(ConversationTurn::default());
+        }
+        let turn = turns.last_mut().expect("a turn was just pushed");
+
+        if let Some(rest) = text.strip_prefix("[SUBACTIVITY]") {
+            let (parent, rest) = match bracketed(rest) {
+                Some((parent, rest)) if !is_tag(parent) => (Some(parent), rest),
+                _ => (None, rest),
+            };
+            let (tag, text) = split_tag(rest);
+            let step = ActivityStep {
+                id: message.id.clone(),
+                tag,
+                text,
+                timestamp: message.timestamp.clone(),
+            };
+            let index = turn
+                .activities
+                .iter()
+                .rposition(|a| parent.is_some() && a.id.as_deref() == parent)
+                .or_else(|| turn.activities.len().checked_sub(1));
+            let activity = index.map(|index| &mut turn.activities[index]);
+            match activity {
+                Some(activity) => activity.steps.push(step),
+                None => turn.activities.push(Activity {
+                    id: step.id,
+                    tag: step.tag,
+                    text: step.text,
+                    timestamp: step.timestamp,
+                    steps: Vec::new(),
+                }),
+            }
+        } else if let Some(rest) = text.strip_prefix("[ACTIVITY]") {
+            let (tag, text) = split_tag(rest);
+            turn.activities.push(Activity {
+                id: message.id.clone(),
+                tag,
+                text,
+                timestamp: message.timestamp.clone(),
+                steps: Vec::new(),
+            });
+        } else {
+            turn.answer = Some(message.clone());
+        }
+    }
+    turns
+}
+
+/// Split a leading `[...]` off `text`, returning its contents and the rest.
+fn bracketed(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix('[')?;
+    let end = rest.find(']')?;
+    Some((&rest[..end], &rest[end + 1..]))
+}
+
+/// Split an optional leading tag such as `[ERROR]` off an activity text.
+fn split_tag(text: &str) -> (Option<String>, String) {
+    match bracketed(text) {
+        Some((tag, rest)) if is_tag(tag) => (Some(tag.to_string()), rest.trim().to_string()),
+        _ => (None, text.trim().to_string()),
+    }
+}
+
+/// Whether bracket contents are a tag such as `ERROR` rather than a message ID.
+fn is_tag(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_uppercase() || c == '_')
+}
+
+fn is_activity(message: &Message) -> bool {
+    match &message.content {
+        MessageContent::Text(text) => {
+            text.starts_with("[ACTIVITY]") || text.starts_with("[SUBACTIVITY]")
+        }
+        MessageContent::Structured(_) => false,
+    }
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        MessageContent::Text(String::new())
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Text(text)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        MessageContent::Text(text.to_string())
+    }
+}
+
+impl From<Vec<ContentPart>> for MessageContent {
+    fn from(parts: Vec<ContentPart>) -> Self {
+        MessageContent::Structured(parts)
+    }
+}
+
+impl ContentPart {
+    /// A text part.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Self::default()
+        }
+    }
+
+    /// An image referenced by URL (http(s) or `data:` URI).
+    pub fn image_url(url: impl Into<String>) -> Self {
+        Self {
+            image_url: Some(ImageUrl { url: url.into() }),
+            ..Self::default()
+        }
+    }
+
+    /// An image embedded as a base64 data URI.
+    pub fn image_from_bytes(mime_type: &str, bytes: &[u8]) -> Self {
+        Self::image_url(data_uri(mime_type, bytes))
+    }
+
+    /// An image file embedded as a base64 data URI, with the MIME type guessed
+    /// from its extension.
+    pub fn image_from_path(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        Ok(Self::image_from_bytes(
+            mime_type_for(path),
+            &read_file(path)?,
+        ))
+    }
+
+    /// A file referenced by URL (http(s) or `data:` URI).
+    pub fn file_url(url: impl Into<String>) -> Self {
+        Self {
+            file_url: Some(FileUrl { url: url.into() }),
+            ..Self::default()
+        }
+    }
+
+    /// A file embedded as a base64 data URI, with the MIME type guessed from
+    /// `file_name`'s extension.
+    pub fn file_from_bytes(file_name: &str, bytes: &[u8]) -> Self {
+        Self::file_url(data_uri(
+            mime_type_for(std::path::Path::new(file_name)),
+            bytes,
+        ))
+    }
+
+    /// A file read from disk and embedded as a base64 data URI.
+    pub fn file_from_path(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        Ok(Self::file_url(data_uri(
+            mime_type_for(path),
+            &read_file(path)?,
+        )))
+    }
+
+    /// An audio clip, such as a voice note, referenced by URL (http(s) or `data:` URI).
+    pub fn audio_url(url: impl Into<String>) -> Self {
+        Self {
+            audio_url: Some(AudioUrl { url: url.into() }),
+            ..Self::default()
+        }
+    }
+
+    /// An audio clip embedded as a base64 data URI.
+    pub fn audio_from_bytes(mime_type: &str, bytes: &[u8]) -> Self {
+        Self::audio_url(data_uri(mime_type, bytes))
+    }
+
+    /// An audio file embedded as a base64 data URI, with the MIME type guessed
+    /// from its extension.
+    pub fn audio_from_path(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        Ok(Self::audio_from_bytes(
+            mime_type_for(path),
+            &read_file(path)?,
+        ))
+    }
+
+    /// A video clip referenced by URL (http(s) or `data:` URI).
+    pub fn video_url(url: impl Into<String>) -> Self {
+        Self {
+            video_url: Some(VideoUrl { url: url.into() }),
+            ..Self::default()
+        }
+    }
+
+    /// A video clip embedded as a base64 data URI.
+    pub fn video_from_bytes(mime_type: &str, bytes: &[u8]) -> Self {
+        Self::video_url(data_uri(mime_type, bytes))
+    }
+
+    /// A video file embedded as a base64 data URI, with the MIME type guessed
+    /// from its extension.
+    pub fn video_from_path(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        Ok(Self::video_from_bytes(
+            mime_type_for(path),
+            &read_file(path)?,
+        ))
+    }
+}
+
+fn read_file(path: &std::path::Path) -> crate::Result<Vec<u8>> {
+    std::fs::read(path)
+        .map_err(|e| crate::Error::InvalidInput(format!("cannot read {}: {}", path.display(), e)))
 }
 
 /// Image URL reference.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImageUrl {
     pub url: String,
 }
 
 /// File URL reference.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileUrl {
     pub url: String,
 }
 
+/// Audio URL reference.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioUrl {
+    pub url: String,
+}
+
+/// Video URL reference.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoUrl {
+    pub url: String,
+}
+
 /// Tool definition for function calling.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tool {
@@ -122,6 +747,56 @@ pub struct ToolFunction {
     pub parameters: serde_json::Value,
 }
 
+/// Format a chat completion answer must follow, sent as `response_format`.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::ResponseFormat;
+///
+/// let format = ResponseFormat::json_schema(
+///     "city",
+///     serde_json::json!({
+///         "type": "object",
+///         "properties": {"name": {"type": "string"}},
+///         "required": ["name"]
+///     }),
+/// );
+/// assert_eq!(serde_json::to_value(&format).unwrap()["type"], "json_schema");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Free text, the default
+    Text,
+    /// Any valid JSON object
+    JsonObject,
+    /// JSON matching a schema
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+impl ResponseFormat {
+    /// Require JSON matching `schema`, enforced strictly by providers that
+    /// support it.
+    pub fn json_schema(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        ResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: name.into(),
+                schema,
+                strict: Some(true),
+            },
+        }
+    }
+}
+
+/// Named JSON schema of a [`ResponseFormat::JsonSchema`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub schema: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
 fn default_model() -> String {
     "gpt4free".to_string()
 }
@@ -143,7 +818,255 @@ impl Default for ChatCompletions {
             frequency_penalty: Some(0.0),
             logit_bias: None,
             user: Some("Chat".to_string()),
+            response_format: None,
+        }
+    }
+}
+
+impl ChatCompletions {
+    /// Start building a request. Ranges are validated by
+    /// [`ChatCompletionsBuilder::build`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use agixt_sdk::ChatCompletions;
+    ///
+    /// let request = ChatCompletions::builder()
+    ///     .model("my_agent")
+    ///     .message("system", "Answer briefly.")
+    ///     .message("user", "What is AGiXT?")
+    ///     .temperature(0.2)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(request.messages.unwrap().len(), 2);
+    /// ```
+    pub fn builder() -> ChatCompletionsBuilder {
+        ChatCompletionsBuilder::default()
+    }
+}
+
+/// Fluent builder for [`ChatCompletions`], starting from its defaults.
+#[derive(Debug, Clone, Default)]
+pub struct ChatCompletionsBuilder {
+    request: ChatCompletions,
+    messages: Vec<Message>,
+    error: Option<String>,
+}
+
+impl ChatCompletionsBuilder {
+    /// Set the model, i.e. the agent name.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.request.model = model.into();
+        self
+    }
+
+    /// Append a text message.
+    pub fn message(mut self, role: impl Into<Role>, text: impl Into<String>) -> Self {
+        self.messages.push(Message::new(role, text.into()));
+        self
+    }
+
+    /// Append a prepared message.
+    pub fn push_message(mut self, message: Message) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    /// Append stored conversation history, converted with [`history_to_messages`].
+    pub fn history(mut self, history: &[Message]) -> Self {
+        self.messages.extend(history_to_messages(history));
+        self
+    }
+
+    /// Attach an image file to the last message as a base64 data URI, starting a
+    /// user message if there is none. Read errors are reported by `build`.
+    pub fn image(self, path: impl AsRef<std::path::Path>) -> Self {
+        self.attach(ContentPart::image_from_path(path))
+    }
+
+    /// Attach an audio file, such as a voice note, to the last message.
+    pub fn audio(self, path: impl AsRef<std::path::Path>) -> Self {
+        self.attach(ContentPart::audio_from_path(path))
+    }
+
+    /// Attach a video file to the last message.
+    pub fn video(self, path: impl AsRef<std::path::Path>) -> Self {
+        self.attach(ContentPart::video_from_path(path))
+    }
+
+    fn attach(mut self, part: crate::Result<ContentPart>) -> Self {
+        match part {
+            Ok(part) => self.push_part(part),
+            Err(e) => {
+                self.error.get_or_insert_with(|| e.to_string());
+            }
+        }
+        self
+    }
+
+    /// Set the sampling temperature (0.0 to 2.0).
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.request.temperature = Some(temperature);
+        self
+    }
+
+    /// Set nucleus sampling (0.0 to 1.0).
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.request.top_p = Some(top_p);
+        self
+    }
+
+    /// Set the maximum number of tokens to generate.
+    pub fn max_tokens(mut self, max_tokens: i32) -> Self {
+        self.request.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the number of completions to generate (at least 1).
+    pub fn n(mut self, n: i32) -> Self {
+        self.request.n = Some(n);
+        self
+    }
+
+    /// Add a stop sequence.
+    pub fn stop(mut self, sequence: impl Into<String>) -> Self {
+        self.request
+            .stop
+            .get_or_insert_with(Vec::new)
+            .push(sequence.into());
+        self
+    }
+
+    /// Offer a tool to the model.
+    pub fn tool(mut self, tool: Tool) -> Self {
+        self.request.tools.get_or_insert_with(Vec::new).push(tool);
+        self
+    }
+
+    /// Set how the model chooses tools, e.g. "auto" or "none".
+    pub fn tools_choice(mut self, choice: impl Into<String>) -> Self {
+        self.request.tools_choice = Some(choice.into());
+        self
+    }
+
+    /// Request a streamed response.
+    pub fn stream(mut self, stream: bool) -> Self {
+        self.request.stream = Some(stream);
+        self
+    }
+
+    /// Set the presence penalty (-2.0 to 2.0).
+    pub fn presence_penalty(mut self, penalty: f32) -> Self {
+        self.request.presence_penalty = Some(penalty);
+        self
+    }
+
+    /// Set the frequency penalty (-2.0 to 2.0).
+    pub fn frequency_penalty(mut self, penalty: f32) -> Self {
+        self.request.frequency_penalty = Some(penalty);
+        self
+    }
+
+    /// Set the bias of a token.
+    pub fn logit_bias(mut self, token: impl Into<String>, bias: f32) -> Self {
+        self.request
+            .logit_bias
+            .get_or_insert_with(HashMap::new)
+            .insert(token.into(), bias);
+        self
+    }
+
+    /// Require the answer in a format such as JSON.
+    pub fn response_format(mut self, format: ResponseFormat) -> Self {
+        self.request.response_format = Some(format);
+        self
+    }
+
+    /// Set the conversation the completion belongs to.
+    pub fn conversation(mut self, conversation: impl Into<String>) -> Self {
+        self.request.user = Some(conversation.into());
+        self
+    }
+
+    /// Validate the parameters and build the request.
+    pub fn build(self) -> crate::Result<ChatCompletions> {
+        if let Some(error) = self.error {
+            return Err(crate::Error::InvalidInput(error));
         }
+        let mut request = self.request;
+        if request.model.trim().is_empty() {
+            return Err(crate::Error::InvalidInput("model must not be empty".into()));
+        }
+        check_range("temperature", request.temperature, 0.0, 2.0)?;
+        check_range("top_p", request.top_p, 0.0, 1.0)?;
+        check_range("presence_penalty", request.presence_penalty, -2.0, 2.0)?;
+        check_range("frequency_penalty", request.frequency_penalty, -2.0, 2.0)?;
+        if matches!(request.n, Some(n) if n < 1) {
+            return Err(crate::Error::InvalidInput("n must be at least 1".into()));
+        }
+        if matches!(request.max_tokens, Some(max) if max < 1) {
+            return Err(crate::Error::InvalidInput(
+                "max_tokens must be at least 1".into(),
+            ));
+        }
+        if !self.messages.is_empty() {
+            request.messages = Some(self.messages);
+        }
+        Ok(request)
+    }
+
+    fn push_part(&mut self, part: ContentPart) {
+        match self.messages.pop() {
+            Some(message) => self.messages.push(message.with_part(part)),
+            None => self.messages.push(Message::user(vec![part])),
+        }
+    }
+}
+
+fn check_range(name: &str, value: Option<f32>, min: f32, max: f32) -> crate::Result<()> {
+    match value {
+        Some(v) if !(min..=max).contains(&v) => Err(crate::Error::InvalidInput(format!(
+            "{} must be between {} and {}, got {}",
+            name, min, max, v
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Encode bytes as a base64 `data:` URI.
+fn data_uri(mime_type: &str, bytes: &[u8]) -> String {
+    use base64::Engine;
+    format!(
+        "data:{};base64,{}",
+        mime_type,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    )
+}
+
+/// MIME type guessed from a file extension.
+fn mime_type_for(path: &std::path::Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("pdf") => "application/pdf",
+        Some("txt") => "text/plain",
+        Some("json") => "application/json",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("ogg" | "oga" | "opus") => "audio/ogg",
+        Some("m4a") => "audio/mp4",
+        Some("flac") => "audio/flac",
+        Some("mp4" | "m4v") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mov") => "video/quicktime",
+        _ => "application/octet-stream",
     }
 }
 
@@ -169,15 +1092,16 @@ pub struct Choice {
 }
 
 /// Token usage in chat completion response.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Usage {
     pub prompt_tokens: i32,
     pub completion_tokens: i32,
     pub total_tokens: i32,
 }
 
-/// Agent configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Agent as listed by `get_agents`. `Debug` output redacts credential
+/// settings such as API keys.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Agent {
     pub id: String,
     pub name: String,
@@ -185,6 +1109,704 @@ pub struct Agent {
     pub settings: HashMap<String, serde_json::Value>,
     #[serde(default)]
     pub commands: HashMap<String, serde_json::Value>,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Full configuration of an agent, as returned by `get_agentconfig`. `Debug`
+/// output redacts credential settings such as API keys.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AgentConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub settings: HashMap<String, serde_json::Value>,
+    /// Command name to enabled flag
+    #[serde(default)]
+    pub commands: HashMap<String, serde_json::Value>,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl fmt::Debug for Agent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Agent")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("settings", &RedactedSettings(&self.settings))
+            .field("commands", &self.commands)
+            .field("extra", &RedactedSettings(&self.extra))
+            .finish()
+    }
+}
+
+impl fmt::Debug for AgentConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AgentConfig")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("settings", &RedactedSettings(&self.settings))
+            .field("commands", &self.commands)
+            .field("extra", &RedactedSettings(&self.extra))
+            .finish()
+    }
+}
+
+impl AgentConfig {
+    /// Look up a setting as a string.
+    pub fn setting(&self, key: &str) -> Option<&str> {
+        self.settings.get(key).and_then(|v| v.as_str())
+    }
+
+    /// Look up a credential setting such as `OPENAI_API_KEY`, wrapped so it
+    /// stays out of logs. Empty values are treated as unset.
+    pub fn secret_setting(&self, key: &str) -> Option<Secret<String>> {
+        self.setting(key)
+            .filter(|value| !value.is_empty())
+            .map(|value| Secret::new(value.to_string()))
+    }
+
+    /// Whether a command is enabled for the agent.
+    pub fn command_enabled(&self, command: &str) -> bool {
+        match self.commands.get(command) {
+            Some(serde_json::Value::Bool(enabled)) => *enabled,
+            Some(serde_json::Value::String(enabled)) => enabled.eq_ignore_ascii_case("true"),
+            _ => false,
+        }
+    }
+
+    /// The agent's provider and fallbacks, if a provider is set.
+    pub fn provider_fallback(&self) -> Option<ProviderFallback> {
+        ProviderFallback::from_settings(&self.settings)
+    }
+}
+
+/// Which providers an agent uses: `primary` normally, then each of
+/// `fallbacks` in order when a request to the previous one fails.
+///
+/// Stored in the agent settings `provider`, `FALLBACK_PROVIDERS` (a
+/// comma-separated list) and `ROTATE_PROVIDERS_ON_ERROR`; apply it with
+/// `set_provider_fallback`.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::ProviderFallback;
+///
+/// let fallback = ProviderFallback::new("openai")
+///     .fallback("anthropic")
+///     .fallback("ezlocalai")
+///     .rotate_on_error(true);
+/// let settings = fallback.to_settings();
+/// assert_eq!(settings["FALLBACK_PROVIDERS"], "anthropic,ezlocalai");
+/// assert_eq!(ProviderFallback::from_settings(&settings), Some(fallback));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderFallback {
+    pub primary: String,
+    #[serde(default)]
+    pub fallbacks: Vec<String>,
+    /// Keep using the provider that answered after a failure instead of
+    /// returning to `primary` for the next request
+    #[serde(default)]
+    pub rotate_on_error: bool,
+}
+
+impl ProviderFallback {
+    /// Use `primary` without fallbacks.
+    pub fn new(primary: impl Into<String>) -> Self {
+        Self {
+            primary: primary.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Append a provider to try when the previous ones fail.
+    pub fn fallback(mut self, provider: impl Into<String>) -> Self {
+        self.fallbacks.push(provider.into());
+        self
+    }
+
+    /// Set whether to stay on a fallback once it has answered.
+    pub fn rotate_on_error(mut self, rotate: bool) -> Self {
+        self.rotate_on_error = rotate;
+        self
+    }
+
+    /// Every provider in order of preference.
+    pub fn providers(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.primary.as_str()).chain(self.fallbacks.iter().map(String::as_str))
+    }
+
+    /// Read the fallback from agent settings; `None` without a provider.
+    pub fn from_settings(settings: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        let text = |key| {
+            settings
+                .get(key)
+                .and_then(|v: &serde_json::Value| v.as_str())
+        };
+        let primary = text("provider").filter(|p| !p.is_empty())?;
+        let fallbacks = text("FALLBACK_PROVIDERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect();
+        let rotate_on_error = match settings.get("ROTATE_PROVIDERS_ON_ERROR") {
+            Some(serde_json::Value::Bool(rotate)) => *rotate,
+            Some(serde_json::Value::String(rotate)) => rotate.eq_ignore_ascii_case("true"),
+            _ => false,
+        };
+        Some(Self {
+            primary: primary.to_string(),
+            fallbacks,
+            rotate_on_error,
+        })
+    }
+
+    /// The agent settings storing this fallback.
+    pub fn to_settings(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::from([
+            ("provider".to_string(), self.primary.clone().into()),
+            (
+                "FALLBACK_PROVIDERS".to_string(),
+                self.fallbacks.join(",").into(),
+            ),
+            (
+                "ROTATE_PROVIDERS_ON_ERROR".to_string(),
+                self.rotate_on_error.to_string().into(),
+            ),
+        ])
+    }
+}
+
+/// Desired state of a set of agents, applied with `apply_fleet`.
+///
+/// The spec is plain serde data, so it can be kept in JSON, or in TOML or YAML
+/// with the matching serde crate.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::FleetSpec;
+///
+/// let spec: FleetSpec = serde_json::from_str(r#"{"agents": [{
+///     "name": "Support",
+///     "settings": {"provider": "openai", "AI_MODEL": "gpt-4o"},
+///     "commands": {"Web Search": true},
+///     "training_urls": ["https://docs.example.com"]
+/// }]}"#).unwrap();
+/// assert_eq!(spec.agents[0].name, "Support");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FleetSpec {
+    #[serde(default)]
+    pub agents: Vec<AgentSpec>,
+}
+
+/// Desired state of one agent in a `FleetSpec`. Settings and commands the
+/// spec leaves out are not touched.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentSpec {
+    pub name: String,
+    #[serde(default)]
+    pub settings: HashMap<String, serde_json::Value>,
+    /// Command name to enabled flag
+    #[serde(default)]
+    pub commands: HashMap<String, bool>,
+    /// Pages the agent learns when it is created
+    #[serde(default)]
+    pub training_urls: Vec<String>,
+}
+
+impl fmt::Debug for AgentSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AgentSpec")
+            .field("name", &self.name)
+            .field("settings", &RedactedSettings(&self.settings))
+            .field("commands", &self.commands)
+            .field("training_urls", &self.training_urls)
+            .finish()
+    }
+}
+
+/// What `apply_fleet` changed, or would change in a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FleetPlan {
+    /// Whether the server was left untouched
+    pub dry_run: bool,
+    pub changes: Vec<AgentChange>,
+}
+
+impl FleetPlan {
+    /// Whether the server already matched the spec.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// One change in a `FleetPlan`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AgentChange {
+    /// The agent does not exist and is created with the spec
+    Create { agent: String },
+    /// The settings named by `keys` differ from the spec
+    UpdateSettings { agent: String, keys: Vec<String> },
+    /// The commands are enabled or disabled differently than the spec says
+    UpdateCommands {
+        agent: String,
+        commands: Vec<String>,
+    },
+}
+
+/// Result of creating or importing an agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentCreated {
+    #[serde(alias = "id")]
+    pub agent_id: String,
+    #[serde(default, alias = "name", skip_serializing_if = "Option::is_none")]
+    pub agent_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Result of adding a message to a conversation with `new_conversation_message`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageCreated {
+    /// ID of the new message, used to update, delete or give feedback on it
+    #[serde(alias = "message_id")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Memory stored by an agent, as returned by memory queries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Memory {
+    #[serde(default)]
+    pub id: String,
+    /// Remembered text
+    #[serde(default, alias = "additional_metadata", alias = "content")]
+    pub text: String,
+    /// Key of the embedding the memory is stored under
+    #[serde(default, alias = "key", skip_serializing_if = "Option::is_none")]
+    pub embedding_id: Option<String>,
+    /// Where the memory was learned from, such as a URL or file name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_source_name: Option<String>,
+    /// Similarity to the query, from 0.0 to 1.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relevance_score: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// File format for exports written to disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A single JSON array
+    #[default]
+    Json,
+    /// One JSON object per line (JSON Lines)
+    Jsonl,
+}
+
+/// Preference pair for DPO fine-tuning, as returned by `get_dpo_response`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DpoTriple {
+    pub prompt: String,
+    /// Preferred answer
+    pub chosen: String,
+    /// Answer to train away from
+    pub rejected: String,
+}
+
+/// Order of the messages returned by `get_conversation_with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// In the order they were written
+    #[default]
+    OldestFirst,
+    /// Latest message first
+    NewestFirst,
+}
+
+/// Page, order and role filter for `get_conversation_with`.
+///
+/// The order and role filter apply to the messages of the requested page.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::HistoryQuery;
+///
+/// let query = HistoryQuery::new().limit(20).newest_first().role("user");
+/// assert_eq!(query.limit, Some(20));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    /// Messages per page (the server defaults to 100)
+    pub limit: Option<i32>,
+    /// 1-based page number
+    pub page: Option<i32>,
+    pub order: SortOrder,
+    /// Roles to keep, compared case-insensitively with both the stored role
+    /// and `Message::normalized_role`; empty keeps every message
+    pub roles: Vec<String>,
+}
+
+impl HistoryQuery {
+    /// Query the first page, oldest message first, without a role filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request `limit` messages per page.
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Request the 1-based `page`.
+    pub fn page(mut self, page: i32) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    /// Return the latest message first.
+    pub fn newest_first(mut self) -> Self {
+        self.order = SortOrder::NewestFirst;
+        self
+    }
+
+    /// Keep messages with `role`, e.g. `user`, `assistant` or an agent name.
+    /// Can be called repeatedly to keep several roles.
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.roles.push(role.into());
+        self
+    }
+
+    /// Apply the role filter and order to a page of history.
+    pub(crate) fn apply(&self, mut messages: Vec<Message>) -> Vec<Message> {
+        if !self.roles.is_empty() {
+            messages.retain(|message| {
+                self.roles.iter().any(|role| {
+                    role.eq_ignore_ascii_case(message.role.as_str())
+                        || role.eq_ignore_ascii_case(message.normalized_role())
+                })
+            });
+        }
+        if self.order == SortOrder::NewestFirst {
+            messages.reverse();
+        }
+        messages
+    }
+}
+
+/// How conversations and DPO triples are turned into OpenAI-style JSONL
+/// fine-tuning examples.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::{FineTuneOptions, Message};
+///
+/// let options = FineTuneOptions::new()
+///     .system_prompt("You are a support agent.")
+///     .role("Support Bot", "assistant");
+/// let example = options
+///     .chat_example(&[
+///         Message::new("USER", "Reset my password"),
+///         Message::new("Support Bot", "Done."),
+///     ])
+///     .unwrap();
+/// assert_eq!(example["messages"][0]["role"], "system");
+/// assert_eq!(example["messages"][2]["content"], "Done.");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FineTuneOptions {
+    /// System message put at the start of every example
+    pub system_prompt: Option<String>,
+    /// Stored role (compared case-insensitively) to training role; roles
+    /// mapped to an empty string are left out
+    pub roles: HashMap<String, String>,
+}
+
+impl FineTuneOptions {
+    /// Create options that map roles with `Message::normalized_role` and add
+    /// no system prompt.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start every example with `prompt` as the system message.
+    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Write messages stored under `from` with the role `to`, or leave them out
+    /// when `to` is empty.
+    pub fn role(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.roles.insert(from.into().to_lowercase(), to.into());
+        self
+    }
+
+    /// Chat example (`{"messages": [...]}`) for a conversation history, or
+    /// `None` when no user or assistant message is left after activity log
+    /// entries and dropped roles are removed.
+    pub fn chat_example(&self, history: &[Message]) -> Option<serde_json::Value> {
+        let mut messages = self.system_messages();
+        let preamble = messages.len();
+        for message in history.iter().filter(|message| !is_activity(message)) {
+            let role = match self.roles.get(&message.role.as_str().to_lowercase()) {
+                Some(role) => role.as_str(),
+                None => message.normalized_role(),
+            };
+            if !role.is_empty() {
+                messages.push(serde_json::json!({"role": role, "content": message.text()}));
+            }
+        }
+        (messages.len() > preamble).then(|| serde_json::json!({ "messages": messages }))
+    }
+
+    /// Preference example in the OpenAI DPO format for a DPO triple.
+    pub fn dpo_example(&self, triple: &DpoTriple) -> serde_json::Value {
+        let mut messages = self.system_messages();
+        messages.push(serde_json::json!({"role": "user", "content": triple.prompt}));
+        serde_json::json!({
+            "input": { "messages": messages },
+            "preferred_output": [{"role": "assistant", "content": triple.chosen}],
+            "non_preferred_output": [{"role": "assistant", "content": triple.rejected}],
+        })
+    }
+
+    fn system_messages(&self) -> Vec<serde_json::Value> {
+        self.system_prompt
+            .iter()
+            .map(|prompt| serde_json::json!({"role": "system", "content": prompt}))
+            .collect()
+    }
+}
+
+/// Result of `voice_chat`: what was heard, what the agent answered and the
+/// answer as speech.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VoiceReply {
+    pub transcript: String,
+    pub answer_text: String,
+    /// Encoded audio returned by the text-to-speech endpoint
+    pub answer_audio: Vec<u8>,
+}
+
+/// Options for `transcribe_audio_with_options`.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::TranscriptionOptions;
+///
+/// // Word timings for subtitles of a Portuguese recording
+/// let options = TranscriptionOptions::new().language("pt").word_timestamps();
+/// assert_eq!(options.response_format.as_deref(), Some("verbose_json"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptionOptions {
+    /// ISO-639-1 language of the audio, e.g. `en`; detected when unset
+    pub language: Option<String>,
+    /// Text guiding the transcription's spelling and style, e.g. names
+    pub prompt: Option<String>,
+    /// `json`, `verbose_json`, `text`, `srt` or `vtt`
+    pub response_format: Option<String>,
+    /// `segment` and/or `word`; only honored with `verbose_json`
+    pub timestamp_granularities: Vec<String>,
+}
+
+impl TranscriptionOptions {
+    /// Options that leave every setting at the server default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the language of the audio.
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Set the text guiding the transcription.
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    /// Set the response format.
+    pub fn response_format(mut self, response_format: impl Into<String>) -> Self {
+        self.response_format = Some(response_format.into());
+        self
+    }
+
+    /// Request segments with start and end times.
+    pub fn segment_timestamps(self) -> Self {
+        self.verbose_with_granularity("segment")
+    }
+
+    /// Request word-level start and end times.
+    pub fn word_timestamps(self) -> Self {
+        self.verbose_with_granularity("word")
+    }
+
+    fn verbose_with_granularity(mut self, granularity: &str) -> Self {
+        self.response_format = Some("verbose_json".to_string());
+        if !self
+            .timestamp_granularities
+            .iter()
+            .any(|g| g == granularity)
+        {
+            self.timestamp_granularities.push(granularity.to_string());
+        }
+        self
+    }
+
+    /// Whether the server answers with plain text rather than JSON.
+    pub(crate) fn is_plain_text(&self) -> bool {
+        matches!(
+            self.response_format.as_deref(),
+            Some("text") | Some("srt") | Some("vtt")
+        )
+    }
+}
+
+/// Result of `transcribe_audio_with_options`.
+///
+/// For the `text`, `srt` and `vtt` formats `text` holds the response body
+/// verbatim and the other fields are empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcription {
+    pub text: String,
+    /// Language detected or given, with `verbose_json`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Length of the audio in seconds, with `verbose_json`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<TranscriptionSegment>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<TranscriptionWord>,
+}
+
+/// A stretch of transcribed speech; times are in seconds from the start.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionSegment {
+    #[serde(default)]
+    pub id: u32,
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// A single transcribed word; times are in seconds from the start.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Options for `generate_image_to_file`.
+#[derive(Debug, Clone, Default)]
+pub struct ImageOptions {
+    /// Image model; the server default when unset
+    pub model: Option<String>,
+    /// Dimensions such as `1024x1024`
+    pub size: Option<String>,
+    /// `url` or `b64_json`; the server default when unset
+    pub response_format: Option<String>,
+}
+
+impl ImageOptions {
+    /// Options that leave every setting at the server default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the image model.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Set the image dimensions, e.g. `1024x1024`.
+    pub fn size(mut self, size: impl Into<String>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    /// Ask for the image inline (`b64_json`) or as a link (`url`).
+    pub fn response_format(mut self, response_format: impl Into<String>) -> Self {
+        self.response_format = Some(response_format.into());
+        self
+    }
+}
+
+/// Encoding of an image, detected from its leading bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    /// Not one of the formats above
+    Unknown,
+}
+
+impl ImageFormat {
+    /// Detect the format of encoded image `data`.
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            ImageFormat::Png
+        } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            ImageFormat::Jpeg
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            ImageFormat::Gif
+        } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            ImageFormat::Webp
+        } else {
+            ImageFormat::Unknown
+        }
+    }
+
+    /// The usual file extension, or `None` for `Unknown`.
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            ImageFormat::Png => Some("png"),
+            ImageFormat::Jpeg => Some("jpg"),
+            ImageFormat::Gif => Some("gif"),
+            ImageFormat::Webp => Some("webp"),
+            ImageFormat::Unknown => None,
+        }
+    }
+}
+
+/// Result of `generate_image_to_file`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedImage {
+    /// Where the image was written; the requested path, with the format's
+    /// extension added if it had none
+    pub path: std::path::PathBuf,
+    pub format: ImageFormat,
 }
 
 /// Conversation information.
@@ -196,6 +1818,93 @@ pub struct Conversation {
     pub agent_id: Option<String>,
 }
 
+/// One page of a paginated listing such as `get_conversations`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items across all pages, when the server reports it
+    pub total: Option<u64>,
+    /// 1-based number of this page, when a page was requested
+    pub page: Option<i32>,
+    /// Requested page size
+    pub limit: Option<i32>,
+}
+
+impl<T> Page<T> {
+    /// Whether a following page may hold more items. Without a total count
+    /// this assumes so whenever the page came back full.
+    pub fn has_more(&self) -> bool {
+        let Some(limit) = self.limit.filter(|limit| *limit > 0) else {
+            return false;
+        };
+        match self.total {
+            Some(total) => {
+                let page = self.page.unwrap_or(1).max(1) as u64;
+                page * (limit as u64) < total
+            }
+            None => self.items.len() >= limit as usize,
+        }
+    }
+}
+
+impl<T> IntoIterator for Page<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// Summary of a conversation without its message history, as returned by
+/// `get_conversation_metadata`. Timestamps are the server's ISO 8601 strings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationMetadata {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// Time of the latest activity in the conversation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Short title and abstract of a conversation, as returned by
+/// `summarize_conversation`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationSummary {
+    pub title: String,
+    #[serde(default)]
+    pub summary: String,
+}
+
+/// Alert about agent activity in one of the user's conversations, as returned
+/// by `get_notifications`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
 /// Chain information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chain {
@@ -214,6 +1923,68 @@ pub struct ChainStep {
     pub prompt: serde_json::Value,
 }
 
+/// A past execution of a chain, as listed by `get_chain_runs`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChainRun {
+    #[serde(alias = "run_id")]
+    pub id: String,
+    /// When the run started
+    #[serde(default, alias = "created_at", skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// e.g. `running`, `completed` or `failed`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Step outputs; only filled in by `get_chain_run`
+    #[serde(default, alias = "responses", skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<ChainRunStep>,
+}
+
+/// Output of one step of a chain run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChainRunStep {
+    #[serde(alias = "step")]
+    pub step_number: i32,
+    #[serde(default, alias = "output")]
+    pub response: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+/// Progress event reported by `run_chain_with_progress`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ChainProgress {
+    /// A step is about to run
+    StepStarted { step: i32, total: usize },
+    /// A step finished with the given response
+    StepCompleted {
+        step: i32,
+        total: usize,
+        response: serde_json::Value,
+    },
+}
+
+/// Progress of a download, reported by `download_url_to` and
+/// `download_workspace_file_with_progress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Bytes in the file so far, including those of a resumed earlier attempt
+    pub received: u64,
+    /// Size of the whole file, if the server reported it
+    pub total: Option<u64>,
+}
+
+impl DownloadProgress {
+    /// Share of the file received, from 0.0 to 1.0, if the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.received as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+}
+
 /// Prompt information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Prompt {
@@ -224,6 +1995,136 @@ pub struct Prompt {
     pub category: Option<String>,
 }
 
+/// Server-side options of `prompt_agent_with_options` and `chat_with_options`
+/// that control what context the agent gathers before answering.
+///
+/// Unset options are left to the server and agent defaults.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::PromptOptions;
+///
+/// // Research question: search the web and read the links the user gave
+/// let options = PromptOptions::new()
+///     .websearch(true)
+///     .websearch_depth(3)
+///     .browse_links(true)
+///     .context_results(10);
+/// let args = options.to_args();
+/// assert_eq!(args["websearch"], true);
+/// assert!(!args.contains_key("shots"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptOptions {
+    /// Number of memories injected into the prompt's `{context}`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_results: Option<u32>,
+    /// Number of memories of the conversation's own collection injected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub injected_memories: Option<u32>,
+    /// Number of answers to generate; the server returns the best one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shots: Option<u32>,
+    /// Search the web for the user input before answering
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub websearch: Option<bool>,
+    /// Number of result pages followed per web search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub websearch_depth: Option<u32>,
+    /// Read the pages of links found in the user input
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browse_links: Option<bool>,
+    /// Skip storing the exchange in the agent's memories
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_memory: Option<bool>,
+}
+
+impl PromptOptions {
+    /// Options that leave every setting at the server default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of memories injected as context.
+    pub fn context_results(mut self, context_results: u32) -> Self {
+        self.context_results = Some(context_results);
+        self
+    }
+
+    /// Set the number of conversation memories injected.
+    pub fn injected_memories(mut self, injected_memories: u32) -> Self {
+        self.injected_memories = Some(injected_memories);
+        self
+    }
+
+    /// Set the number of answers generated.
+    pub fn shots(mut self, shots: u32) -> Self {
+        self.shots = Some(shots);
+        self
+    }
+
+    /// Enable or disable web search.
+    pub fn websearch(mut self, websearch: bool) -> Self {
+        self.websearch = Some(websearch);
+        self
+    }
+
+    /// Set how deep web searches go.
+    pub fn websearch_depth(mut self, websearch_depth: u32) -> Self {
+        self.websearch_depth = Some(websearch_depth);
+        self
+    }
+
+    /// Enable or disable reading the links in the user input.
+    pub fn browse_links(mut self, browse_links: bool) -> Self {
+        self.browse_links = Some(browse_links);
+        self
+    }
+
+    /// Enable or disable storing the exchange in memory.
+    pub fn disable_memory(mut self, disable_memory: bool) -> Self {
+        self.disable_memory = Some(disable_memory);
+        self
+    }
+
+    /// The options that are set, as prompt arguments.
+    pub fn to_args(&self) -> HashMap<String, serde_json::Value> {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(map)) => map.into_iter().collect(),
+            _ => HashMap::new(),
+        }
+    }
+}
+
+/// What `sync_prompts_from_dir` changed, or would change in a dry run, by
+/// prompt name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PromptSyncReport {
+    /// Whether the server was left untouched
+    pub dry_run: bool,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub deleted: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
+/// Contents of an account backup written by `export_account`, stored as its
+/// `manifest.json`. From `import_account`, the counts are of the items that
+/// were created.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Backup layout version
+    pub version: u32,
+    /// When the backup was written, in RFC 3339
+    pub created_at: String,
+    /// Server the backup was taken from
+    pub source: String,
+    pub agents: usize,
+    pub prompts: usize,
+    pub chains: usize,
+    pub conversations: usize,
+}
+
 /// Provider information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Provider {
@@ -234,32 +2135,858 @@ pub struct Provider {
     pub supports_embeddings: bool,
 }
 
-/// Company information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Company {
-    pub id: String,
-    pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub agents: Option<Vec<Agent>>,
+/// Service a provider offers, for `get_providers_by_service`.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::ProviderService;
+///
+/// assert_eq!(ProviderService::Embeddings.as_str(), "embeddings");
+/// assert_eq!(ProviderService::from("TTS"), ProviderService::Tts);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProviderService {
+    /// Text generation
+    Llm,
+    /// Image understanding
+    Vision,
+    /// Image generation
+    Image,
+    /// Text to speech
+    Tts,
+    /// Speech to text
+    Transcription,
+    Embeddings,
+    Translation,
+    /// A service this SDK does not know yet, by its server name
+    Other(String),
 }
 
-/// User information.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct User {
-    pub id: String,
-    pub email: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub first_name: Option<String>,
+impl ProviderService {
+    /// The service name used by the server, e.g. `llm`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ProviderService::Llm => "llm",
+            ProviderService::Vision => "vision",
+            ProviderService::Image => "image",
+            ProviderService::Tts => "tts",
+            ProviderService::Transcription => "transcription",
+            ProviderService::Embeddings => "embeddings",
+            ProviderService::Translation => "translation",
+            ProviderService::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for ProviderService {
+    /// Parse a server service name, case-insensitively.
+    fn from(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "llm" => ProviderService::Llm,
+            "vision" => ProviderService::Vision,
+            "image" => ProviderService::Image,
+            "tts" => ProviderService::Tts,
+            "transcription" => ProviderService::Transcription,
+            "embeddings" => ProviderService::Embeddings,
+            "translation" => ProviderService::Translation,
+            _ => ProviderService::Other(name.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ProviderService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ProviderService {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProviderService {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(ProviderService::from(
+            String::deserialize(deserializer)?.as_str(),
+        ))
+    }
+}
+
+/// Provider with its services and settings schema, as returned by
+/// `get_provider_settings` and `get_all_providers_with_settings`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderDetails {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub friendly_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Services the provider offers, e.g. "llm", "tts", "embeddings"
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Settings sorted by name
+    #[serde(default)]
+    pub settings: Vec<ProviderSetting>,
+}
+
+impl ProviderDetails {
+    /// Build provider details from a server payload.
+    ///
+    /// `settings` may map each setting name either to its default value or to an
+    /// object with `type`, `default` and `description` keys.
+    pub fn from_value(name: &str, value: &serde_json::Value) -> Self {
+        let field = |key: &str| value.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let mut settings: Vec<ProviderSetting> = value
+            .get("settings")
+            .and_then(|v| v.as_object())
+            .map(|settings| {
+                settings
+                    .iter()
+                    .map(|(name, value)| ProviderSetting::from_value(name, value))
+                    .collect()
+            })
+            .unwrap_or_default();
+        settings.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            name: field("name").unwrap_or_else(|| name.to_string()),
+            friendly_name: field("friendly_name"),
+            description: field("description"),
+            services: value
+                .get("services")
+                .and_then(|v| v.as_array())
+                .map(|services| {
+                    services
+                        .iter()
+                        .filter_map(|s| s.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            settings,
+        }
+    }
+
+    /// Look up a setting by name.
+    pub fn setting(&self, name: &str) -> Option<&ProviderSetting> {
+        self.settings.iter().find(|s| s.name == name)
+    }
+
+    /// Whether the provider offers the given service.
+    pub fn supports(&self, service: &str) -> bool {
+        self.services.iter().any(|s| s == service)
+    }
+}
+
+/// Problems found in agent settings by `validate_agent_settings`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsValidation {
+    /// The agent's `provider`, whose schema the settings were checked against
+    pub provider: Option<String>,
+    /// Settings the provider has no default for, such as API keys, that are
+    /// absent or empty; `provider` itself when it is not set
+    pub missing: Vec<String>,
+    /// Settings known to none of the agent's providers and that are not
+    /// general agent settings, e.g. misspelled names
+    pub unknown: Vec<String>,
+}
+
+impl SettingsValidation {
+    /// Whether nothing is missing or unknown.
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.unknown.is_empty()
+    }
+}
+
+impl fmt::Display for SettingsValidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let provider = self.provider.as_deref().unwrap_or("no provider");
+        write!(f, "agent settings for {}", provider)?;
+        if !self.missing.is_empty() {
+            write!(f, " are missing {}", self.missing.join(", "))?;
+        }
+        if !self.unknown.is_empty() {
+            let and = if self.missing.is_empty() { "" } else { " and" };
+            write!(f, "{} have unknown {}", and, self.unknown.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Provider that computes embeddings, as returned by `get_embedders`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Embedder {
+    pub name: String,
+    /// Length of the vectors the provider returns, when the server reports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<usize>,
+    /// Settings sorted by name
+    #[serde(default)]
+    pub settings: Vec<ProviderSetting>,
+}
+
+impl Embedder {
+    /// Build an embedder from a `get_providers` entry, or `None` if the
+    /// provider does not support embeddings.
+    pub fn from_value(value: &serde_json::Value) -> Option<Self> {
+        let flagged = value
+            .get("supports_embeddings")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let details = ProviderDetails::from_value("", value);
+        if details.name.is_empty() || !(flagged || details.supports("embeddings")) {
+            return None;
+        }
+        let dimensions = ["embedding_dimensions", "dimensions"]
+            .iter()
+            .find_map(|key| value.get(key).and_then(|v| v.as_u64()))
+            .map(|dimensions| dimensions as usize);
+        Some(Self {
+            name: details.name,
+            dimensions,
+            settings: details.settings,
+        })
+    }
+}
+
+/// A single configurable provider setting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderSetting {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub setting_type: SettingType,
+    #[serde(default)]
+    pub default: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ProviderSetting {
+    fn from_value(name: &str, value: &serde_json::Value) -> Self {
+        let schema = value
+            .as_object()
+            .filter(|obj| obj.contains_key("default") || obj.contains_key("type"));
+        match schema {
+            Some(obj) => {
+                let default = obj.get("default").cloned().unwrap_or_default();
+                let setting_type = obj
+                    .get("type")
+                    .and_then(|v| v.as_str())
+                    .map(SettingType::from_name)
+                    .unwrap_or_else(|| SettingType::of(&default));
+                Self {
+                    name: name.to_string(),
+                    setting_type,
+                    default,
+                    description: obj
+                        .get("description")
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string),
+                }
+            }
+            None => Self {
+                name: name.to_string(),
+                setting_type: SettingType::of(value),
+                default: value.clone(),
+                description: None,
+            },
+        }
+    }
+}
+
+/// Value type of a provider setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Array,
+    Object,
+    /// The server gave no type and the default is `null`
+    Unknown,
+}
+
+impl SettingType {
+    /// Infer the type from a default value.
+    pub fn of(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::String(_) => SettingType::String,
+            serde_json::Value::Number(n) if n.is_f64() => SettingType::Number,
+            serde_json::Value::Number(_) => SettingType::Integer,
+            serde_json::Value::Bool(_) => SettingType::Boolean,
+            serde_json::Value::Array(_) => SettingType::Array,
+            serde_json::Value::Object(_) => SettingType::Object,
+            serde_json::Value::Null => SettingType::Unknown,
+        }
+    }
+
+    /// Parse a JSON Schema or Python type name such as "integer" or "bool".
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "str" | "string" => SettingType::String,
+            "int" | "integer" => SettingType::Integer,
+            "float" | "number" => SettingType::Number,
+            "bool" | "boolean" => SettingType::Boolean,
+            "list" | "array" => SettingType::Array,
+            "dict" | "object" => SettingType::Object,
+            _ => SettingType::Unknown,
+        }
+    }
+}
+
+/// File contents to upload, read from disk or given in memory.
+#[derive(Debug, Clone)]
+pub enum UploadSource {
+    /// A local file; its name is used as the uploaded file name
+    Path(std::path::PathBuf),
+    /// In-memory contents with the file name to store them under
+    Bytes { file_name: String, data: Vec<u8> },
+}
+
+impl UploadSource {
+    /// In-memory contents stored under `file_name`.
+    pub fn bytes(file_name: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        UploadSource::Bytes {
+            file_name: file_name.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Resolve to the file name and contents, reading the file if needed.
+    pub(crate) fn into_parts(self) -> crate::Result<(String, Vec<u8>)> {
+        match self {
+            UploadSource::Path(path) => {
+                let file_name = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .ok_or_else(|| {
+                        crate::Error::InvalidInput(format!("{} has no file name", path.display()))
+                    })?
+                    .to_string();
+                Ok((file_name, read_file(&path)?))
+            }
+            UploadSource::Bytes { file_name, data } => Ok((file_name, data)),
+        }
+    }
+
+    /// Encode as a base64 `data:` URI, with the MIME type guessed from the file name.
+    pub(crate) fn into_data_uri(self) -> crate::Result<String> {
+        let (file_name, data) = self.into_parts()?;
+        Ok(data_uri(
+            mime_type_for(std::path::Path::new(&file_name)),
+            &data,
+        ))
+    }
+}
+
+impl From<std::path::PathBuf> for UploadSource {
+    fn from(path: std::path::PathBuf) -> Self {
+        UploadSource::Path(path)
+    }
+}
+
+impl From<&std::path::Path> for UploadSource {
+    fn from(path: &std::path::Path) -> Self {
+        UploadSource::Path(path.to_path_buf())
+    }
+}
+
+/// File or directory in an agent's workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceFile {
+    /// Path relative to the workspace root
+    pub path: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    /// "file" or "directory"
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl WorkspaceFile {
+    /// Whether the entry is a directory.
+    pub fn is_dir(&self) -> bool {
+        matches!(self.kind.as_deref(), Some("directory" | "dir" | "folder"))
+    }
+}
+
+/// Whether an [`Artifact`] was embedded as an image or linked as a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactKind {
+    /// Embedded with `![alt](url)`
+    Image,
+    /// Linked with `[name](url)`
+    File,
+}
+
+/// A workspace file an agent linked in a conversation message, as found by
+/// [`extract_artifacts`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Artifact {
+    pub kind: ArtifactKind,
+    /// The link text or image alt text
+    pub label: String,
+    /// The link target, either a path on the AGiXT server or a full URL
+    pub url: String,
+    /// ID of the message containing the link
+    pub message_id: Option<String>,
+}
+
+impl Artifact {
+    /// The file name at the end of the URL path, without query or fragment.
+    pub fn file_name(&self) -> &str {
+        let path = self.url.split(['?', '#']).next().unwrap_or_default();
+        path.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+/// Collect the images and files that agent messages link from the AGiXT
+/// workspace, e.g. to download what a chain run generated.
+///
+/// Markdown links whose path goes through `/outputs/` or a workspace download
+/// endpoint are returned in message order; links to other places and user
+/// messages are ignored, and a URL linked more than once is returned once.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::models::extract_artifacts;
+/// use agixt_sdk::{ArtifactKind, Message};
+///
+/// let history = vec![Message::new(
+///     "XT",
+///     "Here is the chart:\n![Sales](http://localhost:7437/outputs/agent-1/chart.png)\n\
+///      See [the docs](https://docs.agixt.com) for details.",
+/// )];
+/// let artifacts = extract_artifacts(&history);
+/// assert_eq!(artifacts.len(), 1);
+/// assert_eq!(artifacts[0].kind, ArtifactKind::Image);
+/// assert_eq!(artifacts[0].file_name(), "chart.png");
+/// ```
+pub fn extract_artifacts(history: &[Message]) -> Vec<Artifact> {
+    let mut artifacts: Vec<Artifact> = Vec::new();
+    for message in history {
+        if message.normalized_role() == "user" {
+            continue;
+        }
+        let text = message.text();
+        for (image, label, url) in markdown_links(&text) {
+            let path = url.split(['?', '#']).next().unwrap_or_default();
+            let in_workspace = path.contains("/outputs/") || path.ends_with("/workspace/download");
+            if !in_workspace || artifacts.iter().any(|a| a.url == url) {
+                continue;
+            }
+            artifacts.push(Artifact {
+                kind: if image {
+                    ArtifactKind::Image
+                } else {
+                    ArtifactKind::File
+                },
+                label: label.to_string(),
+                url: url.to_string(),
+                message_id: message.id.clone(),
+            });
+        }
+    }
+    artifacts
+}
+
+/// A file or image attached to a conversation message, as returned by
+/// `get_conversation_attachments`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Attachment {
+    pub kind: ArtifactKind,
+    /// The file name at the end of the URL path
+    pub name: String,
+    /// Workspace URL of the file, either a path on the AGiXT server or a full URL
+    pub url: String,
+    /// Size in bytes, if the server reported it
+    pub size: Option<u64>,
+    /// ID of the message the file is attached to
+    pub message_id: Option<String>,
+    /// Role of the message sender, e.g. `Role::User` for uploads
+    pub role: Role,
+}
+
+/// Collect the files and images attached to messages of a conversation, by
+/// users and agents alike, without their sizes.
+///
+/// Attachments are the workspace links [`extract_artifacts`] finds, plus the
+/// `image_url` and `file_url` parts of structured messages; inline `data:`
+/// URLs are skipped. A URL attached more than once is returned once, for the
+/// first message that has it.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::models::{extract_attachments, Message};
+///
+/// let history = vec![Message::user(
+///     "Please review [contract.pdf](https://agixt.example.com/outputs/a1/c1/contract.pdf)",
+/// )];
+/// let attachments = extract_attachments(&history);
+/// assert_eq!(attachments[0].name, "contract.pdf");
+/// assert_eq!(attachments[0].role, "user");
+/// ```
+pub fn extract_attachments(history: &[Message]) -> Vec<Attachment> {
+    let mut attachments: Vec<Attachment> = Vec::new();
+    for message in history {
+        let mut links = Vec::new();
+        if let MessageContent::Structured(parts) = &message.content {
+            for part in parts {
+                if let Some(image) = &part.image_url {
+                    links.push((ArtifactKind::Image, image.url.clone()));
+                }
+                if let Some(file) = &part.file_url {
+                    links.push((ArtifactKind::File, file.url.clone()));
+                }
+            }
+        }
+        for (image, _, url) in markdown_links(&message.text()) {
+            let path = url.split(['?', '#']).next().unwrap_or_default();
+            if path.contains("/outputs/") || path.ends_with("/workspace/download") {
+                let kind = if image {
+                    ArtifactKind::Image
+                } else {
+                    ArtifactKind::File
+                };
+                links.push((kind, url.to_string()));
+            }
+        }
+        for (kind, url) in links {
+            if url.starts_with("data:") || attachments.iter().any(|a| a.url == url) {
+                continue;
+            }
+            let path = url.split(['?', '#']).next().unwrap_or_default();
+            attachments.push(Attachment {
+                kind,
+                name: path.rsplit('/').next().unwrap_or_default().to_string(),
+                url,
+                size: None,
+                message_id: message.id.clone(),
+                role: message.role.normalized(),
+            });
+        }
+    }
+    attachments
+}
+
+/// Markdown links in `text` as (is image, label, URL), ignoring link titles.
+fn markdown_links(text: &str) -> Vec<(bool, &str, &str)> {
+    let mut links = Vec::new();
+    let mut offset = 0;
+    while let Some(open) = text[offset..].find('[').map(|i| offset + i) {
+        let label_start = open + 1;
+        let Some(close) = text[label_start..].find("](").map(|i| label_start + i) else {
+            break;
+        };
+        let label = &text[label_start..close];
+        if let Some(nested) = label.rfind('[') {
+            // A `[` inside the label means this bracket did not open the link
+            offset = label_start + nested;
+            continue;
+        }
+        let target_start = close + 2;
+        let Some(end) = text[target_start..].find(')').map(|i| target_start + i) else {
+            break;
+        };
+        let url = text[target_start..end]
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches('<')
+            .trim_end_matches('>');
+        if !url.is_empty() {
+            links.push((text[..open].ends_with('!'), label, url));
+        }
+        offset = end + 1;
+    }
+    links
+}
+
+/// Version and enabled features of an AGiXT server, as returned by `server_info`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServerInfo {
+    /// Server version, or `None` for servers that predate the info endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Enabled features, sent by the server as a list of names or a map of flags
+    #[serde(default, deserialize_with = "deserialize_features")]
+    pub features: Vec<String>,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl ServerInfo {
+    /// Whether the server reports `feature` as enabled. Names are compared
+    /// case-insensitively.
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features
+            .iter()
+            .any(|enabled| enabled.eq_ignore_ascii_case(feature))
+    }
+}
+
+fn deserialize_features<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(names) => names
+            .into_iter()
+            .filter_map(|name| name.as_str().map(String::from))
+            .collect(),
+        serde_json::Value::Object(flags) => flags
+            .into_iter()
+            .filter(|(_, enabled)| enabled.as_bool().unwrap_or(false))
+            .map(|(name, _)| name)
+            .collect(),
+        _ => Vec::new(),
+    })
+}
+
+/// Solana wallet of the current user, as returned by `get_user_wallet`.
+///
+/// The private key and passphrase are only present when the server allows them
+/// to be exported.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UserWallet {
+    #[serde(default, alias = "wallet_address", alias = "public_key")]
+    pub address: String,
+    #[serde(default)]
+    pub private_key: Option<Secret<String>>,
+    #[serde(default)]
+    pub passphrase: Option<Secret<String>>,
+}
+
+/// Login state of a client, exported with `AGiXTSDK::session` and applied with
+/// `AGiXTSDK::restore_session`, so apps can stay logged in across restarts
+/// without storing the user's password.
+///
+/// The token is redacted in `Debug` output but serialized as is; keep the
+/// serialized state somewhere private, such as the platform keychain.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    pub base_uri: String,
+    /// API key or JWT token, if the client was authenticated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<Secret<String>>,
+    /// Company the client was scoped to with `set_company`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company_id: Option<String>,
+}
+
+/// Result of a magic-link login, returned by `login_magic_link` and
+/// `login_with_secret`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoginOutcome {
+    /// The server issued a token, which the client now sends with each request
+    TokenIssued { token: Secret<String> },
+    /// The server sent a login link; finish with `complete_magic_link_login`
+    MagicLinkSent { url: String },
+    /// The server sent neither a token nor a login link
+    Failed { message: Option<String> },
+}
+
+impl LoginOutcome {
+    /// The issued token, if the login succeeded.
+    pub fn token(&self) -> Option<&str> {
+        match self {
+            LoginOutcome::TokenIssued { token } => Some(token.expose_secret()),
+            _ => None,
+        }
+    }
+}
+
+/// OAuth provider configured on the server for single sign-on, as returned by
+/// `get_sso_providers`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SsoProvider {
+    /// Provider name to pass to `oauth2_login`
+    pub name: String,
+    #[serde(default)]
+    pub client_id: String,
+    /// Scopes to request, sent by the server as a list or a space-separated string
+    #[serde(default, deserialize_with = "deserialize_scopes")]
+    pub scopes: Vec<String>,
+    /// Authorization endpoint the login button should redirect to
+    #[serde(default, alias = "authorize")]
+    pub authorize_url: String,
+    #[serde(default)]
+    pub pkce_required: bool,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+fn deserialize_scopes<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(scopes) => scopes
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|scope| !scope.is_empty())
+            .map(String::from)
+            .collect(),
+        serde_json::Value::Array(scopes) => scopes
+            .into_iter()
+            .filter_map(|scope| scope.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    })
+}
+
+/// Company information.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Company {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_company_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agents: Option<Vec<Agent>>,
+}
+
+/// User membership within a company.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanyUser {
+    pub id: String,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_id: Option<i32>,
+}
+
+/// Pending invitation for a user to join a company.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invitation {
+    pub id: String,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inviter_id: Option<String>,
+    #[serde(default)]
+    pub is_accepted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+}
+
+/// User information.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: String,
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub companies: Vec<Company>,
+    #[serde(default)]
+    pub preferences: HashMap<String, serde_json::Value>,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Partial update of the current user, built with `UserUpdate::new()` and the
+/// setters below. Only fields that were set are serialized, so attributes the
+/// update does not mention keep their values on the server.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UserUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(flatten)]
+    pub preferences: HashMap<String, serde_json::Value>,
+}
+
+impl UserUpdate {
+    /// Create an empty update.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the first name.
+    pub fn first_name(mut self, first_name: impl Into<String>) -> Self {
+        self.first_name = Some(first_name.into());
+        self
+    }
+
+    /// Set the last name.
+    pub fn last_name(mut self, last_name: impl Into<String>) -> Self {
+        self.last_name = Some(last_name.into());
+        self
+    }
+
+    /// Set the IANA timezone (e.g. "America/New_York").
+    pub fn timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Set a user preference. Keys naming a field with its own setter, such as
+    /// `timezone`, are rejected by `update_user`.
+    pub fn preference(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.preferences.insert(key.into(), value.into());
+        self
+    }
+
+    /// Whether no field or preference was set.
+    pub fn is_empty(&self) -> bool {
+        self.first_name.is_none()
+            && self.last_name.is_none()
+            && self.timezone.is_none()
+            && self.preferences.is_empty()
+    }
+
+    /// A preference key that names a field with its own setter, which would be
+    /// serialized twice.
+    pub(crate) fn conflicting_preference(&self) -> Option<&str> {
+        const FIELDS: [&str; 3] = ["first_name", "last_name", "timezone"];
+        self.preferences
+            .keys()
+            .map(String::as_str)
+            .find(|key| FIELDS.contains(key))
+    }
 }
 
 /// Extension information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Extension {
+    #[serde(alias = "extension_name")]
     pub name: String,
-    pub description: String,
     #[serde(default)]
+    pub description: String,
+    #[serde(default, deserialize_with = "deserialize_settings")]
     pub settings: HashMap<String, serde_json::Value>,
     #[serde(default)]
     pub commands: Vec<ExtensionCommand>,
@@ -268,8 +2995,652 @@ pub struct Extension {
 /// Command within an extension.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtensionCommand {
+    #[serde(alias = "friendly_name")]
     pub name: String,
-    pub description: String,
     #[serde(default)]
+    pub description: String,
+    #[serde(default, alias = "command_args")]
     pub args: HashMap<String, serde_json::Value>,
+    /// Whether the command is enabled for the agent, in `get_extension_commands`
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Settings are sent as a map, or by some servers as a list of names.
+fn deserialize_settings<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<String, serde_json::Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Object(map) => map.into_iter().collect(),
+        serde_json::Value::Array(names) => names
+            .into_iter()
+            .filter_map(|name| {
+                name.as_str()
+                    .map(|n| (n.to_string(), serde_json::Value::Null))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    })
+}
+
+/// Command invocation checked against the argument schema returned by
+/// `get_command_args`, sent with `run_command`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecuteCommand {
+    pub command_name: String,
+    pub command_args: HashMap<String, serde_json::Value>,
+    /// Conversation (name or ID) the command and its output are logged to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+}
+
+impl ExecuteCommand {
+    /// Start building an invocation of `command_name` whose arguments are
+    /// described by `schema`, the value returned by `get_command_args`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use agixt_sdk::ExecuteCommand;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"query": "", "max_results": 5});
+    /// let command = ExecuteCommand::builder("Web Search", &schema)
+    ///     .arg("query", "AGiXT")
+    ///     .arg("max_results", 3)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(command.command_args["max_results"], 3);
+    ///
+    /// let missing = ExecuteCommand::builder("Web Search", &schema).build();
+    /// assert!(missing.unwrap_err().to_string().contains("query"));
+    /// ```
+    pub fn builder(
+        command_name: impl Into<String>,
+        schema: &serde_json::Value,
+    ) -> ExecuteCommandBuilder {
+        let args = schema
+            .as_object()
+            .map(|obj| {
+                obj.iter()
+                    .map(|(name, value)| CommandArg::from_value(name, value))
+                    .collect()
+            })
+            .unwrap_or_default();
+        ExecuteCommandBuilder {
+            command: ExecuteCommand {
+                command_name: command_name.into(),
+                ..Self::default()
+            },
+            args,
+            error: None,
+        }
+    }
+}
+
+/// Argument accepted by a command, parsed from its `get_command_args` schema.
+#[derive(Debug, Clone, PartialEq)]
+struct CommandArg {
+    setting: ProviderSetting,
+    required: bool,
+}
+
+impl CommandArg {
+    /// Arguments are required when the schema says so or, failing that, when
+    /// their default is empty.
+    fn from_value(name: &str, value: &serde_json::Value) -> Self {
+        let setting = ProviderSetting::from_value(name, value);
+        let required =
+            value
+                .get("required")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(match &setting.default {
+                    serde_json::Value::Null => true,
+                    serde_json::Value::String(s) => s.is_empty(),
+                    _ => false,
+                });
+        Self { setting, required }
+    }
+
+    fn accepts(&self, value: &serde_json::Value) -> bool {
+        match self.setting.setting_type {
+            SettingType::String => value.is_string(),
+            SettingType::Integer => value.is_i64() || value.is_u64(),
+            SettingType::Number => value.is_number(),
+            SettingType::Boolean => value.is_boolean(),
+            SettingType::Array => value.is_array(),
+            SettingType::Object => value.is_object(),
+            SettingType::Unknown => true,
+        }
+    }
+}
+
+/// Builder for [`ExecuteCommand`] that checks argument names and types.
+#[derive(Debug, Clone)]
+pub struct ExecuteCommandBuilder {
+    command: ExecuteCommand,
+    args: Vec<CommandArg>,
+    error: Option<String>,
+}
+
+impl ExecuteCommandBuilder {
+    /// Set an argument. Unknown names and values of the wrong type are reported by `build`.
+    pub fn arg(mut self, name: &str, value: impl Into<serde_json::Value>) -> Self {
+        let value = value.into();
+        let error = match self.args.iter().find(|arg| arg.setting.name == name) {
+            None => Some(format!(
+                "command '{}' has no argument '{}'",
+                self.command.command_name, name
+            )),
+            Some(arg) if !arg.accepts(&value) => Some(format!(
+                "argument '{}' of command '{}' must be of type {:?}, got {}",
+                name, self.command.command_name, arg.setting.setting_type, value
+            )),
+            Some(_) => None,
+        };
+        if let Some(error) = error {
+            self.error.get_or_insert(error);
+        }
+        self.command.command_args.insert(name.to_string(), value);
+        self
+    }
+
+    /// Log the command and its output to a conversation, by name or ID.
+    pub fn conversation(mut self, conversation_id: impl Into<String>) -> Self {
+        self.command.conversation_id = Some(conversation_id.into());
+        self
+    }
+
+    /// Check the arguments and return the command, or `InvalidInput` naming the
+    /// first invalid argument or every missing required one.
+    pub fn build(self) -> crate::Result<ExecuteCommand> {
+        if let Some(error) = self.error {
+            return Err(crate::Error::InvalidInput(error));
+        }
+        let missing: Vec<&str> = self
+            .args
+            .iter()
+            .filter(|arg| {
+                arg.required && !self.command.command_args.contains_key(&arg.setting.name)
+            })
+            .map(|arg| arg.setting.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(crate::Error::InvalidInput(format!(
+                "command '{}' is missing required argument(s): {}",
+                self.command.command_name,
+                missing.join(", ")
+            )));
+        }
+        Ok(self.command)
+    }
+}
+
+/// Scheduled task assigned to an agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_hours: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<TaskFrequency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Status reported by servers that track task runs, e.g. "running",
+    /// "completed" or "failed"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// What the task's run produced, or why it failed
+    #[serde(
+        default,
+        alias = "result",
+        alias = "response",
+        skip_serializing_if = "serde_json::Value::is_null"
+    )]
+    pub output: serde_json::Value,
+}
+
+impl Task {
+    /// The task's final outcome, or `None` while it is still pending or
+    /// running.
+    pub fn outcome(&self) -> Option<TaskOutcome> {
+        let status = self
+            .status
+            .as_deref()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let failed = matches!(
+            status.as_str(),
+            "failed" | "failure" | "error" | "cancelled" | "canceled"
+        );
+        let completed = matches!(
+            status.as_str(),
+            "completed" | "complete" | "done" | "succeeded" | "success"
+        );
+        if failed {
+            Some(TaskOutcome::Failed(self.clone()))
+        } else if completed || self.completed {
+            Some(TaskOutcome::Completed(self.clone()))
+        } else {
+            None
+        }
+    }
+}
+
+/// How a task ended, as returned by `wait_for_task`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum TaskOutcome {
+    /// The task finished; its output is in `Task::output`
+    Completed(Task),
+    /// The task failed or was cancelled; `Task::output` may say why
+    Failed(Task),
+}
+
+impl TaskOutcome {
+    /// The task as last polled.
+    pub fn task(&self) -> &Task {
+        match self {
+            TaskOutcome::Completed(task) | TaskOutcome::Failed(task) => task,
+        }
+    }
+
+    /// What the task produced, or why it failed.
+    pub fn output(&self) -> &serde_json::Value {
+        &self.task().output
+    }
+
+    /// Whether the task completed successfully.
+    pub fn is_completed(&self) -> bool {
+        matches!(self, TaskOutcome::Completed(_))
+    }
+}
+
+/// How often a reoccurring task repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Outgoing webhook notified when agent events occur.
+///
+/// The signing secret is redacted in `Debug` output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub target_url: String,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<Secret<String>>,
+    #[serde(default = "default_true")]
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_completions_builder_validates() {
+        let err = ChatCompletions::builder()
+            .temperature(2.5)
+            .build()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("temperature must be between 0 and 2"));
+        assert!(ChatCompletions::builder().n(0).build().is_err());
+        assert!(ChatCompletions::builder().model(" ").build().is_err());
+        assert!(ChatCompletions::builder()
+            .image("/nonexistent/image.png")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_chat_completions_builder_image() {
+        let path = std::env::temp_dir().join(format!("agixt-{}.png", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"png").unwrap();
+        let request = ChatCompletions::builder()
+            .model("agent")
+            .message("user", "Describe this")
+            .image(&path)
+            .stop("END")
+            .build()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(request.model, "agent");
+        assert_eq!(request.stop, Some(vec!["END".to_string()]));
+        let messages = request.messages.unwrap();
+        match &messages[0].content {
+            MessageContent::Structured(parts) => {
+                assert_eq!(parts[0].text.as_deref(), Some("Describe this"));
+                assert_eq!(
+                    parts[1].image_url.as_ref().unwrap().url,
+                    "data:image/png;base64,cG5n"
+                );
+            }
+            other => panic!("unexpected content: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_role_round_trip() {
+        let roles: Vec<Role> =
+            serde_json::from_value(serde_json::json!(["USER", "XT", "assistant", "Tool"])).unwrap();
+        assert_eq!(
+            roles,
+            [
+                Role::User,
+                Role::Agent("XT".to_string()),
+                Role::Assistant,
+                Role::Tool
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&roles).unwrap(),
+            serde_json::json!(["user", "XT", "assistant", "tool"])
+        );
+        assert_eq!(roles[1].normalized(), Role::Assistant);
+        assert_eq!(roles[0], "user");
+        assert_ne!(Role::Assistant, "asistant");
+    }
+
+    #[test]
+    fn test_message_constructors() {
+        let message =
+            Message::user("Summarize").with_part(ContentPart::file_from_bytes("notes.txt", b"hi"));
+        assert_eq!(message.role, "user");
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            json["content"],
+            serde_json::json!([
+                {"text": "Summarize"},
+                {"file_url": {"url": "data:text/plain;base64,aGk="}}
+            ])
+        );
+        assert_eq!(
+            serde_json::to_value(Message::system("Be brief")).unwrap(),
+            serde_json::json!({"role": "system", "content": "Be brief"})
+        );
+        assert!(ContentPart::image_from_path("/nonexistent.png").is_err());
+    }
+
+    #[test]
+    fn test_audio_and_video_parts() {
+        let message = Message::user("What is said here?")
+            .with_part(ContentPart::audio_from_bytes("audio/ogg", b"hi"))
+            .with_part(ContentPart::video_url("https://example.com/clip.mp4"));
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            json["content"],
+            serde_json::json!([
+                {"text": "What is said here?"},
+                {"audio_url": {"url": "data:audio/ogg;base64,aGk="}},
+                {"video_url": {"url": "https://example.com/clip.mp4"}}
+            ])
+        );
+        assert_eq!(
+            mime_type_for(std::path::Path::new("note.MP3")),
+            "audio/mpeg"
+        );
+        assert!(ContentPart::video_from_path("/nonexistent.mp4").is_err());
+    }
+
+    #[test]
+    fn test_tool_calls_round_trip() {
+        let choice: Choice = serde_json::from_str(
+            r#"{"index": 0, "finish_reason": "tool_calls", "message": {
+                "role": "assistant", "content": null, "tool_calls": [
+                    {"id": "call_1", "type": "function",
+                     "function": {"name": "lookup", "arguments": "{\"q\": \"rust\"}"}},
+                    {"id": "call_2", "function": {"name": "now", "arguments": {}}}
+                ]}}"#,
+        )
+        .unwrap();
+        let calls = &choice.message.tool_calls;
+        assert_eq!(choice.message.text(), "");
+        assert_eq!(calls[0].function.name, "lookup");
+        assert_eq!(
+            calls[0].function.arguments_json().unwrap(),
+            serde_json::json!({"q": "rust"})
+        );
+        assert_eq!(calls[1].call_type, "function");
+        assert_eq!(calls[1].function.arguments, "{}");
+
+        let result = Message::tool("call_1", "42");
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            serde_json::json!({"role": "tool", "content": "42", "tool_call_id": "call_1"})
+        );
+    }
+
+    #[test]
+    fn test_history_conversion() {
+        let history: Vec<Message> = serde_json::from_value(serde_json::json!([
+            {"id": "1", "role": "USER", "content": "Hi", "timestamp": "t1"},
+            {"id": "2", "role": "XT", "content": "[ACTIVITY] Searching memories", "timestamp": "t2"},
+            {"id": "3", "role": "XT", "content": "Hello!", "timestamp": "t3"}
+        ]))
+        .unwrap();
+
+        let messages = history_to_messages(&history);
+        let roles: Vec<_> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["user", "assistant"]);
+        assert!(messages[0].id.is_none() && messages[0].timestamp.is_none());
+
+        let restored = messages_to_history(&messages, "XT");
+        let roles: Vec<_> = restored.iter().map(|m| m.role.clone()).collect();
+        assert_eq!(roles, vec![Role::User, Role::Agent("XT".to_string())]);
+        assert_eq!(restored[1].text(), "Hello!");
+    }
+
+    #[test]
+    fn test_execute_command_builder_checks_args() {
+        let schema = serde_json::json!({
+            "query": "",
+            "max_results": 5,
+            "safe_search": {"type": "bool", "default": true},
+        });
+        let command = ExecuteCommand::builder("Web Search", &schema)
+            .arg("query", "rust")
+            .arg("safe_search", false)
+            .conversation("c1")
+            .build()
+            .unwrap();
+        assert_eq!(command.command_args.len(), 2);
+        assert_eq!(command.conversation_id.as_deref(), Some("c1"));
+
+        let wrong_type = ExecuteCommand::builder("Web Search", &schema)
+            .arg("query", "rust")
+            .arg("max_results", "ten")
+            .build()
+            .unwrap_err();
+        assert!(wrong_type.to_string().contains("max_results"));
+
+        let unknown = ExecuteCommand::builder("Web Search", &schema)
+            .arg("query", "rust")
+            .arg("qeury", "rust")
+            .build()
+            .unwrap_err();
+        assert!(unknown.to_string().contains("no argument 'qeury'"));
+    }
+
+    #[test]
+    fn test_extension_catalogue_parsing() {
+        let extension: Extension = serde_json::from_value(serde_json::json!({
+            "extension_name": "Web",
+            "description": "Browse the web",
+            "settings": ["SEARX_URL"],
+            "commands": [{
+                "friendly_name": "Web Search",
+                "description": "Search the web",
+                "command_args": {"query": ""},
+                "enabled": true
+            }]
+        }))
+        .unwrap();
+        assert_eq!(extension.name, "Web");
+        assert!(extension.settings.contains_key("SEARX_URL"));
+        assert_eq!(extension.commands[0].name, "Web Search");
+        assert!(extension.commands[0].enabled);
+        assert!(extension.commands[0].args.contains_key("query"));
+    }
+
+    #[test]
+    fn test_memory_from_server_fields() {
+        let memory: Memory = serde_json::from_value(serde_json::json!({
+            "id": "m1",
+            "key": "e1",
+            "additional_metadata": "AGiXT is an agent platform",
+            "external_source_name": "https://agixt.com",
+            "relevance_score": 0.82,
+            "timestamp": "2024-05-01T10:00:00",
+            "description": ""
+        }))
+        .unwrap();
+        assert_eq!(memory.text, "AGiXT is an agent platform");
+        assert_eq!(memory.embedding_id.as_deref(), Some("e1"));
+        assert_eq!(memory.relevance_score, Some(0.82));
+        assert!(memory.extra.contains_key("description"));
+    }
+
+    #[test]
+    fn test_page_has_more() {
+        let page = |items: usize, total, page, limit| Page {
+            items: vec![(); items],
+            total,
+            page,
+            limit,
+        };
+        assert!(page(10, Some(25), Some(2), Some(10)).has_more());
+        assert!(!page(5, Some(25), Some(3), Some(10)).has_more());
+        assert!(page(10, None, Some(1), Some(10)).has_more());
+        assert!(!page(4, None, Some(2), Some(10)).has_more());
+        assert!(!page(40, None, None, None).has_more());
+    }
+
+    #[test]
+    fn test_agent_config_debug_redacts_credentials() {
+        let config: AgentConfig = serde_json::from_value(serde_json::json!({
+            "name": "XT",
+            "settings": {
+                "provider": "openai",
+                "OPENAI_API_KEY": "sk-live-123",
+                "AI_MAX_TOKENS": "4096",
+                "GITHUB_TOKEN": ""
+            }
+        }))
+        .unwrap();
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("sk-live-123"), "{}", debug);
+        assert!(debug.contains(r#""OPENAI_API_KEY": [REDACTED]"#));
+        assert!(debug.contains(r#""AI_MAX_TOKENS": String("4096")"#));
+        assert!(debug.contains(r#""GITHUB_TOKEN": String("")"#));
+
+        let key = config.secret_setting("OPENAI_API_KEY").unwrap();
+        assert_eq!(key.expose_secret(), "sk-live-123");
+        assert_eq!(serde_json::to_value(&key).unwrap(), "sk-live-123");
+        assert!(config.secret_setting("GITHUB_TOKEN").is_none());
+    }
+
+    #[test]
+    fn test_webhook_debug_redacts_secret() {
+        let webhook: Webhook = serde_json::from_value(serde_json::json!({
+            "id": "w1",
+            "target_url": "https://example.com/hook",
+            "secret": "whsec-123"
+        }))
+        .unwrap();
+        assert!(!format!("{:?}", webhook).contains("whsec-123"));
+        assert_eq!(
+            webhook.secret.as_ref().map(|s| s.expose_secret().as_str()),
+            Some("whsec-123")
+        );
+        assert_eq!(
+            serde_json::to_value(&webhook).unwrap()["secret"],
+            "whsec-123"
+        );
+    }
+
+    #[test]
+    fn test_parse_activities() {
+        let message = |role: &str, id: &str, text: &str| Message {
+            id: Some(id.to_string()),
+            ..Message::new(role, text)
+        };
+        let history = vec![
+            message("XT", "0", "Hello! How can I help?"),
+            message("USER", "1", "Plan my trip"),
+            message("XT", "2", "[ACTIVITY] Researching flights"),
+            message("XT", "3", "[ACTIVITY][ERROR] Hotel search failed"),
+            message("XT", "4", "[SUBACTIVITY][2][EXECUTION] Queried 3 airlines"),
+            message("XT", "5", "[SUBACTIVITY] Retrying later"),
+            message("XT", "6", "Here is your itinerary."),
+            message("USER", "7", "Thanks"),
+            message("XT", "8", "[SUBACTIVITY][missing] Thinking"),
+        ];
+        let turns = parse_activities(&history);
+        assert_eq!(turns.len(), 3);
+
+        assert!(turns[0].prompt.is_none());
+        assert_eq!(
+            turns[0].answer.as_ref().unwrap().text(),
+            "Hello! How can I help?"
+        );
+
+        let turn = &turns[1];
+        assert_eq!(turn.prompt.as_ref().unwrap().text(), "Plan my trip");
+        assert_eq!(turn.answer.as_ref().unwrap().id.as_deref(), Some("6"));
+        let [flights, hotels] = &turn.activities[..] else {
+            panic!("unexpected activities: {:?}", turn.activities);
+        };
+        assert_eq!(flights.text, "Researching flights");
+        assert_eq!(flights.steps.len(), 1);
+        assert_eq!(flights.steps[0].tag.as_deref(), Some("EXECUTION"));
+        assert_eq!(flights.steps[0].text, "Queried 3 airlines");
+        assert!(!flights.has_error());
+        assert_eq!(hotels.tag.as_deref(), Some("ERROR"));
+        assert_eq!(hotels.steps[0].text, "Retrying later");
+        assert!(hotels.has_error());
+
+        let turn = &turns[2];
+        assert!(turn.answer.is_none());
+        assert_eq!(turn.activities[0].text, "Thinking");
+        assert!(turn.activities[0].steps.is_empty());
+    }
 }
@@ -0,0 +1,454 @@
+//! OpenAI-style client backed by AGiXT's OpenAI-compatible endpoints.
+//!
+//! Request and response types and method names follow the OpenAI API, so code
+//! written against an OpenAI client mostly needs its client construction
+//! changed. The `model` of each request is the AGiXT agent name.
+//!
+//! # Example
+//! ```rust,no_run
+//! use agixt_sdk::openai_compat::{Client, CreateChatCompletionRequest, CreateEmbeddingRequest};
+//! use agixt_sdk::{AGiXTSDK, Message};
+//!
+//! # async fn run() -> agixt_sdk::Result<()> {
+//! let client = Client::new(AGiXTSDK::new(None, Some("your-api-key".to_string()), false));
+//!
+//! let request = CreateChatCompletionRequest::builder()
+//!     .model("XT")
+//!     .push_message(Message::user("Hello!"))
+//!     .build()?;
+//! let response = client.chat().create(&request).await?;
+//! println!("{}", response.choices[0].message.text());
+//!
+//! let embeddings = client
+//!     .embeddings()
+//!     .create(&CreateEmbeddingRequest::new("XT", "Some text to embed"))
+//!     .await?;
+//! println!("{} dimensions", embeddings.data[0].embedding.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::Result;
+use crate::AGiXTSDK;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub use crate::models::{
+    ChatCompletions as CreateChatCompletionRequest, ChatResponse as CreateChatCompletionResponse,
+};
+
+/// OpenAI-shaped entry point wrapping an [`AGiXTSDK`].
+#[derive(Clone)]
+pub struct Client {
+    sdk: AGiXTSDK,
+}
+
+impl Client {
+    /// Wrap a configured AGiXT client.
+    pub fn new(sdk: AGiXTSDK) -> Self {
+        Self { sdk }
+    }
+
+    /// The underlying AGiXT client.
+    pub fn sdk(&self) -> &AGiXTSDK {
+        &self.sdk
+    }
+
+    /// Chat completions (`/v1/chat/completions`).
+    pub fn chat(&self) -> Chat<'_> {
+        Chat { client: self }
+    }
+
+    /// Embeddings (`/v1/embeddings`).
+    pub fn embeddings(&self) -> Embeddings<'_> {
+        Embeddings { client: self }
+    }
+
+    /// Speech synthesis and transcription (`/v1/audio/*`).
+    pub fn audio(&self) -> Audio<'_> {
+        Audio { client: self }
+    }
+
+    /// Image generation (`/v1/images/generations`).
+    pub fn images(&self) -> Images<'_> {
+        Images { client: self }
+    }
+
+    /// Available models, i.e. agents (`/v1/models`).
+    pub fn models(&self) -> Models<'_> {
+        Models { client: self }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let response = self.sdk.execute(request).await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if self.sdk.verbose {
+            self.sdk.parse_response(status, &text).await?;
+        }
+
+        text.json()
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.sdk.base_uri, path)
+    }
+}
+
+impl From<AGiXTSDK> for Client {
+    fn from(sdk: AGiXTSDK) -> Self {
+        Self::new(sdk)
+    }
+}
+
+// ==================== Chat ====================
+
+/// Chat completions API group.
+pub struct Chat<'c> {
+    client: &'c Client,
+}
+
+impl Chat<'_> {
+    /// Create a chat completion.
+    pub async fn create(
+        &self,
+        request: &CreateChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        self.client.sdk.chat_completions(request).await
+    }
+}
+
+// ==================== Embeddings ====================
+
+/// Text to embed: one string or a batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    String(String),
+    StringArray(Vec<String>),
+}
+
+impl From<&str> for EmbeddingInput {
+    fn from(input: &str) -> Self {
+        EmbeddingInput::String(input.to_string())
+    }
+}
+
+impl From<String> for EmbeddingInput {
+    fn from(input: String) -> Self {
+        EmbeddingInput::String(input)
+    }
+}
+
+impl From<Vec<String>> for EmbeddingInput {
+    fn from(input: Vec<String>) -> Self {
+        EmbeddingInput::StringArray(input)
+    }
+}
+
+/// Request body of `POST /v1/embeddings`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateEmbeddingRequest {
+    pub model: String,
+    pub input: EmbeddingInput,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl CreateEmbeddingRequest {
+    /// Embed `input` with the given agent's embedder.
+    pub fn new(model: impl Into<String>, input: impl Into<EmbeddingInput>) -> Self {
+        Self {
+            model: model.into(),
+            input: input.into(),
+            encoding_format: None,
+            user: None,
+        }
+    }
+}
+
+/// Response of `POST /v1/embeddings`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateEmbeddingResponse {
+    #[serde(default)]
+    pub object: String,
+    pub data: Vec<Embedding>,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub usage: Option<EmbeddingUsage>,
+}
+
+/// One embedding vector.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Embedding {
+    #[serde(default)]
+    pub object: String,
+    #[serde(default)]
+    pub index: u32,
+    pub embedding: Vec<f32>,
+}
+
+/// Token usage of an embeddings request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingUsage {
+    pub prompt_tokens: u32,
+    pub total_tokens: u32,
+}
+
+/// Embeddings API group.
+pub struct Embeddings<'c> {
+    client: &'c Client,
+}
+
+impl Embeddings<'_> {
+    /// Create embeddings for the input.
+    pub async fn create(
+        &self,
+        request: &CreateEmbeddingRequest,
+    ) -> Result<CreateEmbeddingResponse> {
+        let request = self
+            .client
+            .sdk
+            .client
+            .post(self.client.url("/v1/embeddings"))
+            .json(request);
+        self.client.send(request).await
+    }
+}
+
+// ==================== Audio ====================
+
+/// Request body of `POST /v1/audio/speech`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSpeechRequest {
+    pub model: String,
+    pub input: String,
+    pub voice: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+}
+
+/// Audio upload for `POST /v1/audio/transcriptions`, sent as multipart form data.
+#[derive(Debug, Clone, Default)]
+pub struct CreateTranscriptionRequest {
+    /// File name, used by the server to detect the audio format
+    pub file_name: String,
+    /// Raw audio bytes
+    pub file: Vec<u8>,
+    pub model: String,
+    pub language: Option<String>,
+    pub prompt: Option<String>,
+    pub response_format: Option<String>,
+    pub temperature: Option<f32>,
+}
+
+/// Response of `POST /v1/audio/transcriptions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTranscriptionResponse {
+    pub text: String,
+    /// Fields returned by the server that are not modelled above
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Audio API group.
+pub struct Audio<'c> {
+    client: &'c Client,
+}
+
+impl Audio<'_> {
+    /// Synthesize speech, returning the encoded audio.
+    pub async fn speech(&self, request: &CreateSpeechRequest) -> Result<Vec<u8>> {
+        let request = self
+            .client
+            .sdk
+            .client
+            .post(self.client.url("/v1/audio/speech"))
+            .json(request);
+        let response = self.client.sdk.execute(request).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Transcribe audio to text.
+    pub async fn transcriptions(
+        &self,
+        request: &CreateTranscriptionRequest,
+    ) -> Result<CreateTranscriptionResponse> {
+        let file = reqwest::multipart::Part::bytes(request.file.clone())
+            .file_name(request.file_name.clone());
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", request.model.clone());
+        if let Some(language) = &request.language {
+            form = form.text("language", language.clone());
+        }
+        if let Some(prompt) = &request.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+        if let Some(response_format) = &request.response_format {
+            form = form.text("response_format", response_format.clone());
+        }
+        if let Some(temperature) = request.temperature {
+            form = form.text("temperature", temperature.to_string());
+        }
+        let request = self
+            .client
+            .sdk
+            .client
+            .post(self.client.url("/v1/audio/transcriptions"))
+            .multipart(form);
+        self.client.send(request).await
+    }
+}
+
+// ==================== Images ====================
+
+/// Request body of `POST /v1/images/generations`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateImageRequest {
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    /// "url" or "b64_json"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// Response of `POST /v1/images/generations`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImagesResponse {
+    #[serde(default)]
+    pub created: i64,
+    pub data: Vec<ImageData>,
+}
+
+/// One generated image, by URL or inline base64.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub b64_json: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revised_prompt: Option<String>,
+}
+
+/// Images API group.
+pub struct Images<'c> {
+    client: &'c Client,
+}
+
+impl Images<'_> {
+    /// Generate images from a prompt.
+    pub async fn generate(&self, request: &CreateImageRequest) -> Result<ImagesResponse> {
+        let request = self
+            .client
+            .sdk
+            .client
+            .post(self.client.url("/v1/images/generations"))
+            .json(request);
+        self.client.send(request).await
+    }
+}
+
+// ==================== Models ====================
+
+/// Response of `GET /v1/models`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListModelsResponse {
+    #[serde(default)]
+    pub object: String,
+    pub data: Vec<Model>,
+}
+
+/// A model, which AGiXT maps to an agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Model {
+    pub id: String,
+    #[serde(default)]
+    pub object: String,
+    #[serde(default)]
+    pub created: i64,
+    #[serde(default)]
+    pub owned_by: String,
+}
+
+/// Models API group.
+pub struct Models<'c> {
+    client: &'c Client,
+}
+
+impl Models<'_> {
+    /// List the available models.
+    pub async fn list(&self) -> Result<ListModelsResponse> {
+        let request = self.client.sdk.client.get(self.client.url("/v1/models"));
+        self.client.send(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_embeddings_and_transcriptions() {
+        let mut server = mockito::Server::new_async().await;
+        let embeddings = server
+            .mock("POST", "/v1/embeddings")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"model": "XT", "input": ["a", "b"]}),
+            ))
+            .with_body(
+                r#"{"object": "list", "model": "XT", "data": [
+                    {"object": "embedding", "index": 0, "embedding": [0.1, 0.2]},
+                    {"object": "embedding", "index": 1, "embedding": [0.3, 0.4]}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let transcription = server
+            .mock("POST", "/v1/audio/transcriptions")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Regex("^multipart/form-data; boundary=".to_string()),
+            )
+            .match_body(mockito::Matcher::Regex(
+                r#"name="file"; filename="clip.wav""#.to_string(),
+            ))
+            .with_body(r#"{"text": "hello world"}"#)
+            .create_async()
+            .await;
+
+        let client = Client::new(AGiXTSDK::new(Some(server.url()), None, false));
+        let request = CreateEmbeddingRequest::new("XT", vec!["a".to_string(), "b".to_string()]);
+        let response = client.embeddings().create(&request).await.unwrap();
+        assert_eq!(response.data[1].embedding, vec![0.3, 0.4]);
+
+        let response = client
+            .audio()
+            .transcriptions(&CreateTranscriptionRequest {
+                file_name: "clip.wav".to_string(),
+                file: b"RIFF".to_vec(),
+                model: "XT".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.text, "hello world");
+        embeddings.assert_async().await;
+        transcription.assert_async().await;
+    }
+}
@@ -0,0 +1,250 @@
+//! Local rendering of AGiXT prompt templates.
+//!
+//! AGiXT prompts contain `{name}` placeholders that the server fills from the
+//! prompt arguments. [`PromptTemplate`] performs the same substitution locally,
+//! so the text sent by `prompt_agent` can be previewed and unit-tested offline.
+//!
+//! [`to_args`] builds prompt and chain arguments from a `Serialize` struct, so
+//! argument names are checked by the compiler instead of spelled out as map keys.
+
+use crate::error::{Error, Result};
+use crate::models::Prompt;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A prompt with `{name}` placeholders and the values to substitute into them.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::PromptTemplate;
+///
+/// let template = PromptTemplate::new("Context: {context}\nTranslate to {language}: {user_input}")
+///     .user_input("Good morning")
+///     .context("The user is travelling")
+///     .var("language", "French");
+/// assert_eq!(
+///     template.render(),
+///     "Context: The user is travelling\nTranslate to French: Good morning"
+/// );
+/// assert!(template.missing_variables().is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PromptTemplate {
+    content: String,
+    args: HashMap<String, serde_json::Value>,
+}
+
+impl PromptTemplate {
+    /// Create a template from prompt text.
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            args: HashMap::new(),
+        }
+    }
+
+    /// Set the `{user_input}` variable.
+    pub fn user_input(self, user_input: impl Into<String>) -> Self {
+        self.var("user_input", user_input.into())
+    }
+
+    /// Set the `{context}` variable, normally filled by the server from memories.
+    pub fn context(self, context: impl Into<String>) -> Self {
+        self.var("context", context.into())
+    }
+
+    /// Set a custom variable.
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.args.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set a variable for every field of `args`, flattened with [`to_args`].
+    pub fn vars<T: Serialize + ?Sized>(mut self, args: &T) -> Result<Self> {
+        self.args.extend(to_args(args)?);
+        Ok(self)
+    }
+
+    /// Template text before substitution.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Names of the placeholders in the template, in order of first appearance.
+    pub fn variables(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for (_, name) in placeholders(&self.content) {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+        names
+    }
+
+    /// Placeholders without a value. The server fills some of them itself, such as
+    /// `{context}` or `{agent_name}`, so they are not necessarily errors.
+    pub fn missing_variables(&self) -> Vec<String> {
+        self.variables()
+            .into_iter()
+            .filter(|name| !self.args.contains_key(name))
+            .collect()
+    }
+
+    /// Substitute the variables that have a value. Placeholders without a value
+    /// are left in place, as the server would.
+    pub fn render(&self) -> String {
+        let mut rendered = String::with_capacity(self.content.len());
+        let mut last = 0;
+        for (start, name) in placeholders(&self.content) {
+            if let Some(value) = self.args.get(name) {
+                rendered.push_str(&self.content[last..start]);
+                match value {
+                    serde_json::Value::String(s) => rendered.push_str(s),
+                    other => rendered.push_str(&other.to_string()),
+                }
+                last = start + name.len() + 2;
+            }
+        }
+        rendered.push_str(&self.content[last..]);
+        rendered
+    }
+
+    /// Arguments to pass to `prompt_agent` so the server renders the same text.
+    pub fn prompt_args(&self) -> HashMap<String, serde_json::Value> {
+        self.args.clone()
+    }
+}
+
+impl From<&Prompt> for PromptTemplate {
+    fn from(prompt: &Prompt) -> Self {
+        Self::new(prompt.content.clone())
+    }
+}
+
+/// Flatten a struct or map into the string arguments taken by `prompt_agent`
+/// and `run_chain`, keyed by field name.
+///
+/// Strings are passed as they are, numbers and booleans as their text, and
+/// nested lists and objects as compact JSON. `None` fields are left out, so the
+/// server applies its defaults. Fails with `Error::InvalidInput` if `args`
+/// does not serialize to an object.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::prompt::to_args;
+///
+/// #[derive(serde::Serialize)]
+/// struct ReportArgs {
+///     topic: String,
+///     max_words: u32,
+///     audience: Option<String>,
+/// }
+///
+/// let args = to_args(&ReportArgs {
+///     topic: "Solar power".to_string(),
+///     max_words: 300,
+///     audience: None,
+/// })
+/// .unwrap();
+/// assert_eq!(args["topic"], "Solar power");
+/// assert_eq!(args["max_words"], "300");
+/// assert!(!args.contains_key("audience"));
+/// ```
+pub fn to_args<T: Serialize + ?Sized>(args: &T) -> Result<HashMap<String, serde_json::Value>> {
+    let serde_json::Value::Object(fields) = serde_json::to_value(args)? else {
+        return Err(Error::InvalidInput(
+            "arguments must serialize to a JSON object".to_string(),
+        ));
+    };
+    Ok(fields
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let text = match value {
+                serde_json::Value::Null => return None,
+                serde_json::Value::String(text) => text,
+                value => value.to_string(),
+            };
+            Some((name, serde_json::Value::String(text)))
+        })
+        .collect())
+}
+
+/// Byte offset and name of every `{name}` placeholder, where names are made of
+/// ASCII letters, digits and underscores. Other braces, such as JSON examples in
+/// the prompt, are ignored.
+fn placeholders(content: &str) -> Vec<(usize, &str)> {
+    let mut found = Vec::new();
+    let mut rest = 0;
+    while let Some(offset) = content[rest..].find('{') {
+        let start = rest + offset;
+        let name_len = content[start + 1..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(content.len() - start - 1);
+        let end = start + 1 + name_len;
+        if name_len > 0 && content[end..].starts_with('}') {
+            found.push((start, &content[start + 1..end]));
+            rest = end + 1;
+        } else {
+            rest = start + 1;
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_leaves_unknown_placeholders() {
+        let template = PromptTemplate::new(
+            r#"{agent_name} answers {user_input} as {"json": {x}} {user_input}"#,
+        )
+        .user_input("hi")
+        .var("x", 1);
+        assert_eq!(template.variables(), vec!["agent_name", "user_input", "x"]);
+        assert_eq!(template.missing_variables(), vec!["agent_name"]);
+        assert_eq!(
+            template.render(),
+            r#"{agent_name} answers hi as {"json": 1} hi"#
+        );
+        assert_eq!(template.prompt_args()["user_input"], "hi");
+    }
+
+    #[test]
+    fn test_to_args_flattens_structs() {
+        #[derive(Serialize)]
+        struct Args {
+            language: &'static str,
+            temperature: f32,
+            strict: bool,
+            tags: Vec<&'static str>,
+            note: Option<String>,
+        }
+
+        let args = to_args(&Args {
+            language: "French",
+            temperature: 0.5,
+            strict: true,
+            tags: vec!["a", "b"],
+            note: None,
+        })
+        .unwrap();
+        assert_eq!(args.len(), 4);
+        assert_eq!(args["temperature"], "0.5");
+        assert_eq!(args["strict"], "true");
+        assert_eq!(args["tags"], r#"["a","b"]"#);
+        assert!(matches!(to_args(&[1, 2]), Err(Error::InvalidInput(_))));
+
+        let template = PromptTemplate::new("Answer in {language}.")
+            .vars(&Args {
+                language: "French",
+                temperature: 0.5,
+                strict: true,
+                tags: vec![],
+                note: None,
+            })
+            .unwrap();
+        assert_eq!(template.render(), "Answer in French.");
+    }
+}
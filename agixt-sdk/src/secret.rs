@@ -0,0 +1,151 @@
+//! Wrapper for credentials returned by the server, and redaction of
+//! credential settings in debug and log output.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Replacement shown for redacted values.
+const REDACTED: &str = "[REDACTED]";
+
+/// A sensitive value, such as a private key or an API key setting, that is
+/// redacted in `Debug` and `Display` output and wiped from memory when dropped.
+///
+/// Serializing a `Secret` writes the wrapped value, so it can be sent back to
+/// the server.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::Secret;
+///
+/// let key = Secret::new(String::from("5Kb8kLf9zgWQnogidDA76Mz"));
+/// assert_eq!(format!("{:?}", key), "Secret([REDACTED])");
+/// assert_eq!(key.to_string(), "[REDACTED]");
+/// assert_eq!(key.expose_secret(), "5Kb8kLf9zgWQnogidDA76Mz");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    /// Wrap a sensitive value.
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Borrow the wrapped value. Avoid logging or storing the result.
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T: Zeroize> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Zeroize + serde::Serialize> serde::Serialize for Secret<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Zeroize + serde::Deserialize<'de>> serde::Deserialize<'de> for Secret<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Whether a setting or field name holds a credential, such as
+/// `OPENAI_API_KEY`, `GITHUB_TOKEN` or `password`. Names are split at `_`, `-`
+/// and `.`, so `MAX_TOKENS` is not one; a bare `key` is not one either, as
+/// AGiXT uses it for IDs.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::is_secret_setting;
+///
+/// assert!(is_secret_setting("ANTHROPIC_API_KEY"));
+/// assert!(is_secret_setting("apiKey"));
+/// assert!(!is_secret_setting("AI_MAX_TOKENS"));
+/// ```
+pub fn is_secret_setting(name: &str) -> bool {
+    let parts: Vec<String> = name
+        .split(['_', '-', '.'])
+        .map(str::to_ascii_lowercase)
+        .collect();
+    parts.iter().any(|part| {
+        matches!(
+            part.as_str(),
+            "apikey" | "token" | "secret" | "password" | "passphrase" | "credentials"
+        )
+    }) || (parts.len() > 1 && parts.last().is_some_and(|part| part == "key"))
+}
+
+/// `Debug` view of a settings map with the values of credential settings
+/// redacted.
+pub(crate) struct RedactedSettings<'a>(pub(crate) &'a HashMap<String, Value>);
+
+impl fmt::Debug for RedactedSettings<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for (name, value) in self.0 {
+            if is_secret_setting(name) && !is_blank(value) {
+                map.entry(name, &format_args!("{}", REDACTED));
+            } else {
+                map.entry(name, value);
+            }
+        }
+        map.finish()
+    }
+}
+
+/// Replace the values of credential fields anywhere in `value`, returning
+/// whether any were replaced.
+pub(crate) fn redact_json(value: &mut Value) -> bool {
+    match value {
+        Value::Object(fields) => {
+            let mut redacted = false;
+            for (name, field) in fields.iter_mut() {
+                if is_secret_setting(name) && field.is_string() && !is_blank(field) {
+                    *field = Value::String(REDACTED.to_string());
+                    redacted = true;
+                } else {
+                    redacted |= redact_json(field);
+                }
+            }
+            redacted
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .fold(false, |redacted, item| redact_json(item) | redacted),
+        _ => false,
+    }
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(text) => text.is_empty(),
+        _ => false,
+    }
+}
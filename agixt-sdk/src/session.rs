@@ -0,0 +1,220 @@
+//! Client-side chat sessions with an agent.
+//!
+//! An [`AgentSession`] keeps the conversation name and the history of a chat
+//! with one agent, and sends that history as context with every prompt, so a
+//! chat application only has to pass in what the user typed.
+
+use crate::client::AGiXTSDK;
+use crate::error::Result;
+use crate::models::{history_to_messages, ChatCompletions, Message};
+
+/// A chat with one agent, with its history kept on the client.
+///
+/// Each [`send`](Self::send) replays the history through `chat_completions`,
+/// then appends the prompt and the agent's answer to it. The history can be
+/// capped by message count, and with the `tokens` feature by a token budget,
+/// in which case the oldest messages are dropped first.
+///
+/// # Example
+/// ```rust,no_run
+/// use agixt_sdk::session::AgentSession;
+/// use agixt_sdk::AGiXTSDK;
+///
+/// # async fn run(client: AGiXTSDK) -> agixt_sdk::Result<()> {
+/// let mut session = AgentSession::new(client, "XT")
+///     .conversation("Support chat")
+///     .system_prompt("Answer in one paragraph.")
+///     .max_messages(20);
+/// let answer = session.send("How do I reset my password?").await?;
+/// let follow_up = session.send("And if I lost my email access?").await?;
+/// println!("{}\n{}", answer, follow_up);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AgentSession {
+    client: AGiXTSDK,
+    agent_name: String,
+    conversation_name: String,
+    system_prompt: Option<String>,
+    history: Vec<Message>,
+    max_messages: Option<usize>,
+    #[cfg(feature = "tokens")]
+    token_budget: Option<usize>,
+}
+
+impl AgentSession {
+    /// Start a session with the agent named `agent_name`, in a new
+    /// conversation with a generated name.
+    pub fn new(client: AGiXTSDK, agent_name: impl Into<String>) -> Self {
+        let agent_name = agent_name.into();
+        Self {
+            client,
+            conversation_name: format!("{}-{}", agent_name, uuid::Uuid::new_v4()),
+            agent_name,
+            system_prompt: None,
+            history: Vec::new(),
+            max_messages: None,
+            #[cfg(feature = "tokens")]
+            token_budget: None,
+        }
+    }
+
+    /// Use the conversation named `conversation_name` on the server.
+    pub fn conversation(mut self, conversation_name: impl Into<String>) -> Self {
+        self.conversation_name = conversation_name.into();
+        self
+    }
+
+    /// Send `prompt` as a system message ahead of the history on every turn.
+    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Start from earlier history, e.g. from `get_full_conversation`. Activity
+    /// log entries are left out.
+    pub fn history(mut self, history: &[Message]) -> Self {
+        self.history = history_to_messages(history);
+        self
+    }
+
+    /// Keep at most `max_messages` messages of history.
+    pub fn max_messages(mut self, max_messages: usize) -> Self {
+        self.max_messages = Some(max_messages);
+        self
+    }
+
+    /// Drop the oldest history before each prompt until the request fits into
+    /// `context_window` tokens. Requires the `tokens` feature.
+    #[cfg(feature = "tokens")]
+    pub fn token_budget(mut self, context_window: usize) -> Self {
+        self.token_budget = Some(context_window);
+        self
+    }
+
+    /// Name of the agent the session talks to.
+    pub fn agent_name(&self) -> &str {
+        &self.agent_name
+    }
+
+    /// Name of the session's conversation on the server.
+    pub fn conversation_name(&self) -> &str {
+        &self.conversation_name
+    }
+
+    /// The history sent as context with the next prompt, oldest first.
+    pub fn messages(&self) -> &[Message] {
+        &self.history
+    }
+
+    /// Forget the history. The conversation on the server is kept.
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    /// Send `prompt` with the session's history and return the agent's answer,
+    /// which is added to the history along with the prompt. On error the
+    /// history is left unchanged.
+    pub async fn send(&mut self, prompt: impl Into<String>) -> Result<String> {
+        let prompt = Message::new("user", prompt.into());
+        let mut builder = ChatCompletions::builder()
+            .model(&self.agent_name)
+            .conversation(&self.conversation_name);
+        if let Some(system_prompt) = &self.system_prompt {
+            builder = builder.message("system", system_prompt);
+        }
+        for message in &self.history {
+            builder = builder.push_message(message.clone());
+        }
+        #[allow(unused_mut)]
+        let mut request = builder.push_message(prompt.clone()).build()?;
+        // Only history is trimmed: system messages and the new prompt stay
+        #[cfg(feature = "tokens")]
+        let mut dropped = self
+            .token_budget
+            .map_or(0, |context_window| request.trim_history(context_window));
+        #[cfg(not(feature = "tokens"))]
+        let mut dropped = 0;
+
+        let response = self.client.chat_completions(&request).await?;
+        self.history.retain(|message| {
+            let keep = dropped == 0 || message.normalized_role() == "system";
+            dropped -= usize::from(!keep);
+            keep
+        });
+        let answer = response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.text())
+            .unwrap_or_default();
+        self.history.push(prompt);
+        self.history.push(Message::new("assistant", answer.clone()));
+        if let Some(max_messages) = self.max_messages {
+            let excess = self.history.len().saturating_sub(max_messages);
+            self.history.drain(..excess);
+        }
+        Ok(answer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completion(answer: &str) -> String {
+        serde_json::json!({
+            "id": "1", "object": "chat.completion", "created": 0, "model": "XT",
+            "choices": [{"index": 0, "finish_reason": "stop",
+                "message": {"role": "assistant", "content": answer}}],
+            "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_session_replays_history() {
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "XT",
+                "user": "Support",
+                "messages": [
+                    {"role": "system", "content": "Be brief."},
+                    {"role": "user", "content": "Hi"}
+                ]
+            })))
+            .with_body(completion("Hello!"))
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "messages": [
+                    {"role": "system", "content": "Be brief."},
+                    {"role": "user", "content": "Hi"},
+                    {"role": "assistant", "content": "Hello!"},
+                    {"role": "user", "content": "Bye"}
+                ]
+            })))
+            .with_body(completion("Goodbye!"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AGiXTSDK::new(Some(server.url()), None, false);
+        let mut session = AgentSession::new(client, "XT")
+            .conversation("Support")
+            .system_prompt("Be brief.")
+            .max_messages(3);
+        assert_eq!(session.send("Hi").await.unwrap(), "Hello!");
+        assert_eq!(session.send("Bye").await.unwrap(), "Goodbye!");
+        let texts: Vec<String> = session.messages().iter().map(Message::text).collect();
+        assert_eq!(texts, ["Hello!", "Bye", "Goodbye!"]);
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+}
@@ -0,0 +1,113 @@
+//! Vector similarity for re-ranking on the client.
+//!
+//! Embeddings from the OpenAI-compatible embeddings endpoint
+//! ([`openai_compat::Embeddings`](crate::openai_compat::Embeddings)) can be
+//! compared here, e.g. to re-order memories or documents against a query
+//! without another request to the server.
+
+/// Dot product of two vectors, or 0.0 if their dimensions differ.
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Cosine similarity of two vectors, from -1.0 to 1.0. Vectors of different
+/// dimensions and zero vectors have a similarity of 0.0.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::similarity::cosine;
+///
+/// assert_eq!(cosine(&[1.0, 0.0], &[2.0, 0.0]), 1.0);
+/// assert_eq!(cosine(&[1.0, 0.0], &[0.0, 3.0]), 0.0);
+/// ```
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let norms = dot(a, a).sqrt() * dot(b, b).sqrt();
+    if norms == 0.0 {
+        return 0.0;
+    }
+    dot(a, b) / norms
+}
+
+/// Order candidates by the cosine similarity of their embedding to
+/// `query_embedding`, most similar first, returning each with its score.
+/// Candidates with equal scores keep their order.
+///
+/// # Example
+/// ```rust,no_run
+/// use agixt_sdk::openai_compat::{Client, CreateEmbeddingRequest};
+/// use agixt_sdk::similarity::rerank;
+/// use agixt_sdk::AGiXTSDK;
+///
+/// # async fn run(sdk: AGiXTSDK) -> agixt_sdk::Result<()> {
+/// let memories = sdk
+///     .get_agent_memories("agent-id", "deployment steps", Some(20), None, None)
+///     .await?;
+/// let mut texts = vec!["How do I deploy to staging?".to_string()];
+/// texts.extend(memories.iter().map(|memory| memory.text.clone()));
+///
+/// let client = Client::new(sdk);
+/// let embeddings = client
+///     .embeddings()
+///     .create(&CreateEmbeddingRequest::new("XT", texts))
+///     .await?;
+/// let (query, documents) = embeddings.data.split_first().unwrap();
+/// let ranked = rerank(
+///     &query.embedding,
+///     memories.iter().zip(documents.iter().map(|d| &d.embedding)),
+/// );
+/// for (memory, score) in ranked.iter().take(5) {
+///     println!("{:.3} {}", score, memory.text);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn rerank<T, E>(
+    query_embedding: &[f32],
+    candidates: impl IntoIterator<Item = (T, E)>,
+) -> Vec<(T, f32)>
+where
+    E: AsRef<[f32]>,
+{
+    let mut ranked: Vec<(T, f32)> = candidates
+        .into_iter()
+        .map(|(item, embedding)| {
+            let score = cosine(query_embedding, embedding.as_ref());
+            (item, score)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(dot(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]), 32.0);
+        assert_eq!(dot(&[1.0], &[1.0, 2.0]), 0.0);
+        assert!((cosine(&[1.0, 1.0], &[-1.0, -1.0]) + 1.0).abs() < 1e-6);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_rerank() {
+        let query = [1.0, 0.0];
+        let ranked = rerank(
+            &query,
+            vec![
+                ("unrelated", vec![0.0, 1.0]),
+                ("close", vec![0.9, 0.1]),
+                ("exact", vec![2.0, 0.0]),
+                ("also unrelated", vec![0.0, 2.0]),
+            ],
+        );
+        let order: Vec<&str> = ranked.iter().map(|(item, _)| *item).collect();
+        assert_eq!(order, ["exact", "close", "unrelated", "also unrelated"]);
+        assert_eq!(ranked[0].1, 1.0);
+    }
+}
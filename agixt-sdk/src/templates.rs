@@ -0,0 +1,186 @@
+//! Conversation templates for starting consistently primed conversations.
+//!
+//! A [`ConversationTemplate`] bundles the instructions and opening messages a
+//! product wants every conversation of a kind to start with, and is passed to
+//! `new_conversation_from_template`. Templates serialize to JSON, so they can
+//! be kept in files or a database next to the built-in ones from [`builtin`].
+
+use crate::models::{Message, Role};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A system prompt, opening messages and metadata to start conversations with.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::{ConversationTemplate, Message};
+///
+/// let template = ConversationTemplate::new("onboarding", "Welcome")
+///     .system_prompt("You help new customers set up their account. Be brief.")
+///     .message(Message::assistant("Hi! What would you like to set up first?"))
+///     .metadata("category", "support");
+/// assert_eq!(template.seed_messages().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationTemplate {
+    /// Identifier of the template, e.g. `code-review`
+    pub name: String,
+    /// Name given to conversations created from the template
+    pub title: String,
+    /// What the template is for, e.g. to list templates in a UI
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Instructions stored as the conversation's first message, with the
+    /// `system` role
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Messages the conversation starts with, after the system prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<Message>,
+    /// Data for the application, such as a category or UI hints; not sent
+    /// to the server
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+impl ConversationTemplate {
+    /// Create an empty template named `name` whose conversations are called `title`.
+    pub fn new(name: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the description.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Set the system prompt.
+    pub fn system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    /// Add an opening message.
+    pub fn message(mut self, message: Message) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    /// Set a metadata entry.
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// The messages a conversation created from the template starts with:
+    /// the system prompt, if any, then the opening messages.
+    pub fn seed_messages(&self) -> Vec<Message> {
+        self.system_prompt
+            .iter()
+            .map(|prompt| Message::new(Role::System, prompt.as_str()))
+            .chain(self.messages.iter().cloned())
+            .collect()
+    }
+}
+
+/// The built-in templates, for common kinds of conversation.
+///
+/// Their names are `customer-support`, `code-review`, `brainstorm`, `tutor`
+/// and `meeting-notes`; each carries a `category` metadata entry.
+pub fn builtin() -> Vec<ConversationTemplate> {
+    vec![
+        ConversationTemplate::new("customer-support", "Support request")
+            .description("Answer a customer's question about a product or account")
+            .system_prompt(
+                "You are a friendly support agent. Ask for the details you need before \
+                 answering, keep answers short, and say so when you don't know.",
+            )
+            .message(Message::assistant("Hi! How can I help you today?"))
+            .metadata("category", "support"),
+        ConversationTemplate::new("code-review", "Code review")
+            .description("Review a change for bugs, readability and missing tests")
+            .system_prompt(
+                "You are a senior engineer reviewing code. Point out bugs first, then \
+                 readability issues and missing tests. Quote the lines you comment on.",
+            )
+            .message(Message::assistant(
+                "Paste the diff or code you'd like reviewed, and tell me what it should do.",
+            ))
+            .metadata("category", "engineering"),
+        ConversationTemplate::new("brainstorm", "Brainstorm")
+            .description("Generate and refine ideas on a topic")
+            .system_prompt(
+                "You are a creative partner. Offer many varied ideas, build on the user's \
+                 suggestions, and hold back criticism until asked.",
+            )
+            .message(Message::assistant("What are we brainstorming about?"))
+            .metadata("category", "creative"),
+        ConversationTemplate::new("tutor", "Tutoring session")
+            .description("Teach a topic step by step, checking understanding")
+            .system_prompt(
+                "You are a patient tutor. Explain one step at a time, use examples, and \
+                 ask a short question to check understanding before moving on.",
+            )
+            .message(Message::assistant(
+                "What would you like to learn, and how much do you already know about it?",
+            ))
+            .metadata("category", "education"),
+        ConversationTemplate::new("meeting-notes", "Meeting notes")
+            .description("Turn a meeting transcript into notes and action items")
+            .system_prompt(
+                "You turn meeting transcripts into concise notes: a summary, the decisions \
+                 made, and action items with their owners.",
+            )
+            .message(Message::assistant(
+                "Paste the transcript or your rough notes from the meeting.",
+            ))
+            .metadata("category", "productivity"),
+    ]
+}
+
+/// The built-in template called `name`, if there is one.
+///
+/// # Example
+/// ```rust
+/// let template = agixt_sdk::templates::find_builtin("code-review").unwrap();
+/// assert_eq!(template.title, "Code review");
+/// ```
+pub fn find_builtin(name: &str) -> Option<ConversationTemplate> {
+    builtin().into_iter().find(|template| template.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_templates() {
+        let templates = builtin();
+        for (i, template) in templates.iter().enumerate() {
+            assert!(templates[i + 1..].iter().all(|t| t.name != template.name));
+            assert_eq!(template.seed_messages()[0].role, Role::System);
+            assert!(template.metadata.contains_key("category"));
+        }
+        assert!(find_builtin("tutor").is_some());
+        assert!(find_builtin("unknown").is_none());
+    }
+
+    #[test]
+    fn test_template_round_trip() {
+        let template = find_builtin("brainstorm").unwrap();
+        let json = serde_json::to_string(&template).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ConversationTemplate>(&json).unwrap(),
+            template
+        );
+
+        let minimal: ConversationTemplate =
+            serde_json::from_str(r#"{"name": "blank", "title": "Chat"}"#).unwrap();
+        assert!(minimal.seed_messages().is_empty());
+    }
+}
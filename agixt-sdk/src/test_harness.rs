@@ -0,0 +1,174 @@
+//! Test support: a disposable AGiXT server in Docker.
+//!
+//! `AgixtContainer` starts the AGiXT image with testcontainers, waits until the
+//! server answers its health check and hands out clients configured for it.
+//! The container is removed when the `AgixtContainer` is dropped. Requires a
+//! running Docker daemon.
+//!
+//! # Example
+//! ```rust,no_run
+//! use agixt_sdk::test_harness::AgixtContainer;
+//!
+//! #[tokio::test]
+//! async fn providers_are_listed() {
+//!     let server = AgixtContainer::start().await.unwrap();
+//!     let providers = server.client().get_providers().await.unwrap();
+//!     assert!(!providers.is_empty());
+//! }
+//! ```
+
+use crate::error::{Error, Result};
+use crate::AGiXTSDK;
+use std::time::Duration;
+use testcontainers::core::{IntoContainerPort, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, GenericImage, ImageExt};
+
+/// Image started by default.
+pub const DEFAULT_IMAGE: &str = "joshxt/agixt";
+/// Tag of `DEFAULT_IMAGE` started by default.
+pub const DEFAULT_TAG: &str = "main";
+/// Port the AGiXT API listens on inside the container.
+const API_PORT: u16 = 7437;
+/// Interval between health checks while the server starts.
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A running AGiXT server container.
+pub struct AgixtContainer {
+    container: ContainerAsync<GenericImage>,
+    base_uri: String,
+    api_key: String,
+}
+
+impl AgixtContainer {
+    /// Start `DEFAULT_IMAGE:DEFAULT_TAG` with the default settings.
+    pub async fn start() -> Result<Self> {
+        Self::builder().start().await
+    }
+
+    /// Configure the image, API key and environment before starting.
+    pub fn builder() -> AgixtContainerBuilder {
+        AgixtContainerBuilder::default()
+    }
+
+    /// Base URI of the server, e.g. `http://localhost:32768`.
+    pub fn base_uri(&self) -> &str {
+        &self.base_uri
+    }
+
+    /// API key the server was started with.
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    /// Docker ID of the container.
+    pub fn id(&self) -> &str {
+        self.container.id()
+    }
+
+    /// A client for the server, authenticated with its API key.
+    pub fn client(&self) -> AGiXTSDK {
+        AGiXTSDK::new(
+            Some(self.base_uri.clone()),
+            Some(self.api_key.clone()),
+            false,
+        )
+    }
+}
+
+/// Builder for [`AgixtContainer`].
+#[derive(Debug, Clone)]
+pub struct AgixtContainerBuilder {
+    image: String,
+    tag: String,
+    api_key: String,
+    env: Vec<(String, String)>,
+    startup_timeout: Duration,
+}
+
+impl Default for AgixtContainerBuilder {
+    fn default() -> Self {
+        Self {
+            image: DEFAULT_IMAGE.to_string(),
+            tag: DEFAULT_TAG.to_string(),
+            api_key: "test-api-key".to_string(),
+            env: vec![("DATABASE_TYPE".to_string(), "sqlite".to_string())],
+            startup_timeout: Duration::from_secs(180),
+        }
+    }
+}
+
+impl AgixtContainerBuilder {
+    /// Start another image, e.g. a pinned release or a locally built one.
+    pub fn image(mut self, image: impl Into<String>, tag: impl Into<String>) -> Self {
+        self.image = image.into();
+        self.tag = tag.into();
+        self
+    }
+
+    /// Set the API key the server accepts (defaults to `test-api-key`).
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+
+    /// Set an environment variable of the server, e.g. a provider API key.
+    /// The server uses SQLite unless `DATABASE_TYPE` is set here.
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        self.env.retain(|(existing, _)| *existing != name);
+        self.env.push((name, value.into()));
+        self
+    }
+
+    /// How long to wait for the container to start and pass its health check,
+    /// including pulling the image (defaults to 3 minutes).
+    pub fn startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup_timeout = timeout;
+        self
+    }
+
+    /// Start the container and wait until the server is healthy.
+    pub async fn start(self) -> Result<AgixtContainer> {
+        let docker_error = |e: testcontainers::TestcontainersError| {
+            Error::Other(format!("AGiXT container: {}", e))
+        };
+        let deadline = web_time::Instant::now() + self.startup_timeout;
+
+        let request = GenericImage::new(self.image, self.tag)
+            .with_exposed_port(API_PORT.tcp())
+            .with_wait_for(WaitFor::Nothing)
+            .with_env_var("AGIXT_API_KEY", self.api_key.clone())
+            .with_startup_timeout(self.startup_timeout);
+        let request = self
+            .env
+            .into_iter()
+            .fold(request, |request, (name, value)| {
+                request.with_env_var(name, value)
+            });
+        let container = request.start().await.map_err(docker_error)?;
+        let host = container.get_host().await.map_err(docker_error)?;
+        let port = container
+            .get_host_port_ipv4(API_PORT)
+            .await
+            .map_err(docker_error)?;
+
+        let server = AgixtContainer {
+            container,
+            base_uri: format!("http://{}:{}", host, port),
+            api_key: self.api_key,
+        };
+        let client = server.client();
+        while !client.health().await.unwrap_or(false) {
+            if web_time::Instant::now() >= deadline {
+                return Err(Error::Other(format!(
+                    "AGiXT container {} not healthy after {:?}",
+                    server.id(),
+                    self.startup_timeout
+                )));
+            }
+            tokio::time::sleep(READY_POLL_INTERVAL).await;
+        }
+        Ok(server)
+    }
+}
@@ -0,0 +1,316 @@
+//! Test support: an in-process fake AGiXT server.
+//!
+//! `FakeAgixtServer` listens on a local port and answers with canned, realistic
+//! payloads for agents, conversations and providers. Responses can be replaced
+//! per route, and every request is recorded for assertions.
+//!
+//! # Example
+//! ```rust
+//! use agixt_sdk::testing::FakeAgixtServer;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = FakeAgixtServer::start().await;
+//!     let client = server.client();
+//!
+//!     let agents = client.get_agents().await.unwrap();
+//!     assert_eq!(agents[0].id, FakeAgixtServer::AGENT_ID);
+//!
+//!     server.respond("GET", "/v1/agent", 200, serde_json::json!({"agents": []}));
+//!     assert!(client.get_agents().await.unwrap().is_empty());
+//! }
+//! ```
+
+use crate::AGiXTSDK;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// A canned response returned for a route.
+#[derive(Debug, Clone)]
+pub struct FakeResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// JSON response body.
+    pub body: Value,
+}
+
+/// A request received by the fake server.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// HTTP method, e.g. `POST`.
+    pub method: String,
+    /// Request path without the query string.
+    pub path: String,
+    /// Raw query string, if any.
+    pub query: Option<String>,
+    /// Request headers, with lowercase names.
+    pub headers: HashMap<String, String>,
+    /// Raw request body.
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Parse the request body as JSON.
+    pub fn json(&self) -> serde_json::Result<Value> {
+        serde_json::from_str(&self.body)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    routes: HashMap<(String, String), FakeResponse>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// In-process fake AGiXT server for integration tests.
+///
+/// The server stops when the value is dropped.
+pub struct FakeAgixtServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl FakeAgixtServer {
+    /// ID of the canned agent.
+    pub const AGENT_ID: &'static str = "3f1c9a52-6d1e-4b8f-9a57-2c4e8b1d7f10";
+    /// ID of the canned conversation.
+    pub const CONVERSATION_ID: &'static str = "8a2d4e6f-1b3c-4d5e-8f7a-9b0c1d2e3f40";
+    /// API key accepted by the fake server and used by `client()`.
+    pub const API_KEY: &'static str = "test-api-key";
+
+    /// Start the server on a random local port with the canned routes installed.
+    ///
+    /// # Panics
+    /// Panics if no local port can be bound or if called outside a tokio runtime.
+    pub async fn start() -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")
+            .expect("failed to bind a local port for the fake AGiXT server");
+        listener
+            .set_nonblocking(true)
+            .expect("failed to configure the fake AGiXT server socket");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let state = Arc::new(Mutex::new(State::default()));
+        let make_service = {
+            let state = state.clone();
+            make_service_fn(move |_| {
+                let state = state.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request| handle(state.clone(), request)))
+                }
+            })
+        };
+
+        let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+        let server = hyper::Server::from_tcp(listener)
+            .expect("failed to start the fake AGiXT server")
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                let _ = shutdown_signal.await;
+            });
+        tokio::spawn(server);
+
+        let fake = Self {
+            url,
+            state,
+            shutdown: Some(shutdown),
+        };
+        fake.install_defaults();
+        fake
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:40213`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Create a client pointed at this server.
+    pub fn client(&self) -> AGiXTSDK {
+        AGiXTSDK::new(
+            Some(self.url.clone()),
+            Some(Self::API_KEY.to_string()),
+            false,
+        )
+    }
+
+    /// Answer `method path` with `status` and a JSON `body`, replacing any
+    /// existing response for that route. The query string is ignored when matching.
+    pub fn respond(&self, method: &str, path: &str, status: u16, body: Value) {
+        self.lock().routes.insert(
+            (method.to_uppercase(), path.to_string()),
+            FakeResponse { status, body },
+        );
+    }
+
+    /// All requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn install_defaults(&self) {
+        let agent = format!("/v1/agent/{}", Self::AGENT_ID);
+        let conversation = format!("/v1/conversation/{}", Self::CONVERSATION_ID);
+
+        self.respond("GET", "/health", 200, json!({"status": "UP"}));
+        self.respond(
+            "GET",
+            "/v1/agent",
+            200,
+            json!({"agents": [{"id": Self::AGENT_ID, "name": "AGiXT", "status": false}]}),
+        );
+        self.respond(
+            "GET",
+            &agent,
+            200,
+            json!({"agent": {
+                "name": "AGiXT",
+                "settings": {"provider": "gpt4free", "AI_MODEL": "gpt-4o", "mode": "prompt"},
+                "commands": {"Web Search": false},
+            }}),
+        );
+        self.respond(
+            "POST",
+            &format!("{}/prompt", agent),
+            200,
+            json!({"response": "Hello from the fake AGiXT server."}),
+        );
+        self.respond(
+            "GET",
+            "/v1/conversations",
+            200,
+            json!({"conversations": [{
+                "id": Self::CONVERSATION_ID,
+                "name": "Test Conversation",
+                "agent_id": Self::AGENT_ID,
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-01-01T00:01:00Z",
+            }]}),
+        );
+        self.respond(
+            "GET",
+            &conversation,
+            200,
+            json!({"conversation_history": [
+                {"id": "1", "role": "USER", "content": "Hello", "timestamp": "2024-01-01T00:00:00Z"},
+                {"id": "2", "role": "AGiXT", "content": "Hi! How can I help?", "timestamp": "2024-01-01T00:00:05Z"},
+            ]}),
+        );
+        self.respond(
+            "GET",
+            "/v1/provider",
+            200,
+            json!({"providers": [
+                {"name": "gpt4free", "services": ["llm"]},
+                {"name": "openai", "services": ["llm", "tts", "image", "embeddings", "transcription"]},
+                {"name": "default", "services": ["llm", "tts", "embeddings", "transcription"]},
+            ]}),
+        );
+    }
+}
+
+impl Drop for FakeAgixtServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Body>,
+) -> std::result::Result<Response<Body>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+    let recorded = RecordedRequest {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        query: parts.uri.query().map(String::from),
+        headers: parts
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (recorded.method.clone(), recorded.path.clone());
+        let response = state.routes.get(&key).cloned();
+        state.requests.push(recorded);
+        response
+    }
+    .unwrap_or(FakeResponse {
+        status: 404,
+        body: json!({"detail": "Not Found"}),
+    });
+
+    Ok(Response::builder()
+        .status(response.status)
+        .header("content-type", "application/json")
+        .body(Body::from(response.body.to_string()))
+        .unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_canned_routes() {
+        let server = FakeAgixtServer::start().await;
+        let client = server.client();
+
+        assert!(client.health().await.unwrap());
+        let agent_id = client.get_agent_id_by_name("AGiXT").await.unwrap();
+        assert_eq!(agent_id.as_deref(), Some(FakeAgixtServer::AGENT_ID));
+        let history = client
+            .get_conversation(FakeAgixtServer::CONVERSATION_ID, None, None)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        let reply = client
+            .chat(
+                FakeAgixtServer::AGENT_ID,
+                "Hi",
+                FakeAgixtServer::CONVERSATION_ID,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hello from the fake AGiXT server.");
+        assert_eq!(client.get_providers().await.unwrap().len(), 3);
+
+        let requests = server.requests();
+        let prompt = requests.iter().find(|r| r.method == "POST").unwrap();
+        assert_eq!(prompt.json().unwrap()["prompt_args"]["user_input"], "Hi");
+        assert_eq!(prompt.headers["authorization"], FakeAgixtServer::API_KEY);
+    }
+
+    #[tokio::test]
+    async fn test_programmed_response() {
+        let server = FakeAgixtServer::start().await;
+        server.respond(
+            "GET",
+            "/v1/agent",
+            200,
+            json!({"agents": [{"id": "a1", "name": "One"}, {"id": "a2", "name": "Two"}]}),
+        );
+
+        let client = server.client();
+        assert_eq!(client.get_agents().await.unwrap().len(), 2);
+        assert!(client.get_agentconfig("unknown").await.is_err());
+        assert_eq!(server.requests()[1].path, "/v1/agent/unknown");
+    }
+}
@@ -0,0 +1,179 @@
+//! Local token counting with OpenAI's BPE encodings.
+//!
+//! Counts are estimates: AGiXT agents may run models with other tokenizers,
+//! and the server adds its own prompt around the messages. They are meant for
+//! keeping requests comfortably inside a context window, e.g. with
+//! `ChatCompletions::trim_history`.
+
+use crate::models::{ChatCompletions, Message, MessageContent};
+use std::sync::OnceLock;
+use tiktoken_rs::CoreBPE;
+
+/// Tokens added around every message by the chat format.
+const TOKENS_PER_MESSAGE: usize = 3;
+/// Tokens that prime the assistant's reply.
+const REPLY_PRIMING: usize = 3;
+/// Flat estimate for an image part (a low-detail image in OpenAI's accounting).
+const TOKENS_PER_IMAGE: usize = 85;
+
+/// BPE encoding used to count tokens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// `cl100k_base`, used by GPT-4 and GPT-3.5
+    #[default]
+    Cl100k,
+    /// `o200k_base`, used by GPT-4o and the o-series
+    O200k,
+}
+
+impl Encoding {
+    /// Encoding of an OpenAI model name, falling back to `Cl100k` for other
+    /// names such as AGiXT agent names.
+    pub fn for_model(model: &str) -> Self {
+        let model = model.to_ascii_lowercase();
+        if ["gpt-4o", "gpt-4.1", "gpt-5", "o1", "o3", "o4"]
+            .iter()
+            .any(|prefix| model.starts_with(prefix))
+        {
+            Encoding::O200k
+        } else {
+            Encoding::Cl100k
+        }
+    }
+
+    fn bpe(self) -> &'static CoreBPE {
+        static CL100K: OnceLock<CoreBPE> = OnceLock::new();
+        static O200K: OnceLock<CoreBPE> = OnceLock::new();
+        // The encodings are bundled with tiktoken-rs, so loading cannot fail
+        match self {
+            Encoding::Cl100k => CL100K
+                .get_or_init(|| tiktoken_rs::cl100k_base().expect("bundled cl100k_base encoding")),
+            Encoding::O200k => O200K
+                .get_or_init(|| tiktoken_rs::o200k_base().expect("bundled o200k_base encoding")),
+        }
+    }
+}
+
+/// Number of tokens in `text`.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::tokens::{count_tokens, Encoding};
+///
+/// assert_eq!(count_tokens("Hello, world!", Encoding::Cl100k), 4);
+/// ```
+pub fn count_tokens(text: &str, encoding: Encoding) -> usize {
+    encoding.bpe().encode_ordinary(text).len()
+}
+
+/// Number of tokens `message` takes up in a chat request, including the
+/// per-message overhead of the chat format.
+pub fn count_message_tokens(message: &Message, encoding: Encoding) -> usize {
+    let content = match &message.content {
+        MessageContent::Text(text) => count_tokens(text, encoding),
+        MessageContent::Structured(parts) => parts
+            .iter()
+            .map(|part| match &part.text {
+                Some(text) => count_tokens(text, encoding),
+                None if part.image_url.is_some() => TOKENS_PER_IMAGE,
+                None => 0,
+            })
+            .sum(),
+    };
+    TOKENS_PER_MESSAGE + count_tokens(message.role.as_str(), encoding) + content
+}
+
+impl ChatCompletions {
+    /// Estimate the prompt tokens of the request's messages, with the encoding
+    /// of its model. Requires the `tokens` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use agixt_sdk::ChatCompletions;
+    ///
+    /// let request = ChatCompletions::builder()
+    ///     .model("gpt-4o")
+    ///     .message("user", "Hello, world!")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(request.estimate_tokens(), 11);
+    /// ```
+    pub fn estimate_tokens(&self) -> usize {
+        let encoding = Encoding::for_model(&self.model);
+        match &self.messages {
+            Some(messages) => {
+                messages
+                    .iter()
+                    .map(|message| count_message_tokens(message, encoding))
+                    .sum::<usize>()
+                    + REPLY_PRIMING
+            }
+            None => 0,
+        }
+    }
+
+    /// Drop the oldest messages until `estimate_tokens` plus `max_tokens` fits
+    /// into `context_window`, returning how many were dropped. System messages
+    /// and the latest message are kept, so the result may still not fit.
+    /// Requires the `tokens` feature.
+    pub fn trim_history(&mut self, context_window: usize) -> usize {
+        let budget = context_window.saturating_sub(self.max_tokens.unwrap_or(0).max(0) as usize);
+        let encoding = Encoding::for_model(&self.model);
+        let Some(messages) = self.messages.as_mut() else {
+            return 0;
+        };
+        let mut total = messages
+            .iter()
+            .map(|message| count_message_tokens(message, encoding))
+            .sum::<usize>()
+            + REPLY_PRIMING;
+        let mut dropped = 0;
+        let mut index = 0;
+        while total > budget && index + 1 < messages.len() {
+            if messages[index].normalized_role() == "system" {
+                index += 1;
+                continue;
+            }
+            total -= count_message_tokens(&messages.remove(index), encoding);
+            dropped += 1;
+        }
+        dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_history_keeps_system_and_latest() {
+        let mut request = ChatCompletions::builder()
+            .message("system", "Be brief.")
+            .message("user", "old question ".repeat(50))
+            .message("assistant", "old answer ".repeat(50))
+            .message("user", "New question?")
+            .max_tokens(100)
+            .build()
+            .unwrap();
+        let before = request.estimate_tokens();
+        assert!(before > 200);
+
+        assert_eq!(request.trim_history(150), 2);
+        let messages = request.messages.as_ref().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(messages[1].text(), "New question?");
+        assert!(request.estimate_tokens() + 100 <= 150);
+
+        assert_eq!(request.trim_history(10), 0);
+        assert_eq!(request.messages.as_ref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_encoding_for_model() {
+        assert_eq!(Encoding::for_model("gpt-4o-mini"), Encoding::O200k);
+        assert_eq!(Encoding::for_model("gpt-4-turbo"), Encoding::Cl100k);
+        assert_eq!(Encoding::for_model("my_agent"), Encoding::Cl100k);
+    }
+}
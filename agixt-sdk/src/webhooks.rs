@@ -0,0 +1,404 @@
+//! Helpers for services receiving AGiXT webhook deliveries.
+//!
+//! AGiXT signs each outgoing webhook payload with HMAC-SHA256 using the secret
+//! configured on the webhook, and sends the hex digest in the signature header
+//! (optionally prefixed with `sha256=`).
+//!
+//! [`WebhookEvent::parse`] turns a delivery into a typed event, and an
+//! [`EventDispatcher`] verifies deliveries and routes them to async handlers.
+
+use crate::error::{Error, Result};
+use crate::models::Role;
+use futures_util::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::future::Future;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header AGiXT uses to carry the payload signature.
+pub const SIGNATURE_HEADER: &str = "X-AGiXT-Signature";
+
+/// Compute the signature AGiXT would send for `payload` signed with `secret`.
+pub fn sign_payload(payload: &[u8], secret: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Verify that `signature_header` is a valid signature of `payload` for `secret`.
+///
+/// # Arguments
+/// * `payload` - Raw request body exactly as received
+/// * `signature_header` - Value of the signature header, with or without the `sha256=` prefix
+/// * `secret` - Secret configured on the webhook
+///
+/// # Returns
+/// `true` if the signature matches. The comparison runs in constant time.
+pub fn verify_signature(payload: &[u8], signature_header: &str, secret: &str) -> bool {
+    let signature = signature_header.trim();
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    let expected = match hex::decode(signature) {
+        Ok(bytes) => bytes,
+        Err(_) => return false,
+    };
+
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// A message was added to a conversation (`message.created`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageCreatedEvent {
+    pub conversation_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_name: Option<String>,
+    #[serde(default, alias = "id", skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default, alias = "message")]
+    pub content: String,
+}
+
+/// A chain run finished (`chain.completed`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainCompletedEvent {
+    pub chain_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// Output of the last step
+    #[serde(default, alias = "output")]
+    pub response: serde_json::Value,
+}
+
+/// A scheduled task finished running (`task.completed`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskFinishedEvent {
+    #[serde(alias = "id")]
+    pub task_id: String,
+    #[serde(default, alias = "name", skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// Final status reported by the server, e.g. "completed" or "failed"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, alias = "output", alias = "response")]
+    pub result: serde_json::Value,
+}
+
+/// A webhook delivery, parsed by its event type.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum WebhookEvent {
+    MessageCreated(MessageCreatedEvent),
+    ChainCompleted(ChainCompletedEvent),
+    TaskFinished(TaskFinishedEvent),
+    /// An event type this SDK version does not know
+    Other {
+        event_type: String,
+        data: serde_json::Value,
+    },
+}
+
+impl WebhookEvent {
+    /// Parse a delivery body of the form `{"event_type": ..., "data": {...}}`.
+    /// The type may also be given as `event` or `type`, and a body without
+    /// `data` is taken as the data itself.
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        let mut value: serde_json::Value = serde_json::from_slice(payload)?;
+        let event_type = ["event_type", "event", "type"]
+            .iter()
+            .find_map(|field| value.get(field).and_then(|v| v.as_str()))
+            .unwrap_or_default()
+            .to_string();
+        let data = value
+            .get_mut("data")
+            .map(serde_json::Value::take)
+            .unwrap_or(value);
+
+        Ok(match event_type.as_str() {
+            "message.created" | "message_created" => {
+                WebhookEvent::MessageCreated(serde_json::from_value(data)?)
+            }
+            "chain.completed" | "chain_completed" => {
+                WebhookEvent::ChainCompleted(serde_json::from_value(data)?)
+            }
+            "task.completed" | "task.finished" | "task_completed" => {
+                WebhookEvent::TaskFinished(serde_json::from_value(data)?)
+            }
+            _ => WebhookEvent::Other { event_type, data },
+        })
+    }
+
+    /// The event type, e.g. "message.created".
+    pub fn event_type(&self) -> &str {
+        match self {
+            WebhookEvent::MessageCreated(_) => "message.created",
+            WebhookEvent::ChainCompleted(_) => "chain.completed",
+            WebhookEvent::TaskFinished(_) => "task.completed",
+            WebhookEvent::Other { event_type, .. } => event_type,
+        }
+    }
+}
+
+/// A registered handler, returning the future to run if it wants the event.
+type Handler = Box<dyn Fn(&WebhookEvent) -> Option<BoxFuture<'static, ()>> + Send + Sync>;
+
+/// Routes webhook deliveries to async handlers registered per event type.
+///
+/// Handlers run one after another in the order they were registered. With a
+/// secret set, [`handle`](Self::handle) rejects deliveries whose signature does
+/// not match before any handler runs.
+///
+/// # Example
+/// ```rust
+/// use agixt_sdk::webhooks::{EventDispatcher, SIGNATURE_HEADER};
+///
+/// # async fn run(body: &[u8], signature: Option<&str>) -> agixt_sdk::Result<()> {
+/// let dispatcher = EventDispatcher::new()
+///     .secret("webhook-secret")
+///     .on_message_created(|event| async move {
+///         println!("{}: {}", event.role, event.content);
+///     })
+///     .on_task_finished(|event| async move {
+///         println!("task {} finished", event.task_id);
+///     });
+///
+/// // In the HTTP handler, with the raw body and the SIGNATURE_HEADER value
+/// dispatcher.handle(body, signature).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct EventDispatcher {
+    secret: Option<String>,
+    handlers: Vec<Handler>,
+}
+
+impl std::fmt::Debug for EventDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventDispatcher")
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .field("handlers", &self.handlers.len())
+            .finish()
+    }
+}
+
+impl EventDispatcher {
+    /// Create a dispatcher without handlers or signature checks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require deliveries passed to `handle` to be signed with `secret`.
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Run `handler` for every `message.created` event.
+    pub fn on_message_created<F, Fut>(self, handler: F) -> Self
+    where
+        F: Fn(MessageCreatedEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.register(move |event| match event {
+            WebhookEvent::MessageCreated(event) => Some(Box::pin(handler(event.clone())) as _),
+            _ => None,
+        })
+    }
+
+    /// Run `handler` for every `chain.completed` event.
+    pub fn on_chain_completed<F, Fut>(self, handler: F) -> Self
+    where
+        F: Fn(ChainCompletedEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.register(move |event| match event {
+            WebhookEvent::ChainCompleted(event) => Some(Box::pin(handler(event.clone())) as _),
+            _ => None,
+        })
+    }
+
+    /// Run `handler` for every `task.completed` event.
+    pub fn on_task_finished<F, Fut>(self, handler: F) -> Self
+    where
+        F: Fn(TaskFinishedEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.register(move |event| match event {
+            WebhookEvent::TaskFinished(event) => Some(Box::pin(handler(event.clone())) as _),
+            _ => None,
+        })
+    }
+
+    /// Run `handler` for events of the given type, including types without a
+    /// typed handler of their own.
+    pub fn on<F, Fut>(self, event_type: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(WebhookEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let event_type = event_type.into();
+        self.register(move |event| {
+            (event.event_type() == event_type).then(|| Box::pin(handler(event.clone())) as _)
+        })
+    }
+
+    /// Run `handler` for every event.
+    pub fn on_any<F, Fut>(self, handler: F) -> Self
+    where
+        F: Fn(WebhookEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.register(move |event| Some(Box::pin(handler(event.clone())) as _))
+    }
+
+    fn register(
+        mut self,
+        handler: impl Fn(&WebhookEvent) -> Option<BoxFuture<'static, ()>> + Send + Sync + 'static,
+    ) -> Self {
+        self.handlers.push(Box::new(handler));
+        self
+    }
+
+    /// Run the handlers registered for `event`, returning how many ran.
+    pub async fn dispatch(&self, event: &WebhookEvent) -> usize {
+        let mut ran = 0;
+        for handler in &self.handlers {
+            if let Some(future) = handler(event) {
+                future.await;
+                ran += 1;
+            }
+        }
+        ran
+    }
+
+    /// Verify, parse and dispatch a delivery, given its raw body and the value
+    /// of the [`SIGNATURE_HEADER`] header. Returns the parsed event.
+    ///
+    /// Fails with `Error::InvalidInput` if a secret is set and the signature is
+    /// missing or does not match, and with `Error::JsonError` if the body is
+    /// not a valid event.
+    pub async fn handle(&self, payload: &[u8], signature: Option<&str>) -> Result<WebhookEvent> {
+        if let Some(secret) = &self.secret {
+            if !signature.is_some_and(|signature| verify_signature(payload, signature, secret)) {
+                return Err(Error::InvalidInput(
+                    "webhook signature is missing or does not match".to_string(),
+                ));
+            }
+        }
+        let event = WebhookEvent::parse(payload)?;
+        self.dispatch(&event).await;
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_signature_round_trip() {
+        let payload = br#"{"event":"message.created"}"#;
+        let signature = sign_payload(payload, "shh");
+        assert!(verify_signature(payload, &signature, "shh"));
+        assert!(verify_signature(
+            payload,
+            signature.trim_start_matches("sha256="),
+            "shh"
+        ));
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_tampering() {
+        let payload = br#"{"event":"message.created"}"#;
+        let signature = sign_payload(payload, "shh");
+        assert!(!verify_signature(b"{}", &signature, "shh"));
+        assert!(!verify_signature(payload, &signature, "other"));
+        assert!(!verify_signature(payload, "sha256=not-hex", "shh"));
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let event = WebhookEvent::parse(
+            br#"{"event_type": "message.created", "data": {"conversation_id": "c1",
+                "id": "m1", "role": "assistant", "message": "Done"}}"#,
+        )
+        .unwrap();
+        assert_eq!(event.event_type(), "message.created");
+        let WebhookEvent::MessageCreated(message) = event else {
+            panic!("expected a message event");
+        };
+        assert_eq!(message.message_id.as_deref(), Some("m1"));
+        assert_eq!(message.content, "Done");
+
+        let event =
+            WebhookEvent::parse(br#"{"event": "task.finished", "id": "t1", "status": "failed"}"#)
+                .unwrap();
+        let WebhookEvent::TaskFinished(task) = event else {
+            panic!("expected a task event");
+        };
+        assert_eq!(task.task_id, "t1");
+        assert_eq!(task.status.as_deref(), Some("failed"));
+
+        let event =
+            WebhookEvent::parse(br#"{"type": "agent.deleted", "data": {"id": "a1"}}"#).unwrap();
+        assert_eq!(event.event_type(), "agent.deleted");
+        assert!(WebhookEvent::parse(br#"{"event": "chain.completed", "data": {}}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_routes_events() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (chains, any, deleted) = (seen.clone(), seen.clone(), seen.clone());
+        let dispatcher = EventDispatcher::new()
+            .secret("shh")
+            .on_chain_completed(move |event| {
+                let seen = chains.clone();
+                async move {
+                    seen.lock()
+                        .unwrap()
+                        .push(format!("chain {}", event.chain_name))
+                }
+            })
+            .on("agent.deleted", move |_| {
+                let seen = deleted.clone();
+                async move { seen.lock().unwrap().push("deleted".to_string()) }
+            })
+            .on_any(move |event| {
+                let seen = any.clone();
+                async move { seen.lock().unwrap().push(event.event_type().to_string()) }
+            });
+
+        let payload = br#"{"event_type": "chain.completed", "data": {"chain_name": "Flow"}}"#;
+        let signature = sign_payload(payload, "shh");
+        let event = dispatcher.handle(payload, Some(&signature)).await.unwrap();
+        assert!(matches!(event, WebhookEvent::ChainCompleted(_)));
+        let other = WebhookEvent::parse(br#"{"event": "agent.deleted"}"#).unwrap();
+        assert_eq!(dispatcher.dispatch(&other).await, 2);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            ["chain Flow", "chain.completed", "deleted", "agent.deleted"]
+        );
+
+        let err = dispatcher.handle(payload, None).await.unwrap_err();
+        assert!(matches!(err, Error::InvalidInput(_)));
+        assert!(dispatcher.handle(payload, Some("sha256=00")).await.is_err());
+        assert_eq!(seen.lock().unwrap().len(), 4);
+    }
+}
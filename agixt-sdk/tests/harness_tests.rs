@@ -0,0 +1,46 @@
+//! End-to-end tests against a disposable AGiXT container.
+//!
+//! Requires Docker and the `test-harness` feature; run them with
+//! `cargo test --features test-harness --test harness_tests -- --ignored`.
+
+#![cfg(feature = "test-harness")]
+
+use agixt_sdk::test_harness::AgixtContainer;
+use std::collections::HashMap;
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_agent_and_conversation_round_trip() {
+    let server = AgixtContainer::start().await.expect("AGiXT container");
+    let sdk = server.client();
+
+    assert!(sdk.health().await.unwrap());
+    assert!(!sdk.get_providers().await.unwrap().is_empty());
+
+    let mut settings = HashMap::new();
+    settings.insert("provider".to_string(), serde_json::json!("default"));
+    sdk.add_agent("HarnessAgent", Some(settings), None, None)
+        .await
+        .unwrap();
+    let agent_id = sdk
+        .get_agent_id_by_name("HarnessAgent")
+        .await
+        .unwrap()
+        .expect("agent created");
+
+    let conversation = sdk
+        .new_conversation(&agent_id, "Harness conversation", None)
+        .await
+        .unwrap();
+    let conversation_id = conversation["id"].as_str().unwrap().to_string();
+    sdk.new_conversation_message("user", "Hello!", &conversation_id)
+        .await
+        .unwrap();
+    let history = sdk
+        .get_conversation(&conversation_id, None, None)
+        .await
+        .unwrap();
+    assert!(history.iter().any(|message| message.text() == "Hello!"));
+
+    sdk.delete_agent(&agent_id).await.unwrap();
+}
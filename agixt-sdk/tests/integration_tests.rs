@@ -1,9 +1,13 @@
 //! AGiXT Rust SDK Integration Tests
 //!
-//! These tests run against a live AGiXT server.
+//! These tests run against a live AGiXT server and are ignored by default; run
+//! them with `cargo test --test integration_tests -- --ignored`.
 //! Set the following environment variables:
 //! - AGIXT_URI: AGiXT server URI (default: http://localhost:7437)
 //! - AGIXT_API_KEY: API key for authentication (default: test-api-key)
+//!
+//! To run against a throwaway server instead, see `harness_tests.rs` and the
+//! `test-harness` feature.
 
 use agixt_sdk::AGiXTSDK;
 use std::env;
@@ -12,17 +16,18 @@ use uuid::Uuid;
 fn get_sdk() -> AGiXTSDK {
     let base_uri = env::var("AGIXT_URI").unwrap_or_else(|_| "http://localhost:7437".to_string());
     let api_key = env::var("AGIXT_API_KEY").unwrap_or_else(|_| "test-api-key".to_string());
-    AGiXTSDK::new(&base_uri, Some(&api_key)).expect("Failed to create SDK")
+    AGiXTSDK::new(Some(base_uri), Some(api_key), false)
 }
 
 fn generate_unique_name(prefix: &str) -> String {
-    format!("{}_{}", prefix, Uuid::new_v4().to_string()[..8].to_string())
+    format!("{}_{}", prefix, &Uuid::new_v4().to_string()[..8])
 }
 
 mod connection_tests {
     use super::*;
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_server_reachable() {
         let sdk = get_sdk();
         let providers = sdk.get_providers().await;
@@ -34,18 +39,40 @@ mod user_tests {
     use super::*;
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_register_user() {
         let sdk = get_sdk();
-        let email = format!("test_{}@example.com", Uuid::new_v4().to_string()[..8].to_string());
-        let result = sdk.register_user(&email, "Test", "User").await;
+        let email = format!("test_{}@example.com", &Uuid::new_v4().to_string()[..8]);
+        let result = sdk
+            .register_user(
+                &email,
+                "Password123!",
+                "Password123!",
+                Some("Test"),
+                Some("User"),
+                None,
+                None,
+            )
+            .await;
         assert!(result.is_ok(), "User registration should succeed");
     }
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_user_exists() {
         let sdk = get_sdk();
-        let email = format!("existing_{}@example.com", Uuid::new_v4().to_string()[..8].to_string());
-        let _ = sdk.register_user(&email, "Existing", "User").await;
+        let email = format!("existing_{}@example.com", &Uuid::new_v4().to_string()[..8]);
+        let _ = sdk
+            .register_user(
+                &email,
+                "Password123!",
+                "Password123!",
+                Some("Existing"),
+                Some("User"),
+                None,
+                None,
+            )
+            .await;
         let exists = sdk.user_exists(&email).await;
         assert!(exists.is_ok(), "User exists check should succeed");
     }
@@ -56,6 +83,7 @@ mod agent_tests {
     use std::collections::HashMap;
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_get_agents() {
         let sdk = get_sdk();
         let agents = sdk.get_agents().await;
@@ -63,34 +91,36 @@ mod agent_tests {
     }
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_add_agent() {
         let sdk = get_sdk();
         let agent_name = generate_unique_name("TestAgent");
         let mut settings = HashMap::new();
         settings.insert("provider".to_string(), serde_json::json!("default"));
-        
+
         let result = sdk.add_agent(&agent_name, Some(settings), None, None).await;
         assert!(result.is_ok(), "Add agent should succeed");
-        
+
         // Cleanup
-        if let Ok(agent_id) = sdk.get_agent_id_by_name(&agent_name).await {
+        if let Ok(Some(agent_id)) = sdk.get_agent_id_by_name(&agent_name).await {
             let _ = sdk.delete_agent(&agent_id).await;
         }
     }
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_get_agent_id_by_name() {
         let sdk = get_sdk();
         let agent_name = generate_unique_name("TestAgent");
         let mut settings = HashMap::new();
         settings.insert("provider".to_string(), serde_json::json!("default"));
-        
+
         let _ = sdk.add_agent(&agent_name, Some(settings), None, None).await;
         let agent_id = sdk.get_agent_id_by_name(&agent_name).await;
         assert!(agent_id.is_ok(), "Get agent ID by name should succeed");
-        
+
         // Cleanup
-        if let Ok(id) = agent_id {
+        if let Ok(Some(id)) = agent_id {
             let _ = sdk.delete_agent(&id).await;
         }
     }
@@ -101,30 +131,36 @@ mod conversation_tests {
     use std::collections::HashMap;
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_get_conversations() {
         let sdk = get_sdk();
-        let conversations = sdk.get_conversations().await;
+        let conversations = sdk.get_conversations(None, None).await;
         assert!(conversations.is_ok(), "Get conversations should succeed");
     }
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_new_conversation() {
         let sdk = get_sdk();
-        
+
         // Create agent first
         let agent_name = generate_unique_name("TestAgent");
         let mut settings = HashMap::new();
         settings.insert("provider".to_string(), serde_json::json!("default"));
         let _ = sdk.add_agent(&agent_name, Some(settings), None, None).await;
-        let agent_id = sdk.get_agent_id_by_name(&agent_name).await.expect("Should get agent ID");
-        
+        let agent_id = sdk
+            .get_agent_id_by_name(&agent_name)
+            .await
+            .expect("Should get agent ID")
+            .expect("Agent should exist");
+
         // Create conversation
         let conv_name = generate_unique_name("TestConv");
-        let result = sdk.new_conversation(&agent_id, &conv_name).await;
+        let result = sdk.new_conversation(&agent_id, &conv_name, None).await;
         assert!(result.is_ok(), "New conversation should succeed");
-        
+
         // Cleanup
-        if let Ok(conv_id) = sdk.get_conversation_id_by_name(&conv_name).await {
+        if let Ok(Some(conv_id)) = sdk.get_conversation_id_by_name(&conv_name).await {
             let _ = sdk.delete_conversation(&conv_id).await;
         }
         let _ = sdk.delete_agent(&agent_id).await;
@@ -135,6 +171,7 @@ mod provider_tests {
     use super::*;
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_get_providers() {
         let sdk = get_sdk();
         let providers = sdk.get_providers().await;
@@ -142,9 +179,12 @@ mod provider_tests {
     }
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_get_providers_by_service() {
         let sdk = get_sdk();
-        let providers = sdk.get_providers_by_service("llm").await;
+        let providers = sdk
+            .get_providers_by_service(agixt_sdk::ProviderService::Llm)
+            .await;
         assert!(providers.is_ok(), "Get providers by service should succeed");
     }
 }
@@ -153,6 +193,7 @@ mod chain_tests {
     use super::*;
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_get_chains() {
         let sdk = get_sdk();
         let chains = sdk.get_chains().await;
@@ -164,13 +205,15 @@ mod prompt_tests {
     use super::*;
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_get_prompts() {
         let sdk = get_sdk();
-        let prompts = sdk.get_prompts("Default").await;
+        let prompts = sdk.get_prompts(Some("Default")).await;
         assert!(prompts.is_ok(), "Get prompts should succeed");
     }
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_get_all_prompts() {
         let sdk = get_sdk();
         let prompts = sdk.get_all_prompts().await;
@@ -182,6 +225,7 @@ mod extension_tests {
     use super::*;
 
     #[tokio::test]
+    #[ignore = "requires a live AGiXT server"]
     async fn test_get_extensions() {
         let sdk = get_sdk();
         let extensions = sdk.get_extensions().await;